@@ -77,6 +77,52 @@ pub fn confusion_matrix<T>(predictions: &[T],
     counts
 }
 
+/// Returns the 2x2 confusion matrix of binary predictions made by
+/// thresholding scores.
+///
+/// A sample is predicted to be positive when its score is at least
+/// `threshold`. The first row and column correspond to the negative
+/// class and the second to the positive class, as for `confusion_matrix`
+/// with boolean labels. Evaluating several thresholds shows how the
+/// errors trade off as the cutoff moves.
+///
+/// # Arguments
+/// * `scores`    - The score of each sample, higher meaning more likely positive.
+/// * `labels`    - The true class of each sample, `true` for the positive class.
+/// * `threshold` - The smallest score predicted to be positive.
+///
+/// # Examples
+/// ```
+/// use rusty_machine::analysis::confusion_matrix::confusion_matrix_from_scores;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let scores = [0.9, 0.8, 0.4, 0.1];
+/// let labels = [true, false, true, false];
+///
+/// let confusion = confusion_matrix_from_scores(&scores, &labels, 0.5);
+///
+/// // One false positive and one false negative.
+/// let expected = Matrix::new(2, 2, vec![
+///     1, 1,
+///     1, 1]);
+///
+/// assert_eq!(confusion, expected);
+/// ```
+/// # Panics
+///
+/// - If scores and labels have different lengths.
+/// - If scores contains NaN.
+pub fn confusion_matrix_from_scores(scores: &[f64],
+                                    labels: &[bool],
+                                    threshold: f64) -> Matrix<usize> {
+    assert!(scores.len() == labels.len(),
+        "scores and labels have different lengths");
+    assert!(scores.iter().all(|s| !s.is_nan()), "scores must not contain NaN");
+
+    let predictions = scores.iter().map(|&s| s >= threshold).collect::<Vec<bool>>();
+    confusion_matrix(&predictions, labels, Some(vec![false, true]))
+}
+
 fn ordered_distinct<T: Ord + Eq + Copy>(xs: &[T], ys: &[T]) -> Vec<T> {
     let mut ds: Vec<T> = xs.iter().chain(ys).map(|x| *x).collect();
     ds.sort();
@@ -86,7 +132,8 @@ fn ordered_distinct<T: Ord + Eq + Copy>(xs: &[T], ys: &[T]) -> Vec<T> {
 
 #[cfg(test)]
 mod tests {
-    use super::confusion_matrix;
+    use super::{confusion_matrix, confusion_matrix_from_scores};
+    use linalg::BaseMatrix;
 
     #[test]
     fn confusion_matrix_no_labels() {
@@ -176,4 +223,50 @@ mod tests {
         let predictions = vec![2, 0, 2, 2];
         let _ = confusion_matrix(&predictions, &truth, None);
     }
+
+    #[test]
+    fn confusion_matrix_from_scores_thresholds() {
+        let scores = [0.9, 0.8, 0.4, 0.4, 0.1];
+        let labels = [true, false, true, false, false];
+
+        let confusion = confusion_matrix_from_scores(&scores, &labels, 0.4);
+
+        // Scores equal to the threshold are predicted positive.
+        let expected = matrix!(1, 2;
+                               0, 2);
+
+        assert_eq!(confusion, expected);
+    }
+
+    #[test]
+    fn confusion_matrix_from_scores_monotone_in_threshold() {
+        let scores = [0.05, 0.9, 0.3, 0.7, 0.3, 0.55, 0.2, 0.95, 0.6, 0.1];
+        let labels = [false, true, false, true, true, false, false, true, true, false];
+
+        let mut previous = confusion_matrix_from_scores(&scores, &labels, 0.0);
+        assert_eq!(previous[[0, 0]] + previous[[1, 0]], 0);
+
+        for i in 1..=20 {
+            let confusion = confusion_matrix_from_scores(&scores, &labels, i as f64 * 0.05);
+
+            // Every sample is counted once, and raising the threshold
+            // only ever moves samples from the positive column to the negative.
+            assert_eq!(confusion.iter().sum::<usize>(), scores.len());
+            for row in 0..2 {
+                assert!(confusion[[row, 0]] >= previous[[row, 0]]);
+                assert!(confusion[[row, 1]] <= previous[[row, 1]]);
+                assert_eq!(confusion[[row, 0]] + confusion[[row, 1]],
+                           previous[[row, 0]] + previous[[row, 1]]);
+            }
+            previous = confusion;
+        }
+
+        assert_eq!(previous[[0, 1]] + previous[[1, 1]], 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn confusion_matrix_from_scores_rejects_nan() {
+        let _ = confusion_matrix_from_scores(&[0.5, ::std::f64::NAN], &[true, false], 0.5);
+    }
 }
@@ -1,10 +1,17 @@
 //! Module for performing cross-validation of models.
+//!
+//! Also contains `learning_curve` for diagnosing
+//! whether a model is underfitting or overfitting, and
+//! `grid_search` for choosing between model configurations.
+//! `TimeSeriesSplit` and `time_series_validate` validate models
+//! on ordered data without training on future samples.
 
 use std::cmp;
 use std::iter::Chain;
 use std::slice::Iter;
 use linalg::{BaseMatrix, Matrix};
 use learning::{LearningResult, SupModel};
+use learning::error::{Error, ErrorKind};
 use learning::toolkit::rand_utils::in_place_fisher_yates;
 
 /// Randomly splits the inputs into k 'folds'. For each fold a model
@@ -56,7 +63,21 @@ pub fn k_fold_validate<M, S>(model: &mut M,
     assert_eq!(inputs.rows(), targets.rows());
     let num_samples = inputs.rows();
     let shuffled_indices = create_shuffled_indices(num_samples);
-    let folds = Folds::new(&shuffled_indices, k);
+
+    validate_folds(model, inputs, targets, &shuffled_indices, k, &score)
+}
+
+/// Trains and scores the model on each of the k folds of the shuffled indices.
+fn validate_folds<M, S>(model: &mut M,
+                        inputs: &Matrix<f64>,
+                        targets: &Matrix<f64>,
+                        shuffled_indices: &ShuffledIndices,
+                        k: usize,
+                        score: &S) -> LearningResult<Vec<f64>>
+    where S: Fn(&Matrix<f64>, &Matrix<f64>) -> f64,
+          M: SupModel<Matrix<f64>, Matrix<f64>>,
+{
+    let folds = Folds::new(shuffled_indices, k);
 
     let mut costs: Vec<f64> = Vec::new();
 
@@ -75,6 +96,377 @@ pub fn k_fold_validate<M, S>(model: &mut M,
     Ok(costs)
 }
 
+/// Computes cross-validated scores for models trained on
+/// increasing numbers of samples.
+///
+/// The samples are randomly split into k folds. For each fold and each
+/// training size a fresh model is trained on that many samples from the
+/// other folds. The model is scored on the samples it was trained on and
+/// on the held-out fold. Returns the mean training scores and the mean
+/// validation scores for each training size.
+///
+/// A large gap between the two curves suggests the model is overfitting,
+/// while two low curves which meet early suggest it is underfitting.
+///
+/// # Arguments
+/// * `model_factory` - Creates an untrained model for each fit.
+/// * `inputs` - All input samples.
+/// * `targets` - All targets.
+/// * `train_sizes` - The numbers of samples to train on. Each must be at most the size of the smallest training set.
+/// * `k` - Number of folds to use.
+/// * `score` - Used to compare the outputs to the targets. Higher scores are better. See the `analysis::score` module for examples.
+///
+/// # Examples
+/// ```
+/// use rusty_machine::analysis::cross_validation::learning_curve;
+/// use rusty_machine::analysis::score::row_accuracy;
+/// use rusty_machine::learning::naive_bayes::{NaiveBayes, Bernoulli};
+/// use rusty_machine::linalg::Matrix;
+///
+/// let inputs = Matrix::new(6, 2, vec![1.0, 0.0,
+///                                     1.0, 0.0,
+///                                     1.0, 1.0,
+///                                     0.0, 1.0,
+///                                     0.0, 1.0,
+///                                     0.0, 0.0]);
+///
+/// let targets = Matrix::new(6, 2, vec![1.0, 0.0,
+///                                      1.0, 0.0,
+///                                      1.0, 0.0,
+///                                      0.0, 1.0,
+///                                      0.0, 1.0,
+///                                      0.0, 1.0]);
+///
+/// let (train_scores, validation_scores) = learning_curve(
+///     NaiveBayes::<Bernoulli>::new,
+///     &inputs,
+///     &targets,
+///     &[2, 4],
+///     3,
+///     row_accuracy
+/// ).unwrap();
+///
+/// assert_eq!(train_scores.len(), 2);
+/// assert_eq!(validation_scores.len(), 2);
+/// ```
+///
+/// # Failures
+///
+/// - Some training size is zero or larger than the smallest training set.
+/// - A model fails to train or predict.
+pub fn learning_curve<F, M, S>(model_factory: F,
+                               inputs: &Matrix<f64>,
+                               targets: &Matrix<f64>,
+                               train_sizes: &[usize],
+                               k: usize,
+                               score: S) -> LearningResult<(Vec<f64>, Vec<f64>)>
+    where F: Fn() -> M,
+          S: Fn(&Matrix<f64>, &Matrix<f64>) -> f64,
+          M: SupModel<Matrix<f64>, Matrix<f64>>,
+{
+    assert_eq!(inputs.rows(), targets.rows());
+    let num_samples = inputs.rows();
+    let shuffled_indices = create_shuffled_indices(num_samples);
+    let folds = Folds::new(&shuffled_indices, k);
+
+    // The first fold is the largest, see `Folds::new`.
+    let largest_fold = num_samples / k + cmp::min(num_samples % k, 1);
+    let max_train_size = num_samples - largest_fold;
+    if train_sizes.iter().any(|&size| size == 0 || size > max_train_size) {
+        return Err(Error::new(ErrorKind::InvalidParameters,
+                              format!("Training sizes must be between 1 and {}.", max_train_size)));
+    }
+
+    let mut train_scores = vec![0f64; train_sizes.len()];
+    let mut validation_scores = vec![0f64; train_sizes.len()];
+
+    for p in folds {
+        let test_inputs = inputs.select_rows(p.test_indices_iter.clone());
+        let test_targets = targets.select_rows(p.test_indices_iter.clone());
+
+        for (i, &size) in train_sizes.iter().enumerate() {
+            let train_indices = p.train_indices_iter.clone().take(size).cloned().collect::<Vec<usize>>();
+            let train_inputs = inputs.select_rows(&train_indices);
+            let train_targets = targets.select_rows(&train_indices);
+
+            let mut model = model_factory();
+            model.train(&train_inputs, &train_targets)?;
+
+            train_scores[i] += score(&model.predict(&train_inputs)?, &train_targets);
+            validation_scores[i] += score(&model.predict(&test_inputs)?, &test_targets);
+        }
+    }
+
+    for s in train_scores.iter_mut().chain(validation_scores.iter_mut()) {
+        *s /= k as f64;
+    }
+
+    Ok((train_scores, validation_scores))
+}
+
+/// The results of a grid search over model configurations.
+#[derive(Clone, Debug)]
+pub struct GridSearchResult<P> {
+    /// The configuration with the highest mean score.
+    pub best_params: P,
+    /// The mean cross-validation score of the best configuration.
+    pub best_score: f64,
+    /// Each configuration with its mean cross-validation score,
+    /// in the order they were given.
+    pub results: Vec<(P, f64)>,
+}
+
+/// Selects the best of several model configurations by cross-validation.
+///
+/// For each configuration a model is created by `model_factory`
+/// and scored using k-fold cross-validation. Every configuration
+/// is evaluated on the same folds so the scores are comparable.
+///
+/// # Arguments
+/// * `model_factory` - Creates an untrained model from a configuration.
+/// * `candidates` - The configurations to compare.
+/// * `inputs` - All input samples.
+/// * `targets` - All targets.
+/// * `k` - Number of folds to use.
+/// * `score` - Used to compare the outputs for each fold to the targets. Higher scores are better. See the `analysis::score` module for examples.
+///
+/// # Examples
+/// ```
+/// use rusty_machine::analysis::cross_validation::grid_search;
+/// use rusty_machine::analysis::score::neg_mean_squared_error;
+/// use rusty_machine::learning::gp::{MultiOutputGaussianProcess, ConstMean};
+/// use rusty_machine::learning::toolkit::kernel::SquaredExp;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let inputs = Matrix::new(6, 1, vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+/// let targets = Matrix::new(6, 1, vec![0.1, 0.8, 1.0, 0.1, -0.7, -1.0]);
+///
+/// // Compare the length scales of the kernel.
+/// let result = grid_search(
+///     |&ls: &f64| MultiOutputGaussianProcess::new(SquaredExp::new(ls, 1.0),
+///                                                 ConstMean::default(),
+///                                                 0.01),
+///     &[0.5, 1.0, 2.0],
+///     &inputs,
+///     &targets,
+///     3,
+///     neg_mean_squared_error
+/// ).unwrap();
+///
+/// assert_eq!(result.results.len(), 3);
+/// ```
+///
+/// # Failures
+///
+/// - There are no candidate configurations.
+/// - A model fails to train or predict.
+pub fn grid_search<P, F, M, S>(model_factory: F,
+                               candidates: &[P],
+                               inputs: &Matrix<f64>,
+                               targets: &Matrix<f64>,
+                               k: usize,
+                               score: S) -> LearningResult<GridSearchResult<P>>
+    where P: Clone,
+          F: Fn(&P) -> M,
+          S: Fn(&Matrix<f64>, &Matrix<f64>) -> f64,
+          M: SupModel<Matrix<f64>, Matrix<f64>>,
+{
+    assert_eq!(inputs.rows(), targets.rows());
+    if candidates.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidParameters,
+                              "Grid search requires at least one candidate."));
+    }
+
+    let shuffled_indices = create_shuffled_indices(inputs.rows());
+
+    let mut results = Vec::with_capacity(candidates.len());
+    for params in candidates {
+        let mut model = model_factory(params);
+        let scores = validate_folds(&mut model, inputs, targets, &shuffled_indices, k, &score)?;
+        let mean_score = scores.iter().sum::<f64>() / scores.len() as f64;
+
+        results.push((params.clone(), mean_score));
+    }
+
+    // Ties are broken in favour of the earliest candidate.
+    let mut best = 0;
+    for (i, r) in results.iter().enumerate() {
+        if r.1 > results[best].1 {
+            best = i;
+        }
+    }
+    let (best_params, best_score) = results[best].clone();
+
+    Ok(GridSearchResult {
+        best_params,
+        best_score,
+        results,
+    })
+}
+
+/// Splits time-ordered samples into training and test folds.
+///
+/// The samples are divided, in row order, into `n_splits + 1`
+/// blocks of equal size, with any remainder added to the first block.
+/// The i-th fold tests on block `i + 1` and trains on the blocks before
+/// it, so every training sample precedes every test sample and no
+/// future data leaks into training. Samples are never shuffled.
+///
+/// By default the training window expands with each fold. Setting a
+/// maximum training size gives a sliding window instead, using only
+/// the most recent samples before the test block.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::cross_validation::TimeSeriesSplit;
+///
+/// let folds = TimeSeriesSplit::new(3).split(8).unwrap();
+///
+/// assert_eq!(folds[0], (vec![0, 1], vec![2, 3]));
+/// assert_eq!(folds[1], (vec![0, 1, 2, 3], vec![4, 5]));
+/// assert_eq!(folds[2], (vec![0, 1, 2, 3, 4, 5], vec![6, 7]));
+///
+/// let sliding = TimeSeriesSplit::new(3).with_max_train_size(2).split(8).unwrap();
+/// assert_eq!(sliding[2], (vec![4, 5], vec![6, 7]));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct TimeSeriesSplit {
+    n_splits: usize,
+    max_train_size: Option<usize>,
+}
+
+impl TimeSeriesSplit {
+    /// Constructs a splitter producing `n_splits` expanding-window folds.
+    ///
+    /// # Panics
+    ///
+    /// - `n_splits` is zero.
+    pub fn new(n_splits: usize) -> TimeSeriesSplit {
+        assert!(n_splits > 0, "The number of splits must be positive.");
+
+        TimeSeriesSplit {
+            n_splits,
+            max_train_size: None,
+        }
+    }
+
+    /// Limits each training window to the `max_train_size` most recent samples.
+    ///
+    /// # Panics
+    ///
+    /// - `max_train_size` is zero.
+    pub fn with_max_train_size(mut self, max_train_size: usize) -> Self {
+        assert!(max_train_size > 0, "The maximum training size must be positive.");
+
+        self.max_train_size = Some(max_train_size);
+        self
+    }
+
+    /// The number of folds.
+    pub fn n_splits(&self) -> usize {
+        self.n_splits
+    }
+
+    /// The maximum size of each training window, if the window slides.
+    pub fn max_train_size(&self) -> Option<usize> {
+        self.max_train_size
+    }
+
+    /// Returns the training and test indices of each fold
+    /// for a series of `n_samples` samples.
+    ///
+    /// # Failures
+    ///
+    /// - There are fewer than `n_splits + 1` samples.
+    pub fn split(&self, n_samples: usize) -> LearningResult<Vec<(Vec<usize>, Vec<usize>)>> {
+        if n_samples <= self.n_splits {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  format!("Cannot make {} time series splits of {} samples.",
+                                          self.n_splits,
+                                          n_samples)));
+        }
+
+        let test_size = n_samples / (self.n_splits + 1);
+        let first_test = n_samples - self.n_splits * test_size;
+
+        let folds = (0..self.n_splits)
+            .map(|i| {
+                let test_start = first_test + i * test_size;
+                let train_start = match self.max_train_size {
+                    Some(size) => test_start.saturating_sub(size),
+                    None => 0,
+                };
+
+                ((train_start..test_start).collect(),
+                 (test_start..test_start + test_size).collect())
+            })
+            .collect();
+
+        Ok(folds)
+    }
+}
+
+/// Trains the model on each fold of a time series split, scoring it
+/// on the samples which follow its training window. Returns the scores
+/// for each fold.
+///
+/// The rows of `inputs` and `targets` must be in time order.
+///
+/// # Arguments
+/// * `model` - Used to train and predict for each fold.
+/// * `inputs` - All input samples.
+/// * `targets` - All targets.
+/// * `splitter` - Determines the folds.
+/// * `score` - Used to compare the outputs for each fold to the targets. Higher scores are better. See the `analysis::score` module for examples.
+///
+/// # Examples
+/// ```
+/// use rusty_machine::analysis::cross_validation::{time_series_validate, TimeSeriesSplit};
+/// use rusty_machine::analysis::score::neg_mean_squared_error;
+/// use rusty_machine::learning::gp::{MultiOutputGaussianProcess, ConstMean};
+/// use rusty_machine::learning::toolkit::kernel::SquaredExp;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let inputs = Matrix::new(6, 1, vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+/// let targets = Matrix::new(6, 1, vec![0.0, 0.8, 0.9, 0.1, -0.8, -1.0]);
+///
+/// let mut model = MultiOutputGaussianProcess::new(SquaredExp::new(1.0, 1.0),
+///                                                 ConstMean::default(),
+///                                                 0.05);
+/// let scores = time_series_validate(&mut model,
+///                                   &inputs,
+///                                   &targets,
+///                                   &TimeSeriesSplit::new(2),
+///                                   neg_mean_squared_error).unwrap();
+///
+/// assert_eq!(scores.len(), 2);
+/// ```
+///
+/// # Failures
+///
+/// - There are too few samples for the number of splits.
+/// - The model fails to train or predict.
+pub fn time_series_validate<M, S>(model: &mut M,
+                                  inputs: &Matrix<f64>,
+                                  targets: &Matrix<f64>,
+                                  splitter: &TimeSeriesSplit,
+                                  score: S) -> LearningResult<Vec<f64>>
+    where S: Fn(&Matrix<f64>, &Matrix<f64>) -> f64,
+          M: SupModel<Matrix<f64>, Matrix<f64>>,
+{
+    assert_eq!(inputs.rows(), targets.rows());
+
+    let mut scores = Vec::with_capacity(splitter.n_splits());
+    for (train, test) in splitter.split(inputs.rows())? {
+        model.train(&inputs.select_rows(&train), &targets.select_rows(&train))?;
+        let outputs = model.predict(&inputs.select_rows(&test))?;
+        scores.push(score(&outputs, &targets.select_rows(&test)));
+    }
+
+    Ok(scores)
+}
+
 /// A permutation of 0..n.
 struct ShuffledIndices(Vec<usize>);
 
@@ -199,7 +591,93 @@ impl<'a> Iterator for Folds<'a> {
 
 #[cfg(test)]
 mod tests {
-    use super::{ShuffledIndices, Folds};
+    use super::{ShuffledIndices, Folds, learning_curve, grid_search, TimeSeriesSplit};
+    use analysis::score::neg_mean_squared_error;
+    use learning::gp::{MultiOutputGaussianProcess, ConstMean};
+    use learning::toolkit::kernel::SquaredExp;
+    use linalg::Matrix;
+
+    /// Noisy samples of `sin(x)`.
+    fn sine_data(n: usize) -> (Matrix<f64>, Matrix<f64>) {
+        let xs = (0..n).map(|i| i as f64 * 10.0 / n as f64).collect::<Vec<f64>>();
+        let ys = xs.iter()
+            .enumerate()
+            .map(|(i, x)| x.sin() + 0.2 * (i as f64 * 2.7).sin())
+            .collect::<Vec<f64>>();
+
+        (Matrix::new(n, 1, xs), Matrix::new(n, 1, ys))
+    }
+
+    fn gp_factory() -> MultiOutputGaussianProcess<SquaredExp, ConstMean> {
+        MultiOutputGaussianProcess::new(SquaredExp::new(1.0, 1.0), ConstMean::default(), 0.05)
+    }
+
+    #[test]
+    fn test_learning_curve_converges() {
+        let (inputs, targets) = sine_data(80);
+        let sizes = [4, 15, 60];
+
+        let (train, validation) = learning_curve(gp_factory,
+                                                 &inputs,
+                                                 &targets,
+                                                 &sizes,
+                                                 4,
+                                                 neg_mean_squared_error).unwrap();
+
+        assert_eq!(train.len(), 3);
+        assert_eq!(validation.len(), 3);
+
+        // The validation score improves and the gap to the
+        // training score shrinks as more samples are used.
+        assert!(validation[2] > validation[0]);
+        assert!(train[2] - validation[2] < train[0] - validation[0]);
+    }
+
+    #[test]
+    fn test_grid_search_noise() {
+        let (inputs, targets) = sine_data(60);
+
+        // The observation noise acts as a ridge penalty on the GP fit.
+        let noise_grid = [1e-6, 0.04, 100.0];
+        let result = grid_search(|&noise: &f64| {
+                                     MultiOutputGaussianProcess::new(SquaredExp::new(1.0, 1.0),
+                                                                     ConstMean::default(),
+                                                                     noise)
+                                 },
+                                 &noise_grid,
+                                 &inputs,
+                                 &targets,
+                                 5,
+                                 neg_mean_squared_error).unwrap();
+
+        assert_eq!(result.best_params, 0.04);
+        assert_eq!(result.results.len(), 3);
+        for (&(p, s), &n) in result.results.iter().zip(noise_grid.iter()) {
+            assert_eq!(p, n);
+            assert!(s <= result.best_score);
+        }
+    }
+
+    #[test]
+    fn test_grid_search_no_candidates() {
+        let (inputs, targets) = sine_data(10);
+        let candidates: Vec<f64> = vec![];
+
+        let res = grid_search(|_: &f64| gp_factory(), &candidates, &inputs, &targets, 2,
+                              neg_mean_squared_error);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_learning_curve_invalid_sizes() {
+        let (inputs, targets) = sine_data(8);
+
+        // With 4 folds each training set has 6 samples.
+        for &size in &[0, 7] {
+            let res = learning_curve(gp_factory, &inputs, &targets, &[size], 4, neg_mean_squared_error);
+            assert!(res.is_err());
+        }
+    }
 
     // k % n == 0
     #[test]
@@ -282,4 +760,47 @@ mod tests {
                  p.test_indices_iter.map(|x| *x).collect::<Vec<_>>()))
             .collect::<Vec<(Vec<usize>, Vec<usize>)>>()
     }
+
+    #[test]
+    fn test_time_series_split_train_precedes_test() {
+        for splitter in &[TimeSeriesSplit::new(4), TimeSeriesSplit::new(4).with_max_train_size(3)] {
+            let folds = splitter.split(23).unwrap();
+            assert_eq!(folds.len(), 4);
+
+            for &(ref train, ref test) in &folds {
+                assert!(!train.is_empty());
+                assert!(!test.is_empty());
+                for i in train {
+                    for j in test {
+                        assert!(i < j);
+                    }
+                }
+            }
+
+            // The test folds are consecutive and end at the last sample.
+            assert_eq!(folds.last().unwrap().1.last(), Some(&22));
+            for w in folds.windows(2) {
+                assert_eq!(*w[0].1.last().unwrap() + 1, w[1].1[0]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_time_series_split_window_sizes() {
+        let expanding = TimeSeriesSplit::new(3).split(10).unwrap();
+        let train_sizes = expanding.iter().map(|f| f.0.len()).collect::<Vec<_>>();
+        assert_eq!(train_sizes, vec![4, 6, 8]);
+        assert!(expanding.iter().all(|f| f.1.len() == 2));
+
+        let sliding = TimeSeriesSplit::new(3).with_max_train_size(5).split(10).unwrap();
+        let train_sizes = sliding.iter().map(|f| f.0.len()).collect::<Vec<_>>();
+        assert_eq!(train_sizes, vec![4, 5, 5]);
+        assert_eq!(sliding[2].0, vec![3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_time_series_split_too_few_samples() {
+        assert!(TimeSeriesSplit::new(3).split(3).is_err());
+        assert!(TimeSeriesSplit::new(3).split(4).is_ok());
+    }
 }
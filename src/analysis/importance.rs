@@ -0,0 +1,448 @@
+//! Module for measuring the importance of input features
+//! and their effect on the predictions of a model.
+
+use linalg::{BaseMatrix, BaseMatrixMut, Matrix, Vector};
+use learning::{LearningResult, SupModel, validate_finite_matrix};
+use learning::toolkit::rand_utils::in_place_fisher_yates;
+
+/// Computes the permutation importance of each feature for a trained model.
+///
+/// For each column of the inputs the values are shuffled, breaking the
+/// relationship between that feature and the targets, and the model is
+/// scored on the shuffled inputs. The importance of the feature is the
+/// drop from the baseline score, averaged over `n_repeats` shuffles.
+///
+/// Only `predict` is used, so this works for any trained model.
+///
+/// # Arguments
+/// * `model` - A trained model.
+/// * `inputs` - Input samples, usually held out from training.
+/// * `targets` - The targets for the input samples.
+/// * `score` - Used to compare the outputs to the targets. Higher scores are better. See the `analysis::score` module for examples.
+/// * `n_repeats` - The number of shuffles to average over for each feature.
+///
+/// # Examples
+/// ```
+/// use rusty_machine::analysis::importance::permutation_importance;
+/// use rusty_machine::learning::lin_reg::LinRegressor;
+/// use rusty_machine::learning::SupModel;
+/// use rusty_machine::linalg::{Matrix, Vector};
+///
+/// let inputs = Matrix::new(4, 2, vec![1.0, 0.3,
+///                                     2.0, 0.1,
+///                                     3.0, 0.4,
+///                                     4.0, 0.2]);
+/// let targets = Vector::new(vec![2.0, 4.1, 5.9, 8.0]);
+///
+/// let mut model = LinRegressor::default();
+/// model.train(&inputs, &targets).unwrap();
+///
+/// let importances = permutation_importance(
+///     &model,
+///     &inputs,
+///     &targets,
+///     // Score by the negative sum of squared errors.
+///     |outputs: &Vector<f64>, targets: &Vector<f64>| {
+///         -(outputs - targets).iter().map(|e| e * e).sum::<f64>()
+///     },
+///     5
+/// ).unwrap();
+///
+/// assert_eq!(importances.size(), 2);
+/// ```
+///
+/// # Panics
+///
+/// - `n_repeats` is zero.
+pub fn permutation_importance<M, T, S>(model: &M,
+                                       inputs: &Matrix<f64>,
+                                       targets: &T,
+                                       score: S,
+                                       n_repeats: usize) -> LearningResult<Vector<f64>>
+    where S: Fn(&T, &T) -> f64,
+          M: SupModel<Matrix<f64>, T>,
+{
+    assert!(n_repeats > 0, "The number of repeats must be greater than 0.");
+
+    let baseline = score(&model.predict(inputs)?, targets);
+
+    let mut permuted = inputs.clone();
+    let mut importances = Vec::with_capacity(inputs.cols());
+
+    for j in 0..inputs.cols() {
+        let mut column = inputs.col(j).iter().cloned().collect::<Vec<f64>>();
+        let mut total_drop = 0f64;
+
+        for _ in 0..n_repeats {
+            in_place_fisher_yates(&mut column);
+            for (x, &c) in permuted.col_mut(j).iter_mut().zip(column.iter()) {
+                *x = c;
+            }
+
+            total_drop += baseline - score(&model.predict(&permuted)?, targets);
+        }
+
+        // Restore the column before permuting the next one.
+        for (x, c) in permuted.col_mut(j).iter_mut().zip(inputs.col(j).iter()) {
+            *x = *c;
+        }
+
+        importances.push(total_drop / n_repeats as f64);
+    }
+
+    Ok(Vector::new(importances))
+}
+
+/// Computes the partial dependence of a trained model's output on a feature.
+///
+/// For each value in `grid` the feature is set to that value in every
+/// sample, and the model's outputs for the modified samples are averaged.
+/// The curve shows how the predictions change with the feature, with
+/// the other features averaged over the data.
+///
+/// Only `predict` is used, so this works for any trained model.
+///
+/// # Arguments
+/// * `model` - A trained model.
+/// * `inputs` - Input samples whose other features are averaged over.
+/// * `feature` - The column of the feature.
+/// * `grid` - The values of the feature at which to compute the dependence.
+///
+/// # Examples
+/// ```
+/// use rusty_machine::analysis::importance::partial_dependence;
+/// use rusty_machine::learning::lin_reg::LinRegressor;
+/// use rusty_machine::learning::SupModel;
+/// use rusty_machine::linalg::{Matrix, Vector};
+///
+/// let inputs = Matrix::new(4, 2, vec![1.0, 0.3,
+///                                     2.0, 0.1,
+///                                     3.0, 0.4,
+///                                     4.0, 0.2]);
+/// let targets = Vector::new(vec![2.0, 4.1, 5.9, 8.0]);
+///
+/// let mut model = LinRegressor::default();
+/// model.train(&inputs, &targets).unwrap();
+///
+/// let dependence = partial_dependence(&model, &inputs, 0, &[0.0, 2.5, 5.0]).unwrap();
+/// assert_eq!(dependence.len(), 3);
+/// assert!(dependence[0] < dependence[1] && dependence[1] < dependence[2]);
+/// ```
+///
+/// # Panics
+///
+/// - `feature` is not less than the number of columns.
+pub fn partial_dependence<M>(model: &M,
+                             inputs: &Matrix<f64>,
+                             feature: usize,
+                             grid: &[f64]) -> LearningResult<Vec<f64>>
+    where M: SupModel<Matrix<f64>, Vector<f64>>
+{
+    assert!(feature < inputs.cols(), "The feature must be a column of the inputs.");
+
+    let mut modified = inputs.clone();
+    let mut dependence = Vec::with_capacity(grid.len());
+
+    for &value in grid {
+        for x in modified.col_mut(feature).iter_mut() {
+            *x = value;
+        }
+
+        dependence.push(model.predict(&modified)?.mean());
+    }
+
+    Ok(dependence)
+}
+
+/// Estimates the mutual information between each feature and the labels.
+///
+/// The mutual information measures how much knowing a feature reduces
+/// the uncertainty in the label, independent of any model. It is zero
+/// when the feature and label are independent and at most the entropy
+/// of the labels. The result is in nats.
+///
+/// Each feature is discretized into equal-frequency bins, with the
+/// number of bins the square root of the number of samples rounded up.
+/// Equal values always share a bin. The estimate is biased upwards by
+/// roughly the number of bins times the number of classes divided by
+/// twice the number of samples, so independent features score slightly
+/// above zero.
+///
+/// # Examples
+/// ```
+/// use rusty_machine::analysis::importance::mutual_info_classif;
+/// use rusty_machine::linalg::Matrix;
+///
+/// // The first feature determines the label, the second does not.
+/// let inputs = Matrix::new(4, 2, vec![0.0, 1.0,
+///                                     0.1, 0.0,
+///                                     1.0, 1.0,
+///                                     1.1, 0.0]);
+/// let labels = vec![0, 0, 1, 1];
+///
+/// let scores = mutual_info_classif(&inputs, &labels).unwrap();
+/// assert!(scores[0] > scores[1]);
+/// ```
+///
+/// # Panics
+///
+/// - The number of labels differs from the number of samples.
+///
+/// # Failures
+///
+/// - Some input is NaN or infinite.
+pub fn mutual_info_classif<T: PartialEq>(inputs: &Matrix<f64>, labels: &[T]) -> LearningResult<Vector<f64>> {
+    assert_eq!(inputs.rows(), labels.len(), "There must be one label per sample.");
+    validate_finite_matrix("input", inputs)?;
+
+    let n = labels.len();
+    if n == 0 {
+        return Ok(Vector::zeros(inputs.cols()));
+    }
+
+    // Index the classes in order of first appearance.
+    let mut class_reps: Vec<&T> = Vec::new();
+    let classes = labels.iter()
+        .map(|l| match class_reps.iter().position(|c| *c == l) {
+            Some(idx) => idx,
+            None => {
+                class_reps.push(l);
+                class_reps.len() - 1
+            }
+        })
+        .collect::<Vec<usize>>();
+    let n_classes = class_reps.len();
+
+    let n_bins = (n as f64).sqrt().ceil() as usize;
+    let mut scores = Vec::with_capacity(inputs.cols());
+
+    for j in 0..inputs.cols() {
+        let bins = quantile_bins(&inputs.col(j).iter().cloned().collect::<Vec<f64>>(), n_bins);
+
+        let mut joint = vec![0usize; n_bins * n_classes];
+        for (b, c) in bins.iter().zip(classes.iter()) {
+            joint[b * n_classes + c] += 1;
+        }
+
+        let mut bin_counts = vec![0usize; n_bins];
+        let mut class_counts = vec![0usize; n_classes];
+        for b in 0..n_bins {
+            for c in 0..n_classes {
+                bin_counts[b] += joint[b * n_classes + c];
+                class_counts[c] += joint[b * n_classes + c];
+            }
+        }
+
+        let mut mi = 0f64;
+        for b in 0..n_bins {
+            for c in 0..n_classes {
+                let count = joint[b * n_classes + c];
+                if count > 0 {
+                    let ratio = (count * n) as f64 / (bin_counts[b] * class_counts[c]) as f64;
+                    mi += count as f64 / n as f64 * ratio.ln();
+                }
+            }
+        }
+
+        // Rounding can make the estimate of an independent feature negative.
+        scores.push(mi.max(0f64));
+    }
+
+    Ok(Vector::new(scores))
+}
+
+/// Assigns each value to one of `n_bins` bins holding roughly
+/// equal numbers of values, keeping equal values together.
+///
+/// The values must not be NaN.
+fn quantile_bins(values: &[f64], n_bins: usize) -> Vec<usize> {
+    let n = values.len();
+    let mut order = (0..n).collect::<Vec<usize>>();
+    order.sort_by(|&a, &b| values[a].partial_cmp(&values[b]).unwrap());
+
+    let mut bins = vec![0; n];
+    let mut rank = 0;
+    for (pos, &idx) in order.iter().enumerate() {
+        // Ties take the bin of the first equal value.
+        if pos > 0 && values[idx] != values[order[pos - 1]] {
+            rank = pos;
+        }
+        bins[idx] = rank * n_bins / n;
+    }
+
+    bins
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mutual_info_classif, partial_dependence, permutation_importance};
+    use learning::SupModel;
+    use learning::error::ErrorKind;
+    use learning::lin_reg::LinRegressor;
+    use learning::logistic_reg::LogisticRegressor;
+    use linalg::{Matrix, Vector};
+
+    use rand::{Rng, StdRng, SeedableRng};
+
+    fn neg_sq_error(outputs: &Vector<f64>, targets: &Vector<f64>) -> f64 {
+        -(outputs - targets).iter().map(|e| e * e).sum::<f64>() / outputs.size() as f64
+    }
+
+    #[test]
+    fn test_permutation_importance() {
+        // The target depends only on the first feature.
+        let n = 100;
+        let mut data = Vec::with_capacity(2 * n);
+        let mut targets = Vec::with_capacity(n);
+        for i in 0..n {
+            let x0 = (i as f64 * 0.37).sin();
+            let x1 = (i as f64 * 1.91).cos();
+            data.push(x0);
+            data.push(x1);
+            targets.push(4.0 * x0 + 0.05 * (i as f64 * 2.3).sin());
+        }
+        let inputs = Matrix::new(n, 2, data);
+        let targets = Vector::new(targets);
+
+        let mut model = LinRegressor::default();
+        model.train(&inputs, &targets).unwrap();
+
+        let importances = permutation_importance(&model, &inputs, &targets, neg_sq_error, 10)
+            .unwrap();
+
+        assert!(importances[0] > 10.0);
+        assert!(importances[1].abs() < 0.01);
+    }
+
+    #[test]
+    fn test_partial_dependence_monotone() {
+        // The probability of the positive class increases with the first
+        // feature and decreases with the second.
+        let n = 100;
+        let mut data = Vec::with_capacity(2 * n);
+        let mut targets = Vec::with_capacity(n);
+        for i in 0..n {
+            let x0 = (i as f64 * 0.37).sin() * 2.0;
+            let x1 = (i as f64 * 1.91).cos();
+            data.push(x0);
+            data.push(x1);
+            targets.push(if x0 - 0.5 * x1 + 0.3 * (i as f64 * 2.3).sin() > 0.0 { 1.0 } else { 0.0 });
+        }
+        let inputs = Matrix::new(n, 2, data);
+
+        let mut model = LogisticRegressor::default();
+        model.train(&inputs, &Vector::new(targets)).unwrap();
+
+        let grid = (0..21).map(|i| -2.0 + 0.2 * i as f64).collect::<Vec<f64>>();
+
+        let increasing = partial_dependence(&model, &inputs, 0, &grid).unwrap();
+        assert_eq!(increasing.len(), grid.len());
+        assert!(increasing.windows(2).all(|w| w[1] > w[0]), "{:?}", increasing);
+        assert!(increasing[0] < 0.2 && increasing[20] > 0.8);
+
+        let decreasing = partial_dependence(&model, &inputs, 1, &grid).unwrap();
+        assert!(decreasing.windows(2).all(|w| w[1] < w[0]), "{:?}", decreasing);
+    }
+
+    #[test]
+    fn test_partial_dependence_linear() {
+        let inputs = Matrix::new(4, 2, vec![1.0, 0.0, 2.0, 1.0, 3.0, 0.0, 4.0, 1.0]);
+        let targets = Vector::new(vec![3.0, 6.0, 7.0, 10.0]);
+
+        let mut model = LinRegressor::default();
+        model.train(&inputs, &targets).unwrap();
+        let params = model.parameters().unwrap().clone();
+
+        // For a linear model the curve is the line through the mean prediction.
+        let dependence = partial_dependence(&model, &inputs, 1, &[0.0, 1.0, 3.0]).unwrap();
+        let base = params[0] + params[1] * 2.5;
+        for (d, &v) in dependence.iter().zip([0.0, 1.0, 3.0].iter()) {
+            assert!((d - (base + params[2] * v)).abs() < 1e-10);
+        }
+
+        assert!(partial_dependence(&LinRegressor::default(), &inputs, 0, &[1.0]).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_partial_dependence_bad_feature() {
+        let model = LinRegressor::default();
+        let inputs = Matrix::new(2, 1, vec![1.0, 2.0]);
+
+        let _ = partial_dependence(&model, &inputs, 1, &[0.0]);
+    }
+
+    #[test]
+    fn test_permutation_importance_untrained() {
+        let model = LinRegressor::default();
+        let inputs = Matrix::new(2, 1, vec![1.0, 2.0]);
+        let targets = Vector::new(vec![1.0, 2.0]);
+
+        assert!(permutation_importance(&model, &inputs, &targets, neg_sq_error, 1).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_permutation_importance_zero_repeats() {
+        let model = LinRegressor::default();
+        let inputs = Matrix::new(2, 1, vec![1.0, 2.0]);
+        let targets = Vector::new(vec![1.0, 2.0]);
+
+        let _ = permutation_importance(&model, &inputs, &targets, neg_sq_error, 0);
+    }
+
+    #[test]
+    fn test_mutual_info_classif() {
+        let seed: &[_] = &[7, 11];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+
+        let n = 400;
+        let mut data = Vec::with_capacity(3 * n);
+        let mut labels = Vec::with_capacity(n);
+        for i in 0..n {
+            let label = i % 2 == 0;
+            // Determines the label.
+            data.push(if label { 1.0 } else { -1.0 } + rng.gen_range(0.0, 0.5));
+            // Independent of the label.
+            data.push(rng.gen_range(-1.0, 1.0));
+            // Constant.
+            data.push(3.0);
+            labels.push(label);
+        }
+        let inputs = Matrix::new(n, 3, data);
+
+        let scores = mutual_info_classif(&inputs, &labels).unwrap();
+
+        // The entropy of balanced binary labels is ln(2).
+        assert!((scores[0] - 2f64.ln()).abs() < 1e-10);
+        assert!(scores[1] < 0.05);
+        assert_eq!(scores[2], 0.0);
+    }
+
+    #[test]
+    fn test_mutual_info_classif_multiclass() {
+        // Three classes, the feature only separates the first from the rest.
+        let inputs = Matrix::new(9, 1, vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+        let labels = vec!["a", "a", "a", "b", "b", "b", "c", "c", "c"];
+
+        let scores = mutual_info_classif(&inputs, &labels).unwrap();
+
+        // H(Y) - H(Y | X) = ln(3) - (2 / 3) ln(2).
+        let expected = 3f64.ln() - 2.0 / 3.0 * 2f64.ln();
+        assert!((scores[0] - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mutual_info_classif_mismatched_labels() {
+        let inputs = Matrix::new(2, 1, vec![1.0, 2.0]);
+        let _ = mutual_info_classif(&inputs, &[0, 1, 1]);
+    }
+
+    #[test]
+    fn test_mutual_info_classif_non_finite() {
+        let inputs = Matrix::new(3, 2, vec![1.0, 0.0, ::std::f64::NAN, 1.0, 2.0, 0.0]);
+        let err = mutual_info_classif(&inputs, &[0, 1, 1]).unwrap_err();
+        assert!(matches!(*err.kind(), ErrorKind::InvalidData));
+    }
+}
@@ -0,0 +1,467 @@
+//! Module to summarize the performance of a classifier.
+//!
+//! The per-class precision, recall and F1 scores are computed
+//! from the confusion matrix of the predictions, see the
+//! `analysis::score` module for the binary versions.
+//!
+//! For binary classifiers which output a score, such as a probability
+//! or the distance from a decision boundary, `threshold_report` shows
+//! how the scores change with the cutoff used to predict the positive
+//! class.
+
+use std::fmt;
+use std::hash::Hash;
+use libnum::{Zero, One};
+use linalg::BaseMatrix;
+
+use super::confusion_matrix::confusion_matrix;
+
+/// The scores of a single class.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassMetrics<T> {
+    /// The label of the class.
+    pub label: T,
+    /// The fraction of samples predicted as the class which belong to it.
+    pub precision: f64,
+    /// The fraction of samples in the class which are predicted as it.
+    pub recall: f64,
+    /// The harmonic mean of the precision and recall.
+    pub f1: f64,
+    /// The number of samples in the class.
+    pub support: usize,
+}
+
+/// Averages of the per-class scores.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AverageMetrics {
+    /// The average precision.
+    pub precision: f64,
+    /// The average recall.
+    pub recall: f64,
+    /// The average F1 score.
+    pub f1: f64,
+}
+
+/// A summary of the performance of a classifier.
+///
+/// Use the `Display` implementation to format it as a table.
+#[derive(Clone, Debug)]
+pub struct ClassificationReport<T> {
+    /// The scores of each class, ordered by label.
+    pub classes: Vec<ClassMetrics<T>>,
+    /// The fraction of samples which are predicted correctly.
+    pub accuracy: f64,
+    /// The unweighted mean of the per-class scores.
+    pub macro_avg: AverageMetrics,
+    /// The mean of the per-class scores weighted by support.
+    pub weighted_avg: AverageMetrics,
+}
+
+impl<T> ClassificationReport<T>
+    where T: Ord + Eq + Hash + Copy
+{
+    /// Computes the classification report of a set of predictions.
+    ///
+    /// The classes are the distinct labels appearing in either the
+    /// predictions or the targets. Scores which would divide by
+    /// zero, such as the precision of a class which is never
+    /// predicted, are set to zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::analysis::report::ClassificationReport;
+    ///
+    /// let truth       = vec![2, 0, 2, 2, 0, 1];
+    /// let predictions = vec![0, 0, 2, 2, 0, 2];
+    ///
+    /// let report = ClassificationReport::new(&predictions, &truth);
+    ///
+    /// assert_eq!(report.classes[0].recall, 1.0);
+    /// assert_eq!(report.accuracy, 4.0 / 6.0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - predictions and targets have different lengths.
+    /// - predictions and targets are empty.
+    pub fn new(predictions: &[T], targets: &[T]) -> ClassificationReport<T> {
+        assert!(!targets.is_empty(), "Cannot report on empty predictions.");
+
+        let confusion = confusion_matrix(predictions, targets, None);
+
+        let mut labels = predictions.iter().chain(targets).cloned().collect::<Vec<T>>();
+        labels.sort();
+        labels.dedup();
+
+        let n_classes = labels.len();
+        let total = targets.len();
+        let correct = (0..n_classes).map(|i| confusion[[i, i]]).sum::<usize>();
+
+        let classes = labels.into_iter()
+            .enumerate()
+            .map(|(i, label)| {
+                let tp = confusion[[i, i]] as f64;
+                let predicted = confusion.col(i).iter().sum::<usize>();
+                let support = confusion.row(i).iter().sum::<usize>();
+
+                let precision = safe_div(tp, predicted as f64);
+                let recall = safe_div(tp, support as f64);
+                let f1 = safe_div(2f64 * precision * recall, precision + recall);
+
+                ClassMetrics {
+                    label,
+                    precision,
+                    recall,
+                    f1,
+                    support,
+                }
+            })
+            .collect::<Vec<ClassMetrics<T>>>();
+
+        let macro_avg = average(&classes, |_| 1f64 / n_classes as f64);
+        let weighted_avg = average(&classes, |c| c.support as f64 / total as f64);
+
+        ClassificationReport {
+            classes,
+            accuracy: correct as f64 / total as f64,
+            macro_avg,
+            weighted_avg,
+        }
+    }
+}
+
+/// Divides the numerator by the denominator, or returns zero
+/// if the denominator is zero.
+fn safe_div(num: f64, denom: f64) -> f64 {
+    if denom == 0f64 { 0f64 } else { num / denom }
+}
+
+/// Computes the weighted sum of the class scores.
+fn average<T, F>(classes: &[ClassMetrics<T>], weight: F) -> AverageMetrics
+    where F: Fn(&ClassMetrics<T>) -> f64
+{
+    let mut avg = AverageMetrics {
+        precision: 0f64,
+        recall: 0f64,
+        f1: 0f64,
+    };
+
+    for c in classes {
+        let w = weight(c);
+        avg.precision += w * c.precision;
+        avg.recall += w * c.recall;
+        avg.f1 += w * c.f1;
+    }
+
+    avg
+}
+
+impl<T: fmt::Display> fmt::Display for ClassificationReport<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let support = self.classes.iter().map(|c| c.support).sum::<usize>();
+        let names = self.classes.iter().map(|c| c.label.to_string()).collect::<Vec<String>>();
+        let width = names.iter().map(|n| n.len()).fold("weighted avg".len(), ::std::cmp::max);
+
+        writeln!(f, "{:>w$} {:>9} {:>9} {:>9} {:>9}", "", "precision", "recall", "f1-score", "support",
+                 w = width)?;
+        writeln!(f)?;
+
+        for (name, c) in names.iter().zip(self.classes.iter()) {
+            writeln!(f, "{:>w$} {:>9.2} {:>9.2} {:>9.2} {:>9}", name, c.precision, c.recall, c.f1,
+                     c.support, w = width)?;
+        }
+        writeln!(f)?;
+
+        writeln!(f, "{:>w$} {:>9} {:>9} {:>9.2} {:>9}", "accuracy", "", "", self.accuracy, support,
+                 w = width)?;
+
+        for &(name, ref avg) in &[("macro avg", self.macro_avg), ("weighted avg", self.weighted_avg)] {
+            writeln!(f, "{:>w$} {:>9.2} {:>9.2} {:>9.2} {:>9}", name, avg.precision, avg.recall, avg.f1,
+                     support, w = width)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns a table of the precision, recall, F1 score and support
+/// of each class, with the accuracy and averages of the scores.
+///
+/// See `ClassificationReport` for the structured values.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::report::classification_report;
+///
+/// let truth       = vec![2, 0, 2, 2, 0, 1];
+/// let predictions = vec![0, 0, 2, 2, 0, 2];
+///
+/// println!("{}", classification_report(&predictions, &truth));
+/// ```
+///
+/// # Panics
+///
+/// - predictions and targets have different lengths.
+/// - predictions and targets are empty.
+pub fn classification_report<T>(predictions: &[T], targets: &[T]) -> String
+    where T: Ord + Eq + Hash + Copy + fmt::Display
+{
+    ClassificationReport::new(predictions, targets).to_string()
+}
+
+/// Computes the precision, recall and F1 score at each candidate threshold.
+///
+/// A sample is predicted to be positive when its score is at least the
+/// threshold. The candidate thresholds are the distinct scores, in
+/// decreasing order. Each entry of the output is a tuple of
+/// `(threshold, precision, recall, f1)`, with the scores defined as in
+/// the `analysis::score` module. Scores which would divide by zero are
+/// set to zero.
+///
+/// # Arguments
+///
+/// * `scores` - The score of each sample, higher meaning more likely positive.
+/// * `labels` - The true label of each sample, which only contains 0 or 1.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::report::threshold_report;
+///
+/// let scores = [0.9, 0.8, 0.4, 0.1];
+/// let labels = [1, 0, 1, 0];
+///
+/// let report = threshold_report(&scores, &labels);
+///
+/// // At a threshold of 0.4 both positives are found,
+/// // along with one of the negatives.
+/// assert_eq!(report[2], (0.4, 2.0 / 3.0, 1.0, 0.8));
+/// ```
+///
+/// # Panics
+///
+/// - scores and labels have different lengths
+/// - scores contains NaN
+/// - labels contains a value which is not 0 or 1
+pub fn threshold_report<T>(scores: &[f64], labels: &[T]) -> Vec<(f64, f64, f64, f64)>
+    where T: PartialEq + Zero + One
+{
+    assert!(scores.len() == labels.len(), "scores and labels must have the same length");
+    assert!(labels.iter().all(|l| *l == T::zero() || *l == T::one()),
+            "threshold_report must be used for 2 class classification");
+
+    let mut order = (0..scores.len()).collect::<Vec<usize>>();
+    order.sort_by(|&a, &b| {
+        scores[b].partial_cmp(&scores[a]).expect("scores must not contain NaN")
+    });
+
+    let total_pos = labels.iter().filter(|l| **l == T::one()).count() as f64;
+    let mut report = Vec::new();
+    let (mut tp, mut fp) = (0f64, 0f64);
+
+    for (k, &i) in order.iter().enumerate() {
+        if labels[i] == T::one() {
+            tp += 1f64;
+        } else {
+            fp += 1f64;
+        }
+
+        // Only report once every sample with this score is predicted positive.
+        if let Some(&j) = order.get(k + 1) {
+            if scores[j] == scores[i] {
+                continue;
+            }
+        }
+
+        let precision = safe_div(tp, tp + fp);
+        let recall = safe_div(tp, total_pos);
+        let f1 = safe_div(2f64 * precision * recall, precision + recall);
+        report.push((scores[i], precision, recall, f1));
+    }
+
+    report
+}
+
+/// Returns the threshold with the highest F1 score.
+///
+/// See `threshold_report` for how the thresholds are chosen.
+/// If several thresholds have the highest F1 score the
+/// largest of them is returned.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::report::best_threshold_for_f1;
+///
+/// let scores = [0.9, 0.8, 0.4, 0.1];
+/// let labels = [1, 0, 1, 0];
+///
+/// assert_eq!(best_threshold_for_f1(&scores, &labels), 0.4);
+/// ```
+///
+/// # Panics
+///
+/// - scores and labels have different lengths
+/// - scores is empty or contains NaN
+/// - labels contains a value which is not 0 or 1
+pub fn best_threshold_for_f1<T>(scores: &[f64], labels: &[T]) -> f64
+    where T: PartialEq + Zero + One
+{
+    assert!(!scores.is_empty(), "Cannot choose a threshold for empty scores.");
+
+    let report = threshold_report(scores, labels);
+    let mut best = report[0];
+    for entry in &report[1..] {
+        if entry.3 > best.3 {
+            best = *entry;
+        }
+    }
+
+    best.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClassificationReport, classification_report};
+    use super::{threshold_report, best_threshold_for_f1};
+    use analysis::score::f1;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-12, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_three_class_report() {
+        let truth       = vec![0, 0, 0, 1, 1, 2, 2, 2, 2, 2];
+        let predictions = vec![0, 1, 0, 1, 2, 2, 2, 0, 2, 2];
+
+        let report = ClassificationReport::new(&predictions, &truth);
+        assert_eq!(report.classes.len(), 3);
+
+        // Class 0: 2 of 3 predictions correct, 2 of 3 samples found.
+        let c0 = &report.classes[0];
+        assert_eq!(c0.label, 0);
+        assert_close(c0.precision, 2.0 / 3.0);
+        assert_close(c0.recall, 2.0 / 3.0);
+        assert_close(c0.f1, 2.0 / 3.0);
+        assert_eq!(c0.support, 3);
+
+        // Class 1: 1 of 2 predictions correct, 1 of 2 samples found.
+        let c1 = &report.classes[1];
+        assert_close(c1.precision, 0.5);
+        assert_close(c1.recall, 0.5);
+        assert_close(c1.f1, 0.5);
+        assert_eq!(c1.support, 2);
+
+        // Class 2: 4 of 5 predictions correct, 4 of 5 samples found.
+        let c2 = &report.classes[2];
+        assert_close(c2.precision, 0.8);
+        assert_close(c2.recall, 0.8);
+        assert_close(c2.f1, 0.8);
+        assert_eq!(c2.support, 5);
+
+        assert_close(report.accuracy, 0.7);
+
+        let macro_f1 = (2.0 / 3.0 + 0.5 + 0.8) / 3.0;
+        assert_close(report.macro_avg.precision, macro_f1);
+        assert_close(report.macro_avg.f1, macro_f1);
+
+        let weighted = (3.0 * 2.0 / 3.0 + 2.0 * 0.5 + 5.0 * 0.8) / 10.0;
+        assert_close(report.weighted_avg.recall, weighted);
+        assert_close(report.weighted_avg.f1, weighted);
+    }
+
+    #[test]
+    fn test_unpredicted_class() {
+        let truth       = vec![0, 1, 1];
+        let predictions = vec![0, 0, 0];
+
+        let report = ClassificationReport::new(&predictions, &truth);
+
+        let c1 = &report.classes[1];
+        assert_eq!(c1.precision, 0.0);
+        assert_eq!(c1.recall, 0.0);
+        assert_eq!(c1.f1, 0.0);
+        assert_eq!(c1.support, 2);
+    }
+
+    #[test]
+    fn test_report_string() {
+        let truth       = vec![0, 0, 0, 1, 1, 2, 2, 2, 2, 2];
+        let predictions = vec![0, 1, 0, 1, 2, 2, 2, 0, 2, 2];
+
+        let report = classification_report(&predictions, &truth);
+        let lines = report.lines().collect::<Vec<&str>>();
+
+        assert_eq!(lines.len(), 9);
+        assert!(lines[0].contains("precision"));
+        assert_eq!(lines[3].split_whitespace().collect::<Vec<&str>>(),
+                   vec!["1", "0.50", "0.50", "0.50", "2"]);
+        assert_eq!(lines[6].split_whitespace().collect::<Vec<&str>>(),
+                   vec!["accuracy", "0.70", "10"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_empty_report() {
+        let empty: Vec<usize> = vec![];
+        let _ = ClassificationReport::new(&empty, &empty);
+    }
+
+    #[test]
+    fn test_threshold_report() {
+        let scores = [0.9, 0.7, 0.7, 0.5, 0.2];
+        let labels = [1.0, 1.0, 0.0, 1.0, 0.0];
+
+        let report = threshold_report(&scores, &labels);
+
+        // The tied scores share a threshold.
+        let thresholds = report.iter().map(|r| r.0).collect::<Vec<f64>>();
+        assert_eq!(thresholds, vec![0.9, 0.7, 0.5, 0.2]);
+
+        let (_, precision, recall, _) = report[1];
+        assert_close(precision, 2.0 / 3.0);
+        assert_close(recall, 2.0 / 3.0);
+
+        // The lowest threshold predicts everything as positive.
+        let (_, precision, recall, _) = report[3];
+        assert_close(precision, 0.6);
+        assert_close(recall, 1.0);
+    }
+
+    #[test]
+    fn test_best_threshold_for_f1() {
+        let scores = [0.95, 0.85, 0.8, 0.6, 0.55, 0.4, 0.3, 0.2, 0.1];
+        let labels = [1, 0, 1, 1, 0, 1, 0, 0, 0];
+
+        let best = best_threshold_for_f1(&scores, &labels);
+        assert_eq!(best, 0.4);
+
+        // Compare the F1 score at each threshold with the score module.
+        let f1_at = |threshold: f64| {
+            let outputs = scores.iter().map(|&s| if s >= threshold { 1 } else { 0 }).collect::<Vec<i32>>();
+            f1(outputs.iter(), labels.iter())
+        };
+
+        let best_f1 = f1_at(best);
+        for &(threshold, _, _, report_f1) in &threshold_report(&scores, &labels) {
+            assert_close(report_f1, f1_at(threshold));
+            assert!(report_f1 <= best_f1);
+        }
+    }
+
+    #[test]
+    fn test_threshold_report_no_positives() {
+        let report = threshold_report(&[0.3, 0.1], &[0, 0]);
+
+        assert_eq!(report, vec![(0.3, 0.0, 0.0, 0.0), (0.1, 0.0, 0.0, 0.0)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_threshold_report_invalid_labels() {
+        let _ = threshold_report(&[0.3, 0.1], &[0, 2]);
+    }
+}
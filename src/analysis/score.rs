@@ -2,8 +2,13 @@
 //! how close predictions and truth are. All functions in this
 //! module obey the convention that higher is better.
 
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::f64;
+
 use libnum::{Zero, One};
 
+use analysis::confusion_matrix::confusion_matrix;
 use linalg::{BaseMatrix, Matrix};
 use learning::toolkit::cost_fn::{CostFunc, MeanSqError};
 
@@ -51,6 +56,62 @@ pub fn row_accuracy(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
              targets.row_iter().map(|r| r.raw_slice()))
 }
 
+/// Returns the fraction of samples whose predicted labels all match their targets.
+///
+/// For multi-label classification each row holds the labels of one
+/// sample, so this is the subset accuracy, also known as the exact
+/// match ratio. It is the same as `row_accuracy`.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::subset_accuracy;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let outputs = Matrix::new(3, 2, vec![1.0, 0.0, 1.0, 1.0, 0.0, 0.0]);
+/// let targets = Matrix::new(3, 2, vec![1.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+///
+/// assert_eq!(subset_accuracy(&outputs, &targets), 2.0 / 3.0);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have a different number of rows
+pub fn subset_accuracy(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
+    row_accuracy(outputs, targets)
+}
+
+/// Returns the additive inverse of the Hamming loss of the outputs.
+///
+/// For multi-label classification the Hamming loss is the fraction of
+/// the individual labels which are predicted wrongly. So higher is better,
+/// and the returned value lies between -1 and 0.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::neg_hamming_loss;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let outputs = Matrix::new(2, 2, vec![1.0, 0.0, 1.0, 1.0]);
+/// let targets = Matrix::new(2, 2, vec![1.0, 0.0, 1.0, 0.0]);
+///
+/// assert_eq!(neg_hamming_loss(&outputs, &targets), -0.25);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs is empty
+pub fn neg_hamming_loss(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
+    assert!(outputs.rows() == targets.rows() && outputs.cols() == targets.cols(),
+            "outputs and targets must have the same shape");
+    assert!(!outputs.data().is_empty(), "outputs must not be empty");
+
+    let wrong = outputs.iter().zip(targets.iter()).filter(|&(o, t)| o != t).count();
+    -(wrong as f64) / outputs.data().len() as f64
+}
+
 /// Returns the precision score for 2 class classification.
 ///
 /// Precision is calculated with true-positive / (true-positive + false-positive),
@@ -197,6 +258,224 @@ pub fn f1<'a, I, T>(outputs: I, targets: I) -> f64
     2.0f64 * tpos / (2.0f64 * tpos + fneg + fpos)
 }
 
+/// Returns the fraction of rows whose true class is among the `k`
+/// classes with the highest probability.
+///
+/// A row counts as correct when fewer than `k` classes rank above the
+/// true class. Classes are ranked by probability, with ties broken in
+/// favour of the lower class index as in an argmax. With `k = 1` this
+/// is the usual accuracy.
+///
+/// # Arguments
+///
+/// * `probs` - Matrix of predicted class probabilities, with a row per input.
+/// * `labels` - The true class of each input, as a column index of `probs`.
+/// * `k` - The number of highest-probability classes to consider.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::top_k_accuracy;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let probs = Matrix::new(2, 3, vec![0.5, 0.3, 0.2,
+///                                    0.1, 0.6, 0.3]);
+/// let labels = [1, 2];
+///
+/// assert_eq!(top_k_accuracy(&probs, &labels, 1), 0.0);
+/// assert_eq!(top_k_accuracy(&probs, &labels, 2), 1.0);
+/// ```
+///
+/// # Panics
+///
+/// - probs and labels have different length
+/// - k is zero
+/// - labels contains a class which is not a column of probs
+pub fn top_k_accuracy(probs: &Matrix<f64>, labels: &[usize], k: usize) -> f64 {
+    assert!(probs.rows() == labels.len(), "probs and labels must have the same length");
+    assert!(k > 0, "k must be greater than zero");
+
+    let correct = probs.row_iter()
+        .zip(labels.iter())
+        .filter(|&(ref row, &label)| {
+            assert!(label < row.cols(), "label is not a column of probs");
+            let p = row[label];
+            row.iter()
+                .enumerate()
+                .filter(|&(i, &q)| q > p || (q == p && i < label))
+                .count() < k
+        })
+        .count();
+    correct as f64 / labels.len() as f64
+}
+
+/// Returns the recall of each class which appears in the targets.
+///
+/// The recalls are read from the rows of the confusion matrix,
+/// in increasing order of the class labels.
+fn class_recalls<T>(outputs: &[T], targets: &[T]) -> Vec<f64>
+    where T: Ord + Eq + Hash + Copy
+{
+    assert!(!targets.is_empty(), "targets must not be empty");
+
+    let confusion = confusion_matrix(outputs, targets, None);
+    confusion.row_iter()
+        .enumerate()
+        .filter_map(|(i, row)| {
+            let support = row.iter().sum::<usize>();
+            if support == 0 {
+                // The class only appears in the outputs
+                None
+            } else {
+                Some(row[i] as f64 / support as f64)
+            }
+        })
+        .collect()
+}
+
+/// Returns the balanced accuracy of the outputs.
+///
+/// The balanced accuracy is the mean of the recalls of the classes in
+/// the targets. Unlike the accuracy every class counts equally, so a
+/// classifier which always predicts the majority class scores `1 / k`
+/// for `k` classes however imbalanced they are.
+///
+/// # Arguments
+///
+/// * `outputs` - Slice of output (predicted) labels.
+/// * `targets` - Slice of expected (actual) labels.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::balanced_accuracy;
+/// let outputs = [0, 0, 0, 0, 1, 0];
+/// let targets = [0, 0, 0, 0, 1, 1];
+///
+/// // The recalls are 1 and 1/2.
+/// assert_eq!(balanced_accuracy(&outputs, &targets), 0.75);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different length
+/// - targets is empty
+pub fn balanced_accuracy<T>(outputs: &[T], targets: &[T]) -> f64
+    where T: Ord + Eq + Hash + Copy
+{
+    let recalls = class_recalls(outputs, targets);
+    recalls.iter().sum::<f64>() / recalls.len() as f64
+}
+
+/// Returns the geometric mean of the recalls of the classes in the targets.
+///
+/// This is zero whenever some class is never predicted correctly,
+/// so it rewards classifiers which do well on every class.
+///
+/// # Arguments
+///
+/// * `outputs` - Slice of output (predicted) labels.
+/// * `targets` - Slice of expected (actual) labels.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::geometric_mean_score;
+/// let outputs = [0, 0, 0, 1, 1, 0];
+/// let targets = [0, 0, 0, 0, 1, 1];
+///
+/// // The recalls are 3/4 and 1/2.
+/// let expected = (0.75f64 * 0.5).sqrt();
+/// assert!((geometric_mean_score(&outputs, &targets) - expected).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different length
+/// - targets is empty
+pub fn geometric_mean_score<T>(outputs: &[T], targets: &[T]) -> f64
+    where T: Ord + Eq + Hash + Copy
+{
+    let recalls = class_recalls(outputs, targets);
+    recalls.iter().product::<f64>().powf(1f64 / recalls.len() as f64)
+}
+
+// ************************************
+// Clustering Scores
+// ************************************
+
+/// Returns the adjusted Rand index of two clusterings.
+///
+/// The Rand index counts the pairs of samples which are assigned to the
+/// same or to different clusters in both labelings. The adjusted index
+/// corrects this for chance, so that random labelings score close to 0
+/// and identical labelings (up to a permutation of the labels) score 1,
+/// see [Rand index](https://en.wikipedia.org/wiki/Rand_index#Adjusted_Rand_index)
+/// for details.
+///
+/// The index compares pairs of samples, so it is undefined with fewer
+/// than two samples and `NaN` is returned.
+///
+/// # Arguments
+///
+/// * `labels_true` - The ground truth cluster labels.
+/// * `labels_pred` - The predicted cluster labels.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::adjusted_rand_index;
+/// let labels_true = [0, 0, 1, 1, 2, 2];
+/// let labels_pred = [1, 1, 0, 0, 2, 2];
+///
+/// assert_eq!(adjusted_rand_index(&labels_true, &labels_pred), 1.0);
+/// ```
+///
+/// # Panics
+///
+/// - labels_true and labels_pred have different length
+pub fn adjusted_rand_index<T>(labels_true: &[T], labels_pred: &[T]) -> f64
+    where T: Eq + Hash + Copy
+{
+    assert!(labels_true.len() == labels_pred.len(),
+            "labels_true and labels_pred must have the same length");
+
+    if labels_true.len() < 2 {
+        return f64::NAN;
+    }
+
+    // Number of pairs which can be chosen from x items
+    fn pairs(x: usize) -> f64 {
+        (x * x.saturating_sub(1)) as f64 / 2f64
+    }
+
+    // Build the contingency table along with its row and column sums
+    let mut contingency: HashMap<(T, T), usize> = HashMap::new();
+    let mut true_counts: HashMap<T, usize> = HashMap::new();
+    let mut pred_counts: HashMap<T, usize> = HashMap::new();
+
+    for (t, p) in labels_true.iter().zip(labels_pred) {
+        *contingency.entry((*t, *p)).or_insert(0) += 1;
+        *true_counts.entry(*t).or_insert(0) += 1;
+        *pred_counts.entry(*p).or_insert(0) += 1;
+    }
+
+    let index = contingency.values().map(|&n| pairs(n)).sum::<f64>();
+    let true_pairs = true_counts.values().map(|&n| pairs(n)).sum::<f64>();
+    let pred_pairs = pred_counts.values().map(|&n| pairs(n)).sum::<f64>();
+
+    let expected_index = true_pairs * pred_pairs / pairs(labels_true.len());
+    let max_index = (true_pairs + pred_pairs) / 2f64;
+
+    // Both labelings are trivial (a single cluster, or all singletons)
+    // and so agree perfectly.
+    if max_index == expected_index {
+        return 1f64;
+    }
+
+    (index - expected_index) / (max_index - expected_index)
+}
+
 // ************************************
 // Regression Scores
 // ************************************
@@ -214,7 +493,11 @@ pub fn neg_mean_squared_error(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f
 #[cfg(test)]
 mod tests {
     use linalg::Matrix;
-    use super::{accuracy, precision, recall, f1, neg_mean_squared_error};
+    use super::{accuracy, precision, recall, f1, top_k_accuracy, balanced_accuracy,
+                geometric_mean_score, subset_accuracy, neg_hamming_loss, adjusted_rand_index,
+                neg_mean_squared_error};
+
+    use learning::toolkit::rand_utils::in_place_fisher_yates;
 
     #[test]
     fn test_accuracy() {
@@ -333,6 +616,151 @@ mod tests {
         f1(outputs.iter(), targets.iter());
     }
 
+    #[test]
+    fn test_top_k_accuracy() {
+        // The true class is usually the runner-up.
+        let probs = Matrix::new(5, 4, vec![0.4, 0.3, 0.2, 0.1,
+                                           0.1, 0.5, 0.3, 0.1,
+                                           0.2, 0.1, 0.3, 0.4,
+                                           0.6, 0.2, 0.1, 0.1,
+                                           0.1, 0.2, 0.3, 0.4]);
+        let labels = [1, 2, 2, 0, 1];
+
+        let top_1 = top_k_accuracy(&probs, &labels, 1);
+        let top_2 = top_k_accuracy(&probs, &labels, 2);
+        let top_3 = top_k_accuracy(&probs, &labels, 3);
+
+        assert_eq!(top_1, 0.2);
+        assert_eq!(top_2, 0.8);
+        assert_eq!(top_3, 1.0);
+        assert!(top_3 > top_1);
+
+        assert_eq!(top_k_accuracy(&probs, &labels, 4), 1.0);
+    }
+
+    #[test]
+    fn test_top_k_accuracy_ties() {
+        let probs = Matrix::new(1, 3, vec![0.4, 0.4, 0.2]);
+        assert_eq!(top_k_accuracy(&probs, &[0], 1), 1.0);
+        assert_eq!(top_k_accuracy(&probs, &[1], 1), 0.0);
+        assert_eq!(top_k_accuracy(&probs, &[1], 2), 1.0);
+
+        // A uniform prediction carries no information.
+        let uniform = Matrix::new(4, 4, vec![0.25; 16]);
+        let labels = [0, 1, 2, 3];
+        assert_eq!(top_k_accuracy(&uniform, &labels, 1), 0.25);
+        assert_eq!(top_k_accuracy(&uniform, &labels, 2), 0.5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_top_k_accuracy_different_lengths() {
+        let probs = Matrix::new(2, 2, vec![0.5, 0.5, 0.3, 0.7]);
+        top_k_accuracy(&probs, &[0], 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_top_k_accuracy_zero_k() {
+        let probs = Matrix::new(1, 2, vec![0.5, 0.5]);
+        top_k_accuracy(&probs, &[0], 0);
+    }
+
+    #[test]
+    fn test_multilabel_scores() {
+        let outputs = Matrix::new(3, 3, vec![1.0, 0.0, 1.0,
+                                             0.0, 1.0, 1.0,
+                                             0.0, 0.0, 0.0]);
+        let targets = Matrix::new(3, 3, vec![1.0, 0.0, 1.0,
+                                             1.0, 1.0, 0.0,
+                                             0.0, 0.0, 1.0]);
+
+        assert_eq!(subset_accuracy(&outputs, &targets), 1.0 / 3.0);
+        assert_eq!(neg_hamming_loss(&outputs, &targets), -3.0 / 9.0);
+        assert_eq!(neg_hamming_loss(&targets, &targets), 0.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_hamming_loss_different_shapes() {
+        neg_hamming_loss(&Matrix::new(1, 2, vec![0.0, 1.0]), &Matrix::new(2, 1, vec![0.0, 1.0]));
+    }
+
+    #[test]
+    fn test_balanced_accuracy_majority_classifier() {
+        let targets = (0..100).map(|i| i < 90).collect::<Vec<bool>>();
+        let outputs = vec![true; 100];
+
+        assert!((accuracy(outputs.iter(), targets.iter()) - 0.9).abs() < 1e-12);
+        assert_eq!(balanced_accuracy(&outputs, &targets), 0.5);
+        assert_eq!(geometric_mean_score(&outputs, &targets), 0.0);
+    }
+
+    #[test]
+    fn test_balanced_accuracy_multiclass() {
+        let outputs = [0, 0, 1, 1, 1, 2, 2, 0];
+        let targets = [0, 0, 0, 1, 1, 2, 2, 2];
+
+        // The recalls are 2/3, 1 and 2/3.
+        let expected = (2.0 / 3.0 + 1.0 + 2.0 / 3.0) / 3.0;
+        assert!((balanced_accuracy(&outputs, &targets) - expected).abs() < 1e-12);
+
+        let expected = (2.0f64 / 3.0 * 2.0 / 3.0).powf(1.0 / 3.0);
+        assert!((geometric_mean_score(&outputs, &targets) - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_balanced_accuracy_ignores_predicted_only_classes() {
+        let outputs = [0, 3, 1, 1];
+        let targets = [0, 0, 1, 1];
+
+        assert_eq!(balanced_accuracy(&outputs, &targets), 0.75);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_balanced_accuracy_different_lengths() {
+        balanced_accuracy(&[0, 1], &[0]);
+    }
+
+    #[test]
+    fn test_adjusted_rand_index_permuted_labels() {
+        let labels_true = [0, 0, 0, 1, 1, 2, 2, 2];
+        let labels_pred = [2, 2, 2, 0, 0, 1, 1, 1];
+        assert_eq!(adjusted_rand_index(&labels_true, &labels_pred), 1.0);
+        assert_eq!(adjusted_rand_index(&labels_true, &labels_true), 1.0);
+    }
+
+    #[test]
+    fn test_adjusted_rand_index_known_value() {
+        let labels_true = [0, 0, 1, 1];
+        let labels_pred = [0, 0, 1, 2];
+        assert!((adjusted_rand_index(&labels_true, &labels_pred) - 0.5714285714285714).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_adjusted_rand_index_random_labels() {
+        let labels_true = (0..1000).map(|x| x % 5).collect::<Vec<usize>>();
+        let mut labels_pred = labels_true.clone();
+        in_place_fisher_yates(&mut labels_pred);
+
+        assert!(adjusted_rand_index(&labels_true, &labels_pred).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_adjusted_rand_index_single_sample() {
+        assert!(adjusted_rand_index(&[0], &[1]).is_nan());
+        assert!(adjusted_rand_index::<usize>(&[], &[]).is_nan());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_adjusted_rand_index_different_lengths() {
+        let labels_true = [0, 0, 1, 1];
+        let labels_pred = [0, 0, 1];
+        adjusted_rand_index(&labels_true, &labels_pred);
+    }
+
     #[test]
     fn test_neg_mean_squared_error_1d() {
         let outputs = Matrix::new(3, 1, vec![1f64, 2f64, 3f64]);
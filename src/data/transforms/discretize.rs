@@ -0,0 +1,307 @@
+//! The K-Bins Discretizer
+//!
+//! This module contains the `KBinsDiscretizer` transformer.
+//!
+//! The `KBinsDiscretizer` transformer is used to map each continuous
+//! feature to the index of the bin it falls in. The bin edges for each
+//! column are computed when fitting, using one of two strategies:
+//!
+//! - `Uniform` : Bins of equal width spanning the range of the column.
+//! - `Quantile` : Bins containing roughly equal numbers of data points.
+//!
+//! Finite values outside the fitted range are placed in the first or
+//! last bin. Non-finite values cannot be placed in a bin, so fitting or
+//! transforming data which contains them fails.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::data::transforms::{Transformer, TransformFitter, KBinsDiscretizerFitter};
+//! use rusty_machine::data::transforms::discretize::BinStrategy;
+//! use rusty_machine::linalg::Matrix;
+//!
+//! let inputs = Matrix::new(4, 1, vec![0.0, 1.0, 2.0, 10.0]);
+//!
+//! // Constructs a new `KBinsDiscretizer` with 2 quantile bins per column.
+//! let mut transformer = KBinsDiscretizerFitter::new(2, BinStrategy::Quantile)
+//!                           .fit(&inputs)
+//!                           .unwrap();
+//!
+//! // Transform the inputs to bin indices.
+//! let transformed = transformer.transform(inputs).unwrap();
+//! assert_eq!(transformed.into_vec(), vec![0.0, 0.0, 1.0, 1.0]);
+//! ```
+
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
+use linalg::{Matrix, BaseMatrix, BaseMatrixMut};
+use super::{Transformer, TransformFitter};
+
+use libnum::{Float, FromPrimitive};
+
+/// The strategy used to choose the bin edges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinStrategy {
+    /// Bins of equal width.
+    Uniform,
+    /// Bins with roughly equal numbers of points.
+    Quantile,
+}
+
+/// A builder used to construct a `KBinsDiscretizer`
+#[derive(Clone, Debug)]
+pub struct KBinsDiscretizerFitter {
+    n_bins: usize,
+    strategy: BinStrategy,
+}
+
+impl Default for KBinsDiscretizerFitter {
+    fn default() -> Self {
+        KBinsDiscretizerFitter {
+            n_bins: 5,
+            strategy: BinStrategy::Quantile,
+        }
+    }
+}
+
+impl KBinsDiscretizerFitter {
+    /// Construct a new `KBinsDiscretizerFitter` with
+    /// the specified number of bins and strategy.
+    ///
+    /// Note that this function does not create a `Transformer`
+    /// only a builder which can be used to produce a fitted `Transformer`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::data::transforms::KBinsDiscretizerFitter;
+    /// use rusty_machine::data::transforms::discretize::BinStrategy;
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// let fitter = KBinsDiscretizerFitter::new(3, BinStrategy::Uniform);
+    ///
+    /// // We can call `fit` from the `transform::TransformFitter`
+    /// // trait to create a `KBinsDiscretizer` used to actually transform data.
+    /// use rusty_machine::data::transforms::TransformFitter;
+    /// let mat = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+    /// let transformer = fitter.fit(&mat);
+    /// ```
+    pub fn new(n_bins: usize, strategy: BinStrategy) -> KBinsDiscretizerFitter {
+        KBinsDiscretizerFitter {
+            n_bins,
+            strategy,
+        }
+    }
+}
+
+impl<T: Float + FromPrimitive> TransformFitter<Matrix<T>, KBinsDiscretizer<T>> for KBinsDiscretizerFitter {
+    fn fit(self, inputs: &Matrix<T>) -> LearningResult<KBinsDiscretizer<T>> {
+        if self.n_bins < 2 {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "The number of bins must be at least 2."));
+        }
+
+        if inputs.rows() == 0 {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Cannot fit bins to data without rows."));
+        }
+
+        let mut edges = Vec::with_capacity(inputs.cols());
+
+        for j in 0..inputs.cols() {
+            let mut column = inputs.col(j).iter().cloned().collect::<Vec<T>>();
+
+            if column.iter().any(|x| !x.is_finite()) {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      format!("Data point in column {} cannot be processed", j)));
+            }
+
+            column.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            let col_edges = match self.strategy {
+                BinStrategy::Uniform => uniform_edges(&column, self.n_bins),
+                BinStrategy::Quantile => quantile_edges(&column, self.n_bins),
+            };
+            edges.push(col_edges);
+        }
+
+        Ok(KBinsDiscretizer {
+            n_bins: self.n_bins,
+            strategy: self.strategy,
+            edges,
+        })
+    }
+}
+
+/// Equally spaced edges between the minimum and maximum of a sorted column.
+fn uniform_edges<T: Float + FromPrimitive>(sorted: &[T], n_bins: usize) -> Vec<T> {
+    let min = sorted[0];
+    let max = sorted[sorted.len() - 1];
+    let width = (max - min) / T::from_usize(n_bins).unwrap();
+
+    (0..n_bins + 1).map(|i| min + width * T::from_usize(i).unwrap()).collect()
+}
+
+/// Edges at evenly spaced quantiles of a sorted column, interpolating
+/// linearly between data points.
+fn quantile_edges<T: Float + FromPrimitive>(sorted: &[T], n_bins: usize) -> Vec<T> {
+    let last = sorted.len() - 1;
+
+    (0..n_bins + 1)
+        .map(|i| {
+            let pos = T::from_usize(i * last).unwrap() / T::from_usize(n_bins).unwrap();
+            let lower = pos.floor().to_usize().unwrap();
+            let upper = pos.ceil().to_usize().unwrap();
+            let frac = pos - pos.floor();
+
+            sorted[lower] + (sorted[upper] - sorted[lower]) * frac
+        })
+        .collect()
+}
+
+/// The KBinsDiscretizer
+///
+/// The KBinsDiscretizer provides an implementation of `Transformer`
+/// which replaces each value with the index of its bin, from `0`
+/// to `n_bins - 1`.
+///
+/// See the module description for more information.
+#[derive(Clone, Debug)]
+pub struct KBinsDiscretizer<T: Float> {
+    /// The number of bins per column
+    n_bins: usize,
+    /// The strategy used to compute the edges
+    strategy: BinStrategy,
+    /// The `n_bins + 1` edges of the bins for each column
+    edges: Vec<Vec<T>>,
+}
+
+impl<T: Float> KBinsDiscretizer<T> {
+    /// The number of bins per column.
+    pub fn n_bins(&self) -> usize {
+        self.n_bins
+    }
+
+    /// The strategy used to compute the bin edges.
+    pub fn strategy(&self) -> BinStrategy {
+        self.strategy
+    }
+
+    /// The bin edges for each column, in increasing order.
+    pub fn edges(&self) -> &[Vec<T>] {
+        &self.edges
+    }
+}
+
+impl<T: Float + FromPrimitive> Transformer<Matrix<T>> for KBinsDiscretizer<T> {
+    fn transform(&mut self, mut inputs: Matrix<T>) -> LearningResult<Matrix<T>> {
+        if self.edges.len() != inputs.cols() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Input data has different number of columns from fitted data."));
+        }
+
+        if let Some(idx) = inputs.data().iter().position(|x| !x.is_finite()) {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  format!("Data point in row {}, column {} cannot be processed",
+                                          idx / inputs.cols(),
+                                          idx % inputs.cols())));
+        }
+
+        for mut row in inputs.row_iter_mut() {
+            for (x, edges) in row.iter_mut().zip(self.edges.iter()) {
+                // Count the inner edges at or below the value.
+                let bin = edges[1..self.n_bins].iter().take_while(|e| **e <= *x).count();
+                *x = T::from_usize(bin).unwrap();
+            }
+        }
+
+        Ok(inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{Transformer, TransformFitter};
+    use linalg::Matrix;
+
+    use std::f64;
+
+    #[test]
+    fn uniform_bins_test() {
+        let inputs = Matrix::new(5, 1, vec![0.0, 1.0, 4.0, 5.5, 9.0]);
+
+        let mut transformer = KBinsDiscretizerFitter::new(3, BinStrategy::Uniform).fit(&inputs).unwrap();
+        assert_eq!(transformer.edges()[0], vec![0.0, 3.0, 6.0, 9.0]);
+
+        let transformed = transformer.transform(inputs).unwrap();
+        assert_eq!(transformed.into_vec(), vec![0.0, 0.0, 1.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn quantile_skewed_test() {
+        // A heavily skewed column.
+        let inputs = Matrix::new(100, 1, (0..100).map(|i| (i as f64 / 10.0).exp()).collect::<Vec<_>>());
+
+        let mut quantile = KBinsDiscretizerFitter::new(4, BinStrategy::Quantile).fit(&inputs).unwrap();
+        let transformed = quantile.transform(inputs.clone()).unwrap();
+
+        let mut counts = vec![0; 4];
+        for x in transformed.iter() {
+            counts[*x as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| c >= 24 && c <= 26), "counts: {:?}", counts);
+
+        // Uniform bins are dominated by the long tail.
+        let mut uniform = KBinsDiscretizerFitter::new(4, BinStrategy::Uniform).fit(&inputs).unwrap();
+        let transformed = uniform.transform(inputs).unwrap();
+        let first_bin = transformed.iter().filter(|x| **x == 0.0).count();
+        assert!(first_bin > 80);
+    }
+
+    #[test]
+    fn new_data_clamped_test() {
+        let inputs = Matrix::new(3, 2, vec![0.0, 10.0, 1.0, 20.0, 2.0, 30.0]);
+
+        let mut transformer = KBinsDiscretizerFitter::new(2, BinStrategy::Uniform).fit(&inputs).unwrap();
+        let transformed = transformer.transform(Matrix::new(2, 2, vec![-5.0, 100.0, 1.5, 12.0])).unwrap();
+
+        assert_eq!(transformed.into_vec(), vec![0.0, 1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn too_few_bins_test() {
+        let inputs = Matrix::new(2, 1, vec![0.0, 1.0]);
+
+        let transformer = KBinsDiscretizerFitter::new(1, BinStrategy::Uniform).fit(&inputs);
+        assert!(transformer.is_err());
+    }
+
+    #[test]
+    fn nan_data_test() {
+        let inputs = Matrix::new(2, 2, vec![f64::NAN; 4]);
+
+        let transformer = KBinsDiscretizerFitter::default().fit(&inputs);
+        assert!(transformer.is_err());
+    }
+
+    #[test]
+    fn non_finite_transform_test() {
+        let inputs = Matrix::new(3, 1, vec![0.0, 1.0, 2.0]);
+        let mut transformer = KBinsDiscretizerFitter::new(2, BinStrategy::Uniform).fit(&inputs).unwrap();
+
+        for &bad in &[f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let res = transformer.transform(Matrix::new(2, 1, vec![1.0, bad]));
+            assert!(res.is_err());
+        }
+    }
+
+    #[test]
+    fn wrong_transform_size_test() {
+        let inputs = Matrix::new(2, 2, vec![-1.0f32, 2.0, 0.0, 3.0]);
+
+        let mut transformer = KBinsDiscretizerFitter::default().fit(&inputs).unwrap();
+        let res = transformer.transform(matrix![1.0, 2.0, 3.0; 4.0, 5.0, 6.0]);
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,192 @@
+//! The Feature Hasher
+//!
+//! This module contains the `FeatureHasher`.
+//!
+//! The `FeatureHasher` maps each sample, given as a list of tokens such as
+//! categorical values or words, to a row with a fixed number of columns.
+//! Each token is hashed to a column and the entries of a row count the
+//! tokens hashed to each column, so that colliding tokens are summed.
+//! This is known as the hashing trick. Unlike one-hot encoding the number of
+//! columns does not depend on the number of distinct tokens, and no
+//! vocabulary needs to be fitted or stored.
+//!
+//! With the alternate sign option each token also contributes with a sign
+//! given by another bit of its hash. Colliding tokens then tend to cancel
+//! out, so the inner products of the rows are unbiased estimates of the
+//! inner products of the exact token counts.
+//!
+//! The tokens are hashed with 64-bit FNV-1a, so the columns are the same
+//! on every run and platform.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::data::transforms::FeatureHasher;
+//! use rusty_machine::linalg::BaseMatrix;
+//!
+//! let samples = vec![vec!["user=8812", "country=fr"],
+//!                    vec!["user=17", "country=fr", "country=fr"]];
+//!
+//! let hasher = FeatureHasher::new(16);
+//! let features = hasher.transform(&samples);
+//!
+//! assert_eq!(features.cols(), 16);
+//! assert_eq!(features.row(1).iter().sum::<f64>(), 3.0);
+//! ```
+
+use linalg::Matrix;
+
+use std::hash::{Hash, Hasher};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// The 64-bit FNV-1a hash function.
+struct FnvHasher(u64);
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= u64::from(b);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+/// The `FeatureHasher`
+///
+/// Maps lists of tokens to rows with a fixed number of columns.
+///
+/// See the module description for more information.
+#[derive(Clone, Copy, Debug)]
+pub struct FeatureHasher {
+    n_features: usize,
+    alternate_sign: bool,
+}
+
+impl FeatureHasher {
+    /// Constructs a `FeatureHasher` which maps samples
+    /// to rows with `n_features` columns.
+    ///
+    /// # Panics
+    ///
+    /// - `n_features` is zero.
+    pub fn new(n_features: usize) -> FeatureHasher {
+        assert!(n_features > 0, "The number of features must be positive.");
+
+        FeatureHasher {
+            n_features,
+            alternate_sign: false,
+        }
+    }
+
+    /// Sets whether each token contributes with a sign taken from its hash.
+    pub fn with_alternate_sign(mut self, alternate_sign: bool) -> Self {
+        self.alternate_sign = alternate_sign;
+        self
+    }
+
+    /// The number of columns in the transformed data.
+    pub fn n_features(&self) -> usize {
+        self.n_features
+    }
+
+    /// Whether each token contributes with a sign taken from its hash.
+    pub fn alternate_sign(&self) -> bool {
+        self.alternate_sign
+    }
+
+    /// The column and sign of the given token.
+    fn bucket<T: Hash>(&self, token: &T) -> (usize, f64) {
+        let mut hasher = FnvHasher(FNV_OFFSET_BASIS);
+        token.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let column = (hash % self.n_features as u64) as usize;
+        // The top bit is independent of the column unless
+        // `n_features` is very large.
+        if self.alternate_sign && hash >> 63 == 1 {
+            (column, -1f64)
+        } else {
+            (column, 1f64)
+        }
+    }
+
+    /// Maps each sample to a row of hashed token counts.
+    ///
+    /// Each sample is a list of tokens, and the output
+    /// has one row per sample and `n_features` columns.
+    pub fn transform<T, S>(&self, samples: &[S]) -> Matrix<f64>
+        where T: Hash,
+              S: AsRef<[T]>
+    {
+        let mut data = vec![0f64; samples.len() * self.n_features];
+
+        for (i, sample) in samples.iter().enumerate() {
+            let row = &mut data[i * self.n_features..(i + 1) * self.n_features];
+            for token in sample.as_ref() {
+                let (column, sign) = self.bucket(token);
+                row[column] += sign;
+            }
+        }
+
+        Matrix::new(samples.len(), self.n_features, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FeatureHasher;
+    use linalg::BaseMatrix;
+
+    #[test]
+    fn fixed_dimension_test() {
+        let hasher = FeatureHasher::new(32);
+
+        let few = hasher.transform(&[vec![1usize, 2]]);
+        let many = hasher.transform(&[(0..10000usize).collect::<Vec<usize>>(), vec![]]);
+
+        assert_eq!(few.cols(), 32);
+        assert_eq!(many.cols(), 32);
+        assert_eq!(many.rows(), 2);
+        assert_eq!(many.row(0).iter().sum::<f64>(), 10000.0);
+        assert!(many.row(1).iter().all(|&x| x == 0.0));
+    }
+
+    #[test]
+    fn distinct_tokens_distinct_buckets_test() {
+        let hasher = FeatureHasher::new(1 << 16);
+        let tokens = (0..100).map(|i| format!("user_{}", i)).collect::<Vec<String>>();
+
+        let features = hasher.transform(&[tokens]);
+        let occupied = features.iter().filter(|&&x| x != 0.0).count();
+
+        assert!(occupied >= 99, "Only {} buckets used", occupied);
+        assert!(features.iter().all(|&x| x == 0.0 || x == 1.0));
+    }
+
+    #[test]
+    fn collisions_are_summed_test() {
+        let tokens = (0..50).map(|i| format!("id{}", i)).collect::<Vec<String>>();
+
+        let unsigned = FeatureHasher::new(1).transform(&[tokens.clone()]);
+        assert_eq!(unsigned[[0, 0]], 50.0);
+
+        let signed = FeatureHasher::new(1).with_alternate_sign(true).transform(&[tokens]);
+        assert!(signed[[0, 0]].abs() < 50.0);
+    }
+
+    #[test]
+    fn deterministic_test() {
+        let samples = vec![vec!["a", "b"], vec!["b", "c", "c"]];
+        let hasher = FeatureHasher::new(8).with_alternate_sign(true);
+
+        assert_eq!(hasher.transform(&samples), hasher.transform(&samples));
+        assert_eq!(hasher.transform(&samples[1..]).row(0).raw_slice(),
+                   hasher.transform(&samples).row(1).raw_slice());
+    }
+}
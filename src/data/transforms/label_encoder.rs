@@ -0,0 +1,158 @@
+//! The Label Encoder
+//!
+//! This module contains the `LabelEncoder`.
+//!
+//! The `LabelEncoder` maps arbitrary category identifiers to contiguous
+//! labels in `0..K`, as expected by the classifiers in rusty-machine,
+//! and maps these labels back to the original identifiers.
+//!
+//! The categories are ordered, so the smallest identifier is mapped to `0`.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::data::transforms::LabelEncoder;
+//!
+//! let categories = vec!["cat", "dog", "cat", "bird"];
+//!
+//! // Fit the encoder to the distinct categories.
+//! let encoder = LabelEncoder::fit(&categories).unwrap();
+//!
+//! // Map the categories to labels...
+//! let labels = encoder.transform(&categories).unwrap();
+//! assert_eq!(labels, vec![1, 2, 1, 0]);
+//!
+//! // ...and back again.
+//! let original = encoder.inv_transform(&labels).unwrap();
+//! assert_eq!(original, categories);
+//! ```
+
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
+
+/// The `LabelEncoder`
+///
+/// Stores the distinct categories seen when fitting.
+/// The label of each category is its position in this ordered set.
+///
+/// See the module description for more information.
+#[derive(Debug)]
+pub struct LabelEncoder<T: Ord + Clone> {
+    classes: Vec<T>,
+}
+
+impl<T: Ord + Clone> LabelEncoder<T> {
+    /// Fit a `LabelEncoder` to the distinct values in the inputs.
+    ///
+    /// # Failures
+    ///
+    /// - The inputs are empty.
+    pub fn fit(inputs: &[T]) -> LearningResult<LabelEncoder<T>> {
+        if inputs.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Cannot fit label encoder to empty data."));
+        }
+
+        let mut classes = inputs.to_vec();
+        classes.sort();
+        classes.dedup();
+
+        Ok(LabelEncoder { classes })
+    }
+
+    /// The distinct categories, ordered by their label.
+    pub fn classes(&self) -> &[T] {
+        &self.classes
+    }
+
+    /// The number of distinct categories.
+    pub fn n_classes(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// Map each input category to its label.
+    ///
+    /// # Failures
+    ///
+    /// - Some input was not seen when fitting.
+    pub fn transform(&self, inputs: &[T]) -> LearningResult<Vec<usize>> {
+        inputs.iter()
+            .map(|x| {
+                self.classes.binary_search(x).map_err(|_| {
+                    Error::new(ErrorKind::InvalidData,
+                               "Input contains a value which was not seen when fitting.")
+                })
+            })
+            .collect()
+    }
+
+    /// Map each label back to its category.
+    ///
+    /// # Failures
+    ///
+    /// - Some label is not less than the number of categories.
+    pub fn inv_transform(&self, labels: &[usize]) -> LearningResult<Vec<T>> {
+        labels.iter()
+            .map(|&l| {
+                self.classes.get(l).cloned().ok_or_else(|| {
+                    Error::new(ErrorKind::InvalidData,
+                               "Label is not less than the number of classes.")
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LabelEncoder;
+
+    #[test]
+    fn round_trip_test() {
+        let inputs = vec![40usize, 7, 7, 1000, 40, 3];
+
+        let encoder = LabelEncoder::fit(&inputs).unwrap();
+        assert_eq!(encoder.classes(), &[3, 7, 40, 1000]);
+        assert_eq!(encoder.n_classes(), 4);
+
+        let labels = encoder.transform(&inputs).unwrap();
+        assert_eq!(labels, vec![2, 1, 1, 3, 2, 0]);
+
+        let original = encoder.inv_transform(&labels).unwrap();
+        assert_eq!(original, inputs);
+    }
+
+    #[test]
+    fn string_round_trip_test() {
+        let inputs = vec!["setosa".to_string(), "virginica".to_string(), "versicolor".to_string()];
+
+        let encoder = LabelEncoder::fit(&inputs).unwrap();
+        let labels = encoder.transform(&inputs).unwrap();
+        assert_eq!(labels, vec![0, 2, 1]);
+
+        let original = encoder.inv_transform(&labels).unwrap();
+        assert_eq!(original, inputs);
+    }
+
+    #[test]
+    fn unseen_value_test() {
+        let encoder = LabelEncoder::fit(&[1, 2, 3]).unwrap();
+
+        let res = encoder.transform(&[1, 4]);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn invalid_label_test() {
+        let encoder = LabelEncoder::fit(&[1, 2, 3]).unwrap();
+
+        let res = encoder.inv_transform(&[0, 3]);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn empty_fit_test() {
+        let inputs: Vec<usize> = vec![];
+        assert!(LabelEncoder::fit(&inputs).is_err());
+    }
+}
@@ -35,7 +35,7 @@ use rulinalg::utils;
 use libnum::Float;
 
 /// A builder used to construct a `MinMaxScaler`
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct MinMaxFitter<T: Float> {
     scaled_min: T,
     scaled_max: T
@@ -140,7 +140,7 @@ impl<T: Float> TransformFitter<Matrix<T>, MinMaxScaler<T>> for MinMaxFitter<T> {
 /// and maximum per column.
 ///
 /// See the module description for more information.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct MinMaxScaler<T: Float> {
     /// Values to scale each column by
     scale_factors: Vector<T>,
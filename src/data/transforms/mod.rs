@@ -25,17 +25,31 @@
 //! let transformed = scaler.transform(data).expect("Failed to transformer data");
 //! ```
 
+pub mod discretize;
+pub mod feature_hasher;
+pub mod label_encoder;
 pub mod minmax;
 pub mod normalize;
+pub mod nystroem;
+pub mod polynomial;
+pub mod random_fourier;
 pub mod standardize;
 pub mod shuffle;
+pub mod variance_threshold;
 
 use learning::LearningResult;
 
+pub use self::discretize::KBinsDiscretizerFitter;
+pub use self::feature_hasher::FeatureHasher;
+pub use self::label_encoder::LabelEncoder;
 pub use self::minmax::MinMaxFitter;
 pub use self::normalize::Normalizer;
-pub use self::shuffle::Shuffler;
+pub use self::nystroem::NystroemFitter;
+pub use self::polynomial::PolynomialFitter;
+pub use self::random_fourier::RandomFourierFitter;
+pub use self::shuffle::{Shuffler, ReservoirShuffler};
 pub use self::standardize::StandardizerFitter;
+pub use self::variance_threshold::VarianceThresholdFitter;
 
 /// A trait used to construct Transformers which must first be fitted
 pub trait TransformFitter<U, T: Transformer<U>> {
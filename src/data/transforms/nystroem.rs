@@ -0,0 +1,273 @@
+//! The Nyström Kernel Approximation Transformer
+//!
+//! This module contains the `Nystroem` transformer.
+//!
+//! Like the `RandomFourierFeatures` transformer, the `Nystroem` transformer
+//! maps samples to explicit features whose inner products approximate a
+//! kernel, so that a linear model on the features approximates a kernel
+//! model. Unlike random Fourier features it works for any `Kernel`.
+//!
+//! Fitting picks `n_components` random landmark samples from the inputs.
+//! A sample is mapped to its kernel with each landmark, multiplied by
+//! W<sup>-1/2</sup>, where W is the kernel matrix of the landmarks. The inner
+//! products of the features are then the Nyström approximation of the kernel
+//! matrix, which is exact for the landmarks and improves as more landmarks
+//! are used.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::data::transforms::{Transformer, TransformFitter, NystroemFitter};
+//! use rusty_machine::learning::toolkit::kernel::SquaredExp;
+//! use rusty_machine::linalg::{Matrix, BaseMatrix};
+//!
+//! let inputs = Matrix::new(4, 2, vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+//!
+//! // Approximate the kernel using 3 of the samples as landmarks.
+//! let mut transformer = NystroemFitter::new(SquaredExp::default(), 3)
+//!                                      .with_seed(&[1, 2, 3])
+//!                                      .fit(&inputs)
+//!                                      .unwrap();
+//!
+//! let transformed = transformer.transform(inputs).unwrap();
+//! assert_eq!(transformed.cols(), 3);
+//! ```
+
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
+use learning::toolkit::kernel::Kernel;
+use learning::toolkit::rand_utils::in_place_fisher_yates_with_rng;
+use linalg::{Matrix, BaseMatrix};
+use super::{Transformer, TransformFitter};
+
+use rand::{SeedableRng, StdRng, thread_rng};
+
+/// Eigenvalues of the landmark kernel matrix are clipped
+/// to this value before being inverted.
+const MIN_EIGENVALUE: f64 = 1e-12;
+
+/// A builder used to construct a `Nystroem` transformer
+#[derive(Clone, Debug)]
+pub struct NystroemFitter<K: Kernel> {
+    kernel: K,
+    n_components: usize,
+    seed: Option<Vec<usize>>,
+}
+
+impl<K: Kernel> NystroemFitter<K> {
+    /// Construct a new `NystroemFitter` which approximates `kernel`
+    /// using `n_components` landmark samples.
+    ///
+    /// Note that this function does not create a `Transformer`
+    /// only a builder which can be used to produce a fitted `Transformer`.
+    ///
+    /// # Panics
+    ///
+    /// - `n_components` is zero.
+    pub fn new(kernel: K, n_components: usize) -> NystroemFitter<K> {
+        assert!(n_components > 0, "The number of components must be positive.");
+
+        NystroemFitter {
+            kernel,
+            n_components,
+            seed: None,
+        }
+    }
+
+    /// Chooses the landmarks using a `StdRng` seeded with `seed`,
+    /// so that the fitted transformer is reproducible.
+    pub fn with_seed(mut self, seed: &[usize]) -> Self {
+        self.seed = Some(seed.to_vec());
+        self
+    }
+
+    /// The kernel to approximate.
+    pub fn kernel(&self) -> &K {
+        &self.kernel
+    }
+
+    /// The number of landmark samples.
+    pub fn n_components(&self) -> usize {
+        self.n_components
+    }
+}
+
+/// The kernel between each row of `m1` and each row of `m2`.
+fn kernel_matrix<K: Kernel>(kernel: &K, m1: &Matrix<f64>, m2: &Matrix<f64>) -> Matrix<f64> {
+    let mut data = Vec::with_capacity(m1.rows() * m2.rows());
+    for row1 in m1.row_iter() {
+        for row2 in m2.row_iter() {
+            data.push(kernel.kernel(row1.raw_slice(), row2.raw_slice()));
+        }
+    }
+
+    Matrix::new(m1.rows(), m2.rows(), data)
+}
+
+impl<K: Kernel> TransformFitter<Matrix<f64>, Nystroem<K>> for NystroemFitter<K> {
+    fn fit(self, inputs: &Matrix<f64>) -> LearningResult<Nystroem<K>> {
+        if self.n_components > inputs.rows() {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "The number of components cannot exceed the number of samples."));
+        }
+        if inputs.data().iter().any(|x| !x.is_finite()) {
+            return Err(Error::new(ErrorKind::InvalidData, "Some data point is non-finite."));
+        }
+
+        let mut indices = (0..inputs.rows()).collect::<Vec<usize>>();
+        match self.seed {
+            Some(ref seed) => in_place_fisher_yates_with_rng(&mut indices, &mut StdRng::from_seed(seed)),
+            None => in_place_fisher_yates_with_rng(&mut indices, &mut thread_rng()),
+        }
+        indices.truncate(self.n_components);
+        indices.sort();
+
+        let landmarks = inputs.select_rows(&indices);
+        let landmark_gram = kernel_matrix(&self.kernel, &landmarks, &landmarks);
+
+        // The Gram matrix is symmetric, so its singular value
+        // decomposition is also an eigendecomposition.
+        let (s, u, _) = landmark_gram.svd()?;
+        let mut scaled = u.clone();
+        for i in 0..scaled.rows() {
+            for j in 0..scaled.cols() {
+                scaled[[i, j]] /= s[[j, j]].max(MIN_EIGENVALUE).sqrt();
+            }
+        }
+
+        Ok(Nystroem {
+            kernel: self.kernel,
+            landmarks,
+            normalization: scaled * u.transpose(),
+        })
+    }
+}
+
+/// The Nystroem
+///
+/// The Nystroem provides an implementation of `Transformer`
+/// which maps inputs to features approximating a kernel.
+///
+/// See the module description for more information.
+#[derive(Clone, Debug)]
+pub struct Nystroem<K: Kernel> {
+    /// The approximated kernel
+    kernel: K,
+    /// The landmark samples, one per row
+    landmarks: Matrix<f64>,
+    /// The inverse square root of the landmark kernel matrix
+    normalization: Matrix<f64>,
+}
+
+impl<K: Kernel> Nystroem<K> {
+    /// The approximated kernel.
+    pub fn kernel(&self) -> &K {
+        &self.kernel
+    }
+
+    /// The landmark samples, one per row.
+    pub fn landmarks(&self) -> &Matrix<f64> {
+        &self.landmarks
+    }
+}
+
+impl<K: Kernel> Transformer<Matrix<f64>> for Nystroem<K> {
+    fn transform(&mut self, inputs: Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        if self.landmarks.cols() != inputs.cols() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Input data has different number of columns from fitted data."));
+        }
+
+        Ok(kernel_matrix(&self.kernel, &inputs, &self.landmarks) * &self.normalization)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{Transformer, TransformFitter};
+    use learning::toolkit::kernel::{Polynomial, SquaredExp};
+    use linalg::{Matrix, BaseMatrix};
+
+    use std::f64;
+
+    fn test_inputs() -> Matrix<f64> {
+        let data = (0..80).map(|i| (i as f64 * 0.73).sin() * 2.0).collect::<Vec<f64>>();
+        Matrix::new(40, 2, data)
+    }
+
+    /// The largest difference between the approximate and exact kernel matrices.
+    fn approximation_error<K: Kernel + Clone>(kernel: K, n_components: usize) -> f64 {
+        let inputs = test_inputs();
+        let exact = kernel_matrix(&kernel, &inputs, &inputs);
+
+        let mut transformer = NystroemFitter::new(kernel, n_components)
+            .with_seed(&[1, 2, 3])
+            .fit(&inputs)
+            .unwrap();
+        let features = transformer.transform(inputs).unwrap();
+        let approx = &features * features.transpose();
+
+        approx.iter().zip(exact.iter()).map(|(a, e)| (a - e).abs()).fold(0f64, f64::max)
+    }
+
+    #[test]
+    fn converges_to_exact_test() {
+        let kernel = SquaredExp::new(1.0, 1.0);
+        let errors = [5, 10, 20, 40]
+            .iter()
+            .map(|&m| approximation_error(kernel, m))
+            .collect::<Vec<f64>>();
+
+        for w in errors.windows(2) {
+            assert!(w[1] < w[0], "{:?}", errors);
+        }
+        assert!(errors[3] < 1e-6);
+    }
+
+    #[test]
+    fn low_rank_kernel_test() {
+        // The quadratic kernel on two features has rank 6,
+        // so a few landmarks reproduce it exactly.
+        let kernel = Polynomial::new(1.0, 1.0, 2.0);
+        assert!(approximation_error(kernel, 10) < 1e-6);
+    }
+
+    #[test]
+    fn landmarks_are_samples_test() {
+        let inputs = test_inputs();
+        let transformer = NystroemFitter::new(SquaredExp::default(), 5)
+            .with_seed(&[4])
+            .fit(&inputs)
+            .unwrap();
+
+        let landmarks = transformer.landmarks();
+        assert_eq!(landmarks.rows(), 5);
+        for row in landmarks.row_iter() {
+            assert!(inputs.row_iter().any(|r| r.raw_slice() == row.raw_slice()));
+        }
+    }
+
+    #[test]
+    fn too_many_components_test() {
+        let inputs = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+
+        assert!(NystroemFitter::new(SquaredExp::default(), 4).fit(&inputs).is_err());
+    }
+
+    #[test]
+    fn wrong_transform_size_test() {
+        let inputs = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+
+        let mut transformer = NystroemFitter::new(SquaredExp::default(), 2).fit(&inputs).unwrap();
+        let res = transformer.transform(Matrix::new(1, 3, vec![1.0, 2.0, 3.0]));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn nan_data_test() {
+        let inputs = Matrix::new(2, 2, vec![1.0, f64::NAN, 3.0, 4.0]);
+
+        assert!(NystroemFitter::new(SquaredExp::default(), 2).fit(&inputs).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+//! The Polynomial Features Transformer
+//!
+//! This module contains the `PolynomialFeatures` transformer.
+//!
+//! The `PolynomialFeatures` transformer maps each sample to all of the
+//! products of its features with total degree between one and `degree`.
+//! For two features `a` and `b` and degree two the outputs are
+//! `[a, b, a², ab, b²]`. A linear model trained on the transformed
+//! inputs can then fit polynomial curves and interactions between
+//! features.
+//!
+//! No constant column is added, as the linear models in `learning`
+//! fit their own intercept.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::data::transforms::{Transformer, TransformFitter, PolynomialFitter};
+//! use rusty_machine::linalg::Matrix;
+//!
+//! let inputs = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+//!
+//! let mut transformer = PolynomialFitter::new(2).fit(&inputs).unwrap();
+//! let transformed = transformer.transform(inputs).unwrap();
+//!
+//! assert_eq!(transformed, Matrix::new(2, 5, vec![1.0, 2.0, 1.0, 2.0, 4.0,
+//!                                                3.0, 4.0, 9.0, 12.0, 16.0]));
+//! ```
+
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
+use linalg::{Matrix, BaseMatrix};
+use super::{Transformer, TransformFitter};
+
+/// A builder used to construct a `PolynomialFeatures` transformer
+#[derive(Clone, Copy, Debug)]
+pub struct PolynomialFitter {
+    degree: usize,
+}
+
+impl PolynomialFitter {
+    /// Construct a new `PolynomialFitter` which produces all products
+    /// of the input features up to the given total degree.
+    ///
+    /// Note that this function does not create a `Transformer`
+    /// only a builder which can be used to produce a fitted `Transformer`.
+    ///
+    /// # Panics
+    ///
+    /// - `degree` is zero.
+    pub fn new(degree: usize) -> PolynomialFitter {
+        assert!(degree > 0, "The degree must be positive.");
+
+        PolynomialFitter {
+            degree
+        }
+    }
+
+    /// The highest total degree of the products.
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+}
+
+impl TransformFitter<Matrix<f64>, PolynomialFeatures> for PolynomialFitter {
+    fn fit(self, inputs: &Matrix<f64>) -> LearningResult<PolynomialFeatures> {
+        if inputs.cols() == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "The inputs have no columns."));
+        }
+        if inputs.data().iter().any(|x| !x.is_finite()) {
+            return Err(Error::new(ErrorKind::InvalidData, "Some data point is non-finite."));
+        }
+
+        // The terms of each degree extend the terms of the previous
+        // degree by a column no smaller than their last one.
+        let mut terms = (0..inputs.cols()).map(|j| vec![j]).collect::<Vec<Vec<usize>>>();
+        let mut start = 0;
+        for _ in 1..self.degree {
+            let end = terms.len();
+            for t in start..end {
+                let last = terms[t][terms[t].len() - 1];
+                for j in last..inputs.cols() {
+                    let mut term = terms[t].clone();
+                    term.push(j);
+                    terms.push(term);
+                }
+            }
+            start = end;
+        }
+
+        Ok(PolynomialFeatures {
+            n_inputs: inputs.cols(),
+            terms
+        })
+    }
+}
+
+/// The PolynomialFeatures
+///
+/// The PolynomialFeatures provides an implementation of `Transformer`
+/// which maps inputs to the products of their features.
+///
+/// See the module description for more information.
+#[derive(Clone, Debug)]
+pub struct PolynomialFeatures {
+    /// The number of columns in the fitted data
+    n_inputs: usize,
+    /// The input columns multiplied together for each output column
+    terms: Vec<Vec<usize>>,
+}
+
+impl PolynomialFeatures {
+    /// The input columns multiplied together for each output column.
+    ///
+    /// A column appears once for each power it is raised to.
+    pub fn terms(&self) -> &[Vec<usize>] {
+        &self.terms
+    }
+}
+
+impl Transformer<Matrix<f64>> for PolynomialFeatures {
+    fn transform(&mut self, inputs: Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        if self.n_inputs != inputs.cols() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Input data has different number of columns from fitted data."));
+        }
+
+        let mut data = Vec::with_capacity(inputs.rows() * self.terms.len());
+        for row in inputs.row_iter() {
+            let row = row.raw_slice();
+            for term in &self.terms {
+                data.push(term.iter().fold(1f64, |prod, &j| prod * row[j]));
+            }
+        }
+
+        Ok(Matrix::new(inputs.rows(), self.terms.len(), data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{Transformer, TransformFitter};
+    use linalg::Matrix;
+
+    use std::f64;
+
+    #[test]
+    fn cubic_terms_test() {
+        let inputs = Matrix::new(1, 2, vec![2.0, 3.0]);
+
+        let mut transformer = PolynomialFitter::new(3).fit(&inputs).unwrap();
+        assert_eq!(transformer.terms().len(), 9);
+        assert_eq!(transformer.terms()[5], vec![0, 0, 0]);
+        assert_eq!(transformer.terms()[8], vec![1, 1, 1]);
+
+        let transformed = transformer.transform(inputs).unwrap();
+        assert_eq!(transformed.into_vec(),
+                   vec![2.0, 3.0, 4.0, 6.0, 9.0, 8.0, 12.0, 18.0, 27.0]);
+    }
+
+    #[test]
+    fn degree_one_test() {
+        let inputs = Matrix::new(2, 3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+        let mut transformer = PolynomialFitter::new(1).fit(&inputs).unwrap();
+        assert_eq!(transformer.transform(inputs.clone()).unwrap(), inputs);
+    }
+
+    #[test]
+    fn wrong_transform_size_test() {
+        let inputs = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+
+        let mut transformer = PolynomialFitter::new(2).fit(&inputs).unwrap();
+        let res = transformer.transform(Matrix::new(1, 3, vec![1.0, 2.0, 3.0]));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn nan_data_test() {
+        let inputs = Matrix::new(2, 2, vec![1.0, f64::NAN, 3.0, 4.0]);
+
+        assert!(PolynomialFitter::new(2).fit(&inputs).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_degree_test() {
+        let _ = PolynomialFitter::new(0);
+    }
+}
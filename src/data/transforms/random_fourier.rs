@@ -0,0 +1,307 @@
+//! The Random Fourier Features Transformer
+//!
+//! This module contains the `RandomFourierFeatures` transformer.
+//!
+//! Kernel models such as the SVM and Gaussian processes compute the
+//! kernel between every pair of training samples, which is slow for
+//! large datasets. The `RandomFourierFeatures` transformer instead maps
+//! each sample to `n_features` random cosine features, chosen so that
+//! the inner product of two transformed samples approximates the squared
+//! exponential kernel between them:
+//!
+//! k(x,y) = exp(-||x-y||<sup>2</sup> / 2l<sup>2</sup>)
+//!
+//! Each feature is `sqrt(2 / n_features) * cos(w`<sup>T</sup>`x + b)`, where the
+//! frequencies `w` are drawn from a normal distribution with standard
+//! deviation `1 / l` and the offsets `b` are uniform on `[0, 2π)`. A linear
+//! model trained on the transformed inputs then approximates the kernel
+//! model, and the approximation improves as more features are used.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::data::transforms::{Transformer, TransformFitter, RandomFourierFitter};
+//! use rusty_machine::linalg::{Matrix, BaseMatrix};
+//!
+//! let inputs = Matrix::new(3, 2, vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+//!
+//! // Approximate a kernel with length scale 2 using 100 features.
+//! let mut transformer = RandomFourierFitter::new(100, 2.0)
+//!                                           .with_seed(&[1, 2, 3])
+//!                                           .fit(&inputs)
+//!                                           .unwrap();
+//!
+//! let transformed = transformer.transform(inputs).unwrap();
+//! assert_eq!(transformed.cols(), 100);
+//! ```
+
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
+use linalg::{Matrix, Vector, BaseMatrix, BaseMatrixMut};
+use super::{Transformer, TransformFitter};
+
+use rand::{Rng, SeedableRng, StdRng, thread_rng};
+use rand::distributions::normal::StandardNormal;
+
+use std::f64;
+
+/// A builder used to construct a `RandomFourierFeatures` transformer
+#[derive(Clone, Debug)]
+pub struct RandomFourierFitter {
+    n_features: usize,
+    length_scale: f64,
+    seed: Option<Vec<usize>>,
+}
+
+impl RandomFourierFitter {
+    /// Construct a new `RandomFourierFitter` which approximates the
+    /// squared exponential kernel with the given length scale using
+    /// `n_features` random features.
+    ///
+    /// Note that this function does not create a `Transformer`
+    /// only a builder which can be used to produce a fitted `Transformer`.
+    ///
+    /// # Panics
+    ///
+    /// - `n_features` is zero.
+    /// - `length_scale` is not positive.
+    pub fn new(n_features: usize, length_scale: f64) -> RandomFourierFitter {
+        assert!(n_features > 0, "The number of features must be positive.");
+        assert!(length_scale > 0f64, "The length scale must be positive.");
+
+        RandomFourierFitter {
+            n_features,
+            length_scale,
+            seed: None,
+        }
+    }
+
+    /// Draws the random frequencies from a `StdRng` seeded with `seed`,
+    /// so that the fitted transformer is reproducible.
+    pub fn with_seed(mut self, seed: &[usize]) -> Self {
+        self.seed = Some(seed.to_vec());
+        self
+    }
+
+    /// The number of random features.
+    pub fn n_features(&self) -> usize {
+        self.n_features
+    }
+
+    /// The length scale of the approximated kernel.
+    pub fn length_scale(&self) -> f64 {
+        self.length_scale
+    }
+
+    /// Draws the frequencies and offsets for inputs with `n_cols` columns.
+    fn sample<R: Rng>(&self, n_cols: usize, rng: &mut R) -> RandomFourierFeatures {
+        let frequencies = (0..n_cols * self.n_features)
+            .map(|_| {
+                let StandardNormal(z) = rng.gen::<StandardNormal>();
+                z / self.length_scale
+            })
+            .collect::<Vec<f64>>();
+
+        let offsets = (0..self.n_features)
+            .map(|_| rng.gen_range(0f64, 2f64 * f64::consts::PI))
+            .collect::<Vec<f64>>();
+
+        RandomFourierFeatures {
+            frequencies: Matrix::new(n_cols, self.n_features, frequencies),
+            offsets: Vector::new(offsets),
+        }
+    }
+}
+
+impl TransformFitter<Matrix<f64>, RandomFourierFeatures> for RandomFourierFitter {
+    fn fit(self, inputs: &Matrix<f64>) -> LearningResult<RandomFourierFeatures> {
+        if inputs.cols() == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "The inputs have no columns."));
+        }
+        if inputs.data().iter().any(|x| !x.is_finite()) {
+            return Err(Error::new(ErrorKind::InvalidData, "Some data point is non-finite."));
+        }
+
+        match self.seed {
+            Some(ref seed) => Ok(self.sample(inputs.cols(), &mut StdRng::from_seed(seed))),
+            None => Ok(self.sample(inputs.cols(), &mut thread_rng())),
+        }
+    }
+}
+
+/// The RandomFourierFeatures
+///
+/// The RandomFourierFeatures provides an implementation of `Transformer`
+/// which maps inputs to random cosine features approximating the
+/// squared exponential kernel.
+///
+/// See the module description for more information.
+#[derive(Clone, Debug)]
+pub struct RandomFourierFeatures {
+    /// The random frequencies, one column per feature
+    frequencies: Matrix<f64>,
+    /// The random offset of each feature
+    offsets: Vector<f64>,
+}
+
+impl RandomFourierFeatures {
+    /// The random frequencies, with one column per feature.
+    pub fn frequencies(&self) -> &Matrix<f64> {
+        &self.frequencies
+    }
+
+    /// The random offset of each feature.
+    pub fn offsets(&self) -> &Vector<f64> {
+        &self.offsets
+    }
+}
+
+impl Transformer<Matrix<f64>> for RandomFourierFeatures {
+    fn transform(&mut self, inputs: Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        if self.frequencies.rows() != inputs.cols() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Input data has different number of columns from fitted data."));
+        }
+
+        let scale = (2f64 / self.offsets.size() as f64).sqrt();
+
+        let mut projected = inputs * &self.frequencies;
+        for mut row in projected.row_iter_mut() {
+            for (x, b) in row.iter_mut().zip(self.offsets.iter()) {
+                *x = scale * (*x + b).cos();
+            }
+        }
+
+        Ok(projected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{Transformer, TransformFitter};
+    use learning::SupModel;
+    use learning::lin_reg::LinRegressor;
+    use learning::svm::SVM;
+    use learning::toolkit::kernel::{Kernel, SquaredExp};
+    use linalg::{Matrix, Vector, BaseMatrix};
+
+    use std::f64;
+
+    /// Points on two concentric rings, labelled -1 on the
+    /// inner ring and 1 on the outer ring.
+    fn rings(n: usize, offset: f64) -> (Matrix<f64>, Vector<f64>) {
+        let mut data = Vec::with_capacity(2 * n);
+        let mut labels = Vec::with_capacity(n);
+        for i in 0..n {
+            let angle = (i as f64 + offset) * 2.4;
+            let (radius, label) = if i % 2 == 0 { (1.0, -1.0) } else { (2.5, 1.0) };
+            let radius = radius + 0.3 * ((i as f64 + offset) * 1.3).sin();
+
+            data.push(radius * angle.cos());
+            data.push(radius * angle.sin());
+            labels.push(label);
+        }
+
+        (Matrix::new(n, 2, data), Vector::new(labels))
+    }
+
+    fn accuracy(outputs: &Vector<f64>, targets: &Vector<f64>) -> f64 {
+        let correct = outputs.iter()
+            .zip(targets.iter())
+            .filter(|&(o, t)| o.signum() == *t)
+            .count();
+        correct as f64 / targets.size() as f64
+    }
+
+    /// The test accuracy of least squares on `n_features` random features.
+    fn linear_accuracy(n_features: usize) -> f64 {
+        let (train_inputs, train_targets) = rings(300, 0.0);
+        let (test_inputs, test_targets) = rings(200, 0.5);
+
+        let mut transformer = RandomFourierFitter::new(n_features, 1.0)
+            .with_seed(&[1, 2, 3])
+            .fit(&train_inputs)
+            .unwrap();
+        let train_features = transformer.transform(train_inputs).unwrap();
+        let test_features = transformer.transform(test_inputs).unwrap();
+
+        let mut model = LinRegressor::default();
+        model.train(&train_features, &train_targets).unwrap();
+        accuracy(&model.predict(&test_features).unwrap(), &test_targets)
+    }
+
+    #[test]
+    fn approaches_kernel_svm_test() {
+        let (train_inputs, train_targets) = rings(300, 0.0);
+        let (test_inputs, test_targets) = rings(200, 0.5);
+
+        let mut svm = SVM::new(SquaredExp::new(1.0, 1.0), 0.3);
+        svm.optim_iters = 5000;
+        svm.train(&train_inputs, &train_targets).unwrap();
+        let svm_accuracy = accuracy(&svm.predict(&test_inputs).unwrap(), &test_targets);
+
+        let few = linear_accuracy(2);
+        let many = linear_accuracy(200);
+
+        assert!(svm_accuracy > 0.95);
+        assert!(few < svm_accuracy - 0.1);
+        assert!(many > svm_accuracy - 0.05);
+    }
+
+    #[test]
+    fn approximates_kernel_test() {
+        let inputs = Matrix::new(3, 2, vec![0.0, 0.0, 0.5, -0.5, 1.0, 2.0]);
+
+        let mut transformer = RandomFourierFitter::new(20000, 1.5)
+            .with_seed(&[4, 5, 6])
+            .fit(&inputs)
+            .unwrap();
+        let features = transformer.transform(inputs.clone()).unwrap();
+
+        let ker = SquaredExp::new(1.5, 1.0);
+        for i in 0..3 {
+            for j in 0..3 {
+                let approx = features.row(i).iter()
+                    .zip(features.row(j).iter())
+                    .map(|(a, b)| a * b)
+                    .sum::<f64>();
+                let exact = ker.kernel(inputs.row(i).raw_slice(), inputs.row(j).raw_slice());
+                assert!((approx - exact).abs() < 0.03, "{} != {}", approx, exact);
+            }
+        }
+    }
+
+    #[test]
+    fn seeded_reproducible_test() {
+        let inputs = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+
+        let mut first = RandomFourierFitter::new(10, 1.0).with_seed(&[7]).fit(&inputs).unwrap();
+        let mut second = RandomFourierFitter::new(10, 1.0).with_seed(&[7]).fit(&inputs).unwrap();
+
+        assert_eq!(first.transform(inputs.clone()).unwrap(),
+                   second.transform(inputs).unwrap());
+    }
+
+    #[test]
+    fn wrong_transform_size_test() {
+        let inputs = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+
+        let mut transformer = RandomFourierFitter::new(10, 1.0).fit(&inputs).unwrap();
+        let res = transformer.transform(Matrix::new(1, 3, vec![1.0, 2.0, 3.0]));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn nan_data_test() {
+        let inputs = Matrix::new(2, 2, vec![1.0, f64::NAN, 3.0, 4.0]);
+
+        assert!(RandomFourierFitter::new(10, 1.0).fit(&inputs).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_length_scale_test() {
+        let _ = RandomFourierFitter::new(10, 0.0);
+    }
+}
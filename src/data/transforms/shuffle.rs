@@ -22,12 +22,33 @@
 //!
 //! println!("{}", shuffled_mat);
 //! ```
+//!
+//! This module also contains the `ReservoirShuffler`, which approximately
+//! shuffles the rows of a stream, such as a file too large to load into
+//! memory, while holding only a fixed number of rows at a time.
+//!
+//! ```
+//! use rusty_machine::data::transforms::shuffle::ReservoirShuffler;
+//!
+//! // Some rows which we read one at a time
+//! let rows = (0..1000).map(|i| vec![i as f64, 2.0 * i as f64]);
+//!
+//! // Shuffle the rows using a buffer of 100 rows
+//! let shuffled = ReservoirShuffler::seeded(rows, 100, &[1, 2, 3]);
+//!
+//! for row in shuffled {
+//!     // Train on each row...
+//! #   let _ = row;
+//! }
+//! ```
 
 use learning::LearningResult;
 use linalg::{Matrix, BaseMatrix, BaseMatrixMut};
 use super::Transformer;
 
-use rand::{Rng, thread_rng, ThreadRng};
+use std::iter::Fuse;
+
+use rand::{Rng, StdRng, SeedableRng, thread_rng, ThreadRng};
 
 /// The `Shuffler`
 ///
@@ -88,11 +109,118 @@ impl<R: Rng, T> Transformer<Matrix<T>> for Shuffler<R> {
     }
 }
 
+/// The `ReservoirShuffler`
+///
+/// An iterator adaptor which approximately shuffles the items of
+/// another iterator using a fixed-size buffer.
+///
+/// The buffer is first filled from the source. Each item is then drawn
+/// uniformly at random from the buffer and its slot is refilled with the
+/// next item from the source. If the buffer is at least as large as the
+/// source, the output is a uniformly random permutation. Smaller buffers
+/// use less memory, but an item can only be drawn once it has entered
+/// the buffer, so it cannot appear before items more than roughly a
+/// buffer's length ahead of it in the source.
+///
+/// The source is fused, so it is not polled again once it has
+/// returned `None`.
+#[derive(Debug)]
+pub struct ReservoirShuffler<I: Iterator, R: Rng> {
+    source: Fuse<I>,
+    buffer: Vec<I::Item>,
+    buffer_size: usize,
+    rng: R,
+}
+
+impl<I: Iterator, R: Rng> ReservoirShuffler<I, R> {
+    /// Construct a new `ReservoirShuffler` over the source with
+    /// given buffer size and random number generator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate rand;
+    /// # extern crate rusty_machine;
+    /// use rusty_machine::data::transforms::shuffle::ReservoirShuffler;
+    /// use rand::thread_rng;
+    ///
+    /// # fn main() {
+    /// let shuffled = ReservoirShuffler::new(0..10, 4, thread_rng()).collect::<Vec<_>>();
+    /// assert_eq!(shuffled.len(), 10);
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `buffer_size` is zero.
+    pub fn new<T>(source: T, buffer_size: usize, rng: R) -> ReservoirShuffler<I, R>
+        where T: IntoIterator<IntoIter = I, Item = I::Item>
+    {
+        assert!(buffer_size > 0, "The buffer size must be greater than 0.");
+
+        ReservoirShuffler {
+            source: source.into_iter().fuse(),
+            buffer: Vec::with_capacity(buffer_size),
+            buffer_size,
+            rng,
+        }
+    }
+
+    /// The maximum number of items held by the shuffler.
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+}
+
+impl<I: Iterator> ReservoirShuffler<I, StdRng> {
+    /// Construct a new `ReservoirShuffler` over the source with
+    /// given buffer size, seeding a `StdRng` with `seed`.
+    ///
+    /// # Panics
+    ///
+    /// - `buffer_size` is zero.
+    pub fn seeded<T>(source: T, buffer_size: usize, seed: &[usize]) -> ReservoirShuffler<I, StdRng>
+        where T: IntoIterator<IntoIter = I, Item = I::Item>
+    {
+        ReservoirShuffler::new(source, buffer_size, StdRng::from_seed(seed))
+    }
+}
+
+impl<I: Iterator, R: Rng> Iterator for ReservoirShuffler<I, R> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        // Fill the buffer on the first call
+        while self.buffer.len() < self.buffer_size {
+            match self.source.next() {
+                Some(item) => self.buffer.push(item),
+                None => break,
+            }
+        }
+
+        if self.buffer.is_empty() {
+            return None;
+        }
+
+        let i = self.rng.gen_range(0, self.buffer.len());
+        match self.source.next() {
+            Some(item) => Some(::std::mem::replace(&mut self.buffer[i], item)),
+            None => Some(self.buffer.swap_remove(i)),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.source.size_hint();
+        let n = self.buffer.len();
+        (lower.saturating_add(n), upper.and_then(|u| u.checked_add(n)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use linalg::Matrix;
     use super::super::Transformer;
-    use super::Shuffler;
+    use super::{Shuffler, ReservoirShuffler};
 
     use rand::{StdRng, SeedableRng};
 
@@ -118,4 +246,66 @@ mod tests {
         assert_eq!(shuffled.into_vec(),
                    vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn reservoir_large_buffer_permutes() {
+        let rows = (0..100).map(|i| vec![i as f64, -(i as f64)]);
+        let shuffled = ReservoirShuffler::seeded(rows, 100, &[1, 2, 3]).collect::<Vec<_>>();
+
+        assert_eq!(shuffled.len(), 100);
+        assert!(shuffled.iter().zip(0..100).any(|(r, i)| r[0] != i as f64));
+
+        let mut firsts = shuffled.iter().map(|r| r[0] as usize).collect::<Vec<usize>>();
+        firsts.sort();
+        assert_eq!(firsts, (0..100).collect::<Vec<usize>>());
+        assert!(shuffled.iter().all(|r| r[1] == -r[0]));
+    }
+
+    #[test]
+    fn reservoir_small_buffer_mixes() {
+        let n = 10000;
+        let shuffled = ReservoirShuffler::seeded(0..n, 50, &[4, 5, 6]).collect::<Vec<usize>>();
+
+        let mut sorted = shuffled.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..n).collect::<Vec<usize>>());
+
+        // Neighbouring outputs should be in increasing order about half the time.
+        let ascents = shuffled.windows(2).filter(|w| w[1] > w[0]).count() as f64 / (n - 1) as f64;
+        assert!((ascents - 0.5).abs() < 0.05, "ascents: {}", ascents);
+
+        // Items move by around the buffer size.
+        let mean_shift = shuffled.iter()
+            .enumerate()
+            .map(|(pos, &x)| (pos as f64 - x as f64).abs())
+            .sum::<f64>() / n as f64;
+        assert!(mean_shift > 10.0 && mean_shift < 200.0, "mean shift: {}", mean_shift);
+    }
+
+    #[test]
+    fn reservoir_empty_source() {
+        let empty: Vec<f64> = vec![];
+        let mut shuffler = ReservoirShuffler::seeded(empty, 10, &[1]);
+        assert!(shuffler.next().is_none());
+    }
+
+    #[test]
+    fn reservoir_stops_at_first_none() {
+        // Yields 0, 1, 2, then None, then resumes with 4, 5, ...
+        let mut count = 0;
+        let source = ::std::iter::from_fn(move || {
+            count += 1;
+            if count == 4 { None } else { Some(count - 1) }
+        });
+
+        let mut shuffled = ReservoirShuffler::seeded(source, 2, &[1]).collect::<Vec<_>>();
+        shuffled.sort();
+        assert_eq!(shuffled, vec![0, 1, 2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn reservoir_zero_buffer() {
+        let _ = ReservoirShuffler::seeded(0..10, 0, &[1]);
+    }
+}
@@ -23,6 +23,28 @@
 //! // standard deviation.
 //! let transformed = transformer.transform(inputs).unwrap();
 //! ```
+//!
+//! # Streaming
+//!
+//! When the data does not fit in memory a `Standardizer` can be
+//! fitted in chunks using `partial_fit`. The means and variances are
+//! updated one row at a time with Welford's algorithm, which remains
+//! numerically stable over many chunks, and match a batch fit of all
+//! the rows.
+//!
+//! ```
+//! use rusty_machine::data::transforms::{Transformer, StandardizerFitter};
+//! use rusty_machine::linalg::Matrix;
+//!
+//! let mut transformer = StandardizerFitter::<f64>::default().streaming();
+//! transformer.partial_fit(&Matrix::new(2, 2, vec![-1.0, 2.0, 1.5, 3.0])).unwrap();
+//! transformer.partial_fit(&Matrix::new(1, 2, vec![0.5, 4.0])).unwrap();
+//!
+//! assert_eq!(transformer.n_samples_seen(), 3);
+//! assert!((transformer.means()[0] - 1.0 / 3.0).abs() < 1e-12);
+//!
+//! let transformed = transformer.transform(Matrix::new(1, 2, vec![0.0, 0.0])).unwrap();
+//! ```
 
 use learning::LearningResult;
 use learning::error::{Error, ErrorKind};
@@ -34,7 +56,7 @@ use rulinalg::utils;
 use libnum::{Float, FromPrimitive};
 
 /// A builder used to construct a `Standardizer`
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct StandardizerFitter<T: Float> {
     scaled_mean: T,
     scaled_stdev: T
@@ -76,6 +98,22 @@ impl<T: Float> StandardizerFitter<T> {
             scaled_stdev: stdev
         }
     }
+
+    /// Construct a `Standardizer` which has not seen any data,
+    /// to be fitted in chunks using `Standardizer::partial_fit`.
+    ///
+    /// The `Standardizer` cannot transform data until
+    /// it has been fitted to at least two rows.
+    pub fn streaming(self) -> Standardizer<T> {
+        Standardizer {
+            means: Vector::zeros(0),
+            variances: Vector::zeros(0),
+            sq_deviations: Vector::zeros(0),
+            n_samples: 0,
+            scaled_mean: self.scaled_mean,
+            scaled_stdev: self.scaled_stdev
+        }
+    }
 }
 
 impl<T: Float + FromPrimitive> TransformFitter<Matrix<T>, Standardizer<T>> for StandardizerFitter<T> {
@@ -93,9 +131,12 @@ impl<T: Float + FromPrimitive> TransformFitter<Matrix<T>, Standardizer<T>> for S
                 return Err(Error::new(ErrorKind::InvalidData, "Some data point is non-finite."));
             }
 
+            let n_minus_one = T::from_usize(inputs.rows() - 1).unwrap();
             Ok(Standardizer {
                 means: mean,
+                sq_deviations: &variance * n_minus_one,
                 variances: variance,
+                n_samples: inputs.rows(),
                 scaled_mean: self.scaled_mean,
                 scaled_stdev: self.scaled_stdev
             })
@@ -110,21 +151,92 @@ impl<T: Float + FromPrimitive> TransformFitter<Matrix<T>, Standardizer<T>> for S
 /// and standard deviation.
 ///
 /// See the module description for more information.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Standardizer<T: Float> {
     /// Means per column of input data
     means: Vector<T>,
     /// Variances per column of input data
     variances: Vector<T>,
+    /// Sums of squared deviations from the mean per column
+    sq_deviations: Vector<T>,
+    /// The number of rows fitted
+    n_samples: usize,
     /// The mean of the new data (default 0)
     scaled_mean: T,
     /// The standard deviation of the new data (default 1)
     scaled_stdev: T,
 }
 
+impl<T: Float> Standardizer<T> {
+    /// The means of each column in the fitted data.
+    pub fn means(&self) -> &Vector<T> {
+        &self.means
+    }
+
+    /// The variances of each column in the fitted data.
+    pub fn variances(&self) -> &Vector<T> {
+        &self.variances
+    }
+
+    /// The number of rows the `Standardizer` has been fitted to.
+    pub fn n_samples_seen(&self) -> usize {
+        self.n_samples
+    }
+}
+
+impl<T: Float + FromPrimitive> Standardizer<T> {
+    /// Update the means and variances with a new chunk of data.
+    ///
+    /// Each row is added using Welford's online algorithm, so only
+    /// the running statistics are kept in memory.
+    ///
+    /// # Failures
+    ///
+    /// - The chunk has a different number of columns to previous chunks.
+    /// - Some entry of the chunk is non-finite.
+    pub fn partial_fit(&mut self, chunk: &Matrix<T>) -> LearningResult<()> {
+        if self.n_samples > 0 && chunk.cols() != self.means.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Chunk has a different number of columns to previously seen data."));
+        }
+        if chunk.data().iter().any(|x| !x.is_finite()) {
+            return Err(Error::new(ErrorKind::InvalidData, "Some data point is non-finite."));
+        }
+        if chunk.rows() == 0 {
+            return Ok(());
+        }
+
+        if self.n_samples == 0 {
+            self.means = Vector::zeros(chunk.cols());
+            self.sq_deviations = Vector::zeros(chunk.cols());
+        }
+
+        for row in chunk.row_iter() {
+            self.n_samples += 1;
+            let n = T::from_usize(self.n_samples).unwrap();
+
+            for ((x, mean), sq_dev) in row.iter()
+                .zip(self.means.mut_data().iter_mut())
+                .zip(self.sq_deviations.mut_data().iter_mut()) {
+                let delta = *x - *mean;
+                *mean = *mean + delta / n;
+                *sq_dev = *sq_dev + delta * (*x - *mean);
+            }
+        }
+
+        if self.n_samples > 1 {
+            let n_minus_one = T::from_usize(self.n_samples - 1).unwrap();
+            self.variances = &self.sq_deviations / n_minus_one;
+        }
+        Ok(())
+    }
+}
+
 impl<T: Float + FromPrimitive> Transformer<Matrix<T>> for Standardizer<T> {
     fn transform(&mut self, mut inputs: Matrix<T>) -> LearningResult<Matrix<T>> {
-        if self.means.size() != inputs.cols() {
+        if self.n_samples <= 1 {
+            Err(Error::new_untrained())
+        } else if self.means.size() != inputs.cols() {
             Err(Error::new(ErrorKind::InvalidData,
                             "Input data has different number of columns from fitted data."))
         } else {
@@ -165,7 +277,7 @@ impl<T: Float + FromPrimitive> Invertible<Matrix<T>> for Standardizer<T> {
 mod tests {
     use super::*;
     use super::super::{Transformer, TransformFitter, Invertible};
-    use linalg::{Axes, Matrix};
+    use linalg::{Axes, Matrix, BaseMatrix};
 
     use std::f64;
 
@@ -244,4 +356,71 @@ mod tests {
 
         assert!((inputs - original).data().iter().all(|x| x.abs() < 1e-5));
     }
+
+    #[test]
+    fn streaming_matches_batch_test() {
+        let data = (0..90).map(|i| (i as f64 * 0.37).sin() * 10.0 + i as f64 * 0.1).collect::<Vec<f64>>();
+        let inputs = Matrix::new(30, 3, data);
+
+        let batch = StandardizerFitter::default().fit(&inputs).unwrap();
+
+        let mut streaming = StandardizerFitter::default().streaming();
+        for rows in &[0..1, 1..8, 8..9, 9..20, 20..30] {
+            let rows = rows.clone().collect::<Vec<usize>>();
+            streaming.partial_fit(&inputs.select_rows(&rows)).unwrap();
+        }
+
+        assert_eq!(streaming.n_samples_seen(), 30);
+        for (s, b) in streaming.means().iter().zip(batch.means().iter()) {
+            assert!((s - b).abs() < 1e-12);
+        }
+        for (s, b) in streaming.variances().iter().zip(batch.variances().iter()) {
+            assert!((s - b).abs() < 1e-10);
+        }
+
+        let mut batch = batch;
+        let diff = streaming.transform(inputs.clone()).unwrap() - batch.transform(inputs).unwrap();
+        assert!(diff.data().iter().all(|x| x.abs() < 1e-10));
+    }
+
+    #[test]
+    fn streaming_stability_test() {
+        // A large offset makes the naive sum of squares lose all precision.
+        let mut streaming = StandardizerFitter::default().streaming();
+        for chunk in 0..5000 {
+            let rows = (0..2).map(|i| 1e9 + ((2 * chunk + i) % 10) as f64).collect::<Vec<f64>>();
+            streaming.partial_fit(&Matrix::new(2, 1, rows)).unwrap();
+        }
+
+        // The values 0..9 repeated 1000 times.
+        let expected_var = 8.25 * 10000.0 / 9999.0;
+        assert!((streaming.means()[0] - (1e9 + 4.5)).abs() < 1e-4);
+        assert!((streaming.variances()[0] - expected_var).abs() < 1e-6);
+    }
+
+    #[test]
+    fn partial_fit_after_fit_test() {
+        let inputs = Matrix::new(4, 1, vec![1.0, 2.0, 4.0, 7.0]);
+
+        let mut transformer = StandardizerFitter::default()
+            .fit(&inputs.select_rows(&[0, 1]))
+            .unwrap();
+        transformer.partial_fit(&inputs.select_rows(&[2, 3])).unwrap();
+
+        assert_eq!(transformer.means()[0], 3.5);
+        assert!((transformer.variances()[0] - 7.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn streaming_invalid_test() {
+        let mut transformer = StandardizerFitter::default().streaming();
+        transformer.partial_fit(&Matrix::new(1, 2, vec![1.0, 2.0])).unwrap();
+
+        // One row is not enough to standardize.
+        assert!(transformer.transform(Matrix::new(1, 2, vec![1.0, 2.0])).is_err());
+
+        assert!(transformer.partial_fit(&Matrix::new(1, 3, vec![1.0, 2.0, 3.0])).is_err());
+        assert!(transformer.partial_fit(&Matrix::new(1, 2, vec![f64::NAN, 2.0])).is_err());
+        assert_eq!(transformer.n_samples_seen(), 1);
+    }
 }
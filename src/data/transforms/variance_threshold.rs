@@ -0,0 +1,241 @@
+//! The Variance Threshold Transformer
+//!
+//! This module contains the `VarianceThreshold` transformer.
+//!
+//! The `VarianceThreshold` transformer is used to remove features
+//! whose variance in the fitted data does not exceed a threshold.
+//! By default only constant columns are removed.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::data::transforms::{Transformer, TransformFitter, VarianceThresholdFitter};
+//! use rusty_machine::linalg::{Matrix, BaseMatrix};
+//!
+//! // The second column is constant.
+//! let inputs = Matrix::new(3, 2, vec![1.0, 5.0, 2.0, 5.0, 4.0, 5.0]);
+//!
+//! // Constructs a new `VarianceThreshold` which drops constant columns.
+//! let mut transformer = VarianceThresholdFitter::default().fit(&inputs).unwrap();
+//!
+//! // Transform the inputs to remove the constant column.
+//! let transformed = transformer.transform(inputs).unwrap();
+//! assert_eq!(transformed.cols(), 1);
+//! ```
+
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
+use linalg::{Matrix, Vector, Axes, BaseMatrix};
+use super::{Transformer, TransformFitter};
+
+use libnum::{Float, FromPrimitive};
+
+/// A builder used to construct a `VarianceThreshold`
+#[derive(Clone, Debug)]
+pub struct VarianceThresholdFitter<T: Float> {
+    threshold: T,
+}
+
+impl<T: Float> Default for VarianceThresholdFitter<T> {
+    fn default() -> Self {
+        VarianceThresholdFitter {
+            threshold: T::zero()
+        }
+    }
+}
+
+impl<T: Float> VarianceThresholdFitter<T> {
+    /// Construct a new `VarianceThresholdFitter` which keeps
+    /// columns with a variance greater than `threshold`.
+    ///
+    /// Note that this function does not create a `Transformer`
+    /// only a builder which can be used to produce a fitted `Transformer`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::data::transforms::VarianceThresholdFitter;
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// let fitter = VarianceThresholdFitter::new(0.5);
+    ///
+    /// // We can call `fit` from the `transform::TransformFitter`
+    /// // trait to create a `VarianceThreshold` used to actually transform data.
+    /// use rusty_machine::data::transforms::TransformFitter;
+    /// let mat = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 2.0]);
+    /// let transformer = fitter.fit(&mat);
+    /// ```
+    pub fn new(threshold: T) -> VarianceThresholdFitter<T> {
+        VarianceThresholdFitter {
+            threshold
+        }
+    }
+}
+
+impl<T: Float + FromPrimitive> TransformFitter<Matrix<T>, VarianceThreshold<T>> for VarianceThresholdFitter<T> {
+    fn fit(self, inputs: &Matrix<T>) -> LearningResult<VarianceThreshold<T>> {
+        if inputs.rows() <= 1 {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Cannot compute variance of data with only one row."));
+        }
+
+        let variances = inputs.variance(Axes::Row).map_err(|_| {
+            Error::new(ErrorKind::InvalidData, "Cannot compute variance of data.")
+        })?;
+
+        if variances.data().iter().any(|x| !x.is_finite()) {
+            return Err(Error::new(ErrorKind::InvalidData, "Some data point is non-finite."));
+        }
+
+        // Rounding leaves a tiny positive variance in some constant
+        // columns, so columns with a single distinct value have zero variance.
+        let variances = Vector::new(variances.into_iter()
+            .enumerate()
+            .map(|(j, v)| {
+                let first = inputs[[0, j]];
+                if inputs.col(j).iter().all(|x| *x == first) { T::zero() } else { v }
+            })
+            .collect::<Vec<T>>());
+
+        let selected = variances.data()
+            .iter()
+            .enumerate()
+            .filter(|&(_, v)| *v > self.threshold)
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>();
+
+        if selected.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "No column has a variance above the threshold."));
+        }
+
+        Ok(VarianceThreshold {
+            variances,
+            selected
+        })
+    }
+}
+
+/// The VarianceThreshold
+///
+/// The VarianceThreshold provides an implementation of `Transformer`
+/// which keeps only the columns whose variance exceeded the threshold
+/// in the fitted data.
+///
+/// See the module description for more information.
+#[derive(Clone, Debug)]
+pub struct VarianceThreshold<T: Float> {
+    /// Variances per column of the fitted data
+    variances: Vector<T>,
+    /// Indices of the columns which are kept
+    selected: Vec<usize>,
+}
+
+impl<T: Float> VarianceThreshold<T> {
+    /// The variances of each column in the fitted data.
+    pub fn variances(&self) -> &Vector<T> {
+        &self.variances
+    }
+
+    /// The indices of the columns kept by the transformer.
+    pub fn selected(&self) -> &[usize] {
+        &self.selected
+    }
+}
+
+impl<T: Float> Transformer<Matrix<T>> for VarianceThreshold<T> {
+    fn transform(&mut self, inputs: Matrix<T>) -> LearningResult<Matrix<T>> {
+        if self.variances.size() != inputs.cols() {
+            Err(Error::new(ErrorKind::InvalidData,
+                           "Input data has different number of columns from fitted data."))
+        } else {
+            Ok(inputs.select_cols(&self.selected))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{Transformer, TransformFitter};
+    use linalg::Matrix;
+
+    use std::f64;
+
+    #[test]
+    fn constant_columns_dropped_test() {
+        let inputs = Matrix::new(4, 4, vec![1.0, 3.0, 0.5, -2.0,
+                                            2.0, 3.0, 0.7, -1.0,
+                                            3.0, 3.0, 0.1, -2.0,
+                                            4.0, 3.0, 0.2, -1.0]);
+
+        let mut transformer = VarianceThresholdFitter::default().fit(&inputs).unwrap();
+        assert_eq!(transformer.selected(), &[0, 2, 3]);
+
+        let transformed = transformer.transform(inputs).unwrap();
+        let expected = Matrix::new(4, 3, vec![1.0, 0.5, -2.0,
+                                              2.0, 0.7, -1.0,
+                                              3.0, 0.1, -2.0,
+                                              4.0, 0.2, -1.0]);
+        assert_eq!(transformed, expected);
+    }
+
+    #[test]
+    fn inexact_constant_column_dropped_test() {
+        // The computed variance of this column is not exactly zero.
+        let inputs = Matrix::new(3, 2, vec![1.0, 0.1, 2.0, 0.1, 4.0, 0.1]);
+
+        let transformer = VarianceThresholdFitter::default().fit(&inputs).unwrap();
+        assert_eq!(transformer.selected(), &[0]);
+        assert_eq!(transformer.variances()[1], 0.0);
+    }
+
+    #[test]
+    fn custom_threshold_test() {
+        let inputs = Matrix::new(4, 3, vec![1.0, 0.5, 10.0,
+                                            2.0, 0.7, 20.0,
+                                            3.0, 0.1, 30.0,
+                                            4.0, 0.2, 40.0]);
+
+        let mut transformer = VarianceThresholdFitter::new(0.5).fit(&inputs).unwrap();
+        assert_eq!(transformer.selected(), &[0, 2]);
+
+        // The same selection is applied to new data.
+        let new_data = Matrix::new(1, 3, vec![5.0, 6.0, 7.0]);
+        let transformed = transformer.transform(new_data).unwrap();
+        assert_eq!(transformed, Matrix::new(1, 2, vec![5.0, 7.0]));
+    }
+
+    #[test]
+    fn all_constant_test() {
+        let inputs = Matrix::new(3, 2, vec![1.0, 2.0, 1.0, 2.0, 1.0, 2.0]);
+
+        let transformer = VarianceThresholdFitter::default().fit(&inputs);
+        assert!(transformer.is_err());
+    }
+
+    #[test]
+    fn single_row_test() {
+        let inputs = Matrix::new(1, 2, vec![1.0, 2.0]);
+
+        let transformer = VarianceThresholdFitter::default().fit(&inputs);
+        assert!(transformer.is_err());
+    }
+
+    #[test]
+    fn nan_data_test() {
+        let inputs = Matrix::new(2, 2, vec![f64::NAN; 4]);
+
+        let transformer = VarianceThresholdFitter::default().fit(&inputs);
+        assert!(transformer.is_err());
+    }
+
+    #[test]
+    fn wrong_transform_size_test() {
+        let inputs = Matrix::new(2, 2, vec![-1.0f32, 2.0, 0.0, 3.0]);
+
+        let mut transformer = VarianceThresholdFitter::default().fit(&inputs).unwrap();
+        let res = transformer.transform(matrix![1.0, 2.0, 3.0; 4.0, 5.0, 6.0]);
+        assert!(res.is_err());
+    }
+}
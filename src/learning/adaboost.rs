@@ -0,0 +1,756 @@
+//! AdaBoost Module
+//!
+//! Contains an implementation of the AdaBoost ensemble classifier
+//! and the decision stumps used as its default weak learner.
+//!
+//! AdaBoost trains a sequence of weak learners. After each round the
+//! weights of the misclassified samples are increased, so the next
+//! learner focuses on them. The ensemble predicts with a vote of the
+//! learners, weighted by their accuracy on the training data.
+//!
+//! The models currently only support binary classification.
+//! The model inputs should be a matrix and the training targets are
+//! in the form of a vector of `-1`s and `1`s. The labels `0` and `1`
+//! can be used instead through `LabelClassifier`.
+//!
+//! Any model which supports `train_weighted` can be used as a weak
+//! learner. The outputs of a weak learner are interpreted as `1` if
+//! they are non-negative and `-1` otherwise.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::adaboost::AdaBoost;
+//! use rusty_machine::learning::SupModel;
+//!
+//! use rusty_machine::linalg::Matrix;
+//! use rusty_machine::linalg::Vector;
+//!
+//! // The positive class lies between the negative points.
+//! let inputs = Matrix::new(6,1,vec![1.0,2.0,3.0,4.0,5.0,6.0]);
+//! let targets = Vector::new(vec![-1.,-1.,1.,1.,-1.,-1.]);
+//!
+//! let mut boost_mod = AdaBoost::default();
+//!
+//! // Train the model
+//! boost_mod.train(&inputs, &targets).unwrap();
+//!
+//! // Now we'll predict a new point
+//! let new_point = Matrix::new(1,1,vec![3.5]);
+//! let output = boost_mod.predict(&new_point).unwrap();
+//!
+//! assert!(output[0] == 1f64, "Our classifier isn't very good!");
+//! ```
+
+use linalg::{Matrix, BaseMatrix, Vector};
+use learning::{LearningResult, SupModel, LabelClassifier, validate_finite_matrix, validate_finite_vector,
+               validate_sample_weights, validate_labels, binary_label_targets};
+use learning::error::{Error, ErrorKind};
+use learning::model_info::ModelInfo;
+
+use std::f64;
+
+/// Maps a model output to the class `-1` or `1`.
+fn to_class(x: f64) -> f64 {
+    if x >= 0f64 { 1f64 } else { -1f64 }
+}
+
+/// Checks that every target is the class `-1` or `1`.
+///
+/// Labels such as `0` and `1` should be trained with `train_labels`.
+fn validate_classes(targets: &Vector<f64>) -> LearningResult<()> {
+    match targets.iter().position(|&t| t != -1f64 && t != 1f64) {
+        Some(idx) => {
+            Err(Error::new(ErrorKind::InvalidData,
+                           format!("target {} is {}, but targets must be -1 or 1", idx, targets[idx])))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Decision Stump
+///
+/// Classifies samples by comparing a single feature against a threshold.
+/// Samples above the threshold are assigned the class `polarity`.
+#[derive(Clone, Debug, Default)]
+pub struct DecisionStump {
+    /// The feature, threshold and polarity of the trained stump.
+    split: Option<(usize, f64, f64)>,
+}
+
+impl DecisionStump {
+    /// The index of the feature used by the trained stump.
+    pub fn feature(&self) -> Option<usize> {
+        self.split.map(|(f, _, _)| f)
+    }
+
+    /// The threshold of the trained stump.
+    pub fn threshold(&self) -> Option<f64> {
+        self.split.map(|(_, t, _)| t)
+    }
+
+    /// Summarizes the size of the trained stump.
+    ///
+    /// The parameters are the feature, threshold and polarity, and the
+    /// nodes are the split and its two leaves. The number of inputs is
+    /// unknown, as the stump only reads one feature.
+    ///
+    /// # Failures
+    ///
+    /// - The stump has not been trained.
+    pub fn model_info(&self) -> LearningResult<ModelInfo> {
+        self.split.ok_or_else(Error::new_untrained)?;
+
+        Ok(ModelInfo {
+            n_params: 3,
+            n_inputs: None,
+            n_outputs: Some(1),
+            n_stored_samples: None,
+            n_nodes: Some(3),
+        })
+    }
+
+    /// Describes the split of the trained stump as an if/else statement.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::adaboost::DecisionStump;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let inputs = Matrix::new(4,1,vec![1.0,2.0,3.0,4.0]);
+    /// let targets = Vector::new(vec![-1.,-1.,1.,1.]);
+    ///
+    /// let mut stump = DecisionStump::default();
+    /// stump.train(&inputs, &targets).unwrap();
+    ///
+    /// println!("{}", stump.to_text().unwrap());
+    /// // if x[0] <= 2.5:
+    /// //     predict -1
+    /// // else:
+    /// //     predict 1
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The stump has not been trained.
+    pub fn to_text(&self) -> LearningResult<String> {
+        let (feature, threshold, polarity) = self.split.ok_or_else(Error::new_untrained)?;
+
+        Ok(format!("if x[{}] <= {}:\n    predict {}\nelse:\n    predict {}\n",
+                   feature,
+                   threshold,
+                   -polarity,
+                   polarity))
+    }
+
+    /// Describes the trained stump in the DOT language of Graphviz.
+    ///
+    /// The graph has a node for the split, and an edge to each
+    /// leaf labelled with whether the split condition holds.
+    ///
+    /// # Failures
+    ///
+    /// - The stump has not been trained.
+    pub fn to_graphviz(&self) -> LearningResult<String> {
+        let (feature, threshold, polarity) = self.split.ok_or_else(Error::new_untrained)?;
+
+        let mut dot = String::from("digraph DecisionStump {\n");
+        dot.push_str("    node [shape=box];\n");
+        dot.push_str(&format!("    split [label=\"x[{}] <= {}\"];\n", feature, threshold));
+        dot.push_str(&format!("    below [label=\"predict {}\"];\n", -polarity));
+        dot.push_str(&format!("    above [label=\"predict {}\"];\n", polarity));
+        dot.push_str("    split -> below [label=\"true\"];\n");
+        dot.push_str("    split -> above [label=\"false\"];\n");
+        dot.push_str("}\n");
+        Ok(dot)
+    }
+}
+
+impl SupModel<Matrix<f64>, Vector<f64>> for DecisionStump {
+    /// Predict the class of each input.
+    ///
+    /// Model must be trained before prediction can be made.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        match self.split {
+            Some((feature, threshold, polarity)) => {
+                if inputs.cols() <= feature {
+                    return Err(Error::new(ErrorKind::InvalidData,
+                                          "Input data has too few columns for the stump."));
+                }
+
+                Ok(Vector::new(inputs.col(feature)
+                    .iter()
+                    .map(|&x| if x > threshold { polarity } else { -polarity })
+                    .collect::<Vec<f64>>()))
+            }
+            None => Err(Error::new_untrained()),
+        }
+    }
+
+    /// Train the stump with equal weight on each sample.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        let weights = Vector::new(vec![1f64; inputs.rows()]);
+        self.train_weighted(inputs, targets, &weights)
+    }
+
+    /// Train the stump to minimize the weighted classification error.
+    ///
+    /// # Failures
+    ///
+    /// - The inputs have no rows or no columns.
+    /// - Some input or target is NaN or infinite.
+    /// - Some target is not `-1` or `1`.
+    /// - The number of weights differs from the number of samples.
+    /// - Some weight is negative or non-finite, or all weights are zero.
+    fn train_weighted(&mut self,
+                      inputs: &Matrix<f64>,
+                      targets: &Vector<f64>,
+                      weights: &Vector<f64>)
+                      -> LearningResult<()> {
+        if inputs.rows() == 0 || inputs.cols() == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "Cannot train a stump without data."));
+        }
+        if targets.size() != inputs.rows() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "There must be one target per sample."));
+        }
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_vector("target", targets)?;
+        validate_classes(targets)?;
+        validate_sample_weights(weights, inputs.rows())?;
+
+        let total_pos = targets.iter()
+            .zip(weights.iter())
+            .filter(|&(t, _)| *t > 0f64)
+            .map(|(_, w)| w)
+            .sum::<f64>();
+        let total_neg = weights.sum() - total_pos;
+
+        // Start with every sample above the threshold.
+        let mut best = if total_neg <= total_pos {
+            (total_neg, (0, f64::NEG_INFINITY, 1f64))
+        } else {
+            (total_pos, (0, f64::NEG_INFINITY, -1f64))
+        };
+
+        for j in 0..inputs.cols() {
+            let column = inputs.col(j).iter().cloned().collect::<Vec<f64>>();
+            let mut order = (0..column.len()).collect::<Vec<usize>>();
+            order.sort_by(|&a, &b| column[a].partial_cmp(&column[b]).unwrap());
+
+            // The weight of each class at or below the threshold.
+            let mut below_pos = 0f64;
+            let mut below_neg = 0f64;
+
+            for (k, &i) in order.iter().enumerate() {
+                if targets[i] > 0f64 {
+                    below_pos += weights[i];
+                } else {
+                    below_neg += weights[i];
+                }
+
+                // Only split between distinct values.
+                if k + 1 < order.len() && column[order[k + 1]] == column[i] {
+                    continue;
+                }
+
+                let threshold = match order.get(k + 1) {
+                    Some(&next) => (column[i] + column[next]) / 2f64,
+                    None => column[i],
+                };
+
+                let pos_above_err = below_pos + (total_neg - below_neg);
+                let neg_above_err = below_neg + (total_pos - below_pos);
+
+                if pos_above_err < best.0 {
+                    best = (pos_above_err, (j, threshold, 1f64));
+                }
+                if neg_above_err < best.0 {
+                    best = (neg_above_err, (j, threshold, -1f64));
+                }
+            }
+        }
+
+        self.split = Some(best.1);
+        Ok(())
+    }
+}
+
+/// Train and predict from the stump using the labels `0` and `1`,
+/// which correspond to the classes `-1` and `1`.
+impl LabelClassifier for DecisionStump {
+    fn train_labels(&mut self, inputs: &Matrix<f64>, labels: &[usize]) -> LearningResult<()> {
+        validate_labels(labels, inputs.rows())?;
+        self.train(inputs, &binary_label_targets(labels, -1f64, 1f64)?)
+    }
+
+    fn predict_labels(&self, inputs: &Matrix<f64>) -> LearningResult<Vec<usize>> {
+        Ok(self.predict(inputs)?.iter().map(|&p| if p > 0f64 { 1 } else { 0 }).collect())
+    }
+}
+
+/// AdaBoost Ensemble Classifier
+#[derive(Debug)]
+pub struct AdaBoost<M = DecisionStump> {
+    /// The untrained weak learner cloned for each round.
+    learner: M,
+    /// The maximum number of weak learners.
+    n_estimators: usize,
+    /// Shrinks the contribution of each weak learner.
+    learning_rate: f64,
+    /// The trained weak learners with their vote weights.
+    estimators: Vec<(M, f64)>,
+}
+
+/// The default AdaBoost classifier.
+///
+/// The defaults are:
+///
+/// - learner = `DecisionStump`
+/// - n_estimators = 50
+/// - learning_rate = 1
+impl Default for AdaBoost<DecisionStump> {
+    fn default() -> AdaBoost<DecisionStump> {
+        AdaBoost::new(50, 1f64)
+    }
+}
+
+impl AdaBoost<DecisionStump> {
+    /// Constructs an untrained AdaBoost classifier using decision stumps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::adaboost::AdaBoost;
+    ///
+    /// let model = AdaBoost::new(100, 0.5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `learning_rate` is not positive.
+    pub fn new(n_estimators: usize, learning_rate: f64) -> AdaBoost<DecisionStump> {
+        AdaBoost::with_learner(DecisionStump::default(), n_estimators, learning_rate)
+    }
+}
+
+impl<M> AdaBoost<M>
+    where M: SupModel<Matrix<f64>, Vector<f64>> + Clone
+{
+    /// Constructs an untrained AdaBoost classifier using
+    /// copies of the given weak learner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::adaboost::{AdaBoost, DecisionStump};
+    ///
+    /// let model = AdaBoost::with_learner(DecisionStump::default(), 20, 1.0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `learning_rate` is not positive.
+    pub fn with_learner(learner: M, n_estimators: usize, learning_rate: f64) -> AdaBoost<M> {
+        assert!(learning_rate > 0f64, "The learning rate must be positive.");
+
+        AdaBoost {
+            learner,
+            n_estimators,
+            learning_rate,
+            estimators: Vec::new(),
+        }
+    }
+
+    /// The maximum number of weak learners.
+    pub fn n_estimators(&self) -> usize {
+        self.n_estimators
+    }
+
+    /// The learning rate applied to the vote weight of each learner.
+    pub fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    /// The trained weak learners with their vote weights.
+    ///
+    /// If a learner fits the weighted data perfectly, training stops
+    /// and that learner is the only estimator.
+    pub fn estimators(&self) -> &[(M, f64)] {
+        &self.estimators
+    }
+
+    /// Computes the weighted vote of the learners for each input.
+    ///
+    /// The sign of the vote is the predicted class.
+    pub fn decision_function(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if self.estimators.is_empty() {
+            return Err(Error::new_untrained());
+        }
+
+        let mut votes = Vector::zeros(inputs.rows());
+        for &(ref learner, alpha) in &self.estimators {
+            let outputs = learner.predict(inputs)?.apply(&to_class);
+            votes += outputs * alpha;
+        }
+
+        Ok(votes)
+    }
+}
+
+impl<M> SupModel<Matrix<f64>, Vector<f64>> for AdaBoost<M>
+    where M: SupModel<Matrix<f64>, Vector<f64>> + Clone
+{
+    /// Predict the class of each input.
+    ///
+    /// Model must be trained before prediction can be made.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        Ok(self.decision_function(inputs)?.apply(&to_class))
+    }
+
+    /// Train the ensemble with equal initial weight on each sample.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        let weights = Vector::new(vec![1f64; inputs.rows()]);
+        self.train_weighted(inputs, targets, &weights)
+    }
+
+    /// Train the ensemble starting from the given sample weights.
+    ///
+    /// # Failures
+    ///
+    /// - Some input or target is NaN or infinite.
+    /// - Some target is not `-1` or `1`.
+    /// - The number of weights differs from the number of samples.
+    /// - Some weight is negative or non-finite, or all weights are zero.
+    /// - The first weak learner is no better than chance.
+    /// - A weak learner fails to train or predict.
+    fn train_weighted(&mut self,
+                      inputs: &Matrix<f64>,
+                      targets: &Vector<f64>,
+                      weights: &Vector<f64>)
+                      -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_vector("target", targets)?;
+        validate_classes(targets)?;
+        validate_sample_weights(weights, inputs.rows())?;
+
+        let classes = targets.clone();
+        let mut sample_weights = weights / weights.sum();
+        self.estimators.clear();
+
+        for _ in 0..self.n_estimators {
+            let mut learner = self.learner.clone();
+            learner.train_weighted(inputs, &classes, &sample_weights)?;
+            let outputs = learner.predict(inputs)?.apply(&to_class);
+
+            let error = outputs.iter()
+                .zip(classes.iter())
+                .zip(sample_weights.iter())
+                .filter(|&((o, c), _)| o != c)
+                .map(|(_, w)| w)
+                .sum::<f64>();
+
+            if error >= 0.5 {
+                break;
+            }
+
+            if error <= 0f64 {
+                // A perfect learner replaces the earlier learners,
+                // which could otherwise outvote it.
+                self.estimators.clear();
+                self.estimators.push((learner, 1f64));
+                break;
+            }
+
+            let alpha = self.learning_rate * 0.5 * ((1f64 - error) / error).ln();
+
+            for ((w, o), c) in sample_weights.mut_data().iter_mut().zip(outputs.iter()).zip(classes.iter()) {
+                *w *= (-alpha * o * c).exp();
+            }
+            let total = sample_weights.sum();
+            sample_weights /= total;
+
+            self.estimators.push((learner, alpha));
+        }
+
+        if self.estimators.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The weak learner is no better than chance on the training data."));
+        }
+
+        Ok(())
+    }
+}
+
+/// Train and predict from the ensemble using the labels `0` and `1`,
+/// which correspond to the classes `-1` and `1`.
+impl<M> LabelClassifier for AdaBoost<M>
+    where M: SupModel<Matrix<f64>, Vector<f64>> + Clone
+{
+    fn train_labels(&mut self, inputs: &Matrix<f64>, labels: &[usize]) -> LearningResult<()> {
+        validate_labels(labels, inputs.rows())?;
+        self.train(inputs, &binary_label_targets(labels, -1f64, 1f64)?)
+    }
+
+    fn predict_labels(&self, inputs: &Matrix<f64>) -> LearningResult<Vec<usize>> {
+        Ok(self.predict(inputs)?.iter().map(|&p| if p > 0f64 { 1 } else { 0 }).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AdaBoost, DecisionStump};
+    use learning::{LearningResult, LabelClassifier, SupModel};
+    use learning::error::ErrorKind;
+    use learning::lin_reg::LinRegressor;
+    use linalg::{Matrix, BaseMatrix, Vector};
+
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Points separated by the diagonal `x1 + x2 = 0`,
+    /// which no single axis-aligned split can separate.
+    fn diagonal_data() -> (Matrix<f64>, Vector<f64>) {
+        let n = 100;
+        let mut inputs = Vec::with_capacity(2 * n);
+        let mut targets = Vec::with_capacity(n);
+
+        for i in 0..n {
+            let x1 = (i as f64 * 0.37).sin() * 2.0;
+            let x2 = (i as f64 * 0.91).cos() * 2.0;
+            inputs.push(x1);
+            inputs.push(x2);
+            targets.push(if x1 + x2 > 0.0 { 1.0 } else { -1.0 });
+        }
+
+        (Matrix::new(n, 2, inputs), Vector::new(targets))
+    }
+
+    #[test]
+    fn test_stump_to_text() {
+        // Only the second feature separates the classes.
+        let inputs = Matrix::new(6, 2, vec![0.0, 5.0, 3.0, 4.0, 1.0, 3.5,
+                                            4.0, 2.0, 2.0, 1.0, 5.0, 0.0]);
+        let targets = Vector::new(vec![1., 1., 1., -1., -1., -1.]);
+
+        let mut stump = DecisionStump::default();
+        stump.train(&inputs, &targets).unwrap();
+
+        assert_eq!(stump.to_text().unwrap(),
+                   "if x[1] <= 2.75:\n    predict -1\nelse:\n    predict 1\n");
+    }
+
+    #[test]
+    fn test_stump_to_graphviz() {
+        let inputs = Matrix::new(4, 1, vec![1.0, 2.0, 3.0, 4.0]);
+        let targets = Vector::new(vec![-1., -1., 1., 1.]);
+
+        let mut stump = DecisionStump::default();
+        stump.train(&inputs, &targets).unwrap();
+
+        let dot = stump.to_graphviz().unwrap();
+        assert!(dot.starts_with("digraph DecisionStump {"));
+        assert!(dot.contains("split [label=\"x[0] <= 2.5\"];"));
+        assert!(dot.contains("below [label=\"predict -1\"];"));
+        assert!(dot.contains("above [label=\"predict 1\"];"));
+        assert!(dot.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn test_stump_model_info() {
+        let inputs = Matrix::new(4, 1, vec![1.0, 2.0, 3.0, 4.0]);
+        let targets = Vector::new(vec![-1., -1., 1., 1.]);
+
+        let mut stump = DecisionStump::default();
+        assert!(stump.model_info().is_err());
+
+        stump.train(&inputs, &targets).unwrap();
+        let info = stump.model_info().unwrap();
+        assert_eq!(info.n_params, 3);
+        assert_eq!(info.n_nodes, Some(3));
+        assert_eq!(info.n_outputs, Some(1));
+    }
+
+    #[test]
+    fn test_untrained_stump_export() {
+        let stump = DecisionStump::default();
+
+        assert!(stump.to_text().is_err());
+        assert!(stump.to_graphviz().is_err());
+    }
+
+    fn training_error<M: SupModel<Matrix<f64>, Vector<f64>>>(model: &M,
+                                                          inputs: &Matrix<f64>,
+                                                          targets: &Vector<f64>)
+                                                          -> f64 {
+        let outputs = model.predict(inputs).unwrap();
+        let wrong = outputs.iter().zip(targets.iter()).filter(|&(o, t)| o != t).count();
+        wrong as f64 / targets.size() as f64
+    }
+
+    #[test]
+    fn test_stump_weighted() {
+        let inputs = Matrix::new(4, 1, vec![1.0, 2.0, 3.0, 4.0]);
+        let targets = Vector::new(vec![-1.0, 1.0, -1.0, 1.0]);
+
+        // The heavy samples decide the split.
+        let mut stump = DecisionStump::default();
+        stump.train_weighted(&inputs, &targets, &Vector::new(vec![1.0, 10.0, 10.0, 1.0])).unwrap();
+        assert_eq!(stump.feature(), Some(0));
+        assert_eq!(stump.predict(&inputs).unwrap(), Vector::new(vec![1.0, 1.0, -1.0, -1.0]));
+
+        stump.train_weighted(&inputs, &targets, &Vector::new(vec![10.0, 2.0, 1.0, 10.0])).unwrap();
+        assert_eq!(stump.predict(&inputs).unwrap(), Vector::new(vec![-1.0, 1.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn test_stump_integer_labels() {
+        let inputs = Matrix::new(5, 2, vec![0.3, 4.0, 0.1, 1.0, 0.4, 2.0, 0.2, 5.0, 0.5, 3.0]);
+        let labels = vec![1, 0, 0, 1, 1];
+
+        let mut stump = DecisionStump::default();
+        stump.train_labels(&inputs, &labels).unwrap();
+        assert_eq!(stump.feature(), Some(1));
+        assert_eq!(stump.predict_labels(&inputs).unwrap(), labels);
+
+        let new_inputs = Matrix::new(2, 2, vec![0.0, 0.5, 0.0, 6.0]);
+        assert_eq!(stump.predict_labels(&new_inputs).unwrap(), vec![0, 1]);
+        assert_eq!(stump.predict(&new_inputs).unwrap(), Vector::new(vec![-1.0, 1.0]));
+
+        assert!(stump.train_labels(&inputs, &[0, 1, 2, 1, 0]).is_err());
+        assert!(stump.train_labels(&inputs, &[0, 1]).is_err());
+    }
+
+    #[test]
+    fn test_boosting_reduces_error() {
+        let (inputs, targets) = diagonal_data();
+
+        let mut stump = DecisionStump::default();
+        stump.train(&inputs, &targets).unwrap();
+        let stump_error = training_error(&stump, &inputs, &targets);
+
+        let mut boost = AdaBoost::new(200, 1.0);
+        boost.train(&inputs, &targets).unwrap();
+        let boost_error = training_error(&boost, &inputs, &targets);
+
+        assert!(stump_error > 0.1);
+        assert!(boost_error < 0.02);
+    }
+
+    #[test]
+    fn test_boosting_stops_when_perfect() {
+        let inputs = Matrix::new(4, 1, vec![1.0, 2.0, 3.0, 4.0]);
+        let targets = Vector::new(vec![-1.0, -1.0, 1.0, 1.0]);
+
+        let mut boost = AdaBoost::default();
+        boost.train(&inputs, &targets).unwrap();
+
+        assert_eq!(boost.estimators().len(), 1);
+        assert_eq!(boost.predict(&inputs).unwrap(), targets);
+    }
+
+    /// A weak learner splitting the first feature at a fixed list of
+    /// thresholds, one per round of boosting.
+    #[derive(Clone, Debug)]
+    struct ScriptedStump {
+        thresholds: Vec<f64>,
+        round: Rc<Cell<usize>>,
+        threshold: Option<f64>,
+    }
+
+    impl SupModel<Matrix<f64>, Vector<f64>> for ScriptedStump {
+        fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+            let threshold = self.threshold.unwrap();
+            Ok(Vector::new(inputs.col(0).iter()
+                                 .map(|&x| if x > threshold { 1.0 } else { -1.0 })
+                                 .collect::<Vec<f64>>()))
+        }
+
+        fn train(&mut self, _: &Matrix<f64>, _: &Vector<f64>) -> LearningResult<()> {
+            self.threshold = Some(self.thresholds[self.round.get()]);
+            self.round.set(self.round.get() + 1);
+            Ok(())
+        }
+
+        fn train_weighted(&mut self,
+                          inputs: &Matrix<f64>,
+                          targets: &Vector<f64>,
+                          _: &Vector<f64>)
+                          -> LearningResult<()> {
+            self.train(inputs, targets)
+        }
+    }
+
+    #[test]
+    fn test_boosting_perfect_after_imperfect() {
+        let inputs = Matrix::new(10, 1, (1..11).map(|x| x as f64).collect::<Vec<f64>>());
+        let targets = Vector::new((1..11).map(|x| if x > 5 { 1.0 } else { -1.0 })
+                                         .collect::<Vec<f64>>());
+
+        // The last threshold separates the classes, but the
+        // earlier learners would outvote it on some samples.
+        let learner = ScriptedStump {
+            thresholds: vec![1.5, 4.5, 5.5],
+            round: Rc::new(Cell::new(0)),
+            threshold: None,
+        };
+        let mut boost = AdaBoost::with_learner(learner, 10, 1.0);
+        boost.train(&inputs, &targets).unwrap();
+
+        assert_eq!(boost.estimators().len(), 1);
+        assert_eq!(boost.estimators()[0].0.threshold, Some(5.5));
+        assert_eq!(boost.predict(&inputs).unwrap(), targets);
+    }
+
+    #[test]
+    fn test_boosting_rejects_non_binary_targets() {
+        let inputs = Matrix::new(4, 1, vec![1.0, 2.0, 3.0, 4.0]);
+
+        let mut boost = AdaBoost::default();
+        let res = boost.train(&inputs, &Vector::new(vec![0.0, 0.0, 1.0, 1.0]));
+        assert!(matches!(*res.unwrap_err().kind(), ErrorKind::InvalidData));
+
+        let mut stump = DecisionStump::default();
+        assert!(stump.train(&inputs, &Vector::new(vec![-1.0, 0.5, 1.0, 1.0])).is_err());
+    }
+
+    #[test]
+    fn test_boosting_integer_labels() {
+        let (inputs, targets) = diagonal_data();
+        let labels = targets.iter().map(|&t| if t > 0.0 { 1 } else { 0 }).collect::<Vec<usize>>();
+
+        let mut boost = AdaBoost::new(200, 1.0);
+        boost.train_labels(&inputs, &labels).unwrap();
+
+        let predicted = boost.predict_labels(&inputs).unwrap();
+        let wrong = predicted.iter().zip(labels.iter()).filter(|&(p, l)| p != l).count();
+        assert!(wrong <= 2);
+    }
+
+    #[test]
+    fn test_boosting_custom_learner() {
+        let (inputs, targets) = diagonal_data();
+
+        // A least squares fit of the classes finds the diagonal.
+        let mut boost = AdaBoost::with_learner(LinRegressor::default(), 5, 1.0);
+        boost.train(&inputs, &targets).unwrap();
+
+        assert!(training_error(&boost, &inputs, &targets) < 0.05);
+    }
+
+    #[test]
+    fn test_boosting_untrained() {
+        let boost = AdaBoost::default();
+        let inputs = Matrix::new(1, 1, vec![1.0]);
+
+        assert!(boost.predict(&inputs).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_boosting_invalid_learning_rate() {
+        let _ = AdaBoost::new(10, 0.0);
+    }
+}
@@ -0,0 +1,455 @@
+//! Bayesian Linear Regression module
+//!
+//! Contains an implementation of linear regression which keeps a
+//! Gaussian posterior distribution over the parameters.
+//!
+//! The weights have a zero mean Gaussian prior with precision
+//! `alpha`, and the targets have Gaussian noise with variance
+//! `sigma²`. The posterior over the parameters is then Gaussian,
+//! with covariance and mean
+//!
+//! S = (XᵀX / sigma² + alpha D)⁻¹, m = S Xᵀy / sigma²
+//!
+//! where `X` holds the inputs with a leading column of ones and `D`
+//! is the identity without its first entry, so that the intercept is
+//! not shrunk towards zero. As the prior becomes vague the posterior
+//! mean approaches the least squares parameters.
+//!
+//! The noise variance can be given, or else is estimated by maximizing
+//! the marginal likelihood of the targets for the fixed prior precision.
+//!
+//! The regressor will automatically add the intercept term
+//! so you do not need to format the input matrices yourself.
+//!
+//! # Usage
+//!
+//! ```
+//! use rusty_machine::learning::bayes_lin_reg::BayesianLinearRegression;
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::{Matrix, Vector};
+//!
+//! let inputs = Matrix::new(5,1,vec![1.0,2.0,3.0,4.0,5.0]);
+//! let targets = Vector::new(vec![2.1,3.9,6.2,7.8,10.1]);
+//!
+//! // A weak prior with a known noise variance.
+//! let mut model = BayesianLinearRegression::new(1e-3).with_noise_variance(0.04);
+//! model.train(&inputs, &targets).unwrap();
+//!
+//! // The predictions and their uncertainty.
+//! let new_points = Matrix::new(2,1,vec![3.5,20.0]);
+//! let (mean, variance) = model.predict_with_variance(&new_points).unwrap();
+//!
+//! // Predictions far from the data are less certain.
+//! assert!(variance[1] > variance[0]);
+//! assert!(variance[0] > 0.04);
+//! ```
+
+use linalg::{Matrix, BaseMatrix, Vector};
+use learning::{LearningResult, SupModel, validate_finite_matrix, validate_finite_vector};
+use learning::error::{Error, ErrorKind};
+
+/// The maximum number of iterations used to estimate the noise variance.
+const MAX_NOISE_ITERS: usize = 100;
+
+/// Relative change in the noise variance at which the estimation stops.
+const NOISE_TOL: f64 = 1e-10;
+
+/// The posterior distribution of the parameters.
+#[derive(Clone, Debug)]
+struct Posterior {
+    mean: Vector<f64>,
+    covariance: Matrix<f64>,
+    noise_variance: f64,
+}
+
+/// Bayesian Linear Regression Model.
+///
+/// Contains the prior precision of the weights, the noise
+/// variance if it is known and the posterior once trained.
+#[derive(Clone, Debug)]
+pub struct BayesianLinearRegression {
+    /// The precision of the Gaussian prior on the weights.
+    prior_precision: f64,
+    /// The noise variance, or `None` if it is estimated.
+    known_noise: Option<f64>,
+    /// The posterior from the last fit.
+    posterior: Option<Posterior>,
+}
+
+/// The default Bayesian linear regressor.
+///
+/// The prior precision is 1 and the noise variance is estimated.
+impl Default for BayesianLinearRegression {
+    fn default() -> BayesianLinearRegression {
+        BayesianLinearRegression::new(1f64)
+    }
+}
+
+impl BayesianLinearRegression {
+    /// Constructs an untrained regressor with the given precision of
+    /// the prior on the weights, which estimates the noise variance.
+    ///
+    /// Smaller precisions give vaguer priors.
+    ///
+    /// # Panics
+    ///
+    /// - `prior_precision` is not positive and finite.
+    pub fn new(prior_precision: f64) -> BayesianLinearRegression {
+        assert!(prior_precision > 0f64 && prior_precision.is_finite(),
+                "The prior precision must be positive and finite.");
+
+        BayesianLinearRegression {
+            prior_precision,
+            known_noise: None,
+            posterior: None,
+        }
+    }
+
+    /// Sets a known variance of the noise on the targets.
+    ///
+    /// # Panics
+    ///
+    /// - `variance` is not positive and finite.
+    pub fn with_noise_variance(mut self, variance: f64) -> Self {
+        assert!(variance > 0f64 && variance.is_finite(),
+                "The noise variance must be positive and finite.");
+
+        self.known_noise = Some(variance);
+        self
+    }
+
+    /// The precision of the Gaussian prior on the weights.
+    pub fn prior_precision(&self) -> f64 {
+        self.prior_precision
+    }
+
+    /// The noise variance used in the last fit.
+    ///
+    /// This is the known noise variance if one was given, and
+    /// otherwise the estimate. Returns `None` if the model has
+    /// not been trained.
+    pub fn noise_variance(&self) -> Option<f64> {
+        self.posterior.as_ref().map(|p| p.noise_variance)
+    }
+
+    /// The mean of the posterior over the parameters,
+    /// with the intercept first.
+    ///
+    /// Returns `None` if the model has not been trained.
+    pub fn posterior_mean(&self) -> Option<&Vector<f64>> {
+        self.posterior.as_ref().map(|p| &p.mean)
+    }
+
+    /// The covariance of the posterior over the parameters,
+    /// with the intercept first.
+    ///
+    /// Returns `None` if the model has not been trained.
+    pub fn posterior_covariance(&self) -> Option<&Matrix<f64>> {
+        self.posterior.as_ref().map(|p| &p.covariance)
+    }
+
+    /// Predicts the mean and variance of the target at each input.
+    ///
+    /// The predictive variance is the sum of the uncertainty in the
+    /// parameters, `xᵀSx`, and the noise variance.
+    ///
+    /// # Failures
+    ///
+    /// - The model has not been trained.
+    /// - The inputs have a different number of columns to the training inputs.
+    pub fn predict_with_variance(&self, inputs: &Matrix<f64>) -> LearningResult<(Vector<f64>, Vector<f64>)> {
+        let posterior = self.posterior.as_ref().ok_or_else(Error::new_untrained)?;
+        let full_inputs = BayesianLinearRegression::full_inputs(posterior, inputs)?;
+
+        let mean = &full_inputs * &posterior.mean;
+        let variance = full_inputs.row_iter()
+            .map(|row| {
+                let x = Vector::new(row.raw_slice());
+                x.dot(&(&posterior.covariance * &x)) + posterior.noise_variance
+            })
+            .collect::<Vec<f64>>();
+
+        Ok((mean, Vector::new(variance)))
+    }
+
+    /// Adds the intercept column to the inputs, checking their size.
+    fn full_inputs(posterior: &Posterior, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        if inputs.cols() + 1 != posterior.mean.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The inputs must have the same number of columns as in training."));
+        }
+
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        Ok(ones.hcat(inputs))
+    }
+
+    /// Computes the posterior for the given noise variance from
+    /// the Gram matrix `XᵀX` and the moments `Xᵀy`.
+    fn posterior(&self,
+                 gram: &Matrix<f64>,
+                 moments: &Vector<f64>,
+                 noise_variance: f64)
+                 -> LearningResult<Posterior> {
+        let mut precision = gram / noise_variance;
+        for i in 1..precision.rows() {
+            precision[[i, i]] += self.prior_precision;
+        }
+
+        let factor = match precision.cholesky() {
+            Ok(ref factor) if factor.diag().all(|d| d.is_finite() && *d > 0f64) => factor.clone(),
+            _ => {
+                return Err(Error::new(ErrorKind::LinearAlgebra,
+                                      "The posterior precision is not positive definite."))
+            }
+        };
+
+        let inv_factor = factor.inverse()?;
+        let covariance = inv_factor.transpose() * inv_factor;
+        let mean = &covariance * moments / noise_variance;
+
+        Ok(Posterior {
+            mean,
+            covariance,
+            noise_variance,
+        })
+    }
+
+    /// Estimates the noise variance by maximizing the marginal likelihood.
+    ///
+    /// Uses the fixed point iteration `sigma² = |y - Xm|² / (n - gamma)`,
+    /// where `gamma` is the effective number of parameters.
+    fn estimate_posterior(&self,
+                          full_inputs: &Matrix<f64>,
+                          targets: &Vector<f64>,
+                          gram: &Matrix<f64>,
+                          moments: &Vector<f64>)
+                          -> LearningResult<Posterior> {
+        let n = targets.size() as f64;
+        let target_mean = targets.sum() / n;
+        let target_var = targets.iter().map(|t| (t - target_mean) * (t - target_mean)).sum::<f64>() / n;
+        let mut noise_variance = if target_var > 0f64 { target_var } else { 1f64 };
+
+        let mut posterior = self.posterior(gram, moments, noise_variance)?;
+        for _ in 0..MAX_NOISE_ITERS {
+            let residuals = targets - full_inputs * &posterior.mean;
+            let sq_error = residuals.dot(&residuals);
+
+            // The intercept is determined by the data alone.
+            let n_effective = posterior.mean.size() as f64 -
+                              self.prior_precision * posterior.covariance.diag().skip(1).sum::<f64>();
+            if n <= n_effective {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Not enough samples to estimate the noise variance."));
+            }
+            if sq_error == 0f64 {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "The targets are fit exactly, so the noise variance must be given."));
+            }
+
+            let new_variance = sq_error / (n - n_effective);
+            let change = (new_variance - noise_variance).abs() / noise_variance;
+            noise_variance = new_variance;
+            posterior = self.posterior(gram, moments, noise_variance)?;
+
+            if change < NOISE_TOL {
+                break;
+            }
+        }
+
+        Ok(posterior)
+    }
+}
+
+impl SupModel<Matrix<f64>, Vector<f64>> for BayesianLinearRegression {
+    /// Predict the posterior mean of the target at each input.
+    ///
+    /// Model must be trained before prediction can be made.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        let posterior = self.posterior.as_ref().ok_or_else(Error::new_untrained)?;
+        let full_inputs = BayesianLinearRegression::full_inputs(posterior, inputs)?;
+        Ok(full_inputs * &posterior.mean)
+    }
+
+    /// Compute the posterior over the parameters.
+    ///
+    /// If the noise variance is not known it is estimated first.
+    ///
+    /// # Failures
+    ///
+    /// - The inputs or targets are empty or contain non-finite values.
+    /// - The targets have a different length to the number of inputs.
+    /// - The noise variance is estimated, but there are too few samples
+    ///   or the targets are fit exactly.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_vector("target", targets)?;
+
+        if inputs.rows() == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "Cannot train without samples."));
+        }
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of targets must match the number of inputs."));
+        }
+
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        let full_inputs = ones.hcat(inputs);
+        let xt = full_inputs.transpose();
+        let gram = &xt * &full_inputs;
+        let moments = xt * targets;
+
+        let posterior = match self.known_noise {
+            Some(variance) => self.posterior(&gram, &moments, variance)?,
+            None => self.estimate_posterior(&full_inputs, targets, &gram, &moments)?,
+        };
+
+        self.posterior = Some(posterior);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BayesianLinearRegression;
+    use learning::SupModel;
+    use learning::lin_reg::LinRegressor;
+    use linalg::{Matrix, BaseMatrix, Vector};
+
+    /// Deterministic noise which is roughly uniform on [-1, 1].
+    fn noise(i: usize) -> f64 {
+        ((i * 7919 % 1000) as f64 / 500.0) - 1.0
+    }
+
+    /// Samples of `y = 1 + 2 x0 - x1` with noise of the given scale.
+    fn noisy_plane(n: usize, scale: f64) -> (Matrix<f64>, Vector<f64>) {
+        let mut inputs = Vec::with_capacity(2 * n);
+        let mut targets = Vec::with_capacity(n);
+        for i in 0..n {
+            let x0 = (i as f64 * 0.37).sin() * 3.0;
+            let x1 = (i as f64 * 0.91).cos() * 2.0;
+            inputs.push(x0);
+            inputs.push(x1);
+            targets.push(1.0 + 2.0 * x0 - x1 + scale * noise(i));
+        }
+
+        (Matrix::new(n, 2, inputs), Vector::new(targets))
+    }
+
+    #[test]
+    fn test_variance_shrinks_with_more_data() {
+        let test_inputs = Matrix::new(2, 2, vec![0.5, -0.5, 2.0, 1.0]);
+        let noise_variance = 0.1;
+
+        let mut previous = None;
+        for &n in &[5, 20, 80, 320] {
+            let (inputs, targets) = noisy_plane(n, 0.5);
+            let mut model = BayesianLinearRegression::new(0.1).with_noise_variance(noise_variance);
+            model.train(&inputs, &targets).unwrap();
+
+            let (_, variance) = model.predict_with_variance(&test_inputs).unwrap();
+            assert!(variance.iter().all(|&v| v > noise_variance));
+            if let Some(previous) = previous {
+                let previous: Vector<f64> = previous;
+                assert!(variance.iter().zip(previous.iter()).all(|(v, p)| v < p));
+            }
+            previous = Some(variance);
+        }
+
+        // With plenty of data only the noise remains.
+        assert!(previous.unwrap().iter().all(|&v| v < noise_variance * 1.05));
+    }
+
+    #[test]
+    fn test_vague_prior_approaches_least_squares() {
+        let (inputs, targets) = noisy_plane(30, 0.5);
+
+        let mut lin_mod = LinRegressor::default();
+        lin_mod.train(&inputs, &targets).unwrap();
+        let ols = lin_mod.parameters().unwrap().clone();
+
+        let mut previous_diff = ::std::f64::INFINITY;
+        for &precision in &[10.0, 1.0, 1e-3, 1e-8] {
+            let mut model = BayesianLinearRegression::new(precision);
+            model.train(&inputs, &targets).unwrap();
+
+            let error = model.posterior_mean().unwrap() - &ols;
+            let diff = error.dot(&error).sqrt();
+            assert!(diff < previous_diff);
+            previous_diff = diff;
+        }
+
+        assert!(previous_diff < 1e-6, "{}", previous_diff);
+    }
+
+    #[test]
+    fn test_strong_prior_shrinks_weights() {
+        let (inputs, targets) = noisy_plane(30, 0.5);
+
+        let mut model = BayesianLinearRegression::new(1e8).with_noise_variance(0.1);
+        model.train(&inputs, &targets).unwrap();
+
+        // The weights vanish, leaving the intercept at the target mean.
+        let mean = model.posterior_mean().unwrap();
+        assert!(mean[1].abs() < 1e-4 && mean[2].abs() < 1e-4);
+        assert!((mean[0] - targets.sum() / 30.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_estimated_noise_variance() {
+        let (inputs, targets) = noisy_plane(400, 0.6);
+
+        let mut model = BayesianLinearRegression::new(1e-3);
+        model.train(&inputs, &targets).unwrap();
+
+        // Uniform noise on [-0.6, 0.6] has variance 0.12.
+        let variance = model.noise_variance().unwrap();
+        assert!((variance - 0.12).abs() < 0.02, "{}", variance);
+
+        let outputs = model.predict(&inputs).unwrap();
+        let (mean, _) = model.predict_with_variance(&inputs).unwrap();
+        assert_eq!(outputs.into_vec(), mean.into_vec());
+    }
+
+    #[test]
+    fn test_covariance_matches_known_noise() {
+        let (inputs, targets) = noisy_plane(10, 0.5);
+
+        let mut model = BayesianLinearRegression::new(2.0).with_noise_variance(0.5);
+        model.train(&inputs, &targets).unwrap();
+        assert_eq!(model.noise_variance(), Some(0.5));
+        assert_eq!(model.prior_precision(), 2.0);
+
+        // The covariance inverts the posterior precision.
+        let full_inputs = Matrix::<f64>::ones(10, 1).hcat(&inputs);
+        let mut precision = full_inputs.transpose() * full_inputs / 0.5;
+        for i in 1..3 {
+            precision[[i, i]] += 2.0;
+        }
+        let identity = precision * model.posterior_covariance().unwrap();
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((identity[[i, j]] - expected).abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        let mut model = BayesianLinearRegression::default();
+        assert!(model.predict(&Matrix::new(1, 1, vec![1.0])).is_err());
+
+        let inputs = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+        assert!(model.train(&inputs, &Vector::new(vec![1.0, 2.0])).is_err());
+
+
+        model.train(&inputs, &Vector::new(vec![2.0, 4.5, 5.5])).unwrap();
+        assert!(model.predict(&Matrix::new(1, 2, vec![1.0, 2.0])).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_prior_precision() {
+        let _ = BayesianLinearRegression::new(0.0);
+    }
+}
@@ -0,0 +1,177 @@
+//! Probability Calibration Module
+//!
+//! Contains an implementation of Platt scaling, which maps the
+//! raw scores of a binary classifier to calibrated probabilities.
+//!
+//! The scaler fits a logistic regression on a single feature, the
+//! classifier score, using a held-out calibration set. Following
+//! [Platt (1999)](http://citeseerx.ist.psu.edu/viewdoc/summary?doi=10.1.1.41.1639)
+//! the targets are smoothed to reduce overfitting on small sets.
+//!
+//! # Usage
+//!
+//! ```
+//! use rusty_machine::learning::calibration::PlattScaler;
+//! use rusty_machine::learning::svm::SVM;
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::{Matrix, Vector};
+//!
+//! let inputs = Matrix::new(4,1,vec![1.0,3.0,5.0,7.0]);
+//! let targets = Vector::new(vec![-1.,-1.,1.,1.]);
+//!
+//! let mut svm_mod = SVM::default();
+//! svm_mod.train(&inputs, &targets).unwrap();
+//!
+//! // Fit the scaler on held-out data.
+//! let calib_inputs = Matrix::new(4,1,vec![2.0,4.0,4.5,6.0]);
+//! let calib_targets = Vector::new(vec![-1.,-1.,1.,1.]);
+//! let scores = svm_mod.decision_function(&calib_inputs).unwrap();
+//!
+//! let scaler = PlattScaler::fit(&scores, &calib_targets).unwrap();
+//!
+//! // Get the probability of the positive class for new points.
+//! let new_scores = svm_mod.decision_function(&Matrix::new(1,1,vec![10.])).unwrap();
+//! let probs = scaler.predict_proba(&new_scores).unwrap();
+//! ```
+
+use linalg::{Matrix, Vector};
+use learning::{LearningResult, SupModel};
+use learning::logistic_reg::LogisticRegressor;
+use learning::optim::grad_desc::GradientDesc;
+use learning::error::{Error, ErrorKind};
+
+/// Platt Scaling model.
+///
+/// Wraps a logistic regression from classifier scores to
+/// the probability of the positive class.
+#[derive(Debug)]
+pub struct PlattScaler {
+    model: LogisticRegressor<GradientDesc>,
+}
+
+impl PlattScaler {
+    /// Fit the scaler to the scores of a calibration set.
+    ///
+    /// Targets greater than zero are treated as the positive class,
+    /// so both `0`/`1` and `-1`/`1` labels are accepted.
+    ///
+    /// # Failures
+    ///
+    /// - The scores and targets have different lengths.
+    /// - The targets do not contain both classes.
+    pub fn fit(scores: &Vector<f64>, targets: &Vector<f64>) -> LearningResult<PlattScaler> {
+        if scores.size() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Scores and targets have different lengths."));
+        }
+
+        let n_pos = targets.iter().filter(|&&t| t > 0f64).count() as f64;
+        let n_neg = targets.size() as f64 - n_pos;
+
+        if n_pos == 0f64 || n_neg == 0f64 {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Calibration targets must contain both classes."));
+        }
+
+        // The smoothed targets of Platt's method.
+        let pos_target = (n_pos + 1f64) / (n_pos + 2f64);
+        let neg_target = 1f64 / (n_neg + 2f64);
+        let smoothed = targets.iter()
+            .map(|&t| if t > 0f64 { pos_target } else { neg_target })
+            .collect::<Vec<f64>>();
+
+        let inputs = Matrix::new(scores.size(), 1, scores.data().clone());
+
+        let mut model = LogisticRegressor::new(GradientDesc::new(1.0, 1000));
+        model.train(&inputs, &Vector::new(smoothed))?;
+
+        Ok(PlattScaler { model })
+    }
+
+    /// The fitted intercept and slope of the logistic regression.
+    pub fn parameters(&self) -> Option<&Vector<f64>> {
+        self.model.parameters()
+    }
+
+    /// Compute the probability of the positive class for each score.
+    pub fn predict_proba(&self, scores: &Vector<f64>) -> LearningResult<Vector<f64>> {
+        let inputs = Matrix::new(scores.size(), 1, scores.data().clone());
+        self.model.predict(&inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PlattScaler;
+    use linalg::Vector;
+    use learning::toolkit::activ_fn::{ActivationFunc, Sigmoid};
+    use learning::toolkit::cost_fn::{CostFunc, CrossEntropyError};
+
+    use rand::{Rng, StdRng, SeedableRng};
+    use rand::distributions::{Normal, IndependentSample};
+
+    /// Samples overconfident scores, where the true probability
+    /// of the positive class is `sigmoid(0.5 * s - 0.5)`.
+    fn sample_scores(n: usize, rng: &mut StdRng) -> (Vector<f64>, Vector<f64>) {
+        let normal = Normal::new(0.0, 3.0);
+
+        let scores = (0..n).map(|_| normal.ind_sample(rng)).collect::<Vec<f64>>();
+        let targets = scores.iter()
+            .map(|s| if rng.next_f64() < Sigmoid::func(0.5 * s - 0.5) { 1f64 } else { 0f64 })
+            .collect::<Vec<f64>>();
+
+        (Vector::new(scores), Vector::new(targets))
+    }
+
+    #[test]
+    fn test_platt_monotonic() {
+        let mut rng = StdRng::from_seed(&[1, 2, 3]);
+        let (scores, targets) = sample_scores(200, &mut rng);
+
+        let scaler = PlattScaler::fit(&scores, &targets).unwrap();
+
+        let grid = Vector::new((0..41).map(|i| i as f64 * 0.5 - 10.0).collect::<Vec<f64>>());
+        let probs = scaler.predict_proba(&grid).unwrap();
+
+        for i in 1..probs.size() {
+            assert!(probs[i] > probs[i - 1]);
+        }
+    }
+
+    #[test]
+    fn test_platt_better_calibrated() {
+        let mut rng = StdRng::from_seed(&[1, 2, 3]);
+        let (calib_scores, calib_targets) = sample_scores(500, &mut rng);
+        let (test_scores, test_targets) = sample_scores(500, &mut rng);
+
+        let scaler = PlattScaler::fit(&calib_scores, &calib_targets).unwrap();
+
+        let params = scaler.parameters().unwrap();
+        assert!((params[0] + 0.5).abs() < 0.3);
+        assert!((params[1] - 0.5).abs() < 0.2);
+
+        let platt_probs = scaler.predict_proba(&test_scores).unwrap();
+        let raw_probs = test_scores.apply(&Sigmoid::func);
+
+        let platt_loss = CrossEntropyError::cost(&platt_probs, &test_targets);
+        let raw_loss = CrossEntropyError::cost(&raw_probs, &test_targets);
+
+        assert!(platt_loss < raw_loss);
+    }
+
+    #[test]
+    fn test_platt_single_class() {
+        let scores = Vector::new(vec![0.5, 1.0, 2.0]);
+        let targets = Vector::new(vec![1.0, 1.0, 1.0]);
+
+        assert!(PlattScaler::fit(&scores, &targets).is_err());
+    }
+
+    #[test]
+    fn test_platt_mismatched_lengths() {
+        let scores = Vector::new(vec![0.5, 1.0, 2.0]);
+        let targets = Vector::new(vec![-1.0, 1.0]);
+
+        assert!(PlattScaler::fit(&scores, &targets).is_err());
+    }
+}
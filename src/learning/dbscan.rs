@@ -36,8 +36,9 @@
 //! let clustering = model.clusters().unwrap();
 //! ```
 
-use learning::{LearningResult, UnSupModel};
+use learning::{LearningResult, UnSupModel, validate_finite_matrix};
 use learning::error::{Error, ErrorKind};
+use learning::toolkit::distance::{pairwise_distances, Euclidean};
 
 use linalg::{Matrix, Vector, BaseMatrix};
 use rulinalg::utils;
@@ -78,6 +79,7 @@ impl Default for DBSCAN {
 impl UnSupModel<Matrix<f64>, Vector<Option<usize>>> for DBSCAN {
     /// Train the classifier using input data.
     fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
         self.init_params(inputs.rows());
         let mut cluster = 0;
 
@@ -108,17 +110,10 @@ impl UnSupModel<Matrix<f64>, Vector<Option<usize>>> for DBSCAN {
             if let (&Some(ref cluster_data), &Some(ref clusters)) = (&self._cluster_data,
                                                                      &self.clusters) {
                 let mut classes = Vec::with_capacity(inputs.rows());
+                let all_distances = pairwise_distances(inputs, cluster_data, &Euclidean);
 
-                for input_point in inputs.row_iter() {
-                    let mut distances = Vec::with_capacity(cluster_data.rows());
-
-                    for cluster_point in cluster_data.row_iter() {
-                        let point_distance =
-                            utils::vec_bin_op(input_point.raw_slice(), cluster_point.raw_slice(), |x, y| x - y);
-                        distances.push(utils::dot(&point_distance, &point_distance).sqrt());
-                    }
-
-                    let (closest_idx, closest_dist) = utils::argmin(&distances);
+                for distances in all_distances.row_iter() {
+                    let (closest_idx, closest_dist) = utils::argmin(distances.raw_slice());
                     if closest_dist < self.eps {
                         classes.push(clusters[closest_idx]);
                     } else {
@@ -229,6 +224,7 @@ impl DBSCAN {
 #[cfg(test)]
 mod tests {
     use super::DBSCAN;
+    use learning::UnSupModel;
     use linalg::{Matrix, BaseMatrix};
 
     #[test]
@@ -256,4 +252,23 @@ mod tests {
 
         assert!(neighbours.len() == 1);
     }
+
+    #[test]
+    fn test_predict_far_from_origin() {
+        let offset = 1.7e9;
+        let inputs = Matrix::new(4, 1, vec![offset, offset + 1.0, offset + 2.0, offset + 50.0]);
+
+        let mut model = DBSCAN::new(1.5, 2);
+        model.set_predictive(true);
+        model.train(&inputs).unwrap();
+
+        // Predicting the training points reproduces the training clusters.
+        let clusters = model.clusters().unwrap().clone();
+        assert_eq!(clusters.data(), &[Some(0), Some(0), Some(0), None]);
+        assert_eq!(model.predict(&inputs).unwrap(), clusters);
+
+        // The closest point is 4 away.
+        let new_point = Matrix::new(1, 1, vec![offset + 6.0]);
+        assert_eq!(model.predict(&new_point).unwrap().data(), &[None]);
+    }
 }
@@ -30,7 +30,11 @@ pub enum ErrorKind {
     /// The model has not been trained
     UntrainedModel,
     /// Linear algebra related error
-    LinearAlgebra
+    LinearAlgebra,
+    /// The model does not support the requested operation
+    UnsupportedOperation,
+    /// Reading or writing a file failed
+    Io,
 }
 
 impl Error {
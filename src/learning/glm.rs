@@ -38,7 +38,7 @@
 use linalg::Vector;
 use linalg::{Matrix, BaseMatrix};
 
-use learning::{LearningResult, SupModel};
+use learning::{LearningResult, SupModel, validate_finite_matrix, validate_finite_vector};
 use learning::error::{Error, ErrorKind};
 
 /// The Generalized Linear Model
@@ -97,6 +97,8 @@ impl<C: Criterion> SupModel<Matrix<f64>, Vector<f64>> for GenLinearModel<C> {
             return Err(Error::new(ErrorKind::InvalidData,
                                   "Training data do not have the same dimensions"));
         }
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_vector("target", targets)?;
 
         // Construct initial estimate for mu
         let mut mu = Vector::new(self.criterion.initialize_mu(targets.data()));
@@ -30,11 +30,28 @@
 //! // Probabilities that each point comes from each Gaussian.
 //! println!("{:?}", post_probs.data());
 //! ```
+//!
+//! Data which does not fit in memory can be fit in chunks using
+//! `partial_fit`, which runs online EM. Each call updates running
+//! averages of the sufficient statistics of the mixture with a
+//! step size that decays as more chunks are seen.
+//!
+//! ```
+//! use rusty_machine::linalg::Matrix;
+//! use rusty_machine::learning::gmm::GaussianMixtureModel;
+//!
+//! let chunk_a = Matrix::new(4, 2, vec![1.0, 2.0, -3.0, -3.0, 0.1, 1.5, -5.0, -2.5]);
+//! let chunk_b = Matrix::new(4, 2, vec![1.2, 1.9, -3.5, -2.0, 0.4, 1.8, -4.1, -3.0]);
+//!
+//! let mut model = GaussianMixtureModel::new(2);
+//! model.partial_fit(&chunk_a).unwrap();
+//! model.partial_fit(&chunk_b).unwrap();
+//! ```
 use linalg::{Matrix, MatrixSlice, Vector, BaseMatrix, BaseMatrixMut, Axes};
 use rulinalg::utils;
 use rulinalg::matrix::decomposition::{PartialPivLu};
 
-use learning::{LearningResult, UnSupModel};
+use learning::{LearningResult, UnSupModel, ProbabilisticClassifier, validate_finite_matrix};
 use learning::toolkit::rand_utils;
 use learning::error::{Error, ErrorKind};
 
@@ -42,15 +59,31 @@ use learning::error::{Error, ErrorKind};
 ///
 /// - Full : The full covariance structure.
 /// - Regularized : Adds a regularization constant to the covariance diagonal.
+/// - LedoitWolf : Shrinks the full covariance towards a scaled identity.
 /// - Diagonal : Only the diagonal covariance structure.
+/// - Spherical : A single variance per component.
+/// - Tied : One full covariance shared by all components.
 #[derive(Clone, Copy, Debug)]
 pub enum CovOption {
     /// The full covariance structure.
     Full,
     /// Adds a regularization constant to the covariance diagonal.
     Regularized(f64),
+    /// Shrinks the full covariance towards a scaled identity.
+    ///
+    /// The shrinkage intensity is chosen from the data by the Ledoit-Wolf
+    /// estimate. This keeps the covariances of components with few points,
+    /// or of high dimensional data, well-conditioned. A component whose
+    /// points are identical still has a singular covariance.
+    ///
+    /// This option is not supported by `partial_fit`.
+    LedoitWolf,
     /// Only the diagonal covariance structure.
     Diagonal,
+    /// A single variance per component, shared by all features.
+    Spherical,
+    /// One full covariance structure shared by all components.
+    Tied,
 }
 
 
@@ -63,37 +96,61 @@ pub struct GaussianMixtureModel {
     model_covars: Option<Vec<Matrix<f64>>>,
     log_lik: f64,
     max_iters: usize,
+    tol: f64,
+    warm_start: bool,
+    stream_stats: Option<StreamStats>,
+    chunks_seen: usize,
+    step_offset: f64,
+    step_decay: f64,
+    n_iter: usize,
+    converged: bool,
     /// The covariance options for the GMM.
     pub cov_option: CovOption,
 }
 
+/// Running averages of the sufficient statistics used by online EM.
+#[derive(Debug)]
+struct StreamStats {
+    /// The mean membership weight of each component.
+    weights: Vector<f64>,
+    /// The mean membership weighted input for each component, one per row.
+    sums: Matrix<f64>,
+    /// The mean membership weighted outer product of the inputs for each component.
+    outer: Vec<Matrix<f64>>,
+}
+
 impl UnSupModel<Matrix<f64>, Matrix<f64>> for GaussianMixtureModel {
     /// Train the model using inputs.
     fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+
         let reg_value = if inputs.rows() > 1 {
             1f64 / (inputs.rows() - 1) as f64
         } else {
             return Err(Error::new(ErrorKind::InvalidData, "Only one row of data provided."));
         };
 
-        // Initialization:
-        let k = self.comp_count;
-
-        self.model_covars = {
-            let cov_mat = try!(self.initialize_covariances(inputs, reg_value));
-            Some(vec![cov_mat; k])
-        };
+        if self.warm_start {
+            // Continue from the current parameters
+            self.validate_columns(inputs)?;
+        } else {
+            self.initialize_params(inputs, reg_value)?;
+        }
 
-        let random_rows: Vec<usize> =
-            rand_utils::reservoir_sample(&(0..inputs.rows()).collect::<Vec<usize>>(), k);
-        self.model_means = Some(inputs.select_rows(&random_rows));
+        // Any later online updates start from the batch solution
+        self.stream_stats = None;
+        self.chunks_seen = 0;
+        self.n_iter = 0;
+        self.converged = false;
 
         for _ in 0..self.max_iters {
             let log_lik_0 = self.log_lik;
 
             let (weights, log_lik_1) = try!(self.membership_weights(inputs));
+            self.n_iter += 1;
 
-            if (log_lik_1 - log_lik_0).abs() < 1e-15 {
+            if (log_lik_1 - log_lik_0).abs() < self.tol {
+                self.converged = true;
                 break;
             }
 
@@ -116,11 +173,19 @@ impl UnSupModel<Matrix<f64>, Matrix<f64>> for GaussianMixtureModel {
     }
 }
 
+/// The membership probabilities of each component, as given by `predict`.
+impl ProbabilisticClassifier for GaussianMixtureModel {
+    fn predict_proba(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        self.predict(inputs)
+    }
+}
+
 impl GaussianMixtureModel {
     /// Constructs a new Gaussian Mixture Model
     ///
-    /// Defaults to 100 maximum iterations and
-    /// full covariance structure.
+    /// Defaults to 100 maximum iterations,
+    /// full covariance structure, and the
+    /// step size schedule `(t + 1)^-0.7` for `partial_fit`.
     ///
     /// # Examples
     /// ```
@@ -136,6 +201,14 @@ impl GaussianMixtureModel {
             model_covars: None,
             log_lik: 0f64,
             max_iters: 100,
+            tol: 1e-15,
+            warm_start: false,
+            stream_stats: None,
+            chunks_seen: 0,
+            step_offset: 1f64,
+            step_decay: 0.7,
+            n_iter: 0,
+            converged: false,
             cov_option: CovOption::Full,
         }
     }
@@ -178,11 +251,85 @@ impl GaussianMixtureModel {
                 model_covars: None,
                 log_lik: 0f64,
                 max_iters: 100,
+                tol: 1e-15,
+                warm_start: false,
+                stream_stats: None,
+                chunks_seen: 0,
+                step_offset: 1f64,
+                step_decay: 0.7,
+                n_iter: 0,
+                converged: false,
                 cov_option: CovOption::Full,
             })
         }
     }
 
+    /// Constructs a new GMM which begins the EM algorithm from the given parameters.
+    ///
+    /// The number of components is taken from the rows of `means`. Each
+    /// row is the mean of one component and each entry of `covs` is the
+    /// matching covariance. The mixture weights are normalized to sum to one.
+    /// The covariances must have the structure described by `cov_option`,
+    /// which is used for training.
+    ///
+    /// Training the model will refine these parameters instead of randomly
+    /// initializing them. Training the model again will continue from the
+    /// previous fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gmm::{CovOption, GaussianMixtureModel};
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let means = Matrix::new(2, 2, vec![0.0, 0.0, 5.0, 5.0]);
+    /// let covs = vec![Matrix::identity(2), Matrix::identity(2)];
+    /// let mix_weights = Vector::new(vec![0.5, 0.5]);
+    ///
+    /// let gmm = GaussianMixtureModel::with_init(means, covs, mix_weights, CovOption::Full).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// Fails if any of the following conditions are met:
+    ///
+    /// - There is not one covariance per row of `means`.
+    /// - A covariance is not square with the same size as the `means` columns.
+    /// - A covariance is not symmetric.
+    /// - A covariance is not diagonal for `CovOption::Diagonal`, or not a
+    ///   positive multiple of the identity for `CovOption::Spherical`.
+    /// - The covariances are not all equal for `CovOption::Tied`.
+    /// - Mixture weights do not have one entry per row of `means`.
+    /// - Mixture weights have a negative entry.
+    pub fn with_init(means: Matrix<f64>,
+                     covs: Vec<Matrix<f64>>,
+                     mixture_weights: Vector<f64>,
+                     cov_option: CovOption)
+                     -> LearningResult<GaussianMixtureModel> {
+        let k = means.rows();
+        let d = means.cols();
+
+        if covs.len() != k {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "There must be one covariance for each mean."));
+        }
+
+        if covs.iter().any(|c| c.rows() != d || c.cols() != d) {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "Covariances must be square with the same dimension as the means."));
+        }
+
+        check_cov_structure(cov_option, &covs)?;
+
+        let mut gmm = GaussianMixtureModel::with_weights(k, mixture_weights)?;
+        gmm.cov_option = cov_option;
+        gmm.model_means = Some(means);
+        gmm.model_covars = Some(covs);
+        gmm.warm_start = true;
+
+        Ok(gmm)
+    }
+
     /// The model means
     ///
     /// Returns an Option<&Matrix<f64>> containing
@@ -210,6 +357,49 @@ impl GaussianMixtureModel {
         &self.mix_weights
     }
 
+    /// Whether the last call to `train` stopped because the log-likelihood
+    /// changed by less than `tol`, rather than by reaching the maximum number
+    /// of iterations.
+    ///
+    /// Returns `false` if the model has not been trained with `train`.
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    /// The number of EM iterations run by the last call to `train`.
+    ///
+    /// As for `KMeansClassifier::n_iter`, this includes the iteration
+    /// which found that the model had converged.
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+
+    /// The change in log-likelihood below which `train` stops.
+    pub fn tol(&self) -> f64 {
+        self.tol
+    }
+
+    /// Sets the change in log-likelihood below which `train` stops.
+    ///
+    /// Defaults to `1e-15`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gmm::GaussianMixtureModel;
+    ///
+    /// let mut gmm = GaussianMixtureModel::new(2);
+    /// gmm.set_tol(1e-6);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `tol` is negative or NaN.
+    pub fn set_tol(&mut self, tol: f64) {
+        assert!(tol >= 0f64, "The tolerance must be non-negative.");
+        self.tol = tol;
+    }
+
     /// Sets the max number of iterations for the EM algorithm.
     ///
     /// # Examples
@@ -224,6 +414,210 @@ impl GaussianMixtureModel {
         self.max_iters = iters;
     }
 
+    /// Sets the step size schedule used by `partial_fit`.
+    ///
+    /// The statistics of the `t`-th chunk, counting from zero, are
+    /// mixed into the running statistics with weight
+    /// `(t + offset)^-decay`. A larger offset slows down the early
+    /// updates, and a larger decay forgets old chunks more slowly.
+    /// The online estimates converge when the decay is in `(0.5, 1]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gmm::GaussianMixtureModel;
+    ///
+    /// let mut gmm = GaussianMixtureModel::new(2);
+    /// gmm.set_step_size_schedule(10.0, 0.6);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `offset` is less than 1.
+    /// - `decay` is not in `(0.5, 1]`.
+    pub fn set_step_size_schedule(&mut self, offset: f64, decay: f64) {
+        assert!(offset >= 1f64, "The step size offset must be at least 1.");
+        assert!(decay > 0.5 && decay <= 1f64, "The step size decay must be in (0.5, 1].");
+
+        self.step_offset = offset;
+        self.step_decay = decay;
+    }
+
+    /// The offset and decay of the step size schedule used by `partial_fit`.
+    pub fn step_size_schedule(&self) -> (f64, f64) {
+        (self.step_offset, self.step_decay)
+    }
+
+    /// Updates the model from a chunk of the data using online EM.
+    ///
+    /// The membership weights of the chunk are computed from the current
+    /// parameters, and the statistics they give are averaged into the
+    /// running statistics using the step size schedule. The parameters are
+    /// then set from the running statistics. Passing every chunk of the data
+    /// several times approaches the result of `train` on the full data, but
+    /// only one chunk is held in memory at a time.
+    ///
+    /// If the model has no parameters yet they are initialized
+    /// from the first chunk, as in `train`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gmm::GaussianMixtureModel;
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// let mut gmm = GaussianMixtureModel::new(2);
+    /// let chunk = Matrix::new(4, 1, vec![-1.0, -1.2, 3.0, 3.1]);
+    ///
+    /// gmm.partial_fit(&chunk).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - Some input is NaN or infinite.
+    /// - The first chunk has only one row.
+    /// - The chunk has a different number of columns to the model.
+    /// - The covariance option is `CovOption::LedoitWolf`.
+    pub fn partial_fit(&mut self, chunk: &Matrix<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", chunk)?;
+
+        if let CovOption::LedoitWolf = self.cov_option {
+            return Err(Error::new(ErrorKind::UnsupportedOperation,
+                                  "Ledoit-Wolf shrinkage is not supported by online EM."));
+        }
+
+        if self.model_means.is_none() || self.model_covars.is_none() {
+            if chunk.rows() <= 1 {
+                return Err(Error::new(ErrorKind::InvalidData, "Only one row of data provided."));
+            }
+            let reg_value = 1f64 / (chunk.rows() - 1) as f64;
+            self.initialize_params(chunk, reg_value)?;
+        } else {
+            self.validate_columns(chunk)?;
+        }
+
+        let (member_weights, log_lik) = self.membership_weights(chunk)?;
+        let chunk_stats = self.chunk_stats(chunk, &member_weights);
+
+        let step = (self.chunks_seen as f64 + self.step_offset).powf(-self.step_decay);
+        let stats = match self.stream_stats.take() {
+            Some(old) => {
+                StreamStats {
+                    weights: old.weights * (1f64 - step) + chunk_stats.weights * step,
+                    sums: old.sums * (1f64 - step) + chunk_stats.sums * step,
+                    outer: old.outer
+                        .into_iter()
+                        .zip(chunk_stats.outer)
+                        .map(|(o, c)| o * (1f64 - step) + c * step)
+                        .collect(),
+                }
+            }
+            None => chunk_stats,
+        };
+
+        self.set_params_from_stats(&stats);
+        self.stream_stats = Some(stats);
+        self.chunks_seen += 1;
+        self.log_lik = log_lik;
+        Ok(())
+    }
+
+    /// Randomly chooses the means from the inputs and initializes the covariances.
+    fn initialize_params(&mut self, inputs: &Matrix<f64>, reg_value: f64) -> LearningResult<()> {
+        let k = self.comp_count;
+
+        self.model_covars = {
+            let cov_mat = self.initialize_covariances(inputs, reg_value)?;
+            Some(vec![cov_mat; k])
+        };
+
+        let random_rows: Vec<usize> =
+            rand_utils::reservoir_sample(&(0..inputs.rows()).collect::<Vec<usize>>(), k);
+        self.model_means = Some(inputs.select_rows(&random_rows));
+        Ok(())
+    }
+
+    /// Checks that the inputs have as many columns as the current means.
+    fn validate_columns(&self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        match self.model_means {
+            Some(ref means) if means.cols() == inputs.cols() => Ok(()),
+            _ => {
+                Err(Error::new(ErrorKind::InvalidData,
+                               "Input data has different number of columns from \
+                                the initial means."))
+            }
+        }
+    }
+
+    /// The averaged sufficient statistics of a chunk.
+    fn chunk_stats(&self, chunk: &Matrix<f64>, member_weights: &Matrix<f64>) -> StreamStats {
+        let n = chunk.rows() as f64;
+        let d = chunk.cols();
+
+        let outer = (0..self.comp_count)
+            .map(|k| {
+                let mut weighted = chunk.clone();
+                for (mut row, w) in weighted.row_iter_mut().zip(member_weights.col(k).iter()) {
+                    *row *= *w;
+                }
+                let mut outer_k = chunk.transpose() * weighted / n;
+                if let CovOption::Diagonal | CovOption::Spherical = self.cov_option {
+                    outer_k = Matrix::from_diag(&outer_k.diag().cloned().collect::<Vec<f64>>());
+                }
+                debug_assert_eq!(outer_k.rows(), d);
+                outer_k
+            })
+            .collect();
+
+        StreamStats {
+            weights: member_weights.sum_rows() / n,
+            sums: member_weights.transpose() * chunk / n,
+            outer,
+        }
+    }
+
+    /// Sets the mixture weights, means and covariances from the running statistics.
+    fn set_params_from_stats(&mut self, stats: &StreamStats) {
+        let d = stats.sums.cols();
+
+        let mut means = stats.sums.clone();
+        for (mut mean, w) in means.row_iter_mut().zip(stats.weights.iter()) {
+            *mean /= *w;
+        }
+
+        let mut covs = Vec::with_capacity(self.comp_count);
+        for (k, outer) in stats.outer.iter().enumerate() {
+            let mean_k = MatrixSlice::from_matrix(&means, [k, 0], 1, d).into_matrix();
+            let mut cov = outer / stats.weights[k] - self.compute_cov(mean_k, 1f64);
+
+            if let CovOption::Regularized(eps) = self.cov_option {
+                cov += Matrix::<f64>::identity(d) * eps;
+            }
+            covs.push(cov);
+        }
+
+        match self.cov_option {
+            CovOption::Spherical => {
+                for cov in &mut covs {
+                    let variance = cov.diag().sum::<f64>() / (d as f64);
+                    *cov = Matrix::<f64>::identity(d) * variance;
+                }
+            }
+            CovOption::Tied => {
+                let mut tied_cov = Matrix::zeros(d, d);
+                for (cov, w) in covs.iter().zip(stats.weights.iter()) {
+                    tied_cov += cov * *w;
+                }
+                covs = vec![tied_cov; self.comp_count];
+            }
+            _ => {}
+        }
+
+        self.mix_weights = stats.weights.clone() / stats.weights.sum();
+        self.model_means = Some(means);
+        self.model_covars = Some(covs);
+    }
+
     fn initialize_covariances(&self, inputs: &Matrix<f64>, reg_value: f64) -> LearningResult<Matrix<f64>> {
         match self.cov_option {
             CovOption::Diagonal => {
@@ -231,7 +625,13 @@ impl GaussianMixtureModel {
                 Ok(Matrix::from_diag(&variance.data()) * reg_value.sqrt())
             }
 
-            CovOption::Full | CovOption::Regularized(_) => {
+            CovOption::Spherical => {
+                let variance = inputs.variance(Axes::Row)?;
+                let mean_variance = variance.sum() / (inputs.cols() as f64);
+                Ok(Matrix::<f64>::identity(inputs.cols()) * (mean_variance * reg_value.sqrt()))
+            }
+
+            CovOption::Full | CovOption::Regularized(_) | CovOption::LedoitWolf | CovOption::Tied => {
                 let means = inputs.mean(Axes::Row);
                 let mut cov_mat = Matrix::zeros(inputs.cols(), inputs.cols());
                 for (j, mut row) in cov_mat.row_iter_mut().enumerate() {
@@ -245,6 +645,10 @@ impl GaussianMixtureModel {
                 if let CovOption::Regularized(eps) = self.cov_option {
                     cov_mat += Matrix::<f64>::identity(cov_mat.cols()) * eps;
                 }
+                if let CovOption::LedoitWolf = self.cov_option {
+                    let centered = centered_rows(inputs, means.data());
+                    cov_mat = ledoit_wolf_shrink(cov_mat, &centered, &vec![1f64; inputs.rows()]);
+                }
                 Ok(cov_mat)
             }
         }
@@ -260,14 +664,33 @@ impl GaussianMixtureModel {
         let mut cov_invs = Vec::with_capacity(self.comp_count);
 
         if let Some(ref covars) = self.model_covars {
-            for cov in covars {
-                let lup = PartialPivLu::decompose(cov.clone()).expect("Covariance could not be lup decomposed");
-                let covar_det = lup.det();
-                // TODO: We can probably remove this inverse for a more stable solve elsewhere.
-                let covar_inv = try!(lup.inverse().map_err(Error::from));
-
-                cov_sqrt_dets.push(covar_det.sqrt());
-                cov_invs.push(covar_inv);
+            match self.cov_option {
+                CovOption::Spherical => {
+                    // The covariances are scaled identities so we can skip the decomposition
+                    check_cov_structure(self.cov_option, covars)?;
+                    for cov in covars {
+                        let d = cov.rows();
+                        let variance = cov[[0, 0]];
+                        cov_sqrt_dets.push(variance.powf(d as f64 / 2f64));
+                        cov_invs.push(Matrix::<f64>::identity(d) / variance);
+                    }
+                }
+                CovOption::Tied => {
+                    // All components share a covariance so we only decompose once
+                    check_cov_structure(self.cov_option, covars)?;
+                    if let Some(cov) = covars.first() {
+                        let (covar_sqrt_det, covar_inv) = GaussianMixtureModel::decompose_cov(cov)?;
+                        cov_sqrt_dets = vec![covar_sqrt_det; covars.len()];
+                        cov_invs = vec![covar_inv; covars.len()];
+                    }
+                }
+                _ => {
+                    for cov in covars {
+                        let (covar_sqrt_det, covar_inv) = GaussianMixtureModel::decompose_cov(cov)?;
+                        cov_sqrt_dets.push(covar_sqrt_det);
+                        cov_invs.push(covar_inv);
+                    }
+                }
             }
         }
 
@@ -301,6 +724,17 @@ impl GaussianMixtureModel {
         Ok((Matrix::new(n, self.comp_count, member_weights_data), log_lik))
     }
 
+    /// Computes the square root of the determinant and the inverse of a covariance.
+    fn decompose_cov(cov: &Matrix<f64>) -> LearningResult<(f64, Matrix<f64>)> {
+        let lup = PartialPivLu::decompose(cov.clone())
+            .map_err(|_| Error::new(ErrorKind::LinearAlgebra, "A covariance matrix is singular."))?;
+        let covar_det = lup.det();
+        // TODO: We can probably remove this inverse for a more stable solve elsewhere.
+        let covar_inv = lup.inverse().map_err(Error::from)?;
+
+        Ok((covar_det.sqrt(), covar_inv))
+    }
+
     fn update_params(&mut self, inputs: &Matrix<f64>, membership_weights: Matrix<f64>) {
         let n = membership_weights.rows();
         let d = inputs.cols();
@@ -331,8 +765,33 @@ impl GaussianMixtureModel {
                 cov_mat += Matrix::<f64>::identity(cov_mat.cols()) * eps;
             }
 
-            new_covs.push(cov_mat / sum_weights[k]);
+            cov_mat /= sum_weights[k];
+            if let CovOption::LedoitWolf = self.cov_option {
+                let centered = centered_rows(inputs, new_means.row(k).raw_slice());
+                let weights_k = membership_weights.col(k).iter().cloned().collect::<Vec<f64>>();
+                cov_mat = ledoit_wolf_shrink(cov_mat, &centered, &weights_k);
+            }
+
+            new_covs.push(cov_mat);
+        }
 
+        match self.cov_option {
+            CovOption::Spherical => {
+                // Replace each diagonal covariance with its average variance
+                for cov in &mut new_covs {
+                    let variance = cov.diag().sum::<f64>() / (d as f64);
+                    *cov = Matrix::<f64>::identity(d) * variance;
+                }
+            }
+            CovOption::Tied => {
+                // Pool the component covariances weighted by their membership
+                let mut tied_cov = Matrix::zeros(d, d);
+                for (cov, w) in new_covs.iter().zip(sum_weights.data().iter()) {
+                    tied_cov += cov * (*w / (n as f64));
+                }
+                new_covs = vec![tied_cov; self.comp_count];
+            }
+            _ => {}
         }
 
         self.model_means = Some(new_means);
@@ -341,16 +800,150 @@ impl GaussianMixtureModel {
 
     fn compute_cov(&self, diff: Matrix<f64>, weight: f64) -> Matrix<f64> {
         match self.cov_option {
-            CovOption::Full | CovOption::Regularized(_) => (diff.transpose() * diff) * weight,
-            CovOption::Diagonal => Matrix::from_diag(&diff.elemul(&diff).into_vec()) * weight,
+            CovOption::Full | CovOption::Regularized(_) | CovOption::LedoitWolf | CovOption::Tied => {
+                (diff.transpose() * diff) * weight
+            }
+            CovOption::Diagonal | CovOption::Spherical => {
+                Matrix::from_diag(&diff.elemul(&diff).into_vec()) * weight
+            }
+        }
+    }
+}
+
+/// Checks that the covariances have the structure required by `cov_option`.
+///
+/// Entries are compared with the same relative tolerance used to check
+/// that Gram matrices are symmetric.
+fn check_cov_structure(cov_option: CovOption, covs: &[Matrix<f64>]) -> LearningResult<()> {
+    // Written so that NaN entries are also rejected.
+    let close = |a: f64, b: f64, scale: f64| (a - b).abs() <= 1e-10 * (1f64 + scale);
+    let invalid = |msg| Err(Error::new(ErrorKind::InvalidParameters, msg));
+
+    for cov in covs {
+        for i in 0..cov.rows() {
+            for j in 0..i {
+                let (a, b) = (cov[[i, j]], cov[[j, i]]);
+                if !close(a, b, a.abs().max(b.abs())) {
+                    return invalid("Covariances must be symmetric.");
+                }
+
+                let diag_scale = cov[[i, i]].abs().max(cov[[j, j]].abs());
+                if let CovOption::Diagonal | CovOption::Spherical = cov_option {
+                    if !close(a, 0f64, diag_scale) {
+                        return invalid("Diagonal and spherical covariances must be diagonal.");
+                    }
+                }
+            }
+        }
+
+        if let CovOption::Spherical = cov_option {
+            let variance = cov[[0, 0]];
+            let positive = variance > 0f64;
+            let isotropic = cov.diag().all(|v| close(*v, variance, v.abs().max(variance.abs())));
+            if !positive || !isotropic {
+                return invalid("Spherical covariances must be positive multiples of the identity.");
+            }
+        }
+    }
+
+    if let CovOption::Tied = cov_option {
+        if let Some(first) = covs.first() {
+            let tied = covs.iter().all(|cov| {
+                cov.iter().zip(first.iter()).all(|(a, b)| close(*a, *b, a.abs().max(b.abs())))
+            });
+            if !tied {
+                return invalid("Tied covariances must all be equal.");
+            }
         }
     }
+
+    Ok(())
+}
+
+/// The rows of the inputs minus the given mean.
+fn centered_rows(inputs: &Matrix<f64>, mean: &[f64]) -> Matrix<f64> {
+    let mut centered = inputs.clone();
+    for mut row in centered.row_iter_mut() {
+        for (x, m) in row.iter_mut().zip(mean.iter()) {
+            *x -= *m;
+        }
+    }
+    centered
+}
+
+/// Shrinks a covariance towards the identity scaled by its mean variance.
+///
+/// The rows of `centered` are the deviations of the inputs from the mean
+/// of the component and `weights` are their membership weights. The
+/// shrinkage intensity is the Ledoit-Wolf estimate, the ratio of the
+/// variance of the sample covariance to its squared distance from the
+/// target, capped at one.
+fn ledoit_wolf_shrink(cov: Matrix<f64>, centered: &Matrix<f64>, weights: &[f64]) -> Matrix<f64> {
+    let d = cov.cols();
+    let scale = cov.diag().sum::<f64>() / d as f64;
+    let cov_sq_norm = cov.iter().map(|c| c * c).sum::<f64>();
+
+    // The squared Frobenius distance between the covariance and the target.
+    let dist = cov_sq_norm - scale * scale * d as f64;
+
+    // The estimated variance of the weighted sample covariance, using
+    // |zzᵀ - S|² = |z|⁴ - 2zᵀSz + |S|².
+    let total_weight = weights.iter().sum::<f64>();
+    let spread = centered.row_iter()
+        .zip(weights.iter())
+        .map(|(row, w)| {
+            let z = Vector::new(row.raw_slice());
+            let sq_norm = z.dot(&z);
+            w * w * (sq_norm * sq_norm - 2f64 * z.dot(&(&cov * &z)) + cov_sq_norm)
+        })
+        .sum::<f64>() / (total_weight * total_weight);
+
+    // A covariance which is already a scaled identity needs no shrinkage.
+    let shrinkage = if dist > 0f64 { (spread / dist).min(1f64) } else { 0f64 };
+    cov * (1f64 - shrinkage) + Matrix::<f64>::identity(d) * (scale * shrinkage)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::GaussianMixtureModel;
-    use linalg::Vector;
+    use super::{CovOption, GaussianMixtureModel};
+    use learning::error::ErrorKind;
+    use learning::{UnSupModel, ProbabilisticClassifier};
+    use linalg::{Matrix, Vector, BaseMatrix};
+
+    use rand::{StdRng, SeedableRng};
+    use rand::distributions::{IndependentSample, Normal};
+
+    /// Samples `n` points around each of the `centers`, with the noise
+    /// correlated by the lower triangular `chol` factor.
+    fn sample_clusters(centers: &[Vec<f64>], chol: &Matrix<f64>, n: usize, rng: &mut StdRng) -> Matrix<f64> {
+        let d = chol.rows();
+        let normal = Normal::new(0.0, 1.0);
+        let mut data = Vec::with_capacity(centers.len() * n * d);
+
+        for c in centers {
+            for _ in 0..n {
+                let z = Vector::new((0..d).map(|_| normal.ind_sample(rng)).collect::<Vec<_>>());
+                let x = chol * z;
+                data.extend(x.data().iter().zip(c.iter()).map(|(x, m)| x + m));
+            }
+        }
+
+        Matrix::new(centers.len() * n, d, data)
+    }
+
+    /// Fits the model from a fixed initialization, with one mean on the first
+    /// point of each cluster, and returns the log-likelihood of the test data.
+    fn held_out_log_lik(cov_option: CovOption, train: &Matrix<f64>, n: usize, test: &Matrix<f64>) -> f64 {
+        let d = train.cols();
+        let means = train.select_rows(&[0, n]);
+        let covs = vec![Matrix::identity(d), Matrix::identity(d)];
+
+        let mut model = GaussianMixtureModel::with_init(means, covs, Vector::new(vec![0.5, 0.5]), cov_option)
+            .unwrap();
+        model.train(train).unwrap();
+
+        model.membership_weights(test).unwrap().1
+    }
 
     #[test]
     fn test_means_none() {
@@ -379,4 +972,393 @@ mod tests {
         let gmm_res = GaussianMixtureModel::with_weights(3, mix_weights);
         assert!(gmm_res.is_err());
     }
+
+    #[test]
+    fn test_spherical_data() {
+        let mut rng = StdRng::from_seed(&[1, 2, 3]);
+        let centers = vec![vec![0.0; 4], vec![10.0; 4]];
+        let chol = Matrix::<f64>::identity(4);
+
+        let train = sample_clusters(&centers, &chol, 30, &mut rng);
+        let test = sample_clusters(&centers, &chol, 500, &mut rng);
+
+        let spherical = held_out_log_lik(CovOption::Spherical, &train, 30, &test);
+        let diagonal = held_out_log_lik(CovOption::Diagonal, &train, 30, &test);
+        let full = held_out_log_lik(CovOption::Full, &train, 30, &test);
+
+        assert!(spherical > diagonal);
+        assert!(spherical > full);
+    }
+
+    #[test]
+    fn test_tied_data() {
+        let mut rng = StdRng::from_seed(&[4, 5, 6]);
+        let centers = vec![vec![0.0, 0.0, 0.0], vec![10.0, -10.0, 10.0]];
+        let chol = Matrix::new(3, 3, vec![1.0, 0.0, 0.0,
+                                          0.9, 0.4, 0.0,
+                                          0.8, 0.3, 0.5]);
+
+        let train = sample_clusters(&centers, &chol, 15, &mut rng);
+        let test = sample_clusters(&centers, &chol, 500, &mut rng);
+
+        let tied = held_out_log_lik(CovOption::Tied, &train, 15, &test);
+        let full = held_out_log_lik(CovOption::Full, &train, 15, &test);
+        let diagonal = held_out_log_lik(CovOption::Diagonal, &train, 15, &test);
+        let spherical = held_out_log_lik(CovOption::Spherical, &train, 15, &test);
+
+        assert!(tied > full);
+        assert!(tied > diagonal);
+        assert!(tied > spherical);
+    }
+
+    #[test]
+    fn test_tied_covariances_shared() {
+        let mut rng = StdRng::from_seed(&[7, 8, 9]);
+        let centers = vec![vec![0.0, 0.0], vec![5.0, 5.0]];
+        let train = sample_clusters(&centers, &Matrix::identity(2), 20, &mut rng);
+
+        let mut model = GaussianMixtureModel::new(2);
+        model.cov_option = CovOption::Tied;
+        model.train(&train).unwrap();
+
+        let covs = model.covariances().unwrap();
+        assert_eq!(covs[0], covs[1]);
+    }
+
+    #[test]
+    fn test_spherical_covariances_isotropic() {
+        let mut rng = StdRng::from_seed(&[7, 8, 9]);
+        let centers = vec![vec![0.0, 0.0, 0.0], vec![5.0, 5.0, 5.0]];
+        let train = sample_clusters(&centers, &Matrix::identity(3), 20, &mut rng);
+
+        let mut model = GaussianMixtureModel::new(2);
+        model.cov_option = CovOption::Spherical;
+        model.train(&train).unwrap();
+
+        for cov in model.covariances().unwrap() {
+            assert_eq!(*cov, Matrix::<f64>::identity(3) * cov[[0, 0]]);
+        }
+    }
+
+    #[test]
+    fn test_with_init_wrong_cov_count() {
+        let means = Matrix::new(2, 2, vec![0.0, 0.0, 5.0, 5.0]);
+        let covs = vec![Matrix::identity(2)];
+        let gmm_res = GaussianMixtureModel::with_init(means, covs, Vector::new(vec![0.5, 0.5]), CovOption::Full);
+        assert!(gmm_res.is_err());
+    }
+
+    #[test]
+    fn test_with_init_wrong_cov_shape() {
+        let means = Matrix::new(2, 2, vec![0.0, 0.0, 5.0, 5.0]);
+        let covs = vec![Matrix::identity(2), Matrix::identity(3)];
+        let gmm_res = GaussianMixtureModel::with_init(means, covs, Vector::new(vec![0.5, 0.5]), CovOption::Full);
+        assert!(gmm_res.is_err());
+    }
+
+    #[test]
+    fn test_with_init_wrong_weights_length() {
+        let means = Matrix::new(2, 2, vec![0.0, 0.0, 5.0, 5.0]);
+        let covs = vec![Matrix::identity(2), Matrix::identity(2)];
+        let gmm_res = GaussianMixtureModel::with_init(means, covs, Vector::new(vec![1.0]), CovOption::Full);
+        assert!(gmm_res.is_err());
+    }
+
+    #[test]
+    fn test_with_init_wrong_input_cols() {
+        let means = Matrix::new(2, 2, vec![0.0, 0.0, 5.0, 5.0]);
+        let covs = vec![Matrix::identity(2), Matrix::identity(2)];
+        let mut gmm = GaussianMixtureModel::with_init(means, covs, Vector::new(vec![0.5, 0.5]), CovOption::Full)
+            .unwrap();
+
+        let inputs = Matrix::new(3, 3, vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+        assert!(gmm.train(&inputs).is_err());
+    }
+
+    #[test]
+    fn test_with_init_cov_structure() {
+        let means = || Matrix::new(2, 2, vec![0.0, 0.0, 5.0, 5.0]);
+        let weights = || Vector::new(vec![0.5, 0.5]);
+        let anisotropic = Matrix::new(2, 2, vec![2.0, 0.0, 0.0, 1.0]);
+        let correlated = Matrix::new(2, 2, vec![2.0, 0.5, 0.5, 1.0]);
+        let asymmetric = Matrix::new(2, 2, vec![2.0, 0.5, 0.4, 1.0]);
+
+        let check = |covs: Vec<Matrix<f64>>, cov_option| {
+            match GaussianMixtureModel::with_init(means(), covs, weights(), cov_option) {
+                Ok(_) => true,
+                Err(e) => {
+                    assert!(matches!(*e.kind(), ErrorKind::InvalidParameters));
+                    false
+                }
+            }
+        };
+
+        assert!(!check(vec![asymmetric.clone(), asymmetric], CovOption::Full));
+
+        assert!(!check(vec![Matrix::identity(2), anisotropic.clone()], CovOption::Spherical));
+        assert!(!check(vec![Matrix::identity(2), Matrix::identity(2) * -1.0], CovOption::Spherical));
+        assert!(check(vec![Matrix::identity(2), Matrix::identity(2) * 3.0], CovOption::Spherical));
+
+        assert!(!check(vec![Matrix::identity(2), correlated.clone()], CovOption::Diagonal));
+        assert!(check(vec![Matrix::identity(2), anisotropic.clone()], CovOption::Diagonal));
+
+        assert!(!check(vec![correlated.clone(), anisotropic], CovOption::Tied));
+        assert!(check(vec![correlated.clone(), correlated], CovOption::Tied));
+    }
+
+    #[test]
+    fn test_predict_with_reject() {
+        let means = Matrix::new(2, 1, vec![0.0, 4.0]);
+        let covs = vec![Matrix::identity(1), Matrix::identity(1)];
+        let gmm = GaussianMixtureModel::with_init(means, covs, Vector::new(vec![0.5, 0.5]),
+                                                  CovOption::Full).unwrap();
+
+        let inputs = Matrix::new(3, 1, vec![0.5, 2.0, 3.5]);
+        let components = gmm.predict_with_reject(&inputs, 0.9).unwrap();
+        assert_eq!(components.into_vec(), vec![Some(0), None, Some(1)]);
+        assert!(gmm.predict_with_reject(&inputs, -0.1).is_err());
+    }
+
+    #[test]
+    fn test_cov_option_changed_after_init() {
+        let means = Matrix::new(2, 2, vec![0.0, 0.0, 5.0, 5.0]);
+        let covs = vec![Matrix::identity(2), Matrix::identity(2) * 2.0];
+        let mut gmm = GaussianMixtureModel::with_init(means, covs, Vector::new(vec![0.5, 0.5]), CovOption::Full)
+            .unwrap();
+
+        // The covariances differ, so they cannot be used as tied covariances.
+        gmm.cov_option = CovOption::Tied;
+        let inputs = Matrix::new(4, 2, vec![0.0, 0.1, 0.2, -0.1, 5.0, 5.1, 4.9, 5.2]);
+        assert!(gmm.predict(&inputs).is_err());
+        assert!(gmm.train(&inputs).is_err());
+    }
+
+    #[test]
+    fn test_warm_start_converges_faster() {
+        let mut rng = StdRng::from_seed(&[1, 2, 3]);
+        let centers = vec![vec![0.0, 0.0], vec![10.0, 10.0]];
+        let inputs = sample_clusters(&centers, &Matrix::identity(2), 100, &mut rng);
+
+        let init_model = || {
+            let means = Matrix::new(2, 2, vec![0.1, -0.1, 9.9, 10.1]);
+            let covs = vec![Matrix::identity(2) * 1.1, Matrix::identity(2) * 0.9];
+            GaussianMixtureModel::with_init(means, covs, Vector::new(vec![0.5, 0.5]), CovOption::Full).unwrap()
+        };
+
+        let mut warm = init_model();
+        warm.set_max_iters(1000);
+        warm.train(&inputs).unwrap();
+        assert!(warm.converged());
+
+        // Start far from the clusters, between them.
+        let mut far = GaussianMixtureModel::with_init(Matrix::new(2, 2, vec![4.0, 5.0, 6.0, 5.0]),
+                                                      vec![Matrix::identity(2) * 20.0,
+                                                           Matrix::identity(2) * 20.0],
+                                                      Vector::new(vec![0.5, 0.5]),
+                                                      CovOption::Full)
+            .unwrap();
+        far.set_max_iters(1000);
+        far.train(&inputs).unwrap();
+        assert!(far.converged());
+
+        // Starting near the optimum needs far fewer EM iterations.
+        assert!(warm.n_iter() * 4 < far.n_iter());
+    }
+
+    #[test]
+    fn test_convergence_reported() {
+        let mut rng = StdRng::from_seed(&[5, 8, 2]);
+        let centers = vec![vec![0.0, 0.0], vec![6.0, 6.0]];
+        let inputs = sample_clusters(&centers, &Matrix::identity(2), 40, &mut rng);
+
+        let mut model = GaussianMixtureModel::new(2);
+        model.set_max_iters(1);
+        model.train(&inputs).unwrap();
+        assert!(!model.converged());
+        assert_eq!(model.n_iter(), 1);
+
+        model.set_max_iters(1000);
+        model.train(&inputs).unwrap();
+        assert!(model.converged());
+        assert!(model.n_iter() > 1 && model.n_iter() < 1000);
+    }
+
+    #[test]
+    fn test_convergence_tolerance() {
+        let mut rng = StdRng::from_seed(&[5, 8, 2]);
+        let centers = vec![vec![0.0, 0.0], vec![3.0, 3.0]];
+        let inputs = sample_clusters(&centers, &Matrix::identity(2), 40, &mut rng);
+
+        let init_model = || {
+            let means = Matrix::new(2, 2, vec![1.0, 0.0, 2.0, 3.0]);
+            let covs = vec![Matrix::identity(2), Matrix::identity(2)];
+            GaussianMixtureModel::with_init(means, covs, Vector::new(vec![0.5, 0.5]), CovOption::Full).unwrap()
+        };
+
+        let mut strict = init_model();
+        strict.set_max_iters(1000);
+        strict.train(&inputs).unwrap();
+        assert!(strict.converged());
+
+        let mut loose = init_model();
+        loose.set_max_iters(1000);
+        loose.set_tol(1e-2);
+        assert_eq!(loose.tol(), 1e-2);
+        loose.train(&inputs).unwrap();
+        assert!(loose.converged());
+        assert!(loose.n_iter() < strict.n_iter());
+    }
+
+    #[test]
+    fn test_streaming_matches_batch() {
+        let mut rng = StdRng::from_seed(&[3, 1, 4]);
+        let centers = vec![vec![0.0, 0.0], vec![4.0, 3.0]];
+        let chol = Matrix::new(2, 2, vec![1.0, 0.0, 0.5, 0.8]);
+        let inputs = sample_clusters(&centers, &chol, 500, &mut rng);
+
+        let init_model = || {
+            let means = Matrix::new(2, 2, vec![1.0, 1.0, 3.0, 2.0]);
+            let covs = vec![Matrix::identity(2), Matrix::identity(2)];
+            GaussianMixtureModel::with_init(means, covs, Vector::new(vec![0.5, 0.5]), CovOption::Full).unwrap()
+        };
+
+        let mut batch = init_model();
+        batch.train(&inputs).unwrap();
+        let batch_log_lik = batch.membership_weights(&inputs).unwrap().1;
+
+        // Shuffle the rows so that every chunk contains both clusters.
+        let order = (0..1000).map(|i| (i * 337) % 1000).collect::<Vec<usize>>();
+        let shuffled = inputs.select_rows(&order);
+
+        let mut streaming = init_model();
+        let initial_log_lik = streaming.membership_weights(&inputs).unwrap().1;
+        for _ in 0..3 {
+            for start in (0..1000).step_by(50) {
+                let rows = (start..start + 50).collect::<Vec<usize>>();
+                streaming.partial_fit(&shuffled.select_rows(&rows)).unwrap();
+            }
+        }
+        let streaming_log_lik = streaming.membership_weights(&inputs).unwrap().1;
+
+        assert!(streaming_log_lik > initial_log_lik);
+        assert!((batch_log_lik - streaming_log_lik).abs() < 1e-2 * batch_log_lik.abs(),
+                "{} != {}", streaming_log_lik, batch_log_lik);
+
+        let batch_means = batch.means().unwrap();
+        let streaming_means = streaming.means().unwrap();
+        for (b, s) in batch_means.iter().zip(streaming_means.iter()) {
+            assert!((b - s).abs() < 0.2);
+        }
+    }
+
+    #[test]
+    fn test_partial_fit_initializes() {
+        let mut rng = StdRng::from_seed(&[2, 7, 1]);
+        let centers = vec![vec![0.0, 0.0], vec![10.0, 10.0]];
+        let chunk = sample_clusters(&centers, &Matrix::identity(2), 20, &mut rng);
+
+        for cov_option in &[CovOption::Full, CovOption::Diagonal, CovOption::Spherical, CovOption::Tied] {
+            let mut model = GaussianMixtureModel::new(2);
+            model.cov_option = *cov_option;
+            model.partial_fit(&chunk).unwrap();
+            model.partial_fit(&chunk).unwrap();
+
+            assert_eq!(model.means().unwrap().rows(), 2);
+            assert!((model.mixture_weights().sum() - 1.0).abs() < 1e-12);
+            assert!(model.predict(&chunk).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_partial_fit_wrong_input_cols() {
+        let mut rng = StdRng::from_seed(&[2, 7, 1]);
+        let chunk = sample_clusters(&[vec![0.0, 0.0], vec![10.0, 10.0]], &Matrix::identity(2), 20, &mut rng);
+
+        let mut model = GaussianMixtureModel::new(2);
+        model.partial_fit(&chunk).unwrap();
+
+        assert!(model.partial_fit(&Matrix::new(2, 3, vec![0.0; 6])).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_step_decay() {
+        let mut model = GaussianMixtureModel::new(2);
+        model.set_step_size_schedule(1.0, 0.5);
+    }
+
+    /// A small cluster with fewer points than dimensions and a
+    /// large cluster far away, with one mean on each.
+    fn small_cluster_model(cov_option: CovOption) -> (GaussianMixtureModel, Matrix<f64>) {
+        let mut rng = StdRng::from_seed(&[2, 7, 1, 8]);
+        let d = 6;
+        let small = sample_clusters(&[vec![0.0; d]], &Matrix::identity(d), 3, &mut rng);
+        let large = sample_clusters(&[vec![40.0; d]], &Matrix::identity(d), 50, &mut rng);
+        let inputs = small.vcat(&large);
+
+        let means = inputs.select_rows(&[0, 3]);
+        let covs = vec![Matrix::identity(d), Matrix::identity(d)];
+        let mut model = GaussianMixtureModel::with_init(means, covs, Vector::new(vec![0.5, 0.5]), cov_option)
+            .unwrap();
+        model.set_max_iters(20);
+        (model, inputs)
+    }
+
+    #[test]
+    fn test_ledoit_wolf_small_cluster() {
+        // The small cluster's covariance is singular without shrinkage.
+        let (mut full, inputs) = small_cluster_model(CovOption::Full);
+        let full_fit = full.train(&inputs).and_then(|_| full.membership_weights(&inputs));
+        assert!(full_fit.map(|(_, log_lik)| !log_lik.is_finite()).unwrap_or(true));
+
+        let (mut shrunk, inputs) = small_cluster_model(CovOption::LedoitWolf);
+        shrunk.train(&inputs).unwrap();
+
+        let (weights, log_lik) = shrunk.membership_weights(&inputs).unwrap();
+        assert!(log_lik.is_finite());
+        assert!(weights.iter().all(|w| w.is_finite()));
+        for cov in shrunk.covariances().unwrap() {
+            let factor = cov.clone().cholesky().unwrap();
+            assert!(factor.diag().all(|d| d.is_finite() && *d > 0.0));
+        }
+
+        // Each point is assigned to its own cluster.
+        for i in 0..inputs.rows() {
+            let cluster = if i < 3 { 0 } else { 1 };
+            assert!(weights[[i, cluster]] > 0.99);
+        }
+    }
+
+    #[test]
+    fn test_ledoit_wolf_large_sample() {
+        let mut rng = StdRng::from_seed(&[6, 2, 8]);
+        let chol = Matrix::new(3, 3, vec![1.0, 0.0, 0.0, 0.8, 0.6, 0.0, -0.5, 0.3, 0.4]);
+        let inputs = sample_clusters(&[vec![1.0, -1.0, 2.0]], &chol, 2000, &mut rng);
+
+        let fit = |cov_option| {
+            let means = Matrix::new(1, 3, vec![0.0, 0.0, 0.0]);
+            let mut model = GaussianMixtureModel::with_init(means,
+                                                            vec![Matrix::identity(3)],
+                                                            Vector::new(vec![1.0]),
+                                                            cov_option)
+                .unwrap();
+            model.train(&inputs).unwrap();
+            model.covariances().unwrap()[0].clone()
+        };
+
+        // With plenty of samples there is little shrinkage.
+        let full = fit(CovOption::Full);
+        let shrunk = fit(CovOption::LedoitWolf);
+        assert!((full.diag().sum::<f64>() - shrunk.diag().sum::<f64>()).abs() < 1e-10);
+        assert!((full - shrunk).iter().all(|d| d.abs() < 0.02));
+    }
+
+    #[test]
+    fn test_ledoit_wolf_partial_fit_unsupported() {
+        let mut model = GaussianMixtureModel::new(2);
+        model.cov_option = CovOption::LedoitWolf;
+
+        let chunk = Matrix::new(4, 1, vec![-1.0, -1.2, 3.0, 3.1]);
+        assert!(model.partial_fit(&chunk).is_err());
+    }
 }
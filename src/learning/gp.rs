@@ -26,10 +26,10 @@
 //! the predictive mean and covariance. However, this is likely to change in
 //! a future release.
 
-use learning::toolkit::kernel::{Kernel, SquaredExp};
-use linalg::{Matrix, BaseMatrix};
+use learning::toolkit::kernel::{Kernel, SquaredExp, validate_gram_matrix};
+use linalg::{Matrix, BaseMatrix, BaseMatrixMut};
 use linalg::Vector;
-use learning::{LearningResult, SupModel};
+use learning::{LearningResult, SupModel, validate_finite_matrix, validate_finite_vector};
 use learning::error::{Error, ErrorKind};
 
 
@@ -58,6 +58,90 @@ impl MeanFunc for ConstMean {
     }
 }
 
+/// Linear mean function
+///
+/// The mean is an affine function of the inputs, `x.w + b`.
+#[derive(Clone, Debug)]
+pub struct LinearMean {
+    weights: Vector<f64>,
+    bias: f64,
+}
+
+impl LinearMean {
+    /// Constructs a linear mean function with the given weights and bias.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp::LinearMean;
+    /// use rusty_machine::linalg::Vector;
+    ///
+    /// // The mean function 2x + 1.
+    /// let mean = LinearMean::new(Vector::new(vec![2.0]), 1.0);
+    /// ```
+    pub fn new(weights: Vector<f64>, bias: f64) -> LinearMean {
+        LinearMean {
+            weights,
+            bias,
+        }
+    }
+
+    /// Fits the coefficients of the mean function to the data
+    /// by least squares.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp::{GaussianProcess, LinearMean};
+    /// use rusty_machine::learning::toolkit::kernel::SquaredExp;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let inputs = Matrix::new(4, 1, vec![0.0, 1.0, 2.0, 3.0]);
+    /// let targets = Vector::new(vec![1.0, 3.1, 4.9, 7.0]);
+    ///
+    /// let mean = LinearMean::fit(&inputs, &targets).unwrap();
+    /// let gaussp = GaussianProcess::new(SquaredExp::default(), mean, 1e-3);
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The inputs and targets have different row counts.
+    /// - The least squares problem cannot be solved.
+    pub fn fit(inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<LinearMean> {
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Inputs and targets have different row counts."));
+        }
+
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        let full_inputs = ones.hcat(inputs);
+
+        let xt = full_inputs.transpose();
+        let params = (&xt * full_inputs).solve(&xt * targets)?.into_vec();
+
+        Ok(LinearMean {
+            weights: Vector::new(params[1..].to_vec()),
+            bias: params[0],
+        })
+    }
+
+    /// The weights of the mean function.
+    pub fn weights(&self) -> &Vector<f64> {
+        &self.weights
+    }
+
+    /// The bias of the mean function.
+    pub fn bias(&self) -> f64 {
+        self.bias
+    }
+}
+
+impl MeanFunc for LinearMean {
+    fn func(&self, x: Matrix<f64>) -> Vector<f64> {
+        x * &self.weights + self.bias
+    }
+}
+
 /// Gaussian Process struct
 ///
 /// Gaussian process with generic kernel and deterministic mean function.
@@ -123,21 +207,26 @@ impl<T: Kernel, U: MeanFunc> GaussianProcess<T, U> {
 
     /// Construct a kernel matrix
     fn ker_mat(&self, m1: &Matrix<f64>, m2: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
-        if m1.cols() != m2.cols() {
-            Err(Error::new(ErrorKind::InvalidState,
-                           "Inputs to kernel matrices have different column counts."))
-        } else {
-            let dim1 = m1.rows();
-            let dim2 = m2.rows();
+        kernel_matrix(&self.ker, m1, m2)
+    }
+}
 
-            let mut ker_data = Vec::with_capacity(dim1 * dim2);
-            ker_data.extend(m1.row_iter().flat_map(|row1| {
-                m2.row_iter()
-                    .map(move |row2| self.ker.kernel(row1.raw_slice(), row2.raw_slice()))
-            }));
+/// Construct the kernel matrix between the rows of two matrices.
+fn kernel_matrix<T: Kernel>(ker: &T, m1: &Matrix<f64>, m2: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+    if m1.cols() != m2.cols() {
+        Err(Error::new(ErrorKind::InvalidState,
+                       "Inputs to kernel matrices have different column counts."))
+    } else {
+        let dim1 = m1.rows();
+        let dim2 = m2.rows();
 
-            Ok(Matrix::new(dim1, dim2, ker_data))
-        }
+        let mut ker_data = Vec::with_capacity(dim1 * dim2);
+        ker_data.extend(m1.row_iter().flat_map(|row1| {
+            m2.row_iter()
+                .map(move |row2| ker.kernel(row1.raw_slice(), row2.raw_slice()))
+        }));
+
+        Ok(Matrix::new(dim1, dim2, ker_data))
     }
 }
 
@@ -150,6 +239,9 @@ impl<T: Kernel, U: MeanFunc> SupModel<Matrix<f64>, Vector<f64>> for GaussianProc
             let mean = self.mean.func(inputs.clone());
             let post_mean = try!(self.ker_mat(inputs, t_data)) * alpha;
             Ok(mean + post_mean)
+        } else if self.alpha.is_some() {
+            Err(Error::new(ErrorKind::InvalidState,
+                           "The model was trained on a precomputed kernel matrix."))
         } else {
             Err(Error::new(ErrorKind::UntrainedModel, "The model has not been trained."))
         }
@@ -157,20 +249,109 @@ impl<T: Kernel, U: MeanFunc> SupModel<Matrix<f64>, Vector<f64>> for GaussianProc
 
     /// Train the model using data and outputs.
     fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
-        let noise_mat = Matrix::identity(inputs.rows()) * self.noise;
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_vector("target", targets)?;
 
         let ker_mat = self.ker_mat(inputs, inputs).unwrap();
+        self.fit_kernel_matrix(ker_mat, targets - self.mean.func(inputs.clone()))?;
+        self.train_data = Some(inputs.clone());
+
+        Ok(())
+    }
+}
+
+impl<T: Kernel, U: MeanFunc> GaussianProcess<T, U> {
+    /// Train the model using a precomputed kernel matrix.
+    ///
+    /// The `gram` matrix holds the kernel between each pair of training
+    /// samples and must be square and symmetric. The model's own kernel
+    /// and mean function are not used, the prior mean is zero.
+    ///
+    /// A model trained this way must make predictions with
+    /// `predict_precomputed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp::GaussianProcess;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let mut gaussp = GaussianProcess::default();
+    /// gaussp.noise = 1e-3;
+    ///
+    /// // A custom similarity between three samples.
+    /// let gram = Matrix::new(3, 3, vec![1.0, 0.5, 0.1,
+    ///                                   0.5, 1.0, 0.5,
+    ///                                   0.1, 0.5, 1.0]);
+    /// let targets = Vector::new(vec![0., 1., 0.]);
+    /// gaussp.train_precomputed(&gram, &targets).unwrap();
+    ///
+    /// // The similarity of a new sample to each training sample.
+    /// let cross_gram = Matrix::new(1, 3, vec![0.3, 0.8, 0.3]);
+    /// let outputs = gaussp.predict_precomputed(&cross_gram).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The Gram matrix is not square and symmetric.
+    /// - The Gram matrix and targets have different sizes.
+    /// - The Gram matrix plus noise is not positive definite.
+    pub fn train_precomputed(&mut self,
+                             gram: &Matrix<f64>,
+                             targets: &Vector<f64>)
+                             -> LearningResult<()> {
+        validate_gram_matrix(gram)?;
+        if gram.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The Gram matrix and targets have different sizes."));
+        }
+        validate_finite_vector("target", targets)?;
+
+        self.fit_kernel_matrix(gram.clone(), targets.clone())?;
+        self.train_data = None;
+
+        Ok(())
+    }
+
+    /// Predict outputs from a precomputed kernel matrix.
+    ///
+    /// Each row of `cross_gram` holds the kernel between a new sample
+    /// and each training sample. Requires the model to be trained with
+    /// `train_precomputed`.
+    pub fn predict_precomputed(&self, cross_gram: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        match (self.alpha.as_ref(), self.train_data.as_ref()) {
+            (Some(alpha), None) => {
+                if cross_gram.cols() != alpha.size() {
+                    Err(Error::new(ErrorKind::InvalidData,
+                                   "The kernel matrix must have a column for each training sample."))
+                } else {
+                    Ok(cross_gram * alpha)
+                }
+            }
+            (Some(_), Some(_)) => {
+                Err(Error::new(ErrorKind::InvalidState,
+                               "The model was not trained on a precomputed kernel matrix."))
+            }
+            _ => Err(Error::new_untrained()),
+        }
+    }
+
+    /// Solves the GP system for a kernel matrix and targets with the mean removed.
+    fn fit_kernel_matrix(&mut self,
+                         ker_mat: Matrix<f64>,
+                         centered_targets: Vector<f64>)
+                         -> LearningResult<()> {
+        let noise_mat = Matrix::identity(ker_mat.rows()) * self.noise;
 
         let train_mat = try!((ker_mat + noise_mat).cholesky().map_err(|_| {
             Error::new(ErrorKind::InvalidState,
                        "Could not compute Cholesky decomposition.")
         }));
 
-        let x = train_mat.solve_l_triangular(targets - self.mean.func(inputs.clone())).unwrap();
+        let x = train_mat.solve_l_triangular(centered_targets).unwrap();
         let alpha = train_mat.transpose().solve_u_triangular(x).unwrap();
 
         self.train_mat = Some(train_mat);
-        self.train_data = Some(inputs.clone());
         self.alpha = Some(alpha);
 
         Ok(())
@@ -209,4 +390,206 @@ impl<T: Kernel, U: MeanFunc> GaussianProcess<T, U> {
             Err(Error::new_untrained())
         }
     }
+
+    /// Compute the credible interval of the posterior at each input.
+    ///
+    /// Requires the model to be trained first.
+    ///
+    /// Outputs the lower and upper bounds of the interval containing
+    /// the requested proportion of the posterior mass, i.e. the
+    /// posterior mean plus or minus the matching number of standard
+    /// deviations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp::GaussianProcess;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let mut gaussp = GaussianProcess::default();
+    /// gaussp.noise = 1e-3;
+    ///
+    /// let inputs = Matrix::new(3, 1, vec![0., 1., 2.]);
+    /// let targets = Vector::new(vec![0., 1., 0.]);
+    /// gaussp.train(&inputs, &targets).unwrap();
+    ///
+    /// let test_inputs = Matrix::new(2, 1, vec![0.5, 1.5]);
+    ///
+    /// // The 95% credible interval.
+    /// let (lower, upper) = gaussp.predict_interval(&test_inputs, 0.95).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The model has not been trained.
+    /// - The confidence is not strictly between 0 and 1.
+    pub fn predict_interval(&self,
+                            inputs: &Matrix<f64>,
+                            confidence: f64)
+                            -> LearningResult<(Vector<f64>, Vector<f64>)> {
+        if !(confidence > 0f64 && confidence < 1f64) {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "Confidence must be strictly between 0 and 1."));
+        }
+
+        let (post_mean, post_var) = self.get_posterior(inputs)?;
+        let z = std_normal_quantile(0.5 + confidence / 2f64);
+
+        // Clamp small negative variances caused by rounding.
+        let half_widths = post_var.diag().map(|v| z * v.max(0f64).sqrt()).collect::<Vec<f64>>();
+
+        let lower = post_mean.iter().zip(half_widths.iter()).map(|(m, w)| m - w).collect::<Vec<f64>>();
+        let upper = post_mean.iter().zip(half_widths.iter()).map(|(m, w)| m + w).collect::<Vec<f64>>();
+
+        Ok((Vector::new(lower), Vector::new(upper)))
+    }
+}
+
+/// The quantile function of the standard normal distribution.
+///
+/// Uses the rational approximation of Peter Acklam,
+/// which has a relative error below 1.2e-9.
+fn std_normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [-3.969683028665376e+01, 2.209460984245205e+02, -2.759285104469687e+02,
+                         1.38357751867269e+02, -3.066479806614716e+01, 2.506628277459239e+00];
+    const B: [f64; 5] = [-5.447609879822406e+01, 1.615858368580409e+02, -1.556989798598866e+02,
+                         6.680131188771972e+01, -1.328068155288572e+01];
+    const C: [f64; 6] = [-7.784894002430293e-03, -3.223964580411365e-01, -2.400758277161838e+00,
+                         -2.549732539343734e+00, 4.374664141464968e+00, 2.938163982698783e+00];
+    const D: [f64; 4] = [7.784695709041462e-03, 3.224671290700398e-01, 2.445134137142996e+00,
+                         3.754408661907416e+00];
+    const P_LOW: f64 = 0.02425;
+
+    if p < P_LOW {
+        let q = (-2f64 * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5]) /
+        ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1f64)
+    } else if p <= 1f64 - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q /
+        (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1f64)
+    } else {
+        -std_normal_quantile(1f64 - p)
+    }
+}
+
+/// Multi-output Gaussian Process struct
+///
+/// Gaussian process regression with several independent outputs.
+/// Each column of the targets is modelled by its own GP, with all
+/// outputs sharing the kernel, mean function and noise. The kernel
+/// matrix is factorized once and reused for every output.
+#[derive(Debug)]
+pub struct MultiOutputGaussianProcess<T: Kernel, U: MeanFunc> {
+    ker: T,
+    mean: U,
+    /// The observation noise of the GP.
+    pub noise: f64,
+    alpha: Option<Matrix<f64>>,
+    train_data: Option<Matrix<f64>>,
+}
+
+/// Construct a default multi-output Gaussian Process
+///
+/// The defaults are:
+///
+/// - Squared Exponential kernel.
+/// - Zero-mean function.
+/// - Zero noise.
+///
+/// Note that zero noise can often lead to numerical instability.
+/// A small value for the noise may be a better alternative.
+impl Default for MultiOutputGaussianProcess<SquaredExp, ConstMean> {
+    fn default() -> MultiOutputGaussianProcess<SquaredExp, ConstMean> {
+        MultiOutputGaussianProcess::new(SquaredExp::default(), ConstMean::default(), 0f64)
+    }
+}
+
+impl<T: Kernel, U: MeanFunc> MultiOutputGaussianProcess<T, U> {
+    /// Construct a new multi-output Gaussian Process.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp;
+    /// use rusty_machine::learning::toolkit::kernel;
+    ///
+    /// let ker = kernel::SquaredExp::default();
+    /// let mean = gp::ConstMean::default();
+    /// let gaussp = gp::MultiOutputGaussianProcess::new(ker, mean, 1e-3f64);
+    /// ```
+    pub fn new(ker: T, mean: U, noise: f64) -> MultiOutputGaussianProcess<T, U> {
+        MultiOutputGaussianProcess {
+            ker,
+            mean,
+            noise,
+            alpha: None,
+            train_data: None,
+        }
+    }
+}
+
+impl<T: Kernel, U: MeanFunc> SupModel<Matrix<f64>, Matrix<f64>> for MultiOutputGaussianProcess<T, U> {
+    /// Predict outputs from inputs.
+    ///
+    /// Each column of the output holds the predictions for one output.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        match (self.alpha.as_ref(), self.train_data.as_ref()) {
+            (Some(alpha), Some(t_data)) => {
+                let mean = self.mean.func(inputs.clone());
+                let mut outputs = kernel_matrix(&self.ker, inputs, t_data)? * alpha;
+
+                for (mut row, m) in outputs.row_iter_mut().zip(mean.iter()) {
+                    for x in row.iter_mut() {
+                        *x += *m;
+                    }
+                }
+
+                Ok(outputs)
+            }
+            _ => Err(Error::new_untrained()),
+        }
+    }
+
+    /// Train the model using data and outputs.
+    ///
+    /// Each column of the targets is treated as a separate output.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Matrix<f64>) -> LearningResult<()> {
+        if inputs.rows() != targets.rows() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Inputs and targets have different row counts."));
+        }
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_matrix("target", targets)?;
+
+        let noise_mat = Matrix::identity(inputs.rows()) * self.noise;
+        let ker_mat = kernel_matrix(&self.ker, inputs, inputs)?;
+
+        let train_mat = (ker_mat + noise_mat).cholesky().map_err(|_| {
+            Error::new(ErrorKind::InvalidState,
+                       "Could not compute Cholesky decomposition.")
+        })?;
+        let train_mat_t = train_mat.transpose();
+
+        let mean = self.mean.func(inputs.clone());
+
+        // Solve for each output against the shared factorization.
+        let mut alpha_data = vec![0f64; inputs.rows() * targets.cols()];
+        for j in 0..targets.cols() {
+            let target = Vector::new(targets.col(j).iter().cloned().collect::<Vec<f64>>());
+            let x = train_mat.solve_l_triangular(target - &mean)?;
+            let alpha = train_mat_t.solve_u_triangular(x)?;
+
+            for (i, a) in alpha.into_vec().into_iter().enumerate() {
+                alpha_data[i * targets.cols() + j] = a;
+            }
+        }
+
+        self.train_data = Some(inputs.clone());
+        self.alpha = Some(Matrix::new(inputs.rows(), targets.cols(), alpha_data));
+
+        Ok(())
+    }
 }
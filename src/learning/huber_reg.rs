@@ -0,0 +1,329 @@
+//! Huber Regression module
+//!
+//! Contains an implementation of robust linear regression
+//! using the Huber loss.
+//!
+//! The Huber loss is quadratic for small residuals and linear for
+//! large ones, so a few outlying targets cannot dominate the fit.
+//! The model is fit by iteratively reweighted least squares. Each
+//! iteration scales the residuals by a robust estimate of their spread
+//! (the median absolute deviation) and down-weights any residual larger
+//! than `epsilon` scaled units.
+//!
+//! The regressor will automatically add the intercept term
+//! so you do not need to format the input matrices yourself.
+//!
+//! # Usage
+//!
+//! ```
+//! use rusty_machine::learning::huber_reg::HuberRegressor;
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::{Matrix, Vector};
+//!
+//! let inputs = Matrix::new(6,1,vec![1.0,2.0,3.0,4.0,5.0,6.0]);
+//!
+//! // The last target is an outlier.
+//! let targets = Vector::new(vec![2.1,3.9,6.0,8.1,9.9,50.0]);
+//!
+//! let mut huber_mod = HuberRegressor::default();
+//!
+//! // Train the model
+//! huber_mod.train(&inputs, &targets).unwrap();
+//!
+//! // The slope is close to 2 despite the outlier.
+//! let slope = huber_mod.parameters().unwrap()[1];
+//! assert!((slope - 2.0).abs() < 0.5);
+//! ```
+
+use linalg::{Matrix, BaseMatrix, Vector};
+use learning::{LearningResult, SupModel, validate_finite_matrix, validate_finite_vector};
+use learning::error::Error;
+use learning::lin_reg::weighted_least_squares;
+
+/// Relative change in the parameters at which the iterations stop.
+const CONVERGENCE_TOL: f64 = 1e-8;
+
+/// Scales the median absolute deviation to the standard
+/// deviation of normally distributed residuals.
+const MAD_SCALE: f64 = 0.6745;
+
+/// Huber Regression Model.
+///
+/// Contains the parameters of the Huber loss and
+/// an option for the fitted parameters.
+#[derive(Debug)]
+pub struct HuberRegressor {
+    /// Scaled residuals above this threshold are down-weighted.
+    epsilon: f64,
+    /// The maximum number of reweighting iterations.
+    max_iters: usize,
+    /// The parameters for the regression model.
+    parameters: Option<Vector<f64>>,
+}
+
+/// The default Huber regressor.
+///
+/// The defaults are:
+///
+/// - epsilon = 1.35
+/// - max_iters = 100
+///
+/// This value of `epsilon` is 95% as efficient as least squares
+/// when the residuals are normally distributed.
+impl Default for HuberRegressor {
+    fn default() -> HuberRegressor {
+        HuberRegressor {
+            epsilon: 1.35,
+            max_iters: 100,
+            parameters: None,
+        }
+    }
+}
+
+impl HuberRegressor {
+    /// Constructs an untrained Huber regressor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::huber_reg::HuberRegressor;
+    ///
+    /// let model = HuberRegressor::new(1.5, 50);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `epsilon` is not positive.
+    pub fn new(epsilon: f64, max_iters: usize) -> HuberRegressor {
+        assert!(epsilon > 0f64, "Epsilon must be positive.");
+
+        HuberRegressor {
+            epsilon,
+            max_iters,
+            parameters: None,
+        }
+    }
+
+    /// The threshold above which scaled residuals are down-weighted.
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon
+    }
+
+    /// The maximum number of reweighting iterations.
+    pub fn max_iters(&self) -> usize {
+        self.max_iters
+    }
+
+    /// Get the parameters from the model.
+    ///
+    /// The first parameter is the intercept.
+    ///
+    /// Returns an option that is None if the model has not been trained.
+    pub fn parameters(&self) -> Option<&Vector<f64>> {
+        self.parameters.as_ref()
+    }
+
+    /// Computes the Huber weight of each residual.
+    ///
+    /// Returns `None` if the residuals have no spread, in which
+    /// case the current fit cannot be improved.
+    fn huber_weights(&self, residuals: &Vector<f64>) -> Option<Vec<f64>> {
+        let mut abs_res = residuals.iter().map(|r| r.abs()).collect::<Vec<f64>>();
+        abs_res.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let n = abs_res.len();
+        let median = if n % 2 == 0 {
+            (abs_res[n / 2 - 1] + abs_res[n / 2]) / 2f64
+        } else {
+            abs_res[n / 2]
+        };
+
+        let scale = median / MAD_SCALE;
+        if scale <= 0f64 {
+            return None;
+        }
+
+        Some(residuals.iter()
+            .map(|r| {
+                let scaled = r.abs() / scale;
+                if scaled <= self.epsilon {
+                    1f64
+                } else {
+                    self.epsilon / scaled
+                }
+            })
+            .collect())
+    }
+}
+
+impl SupModel<Matrix<f64>, Vector<f64>> for HuberRegressor {
+    /// Train the Huber regression model.
+    ///
+    /// Takes training data and output values as input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::huber_reg::HuberRegressor;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    /// use rusty_machine::learning::SupModel;
+    ///
+    /// let mut huber_mod = HuberRegressor::default();
+    /// let inputs = Matrix::new(3,1, vec![2.0, 3.0, 4.0]);
+    /// let targets = Vector::new(vec![5.0, 6.0, 7.0]);
+    ///
+    /// huber_mod.train(&inputs, &targets).unwrap();
+    /// ```
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_vector("target", targets)?;
+
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        let full_inputs = ones.hcat(inputs);
+
+        // Start from the ordinary least squares solution.
+        let mut params = weighted_least_squares(&full_inputs, targets, &vec![1f64; inputs.rows()])?;
+
+        for _ in 0..self.max_iters {
+            let residuals = targets - &full_inputs * &params;
+
+            let weights = match self.huber_weights(&residuals) {
+                Some(w) => w,
+                None => break,
+            };
+
+            let new_params = weighted_least_squares(&full_inputs, targets, &weights)?;
+            let change = (&new_params - &params).iter().map(|x| x.abs()).fold(0f64, f64::max);
+            let size = params.iter().map(|x| x.abs()).fold(0f64, f64::max);
+            params = new_params;
+
+            if change <= CONVERGENCE_TOL * size.max(1f64) {
+                break;
+            }
+        }
+
+        self.parameters = Some(params);
+        Ok(())
+    }
+
+    /// Predict output value from input data.
+    ///
+    /// Model must be trained before prediction can be made.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let Some(ref v) = self.parameters {
+            let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+            let full_inputs = ones.hcat(inputs);
+            Ok(full_inputs * v)
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HuberRegressor;
+    use learning::SupModel;
+    use learning::lin_reg::LinRegressor;
+    use learning::error::ErrorKind;
+    use linalg::{Matrix, Vector};
+
+    /// Noisy points on the line `y = 3x + 1`.
+    fn line_data() -> (Matrix<f64>, Vector<f64>) {
+        let xs = (0..40).map(|i| i as f64 * 0.25).collect::<Vec<f64>>();
+        let ys = xs.iter()
+            .enumerate()
+            .map(|(i, x)| 3.0 * x + 1.0 + 0.1 * (i as f64 * 1.7).sin())
+            .collect::<Vec<f64>>();
+
+        (Matrix::new(40, 1, xs), Vector::new(ys))
+    }
+
+    #[test]
+    fn test_huber_resists_outliers() {
+        let (inputs, clean_targets) = line_data();
+
+        let mut clean_ols = LinRegressor::default();
+        clean_ols.train(&inputs, &clean_targets).unwrap();
+        let clean_slope = clean_ols.parameters().unwrap()[1];
+
+        // Inject large outliers at the end of the range.
+        let mut targets = clean_targets.into_vec();
+        for t in targets.iter_mut().skip(35) {
+            *t -= 100.0;
+        }
+        let targets = Vector::new(targets);
+
+        let mut ols = LinRegressor::default();
+        ols.train(&inputs, &targets).unwrap();
+        let ols_slope = ols.parameters().unwrap()[1];
+
+        let mut huber = HuberRegressor::default();
+        huber.train(&inputs, &targets).unwrap();
+        let huber_slope = huber.parameters().unwrap()[1];
+
+        assert!((huber_slope - clean_slope).abs() < 0.1);
+        assert!((ols_slope - clean_slope).abs() > 1.0);
+    }
+
+    #[test]
+    fn test_huber_matches_ols_without_outliers() {
+        let (inputs, targets) = line_data();
+
+        let mut ols = LinRegressor::default();
+        ols.train(&inputs, &targets).unwrap();
+
+        let mut huber = HuberRegressor::new(100.0, 10);
+        huber.train(&inputs, &targets).unwrap();
+
+        let ols_params = ols.parameters().unwrap();
+        let huber_params = huber.parameters().unwrap();
+        for (o, h) in ols_params.iter().zip(huber_params.iter()) {
+            assert!((o - h).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn test_huber_exact_fit() {
+        let inputs = Matrix::new(3, 1, vec![2.0, 3.0, 4.0]);
+        let targets = Vector::new(vec![5.0, 6.0, 7.0]);
+
+        let mut huber = HuberRegressor::default();
+        huber.train(&inputs, &targets).unwrap();
+
+        let outputs = huber.predict(&inputs).unwrap();
+        for (o, t) in outputs.iter().zip(targets.iter()) {
+            assert!((o - t).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn test_huber_untrained() {
+        let huber = HuberRegressor::default();
+        let inputs = Matrix::new(1, 1, vec![1.0]);
+
+        assert!(huber.predict(&inputs).is_err());
+    }
+
+    #[test]
+    fn test_huber_weights_unsupported() {
+        let mut huber = HuberRegressor::default();
+        let inputs = Matrix::new(2, 1, vec![1.0, 2.0]);
+        let targets = Vector::new(vec![1.0, 2.0]);
+
+        let res = huber.train_weighted(&inputs, &targets, &Vector::new(vec![1.0, 1.0]));
+        match res {
+            Err(e) => match *e.kind() {
+                ErrorKind::UnsupportedOperation => {}
+                _ => panic!("Unexpected error kind."),
+            },
+            Ok(_) => panic!("Weighted training should not be supported."),
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_huber_invalid_epsilon() {
+        let _ = HuberRegressor::new(0.0, 10);
+    }
+}
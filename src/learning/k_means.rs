@@ -41,10 +41,30 @@
 //! ## K-means++ initialization
 //!
 //! The [k-means++](https://en.wikipedia.org/wiki/K-means%2B%2B) scheme.
+//!
+//! # Distance
+//!
+//! The clusters minimize the squared Euclidean distance by default.
+//! With a Mahalanobis `DistanceMetric` the inputs are whitened before
+//! clustering, so that the clusters account for the scale and
+//! correlation of the features.
+//!
+//! ```
+//! use rusty_machine::linalg::Matrix;
+//! use rusty_machine::learning::k_means::KMeansClassifier;
+//! use rusty_machine::learning::toolkit::distance::DistanceMetric;
+//! use rusty_machine::learning::UnSupModel;
+//!
+//! let inputs = Matrix::new(4, 2, vec![1.0, 20.0, 1.2, 25.0, 3.0, 21.0, 3.1, 24.0]);
+//!
+//! let mut model = KMeansClassifier::new(2).with_metric(DistanceMetric::EstimatedMahalanobis);
+//! model.train(&inputs).unwrap();
+//! ```
 
 use linalg::{Matrix, MatrixSlice, Axes, Vector, BaseMatrix};
-use learning::{LearningResult, UnSupModel};
+use learning::{LearningResult, UnSupModel, validate_finite_matrix};
 use learning::error::{Error, ErrorKind};
+use learning::toolkit::distance::{DistanceMetric, Mahalanobis};
 
 use rand::{Rng, thread_rng};
 use libnum::abs;
@@ -74,6 +94,16 @@ pub struct KMeansClassifier<InitAlg: Initializer> {
     centroids: Option<Matrix<f64>>,
     /// The initial algorithm to use.
     init_algorithm: InitAlg,
+    /// The change in cost below which training stops.
+    tol: f64,
+    /// The number of iterations run in the last training.
+    n_iter: usize,
+    /// Whether the last training met the convergence tolerance.
+    converged: bool,
+    /// The distance between samples and centroids.
+    metric: DistanceMetric,
+    /// The Mahalanobis distance fitted to the training data.
+    mahalanobis: Option<Mahalanobis>,
 }
 
 impl<InitAlg: Initializer> UnSupModel<Matrix<f64>, Vector<usize>> for KMeansClassifier<InitAlg> {
@@ -82,6 +112,11 @@ impl<InitAlg: Initializer> UnSupModel<Matrix<f64>, Vector<usize>> for KMeansClas
     /// Model must be trained.
     fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<usize>> {
         if let Some(ref centroids) = self.centroids {
+            if let Some(ref m) = self.mahalanobis {
+                let whitened = m.whiten(centroids);
+                return Ok(KMeansClassifier::<InitAlg>::find_closest_centroids(whitened.as_slice(),
+                                                                              &m.whiten(inputs)).0);
+            }
             Ok(KMeansClassifier::<InitAlg>::find_closest_centroids(centroids.as_slice(), inputs).0)
         } else {
             Err(Error::new_untrained())
@@ -90,22 +125,43 @@ impl<InitAlg: Initializer> UnSupModel<Matrix<f64>, Vector<usize>> for KMeansClas
 
     /// Train the classifier using input data.
     fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+
+        // Cluster the whitened inputs for the Mahalanobis distance.
+        self.mahalanobis = None;
+        let mahalanobis = self.metric.fit(inputs)?;
+        let whitened;
+        let inputs = match mahalanobis {
+            Some(ref m) => {
+                whitened = m.whiten(inputs);
+                &whitened
+            }
+            None => inputs,
+        };
+
         try!(self.init_centroids(inputs));
         let mut cost = 0.0;
-        let eps = 1e-14;
+        self.n_iter = 0;
+        self.converged = false;
 
         for _i in 0..self.iters {
             let (idx, distances) = try!(self.get_closest_centroids(inputs));
             self.update_centroids(inputs, idx);
+            self.n_iter += 1;
 
             let cost_i = distances.sum();
-            if abs(cost - cost_i) < eps {
+            if abs(cost - cost_i) < self.tol {
+                self.converged = true;
                 break;
             }
 
             cost = cost_i;
         }
 
+        if let Some(ref m) = mahalanobis {
+            self.centroids = self.centroids.as_ref().map(|c| m.unwhiten(c));
+        }
+        self.mahalanobis = mahalanobis;
         Ok(())
     }
 }
@@ -129,6 +185,11 @@ impl KMeansClassifier<KPlusPlus> {
             k: k,
             centroids: None,
             init_algorithm: KPlusPlus,
+            tol: 1e-14,
+            n_iter: 0,
+            converged: false,
+            metric: DistanceMetric::Euclidean,
+            mahalanobis: None,
         }
     }
 }
@@ -152,9 +213,34 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
             k: k,
             centroids: None,
             init_algorithm: algo,
+            tol: 1e-14,
+            n_iter: 0,
+            converged: false,
+            metric: DistanceMetric::Euclidean,
+            mahalanobis: None,
         }
     }
 
+    /// Sets the distance between samples and centroids.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::k_means::KMeansClassifier;
+    /// use rusty_machine::learning::toolkit::distance::DistanceMetric;
+    ///
+    /// let model = KMeansClassifier::new(3).with_metric(DistanceMetric::EstimatedMahalanobis);
+    /// ```
+    pub fn with_metric(mut self, metric: DistanceMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    /// Get the distance between samples and centroids.
+    pub fn metric(&self) -> &DistanceMetric {
+        &self.metric
+    }
+
     /// Get the number of classes.
     pub fn k(&self) -> usize {
         self.k
@@ -171,10 +257,43 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
     }
 
     /// Get the centroids `Option<Matrix<f64>>`.
+    ///
+    /// The centroids are in the original feature space for every metric.
     pub fn centroids(&self) -> &Option<Matrix<f64>> {
         &self.centroids
     }
 
+    /// Whether the last training stopped because the cost changed by
+    /// less than `tol`, rather than by reaching the maximum number of iterations.
+    ///
+    /// Returns `false` if the model has not been trained.
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    /// The number of iterations run in the last training, including
+    /// the iteration which found that the model had converged.
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+
+    /// The change in cost below which training stops.
+    pub fn tol(&self) -> f64 {
+        self.tol
+    }
+
+    /// Sets the change in cost below which training stops.
+    ///
+    /// Defaults to `1e-14`.
+    ///
+    /// # Panics
+    ///
+    /// - `tol` is negative or NaN.
+    pub fn set_tol(&mut self, tol: f64) {
+        assert!(tol >= 0f64, "The tolerance must be non-negative.");
+        self.tol = tol;
+    }
+
     /// Set the number of iterations.
     pub fn set_iters(&mut self, iters: usize) {
         self.iters = iters;
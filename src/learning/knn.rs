@@ -0,0 +1,517 @@
+//! K-Nearest Neighbours Classification
+//!
+//! Provides the `KNNClassifier`, which predicts the class of a point
+//! by a vote among the `k` closest training points.
+//!
+//! Training stores the points in a `KDTree`, so that the neighbours of
+//! a point are found without computing the distance to every training
+//! point. The classes are the integers `0, 1, ...`.
+//!
+//! Each neighbour votes for its class with a weight chosen by the
+//! `Weighting`. With uniform weighting every neighbour counts equally,
+//! while with distance weighting a neighbour at distance `d` has weight
+//! `1/d`, so that closer neighbours count more.
+//!
+//! The distance is Euclidean by default. With a Mahalanobis
+//! `DistanceMetric` the training points are whitened before they are
+//! stored, so that the neighbours account for the scale and
+//! correlation of the features.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::knn::{KNNClassifier, Weighting};
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::{Matrix, Vector};
+//!
+//! let inputs = Matrix::new(6, 2, vec![0.0, 0.0,
+//!                                     0.5, 0.2,
+//!                                     0.1, 0.6,
+//!                                     4.0, 4.0,
+//!                                     4.2, 3.7,
+//!                                     3.8, 4.4]);
+//! let targets = Vector::new(vec![0, 0, 0, 1, 1, 1]);
+//!
+//! let mut model = KNNClassifier::new(3, Weighting::Distance);
+//! model.train(&inputs, &targets).unwrap();
+//!
+//! let classes = model.predict(&Matrix::new(2, 2, vec![0.3, 0.3, 3.5, 3.9])).unwrap();
+//! assert_eq!(classes.into_vec(), vec![0, 1]);
+//!
+//! // The training points within distance 1 of the origin.
+//! let neighbours = model.radius_neighbors(&[0.0, 0.0], 1.0).unwrap();
+//! assert_eq!(neighbours.iter().map(|&(i, _)| i).collect::<Vec<usize>>(), vec![0, 1, 2]);
+//! ```
+
+use linalg::{Matrix, BaseMatrix, Vector};
+use learning::{LearningResult, SupModel, LabelClassifier, ProbabilisticClassifier, validate_finite_matrix};
+use learning::error::{Error, ErrorKind};
+use learning::model_info::ModelInfo;
+use learning::toolkit::distance::{DistanceMetric, Mahalanobis};
+use learning::toolkit::kd_tree::KDTree;
+
+/// The weight of each neighbour's vote.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Weighting {
+    /// Every neighbour has the same weight.
+    Uniform,
+    /// A neighbour at distance `d` has weight `1/d`.
+    ///
+    /// If some neighbours are at distance zero only they vote.
+    Distance,
+}
+
+/// K-Nearest Neighbours Classifier
+#[derive(Clone, Debug)]
+pub struct KNNClassifier {
+    k: usize,
+    weighting: Weighting,
+    metric: DistanceMetric,
+    mahalanobis: Option<Mahalanobis>,
+    tree: Option<KDTree>,
+    classes: Option<Vector<usize>>,
+    n_classes: usize,
+}
+
+/// Constructs an untrained classifier with `k = 5`
+/// and uniform weighting.
+impl Default for KNNClassifier {
+    fn default() -> KNNClassifier {
+        KNNClassifier::new(5, Weighting::Uniform)
+    }
+}
+
+impl KNNClassifier {
+    /// Constructs an untrained classifier which votes among the
+    /// `k` closest training points with the given weighting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::knn::{KNNClassifier, Weighting};
+    ///
+    /// let model = KNNClassifier::new(3, Weighting::Distance);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `k` is zero.
+    pub fn new(k: usize, weighting: Weighting) -> KNNClassifier {
+        assert!(k > 0, "The number of neighbours must be positive.");
+
+        KNNClassifier {
+            k,
+            weighting,
+            metric: DistanceMetric::Euclidean,
+            mahalanobis: None,
+            tree: None,
+            classes: None,
+            n_classes: 0,
+        }
+    }
+
+    /// Sets the distance between points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::knn::{KNNClassifier, Weighting};
+    /// use rusty_machine::learning::toolkit::distance::DistanceMetric;
+    ///
+    /// let model = KNNClassifier::new(3, Weighting::Uniform)
+    ///                 .with_metric(DistanceMetric::EstimatedMahalanobis);
+    /// ```
+    pub fn with_metric(mut self, metric: DistanceMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    /// The distance between points.
+    pub fn metric(&self) -> &DistanceMetric {
+        &self.metric
+    }
+
+    /// The number of neighbours which vote.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// The weight of each neighbour's vote.
+    pub fn weighting(&self) -> Weighting {
+        self.weighting
+    }
+
+    /// The index of the training points.
+    ///
+    /// For the Mahalanobis distance the index holds the whitened points.
+    pub fn tree(&self) -> Option<&KDTree> {
+        self.tree.as_ref()
+    }
+
+    /// The size of the trained model.
+    ///
+    /// Every training point is stored and searched for each prediction.
+    /// The only learned parameters are the entries of the covariance for
+    /// `DistanceMetric::EstimatedMahalanobis`. The number of neighbours
+    /// and the distance are given by `k` and `metric`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::knn::{KNNClassifier, Weighting};
+    /// use rusty_machine::learning::toolkit::distance::DistanceMetric;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let inputs = Matrix::new(4, 2, vec![0.0, 0.0, 1.0, 0.5, 4.0, 4.0, 5.0, 4.5]);
+    /// let targets = Vector::new(vec![0, 0, 1, 1]);
+    ///
+    /// let mut model = KNNClassifier::new(3, Weighting::Uniform);
+    /// model.train(&inputs, &targets).unwrap();
+    ///
+    /// let info = model.model_info().unwrap();
+    /// assert_eq!(info.n_stored_samples, Some(4));
+    /// assert_eq!(info.n_inputs, Some(2));
+    /// assert_eq!(model.k(), 3);
+    /// assert!(matches!(*model.metric(), DistanceMetric::Euclidean));
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The model has not been trained.
+    pub fn model_info(&self) -> LearningResult<ModelInfo> {
+        let tree = self.tree.as_ref().ok_or_else(Error::new_untrained)?;
+        let n_params = match self.metric {
+            DistanceMetric::EstimatedMahalanobis => tree.data().cols() * tree.data().cols(),
+            _ => 0,
+        };
+
+        Ok(ModelInfo {
+            n_params,
+            n_inputs: Some(tree.data().cols()),
+            n_outputs: Some(1),
+            n_stored_samples: Some(tree.data().rows()),
+            n_nodes: None,
+        })
+    }
+
+    /// Checks that the model is trained and `point` matches the training data.
+    fn trained_tree(&self, n_cols: usize) -> LearningResult<&KDTree> {
+        let tree = self.tree.as_ref().ok_or_else(Error::new_untrained)?;
+        if tree.data().cols() != n_cols {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Input data has different number of columns from training data."));
+        }
+        Ok(tree)
+    }
+
+    /// Whitens the inputs for the Mahalanobis distance.
+    fn whiten(&self, inputs: Matrix<f64>) -> Matrix<f64> {
+        match self.mahalanobis {
+            Some(ref m) => m.whiten(&inputs),
+            None => inputs,
+        }
+    }
+
+    /// Whitens a single point for the Mahalanobis distance.
+    fn whiten_point(&self, point: &[f64]) -> Vec<f64> {
+        self.whiten(Matrix::new(1, point.len(), point.to_vec())).into_vec()
+    }
+
+    /// The `k` training points closest to `point`.
+    ///
+    /// Returns the row index of each point in the training data and its
+    /// distance to `point` under the model's metric, sorted by distance.
+    pub fn kneighbors(&self, point: &[f64]) -> LearningResult<Vec<(usize, f64)>> {
+        let tree = self.trained_tree(point.len())?;
+        Ok(tree.nearest(&self.whiten_point(point), self.k))
+    }
+
+    /// All training points whose distance to `point` is at most `r`.
+    ///
+    /// Returns the row index of each point in the training data and its
+    /// distance to `point` under the model's metric, sorted by distance.
+    pub fn radius_neighbors(&self, point: &[f64], r: f64) -> LearningResult<Vec<(usize, f64)>> {
+        if r.is_nan() || r < 0f64 {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "The radius must be non-negative."));
+        }
+
+        let tree = self.trained_tree(point.len())?;
+        Ok(tree.within_radius(&self.whiten_point(point), r))
+    }
+
+    /// The total vote for each class among the neighbours.
+    fn votes(&self, neighbours: &[(usize, f64)], classes: &Vector<usize>) -> Vec<f64> {
+        let mut votes = vec![0f64; self.n_classes];
+        let exact = neighbours.iter().any(|&(_, d)| d == 0f64);
+
+        for &(i, d) in neighbours {
+            let weight = match self.weighting {
+                Weighting::Uniform => 1f64,
+                Weighting::Distance if exact => if d == 0f64 { 1f64 } else { 0f64 },
+                Weighting::Distance => 1f64 / d,
+            };
+            votes[classes[i]] += weight;
+        }
+        votes
+    }
+
+    /// The class with the largest total vote among the neighbours.
+    ///
+    /// Ties go to the smallest class.
+    fn vote(&self, neighbours: &[(usize, f64)], classes: &Vector<usize>) -> usize {
+        let votes = self.votes(neighbours, classes);
+        let mut best = 0;
+        for (c, &v) in votes.iter().enumerate() {
+            if v > votes[best] {
+                best = c;
+            }
+        }
+        best
+    }
+}
+
+impl SupModel<Matrix<f64>, Vector<usize>> for KNNClassifier {
+    /// Predict the class of each input by a vote among its neighbours.
+    ///
+    /// Model must be trained before prediction can be made.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<usize>> {
+        let tree = self.trained_tree(inputs.cols())?;
+        let classes = self.classes.as_ref().ok_or_else(Error::new_untrained)?;
+
+        let inputs = self.whiten(inputs.clone());
+        let predictions = inputs.row_iter()
+            .map(|row| self.vote(&tree.nearest(row.raw_slice(), self.k), classes))
+            .collect::<Vec<usize>>();
+        Ok(Vector::new(predictions))
+    }
+
+    /// Train the classifier by storing the training points.
+    ///
+    /// # Failures
+    ///
+    /// - The inputs contain non-finite values.
+    /// - There is not one target per input.
+    /// - There are fewer than `k` inputs.
+    /// - The Mahalanobis covariance is invalid or cannot be estimated.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<usize>) -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "There must be one target per input."));
+        }
+        if inputs.rows() < self.k {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "There must be at least k training points."));
+        }
+
+        self.mahalanobis = self.metric.fit(inputs)?;
+        self.n_classes = targets.iter().max().map_or(0, |&c| c + 1);
+        self.tree = Some(KDTree::new(self.whiten(inputs.clone())));
+        self.classes = Some(targets.clone());
+        Ok(())
+    }
+}
+
+/// Train and predict from the classifier using class labels,
+/// without wrapping them in a `Vector`.
+impl LabelClassifier for KNNClassifier {
+    fn train_labels(&mut self, inputs: &Matrix<f64>, labels: &[usize]) -> LearningResult<()> {
+        self.train(inputs, &Vector::new(labels.to_vec()))
+    }
+
+    fn predict_labels(&self, inputs: &Matrix<f64>) -> LearningResult<Vec<usize>> {
+        Ok(self.predict(inputs)?.into_vec())
+    }
+}
+
+/// The share of the (weighted) vote for each class among the neighbours.
+impl ProbabilisticClassifier for KNNClassifier {
+    fn predict_proba(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        let tree = self.trained_tree(inputs.cols())?;
+        let classes = self.classes.as_ref().ok_or_else(Error::new_untrained)?;
+
+        let inputs = self.whiten(inputs.clone());
+        let mut data = Vec::with_capacity(inputs.rows() * self.n_classes);
+        for row in inputs.row_iter() {
+            let votes = self.votes(&tree.nearest(row.raw_slice(), self.k), classes);
+            let total = votes.iter().sum::<f64>();
+            data.extend(votes.into_iter().map(|v| v / total));
+        }
+        Ok(Matrix::new(inputs.rows(), self.n_classes, data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KNNClassifier, Weighting};
+    use learning::{LabelClassifier, ProbabilisticClassifier, SupModel};
+    use learning::toolkit::distance::DistanceMetric;
+    use linalg::{Matrix, Vector};
+
+    use std::f64;
+
+    #[test]
+    fn test_weighting_changes_borderline_prediction() {
+        // The query at 1.0 has one close neighbour of class 0
+        // and two further neighbours of class 1.
+        let inputs = Matrix::new(4, 1, vec![0.8, 2.5, 2.6, 10.0]);
+        let targets = Vector::new(vec![0, 1, 1, 0]);
+        let query = Matrix::new(1, 1, vec![1.0]);
+
+        let mut uniform = KNNClassifier::new(3, Weighting::Uniform);
+        uniform.train(&inputs, &targets).unwrap();
+        assert_eq!(uniform.predict(&query).unwrap().into_vec(), vec![1]);
+
+        let mut weighted = KNNClassifier::new(3, Weighting::Distance);
+        weighted.train(&inputs, &targets).unwrap();
+        assert_eq!(weighted.predict(&query).unwrap().into_vec(), vec![0]);
+    }
+
+    #[test]
+    fn test_weighted_exact_match() {
+        let inputs = Matrix::new(3, 1, vec![0.0, 1.0, 1.1]);
+        let targets = Vector::new(vec![0, 1, 1]);
+
+        let mut model = KNNClassifier::new(3, Weighting::Distance);
+        model.train(&inputs, &targets).unwrap();
+        assert_eq!(model.predict(&Matrix::new(1, 1, vec![0.0])).unwrap().into_vec(), vec![0]);
+    }
+
+    #[test]
+    fn test_radius_neighbors_hand_placed() {
+        let inputs = Matrix::new(6, 2, vec![0.0, 0.0,
+                                            1.0, 0.0,
+                                            0.0, -2.0,
+                                            3.0, 4.0,
+                                            -1.5, 1.5,
+                                            0.5, 0.5]);
+        let targets = Vector::new(vec![0, 0, 0, 1, 1, 1]);
+
+        let mut model = KNNClassifier::default();
+        model.train(&inputs, &targets).unwrap();
+
+        let neighbours = model.radius_neighbors(&[0.0, 0.0], 2.0).unwrap();
+        let indices = neighbours.iter().map(|&(i, _)| i).collect::<Vec<usize>>();
+        // The point at distance exactly 2 is included, the one at 2.12 is not.
+        assert_eq!(indices, vec![0, 5, 1, 2]);
+        assert!((neighbours[1].1 - 0.5f64.sqrt()).abs() < 1e-12);
+
+        let all = model.radius_neighbors(&[0.0, 0.0], 5.0).unwrap();
+        assert_eq!(all.len(), 6);
+        assert_eq!(all[5], (3, 5.0));
+
+        assert!(model.radius_neighbors(&[10.0, 10.0], 1.0).unwrap().is_empty());
+        assert!(model.radius_neighbors(&[0.0, 0.0], -1.0).is_err());
+    }
+
+    #[test]
+    fn test_kneighbors() {
+        let inputs = Matrix::new(4, 1, vec![0.0, 3.0, 1.0, 7.0]);
+        let targets = Vector::new(vec![0, 1, 0, 1]);
+
+        let mut model = KNNClassifier::new(2, Weighting::Uniform);
+        model.train(&inputs, &targets).unwrap();
+
+        let neighbours = model.kneighbors(&[2.75]).unwrap();
+        assert_eq!(neighbours, vec![(1, 0.25), (2, 1.75)]);
+    }
+
+    #[test]
+    fn test_integer_labels() {
+        let inputs = Matrix::new(6, 1, vec![0.0, 0.5, 5.0, 5.5, 10.0, 10.5]);
+        let labels = vec![0, 0, 1, 1, 2, 2];
+
+        let mut model = KNNClassifier::new(2, Weighting::Distance);
+        model.train_labels(&inputs, &labels).unwrap();
+        assert_eq!(model.predict_labels(&inputs).unwrap(), labels);
+    }
+
+    #[test]
+    fn test_predict_proba() {
+        let inputs = Matrix::new(6, 1, vec![0.0, 1.0, 2.0, 3.0, 10.0, 11.0]);
+        let labels = vec![0, 0, 0, 1, 2, 2];
+
+        let mut model = KNNClassifier::new(4, Weighting::Uniform);
+        model.train_labels(&inputs, &labels).unwrap();
+
+        // The four neighbours of 1.5 are three points of class 0 and one of class 1.
+        let probs = model.predict_proba(&Matrix::new(1, 1, vec![1.5])).unwrap();
+        assert_eq!(probs.into_vec(), vec![0.75, 0.25, 0.0]);
+    }
+
+    #[test]
+    fn test_mahalanobis_metric() {
+        // The first feature varies much more than the second.
+        let inputs = Matrix::new(2, 2, vec![5.0, 0.0, 0.0, 2.0]);
+        let targets = Vector::new(vec![0, 1]);
+        let query = Matrix::new(1, 2, vec![0.0, 0.0]);
+
+        let mut euclidean = KNNClassifier::new(1, Weighting::Uniform);
+        euclidean.train(&inputs, &targets).unwrap();
+        assert_eq!(euclidean.predict(&query).unwrap().into_vec(), vec![1]);
+
+        let covariance = Matrix::new(2, 2, vec![100.0, 0.0, 0.0, 1.0]);
+        let mut mahalanobis = KNNClassifier::new(1, Weighting::Uniform)
+            .with_metric(DistanceMetric::Mahalanobis(covariance));
+        mahalanobis.train(&inputs, &targets).unwrap();
+        assert_eq!(mahalanobis.predict(&query).unwrap().into_vec(), vec![0]);
+
+        let neighbours = mahalanobis.radius_neighbors(&[0.0, 0.0], 1.0).unwrap();
+        assert_eq!(neighbours, vec![(0, 0.5)]);
+    }
+
+    #[test]
+    fn test_invalid_training() {
+        let mut model = KNNClassifier::new(3, Weighting::Uniform);
+
+        let inputs = Matrix::new(2, 1, vec![0.0, 1.0]);
+        assert!(model.train(&inputs, &Vector::new(vec![0, 1])).is_err());
+
+        let inputs = Matrix::new(3, 1, vec![0.0, 1.0, f64::NAN]);
+        assert!(model.train(&inputs, &Vector::new(vec![0, 1, 1])).is_err());
+
+        let inputs = Matrix::new(3, 1, vec![0.0, 1.0, 2.0]);
+        assert!(model.train(&inputs, &Vector::new(vec![0, 1])).is_err());
+    }
+
+    #[test]
+    fn test_untrained_and_wrong_size() {
+        let mut model = KNNClassifier::new(1, Weighting::Uniform);
+        assert!(model.predict(&Matrix::new(1, 1, vec![0.0])).is_err());
+        assert!(model.radius_neighbors(&[0.0], 1.0).is_err());
+
+        model.train(&Matrix::new(2, 2, vec![0.0, 0.0, 1.0, 1.0]), &Vector::new(vec![0, 1])).unwrap();
+        assert!(model.predict(&Matrix::new(1, 3, vec![0.0, 0.0, 0.0])).is_err());
+        assert!(model.kneighbors(&[0.0]).is_err());
+    }
+
+    #[test]
+    fn test_model_info() {
+        let inputs = Matrix::new(5, 3, vec![0.0, 0.1, 0.3,
+                                            1.0, 0.4, 0.2,
+                                            0.2, 1.5, 0.7,
+                                            3.0, 2.8, 4.1,
+                                            2.5, 3.3, 3.6]);
+        let targets = Vector::new(vec![0, 0, 0, 1, 1]);
+
+        let mut model = KNNClassifier::new(2, Weighting::Distance);
+        assert!(model.model_info().is_err());
+        model.train(&inputs, &targets).unwrap();
+
+        let info = model.model_info().unwrap();
+        assert_eq!(info.n_params, 0);
+        assert_eq!(info.n_inputs, Some(3));
+        assert_eq!(info.n_outputs, Some(1));
+        assert_eq!(info.n_stored_samples, Some(5));
+        assert_eq!(model.k(), 2);
+        assert!(matches!(*model.metric(), DistanceMetric::Euclidean));
+
+        let mut estimated = KNNClassifier::new(2, Weighting::Distance)
+            .with_metric(DistanceMetric::EstimatedMahalanobis);
+        estimated.train(&inputs, &targets).unwrap();
+        assert_eq!(estimated.model_info().unwrap().n_params, 9);
+        assert!(matches!(*estimated.metric(), DistanceMetric::EstimatedMahalanobis));
+    }
+}
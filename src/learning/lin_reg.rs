@@ -3,6 +3,12 @@
 //! Contains implemention of linear regression using
 //! OLS and gradient descent optimization.
 //!
+//! The OLS parameters are found by a Cholesky factorization of the
+//! normal equations, falling back to LU decomposition when the
+//! factorization fails. For ill-conditioned inputs the parameters
+//! can instead be found from the QR decomposition of the inputs
+//! using `LinRegressor::with_solver`.
+//!
 //! The regressor will automatically add the intercept term
 //! so you do not need to format the input matrices yourself.
 //!
@@ -29,38 +35,370 @@
 //! // Hopefully we classified our new point correctly!
 //! assert!(output[0] > 17f64, "Our regressor isn't very good!");
 //! ```
+//!
+//! Features on very different scales can make the normal equations
+//! ill-conditioned. The regressor can standardize the inputs internally
+//! using `LinRegressor::with_standardization`. The parameters are still
+//! reported on the original scale of the inputs.
 
-use linalg::{Matrix, BaseMatrix};
+use linalg::{Matrix, BaseMatrix, BaseMatrixMut};
 use linalg::Vector;
-use learning::{LearningResult, SupModel};
+use learning::{LearningResult, SupModel, validate_finite_matrix, validate_finite_vector,
+               validate_sample_weights};
 use learning::toolkit::cost_fn::CostFunc;
 use learning::toolkit::cost_fn::MeanSqError;
 use learning::optim::grad_desc::GradientDesc;
 use learning::optim::{OptimAlgorithm, Optimizable};
-use learning::error::Error;
+use learning::error::{Error, ErrorKind};
+use data::transforms::{Transformer, TransformFitter, StandardizerFitter};
+use data::transforms::standardize::Standardizer;
+
+/// The method used to find the least squares parameters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Solver {
+    /// Solve the normal equations `XᵀXβ = Xᵀy` by Cholesky factorization.
+    ///
+    /// This is fast, but forming `XᵀX` squares the condition number
+    /// of the inputs.
+    NormalEquations,
+    /// Solve `Rβ = Qᵀy` using the QR decomposition of `X`.
+    ///
+    /// This is slower, but more accurate when the inputs are
+    /// ill-conditioned.
+    QR,
+}
 
 /// Linear Regression Model.
 ///
 /// Contains option for optimized parameter.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct LinRegressor {
     /// The parameters for the regression model.
     parameters: Option<Vector<f64>>,
+    /// Whether the inputs are standardized before fitting.
+    standardize: bool,
+    /// The Cholesky factor of the normal equations from the last fit.
+    normal_factor: Option<Matrix<f64>>,
+    /// The method used to find the parameters.
+    solver: Solver,
 }
 
 impl Default for LinRegressor {
     fn default() -> LinRegressor {
-        LinRegressor { parameters: None }
+        LinRegressor {
+            parameters: None,
+            standardize: false,
+            normal_factor: None,
+            solver: Solver::NormalEquations,
+        }
     }
 }
 
 impl LinRegressor {
+    /// Constructs an untrained linear regressor which optionally
+    /// standardizes the inputs to zero mean and unit variance
+    /// before fitting.
+    ///
+    /// The parameters are mapped back to the original scale
+    /// after training, so predictions are made on raw inputs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::lin_reg::LinRegressor;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let inputs = Matrix::new(3, 2, vec![1.0, 1000.0, 2.0, 3000.0, 4.0, 2000.0]);
+    /// let targets = Vector::new(vec![3.0, 6.0, 7.0]);
+    ///
+    /// let mut lin_mod = LinRegressor::with_standardization(true);
+    /// lin_mod.train(&inputs, &targets).unwrap();
+    /// ```
+    pub fn with_standardization(standardize: bool) -> LinRegressor {
+        LinRegressor {
+            parameters: None,
+            standardize,
+            normal_factor: None,
+            solver: Solver::NormalEquations,
+        }
+    }
+
+    /// Sets the method used to find the least squares parameters.
+    ///
+    /// The default is `Solver::NormalEquations`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::lin_reg::{LinRegressor, Solver};
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let inputs = Matrix::new(3, 1, vec![2.0, 3.0, 4.0]);
+    /// let targets = Vector::new(vec![5.0, 6.0, 7.0]);
+    ///
+    /// let mut lin_mod = LinRegressor::default().with_solver(Solver::QR);
+    /// lin_mod.train(&inputs, &targets).unwrap();
+    /// ```
+    pub fn with_solver(mut self, solver: Solver) -> LinRegressor {
+        self.solver = solver;
+        self
+    }
+
+    /// The method used to find the least squares parameters.
+    pub fn solver(&self) -> Solver {
+        self.solver
+    }
+
     /// Get the parameters from the model.
     ///
     /// Returns an option that is None if the model has not been trained.
     pub fn parameters(&self) -> Option<&Vector<f64>> {
         self.parameters.as_ref()
     }
+
+    /// Get the Cholesky factor of the normal equations used in the last fit.
+    ///
+    /// This is the lower triangular matrix `L` with `LLᵀ = XᵀWX`, where `X`
+    /// holds the inputs with a leading column of ones and `W` the
+    /// sample weights. If the regressor standardizes its inputs then `X`
+    /// holds the standardized inputs.
+    ///
+    /// With the QR solver this is computed from the R factor, without forming `XᵀWX`.
+    ///
+    /// Returns `None` if the model has not been trained with `train` or
+    /// `train_weighted`, or if `XᵀWX` was not positive definite and the
+    /// parameters were found by LU decomposition instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::lin_reg::LinRegressor;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{BaseMatrix, Matrix, Vector};
+    ///
+    /// let inputs = Matrix::new(3, 1, vec![2.0, 3.0, 4.0]);
+    /// let targets = Vector::new(vec![5.0, 6.0, 7.0]);
+    ///
+    /// let mut lin_mod = LinRegressor::default();
+    /// lin_mod.train(&inputs, &targets).unwrap();
+    ///
+    /// let factor = lin_mod.normal_factor().unwrap();
+    /// assert_eq!(factor.rows(), 2);
+    /// ```
+    pub fn normal_factor(&self) -> Option<&Matrix<f64>> {
+        self.normal_factor.as_ref()
+    }
+
+    /// Fits a standardizer to the inputs and returns it
+    /// with the standardized inputs.
+    fn standardize_inputs(inputs: &Matrix<f64>) -> LearningResult<(Standardizer<f64>, Matrix<f64>)> {
+        let mut standardizer = StandardizerFitter::default().fit(inputs)?;
+
+        if standardizer.variances().iter().any(|&v| v == 0f64) {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Cannot standardize a constant column."));
+        }
+
+        let standardized = standardizer.transform(inputs.clone())?;
+        Ok((standardizer, standardized))
+    }
+
+    /// Maps parameters fitted on standardized inputs back
+    /// to the original scale of the inputs.
+    fn unstandardize_params(standardizer: &Standardizer<f64>, params: Vector<f64>) -> Vector<f64> {
+        let mut params = params.into_vec();
+        let mut intercept = params[0];
+
+        for (p, (m, v)) in params[1..].iter_mut()
+            .zip(standardizer.means().iter().zip(standardizer.variances().iter())) {
+            *p /= v.sqrt();
+            intercept -= *p * m;
+        }
+
+        params[0] = intercept;
+        Vector::new(params)
+    }
+
+    /// Computes the (weighted) least squares parameters, including the intercept.
+    ///
+    /// The normal equations are solved using the Cholesky factorization
+    /// of `XᵀWX`, which is returned with the parameters. If the matrix is
+    /// not positive definite the equations are solved by LU decomposition,
+    /// which fails if the matrix is singular.
+    fn solve_normal_equations(inputs: &Matrix<f64>,
+                              targets: &Vector<f64>,
+                              weights: Option<&Vector<f64>>)
+                              -> LearningResult<(Vector<f64>, Option<Matrix<f64>>)> {
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        let full_inputs = ones.hcat(inputs);
+        let xt = full_inputs.transpose();
+
+        let (gram, moments) = match weights {
+            Some(w) => {
+                let mut weighted_inputs = full_inputs;
+                for (mut row, w_i) in weighted_inputs.row_iter_mut().zip(w.iter()) {
+                    for x in row.iter_mut() {
+                        *x *= *w_i;
+                    }
+                }
+
+                (&xt * weighted_inputs, &xt * targets.elemul(w))
+            }
+            None => (&xt * full_inputs, xt * targets),
+        };
+
+        match gram.cholesky() {
+            Ok(ref factor) if factor.diag().all(|d| d.is_finite() && *d > 0f64) => {
+                let y = factor.solve_l_triangular(moments)?;
+                let params = factor.transpose().solve_u_triangular(y)?;
+                Ok((params, Some(factor.clone())))
+            }
+            _ => {
+                let params = gram.solve(moments)?;
+                Ok((params, None))
+            }
+        }
+    }
+
+    /// Computes the (weighted) least squares parameters, including the intercept,
+    /// using the Householder QR decomposition of the inputs.
+    ///
+    /// The reflections are applied to the targets as they are computed, so `Q`
+    /// is never formed. Returns the parameters and the transpose of the R
+    /// factor, scaled to have a positive diagonal.
+    fn solve_qr(inputs: &Matrix<f64>,
+                targets: &Vector<f64>,
+                weights: Option<&Vector<f64>>)
+                -> LearningResult<(Vector<f64>, Matrix<f64>)> {
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        let mut a = ones.hcat(inputs);
+        let mut b = targets.clone().into_vec();
+
+        // Weighted least squares scales each row by the root of its weight.
+        if let Some(w) = weights {
+            for (i, w_i) in w.iter().enumerate() {
+                let root = w_i.sqrt();
+                for x in a.row_mut(i).iter_mut() {
+                    *x *= root;
+                }
+                b[i] *= root;
+            }
+        }
+
+        let (n, p) = (a.rows(), a.cols());
+        if n < p {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The QR solver requires at least as many samples as parameters."));
+        }
+
+        for k in 0..p {
+            let norm = (k..n).map(|i| a[[i, k]] * a[[i, k]]).sum::<f64>().sqrt();
+            if norm == 0f64 {
+                return Err(Error::new(ErrorKind::InvalidData, "The inputs are rank deficient."));
+            }
+
+            // The reflection maps the column below the diagonal onto -sign(a_kk) * norm * e_k.
+            let alpha = if a[[k, k]] > 0f64 { -norm } else { norm };
+            let mut v = (k..n).map(|i| a[[i, k]]).collect::<Vec<f64>>();
+            v[0] -= alpha;
+            let v_sq = v.iter().map(|x| x * x).sum::<f64>();
+
+            for j in k..p {
+                let scale = 2f64 * (k..n).map(|i| v[i - k] * a[[i, j]]).sum::<f64>() / v_sq;
+                for i in k..n {
+                    a[[i, j]] -= scale * v[i - k];
+                }
+            }
+
+            let scale = 2f64 * (k..n).map(|i| v[i - k] * b[i]).sum::<f64>() / v_sq;
+            for i in k..n {
+                b[i] -= scale * v[i - k];
+            }
+        }
+
+        let mut r = a.select_rows(&(0..p).collect::<Vec<usize>>());
+        let max_diag = r.diag().fold(0f64, |m, d| m.max(d.abs()));
+        if r.diag().any(|d| d.abs() <= max_diag * 1e-14) {
+            return Err(Error::new(ErrorKind::InvalidData, "The inputs are rank deficient."));
+        }
+
+        // Make the diagonal positive so that Rᵀ is the Cholesky factor of XᵀX.
+        for i in 0..p {
+            if r[[i, i]] < 0f64 {
+                for x in r.row_mut(i).iter_mut() {
+                    *x = -*x;
+                }
+                b[i] = -b[i];
+            }
+        }
+
+        b.truncate(p);
+        let params = r.solve_u_triangular(Vector::new(b))?;
+        Ok((params, r.transpose()))
+    }
+
+    /// Computes the parameters and normal factor with the configured solver.
+    fn solve(&self,
+             inputs: &Matrix<f64>,
+             targets: &Vector<f64>,
+             weights: Option<&Vector<f64>>)
+             -> LearningResult<(Vector<f64>, Option<Matrix<f64>>)> {
+        match self.solver {
+            Solver::NormalEquations => {
+                LinRegressor::solve_normal_equations(inputs, targets, weights)
+            }
+            Solver::QR => {
+                let (params, factor) = LinRegressor::solve_qr(inputs, targets, weights)?;
+                Ok((params, Some(factor)))
+            }
+        }
+    }
+
+    /// Fits the parameters, standardizing the inputs if required.
+    fn fit(&mut self,
+           inputs: &Matrix<f64>,
+           targets: &Vector<f64>,
+           weights: Option<&Vector<f64>>)
+           -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_vector("target", targets)?;
+
+        if self.standardize {
+            let (standardizer, standardized) = LinRegressor::standardize_inputs(inputs)?;
+            let (params, factor) = self.solve(&standardized, targets, weights)?;
+            self.parameters = Some(LinRegressor::unstandardize_params(&standardizer, params));
+            self.normal_factor = factor;
+        } else {
+            let (params, factor) = self.solve(inputs, targets, weights)?;
+            self.parameters = Some(params);
+            self.normal_factor = factor;
+        }
+        Ok(())
+    }
+}
+
+/// Solves the weighted least squares problem for the given weights.
+///
+/// The inputs must already include any intercept column. This is
+/// shared by the iteratively reweighted least squares models.
+pub(crate) fn weighted_least_squares(inputs: &Matrix<f64>,
+                                     targets: &Vector<f64>,
+                                     weights: &[f64])
+                                     -> LearningResult<Vector<f64>> {
+    let mut weighted_inputs = inputs.clone();
+    for (mut row, w) in weighted_inputs.row_iter_mut().zip(weights.iter()) {
+        for x in row.iter_mut() {
+            *x *= *w;
+        }
+    }
+
+    let weighted_targets = targets.elemul(&Vector::new(weights.to_vec()));
+
+    let xt = inputs.transpose();
+    let params = (&xt * weighted_inputs).solve(xt * weighted_targets)?;
+    Ok(params)
 }
 
 impl SupModel<Matrix<f64>, Vector<f64>> for LinRegressor {
@@ -83,13 +421,41 @@ impl SupModel<Matrix<f64>, Vector<f64>> for LinRegressor {
     /// lin_mod.train(&inputs, &targets).unwrap();
     /// ```
     fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
-        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
-        let full_inputs = ones.hcat(inputs);
+        self.fit(inputs, targets, None)
+    }
 
-        let xt = full_inputs.transpose();
-        self.parameters = Some((&xt * full_inputs).solve(&xt * targets)
-                                                  .expect("Unable to solve linear equation."));
-        Ok(())
+    /// Train the linear regression model using weighted least squares.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::lin_reg::LinRegressor;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    /// use rusty_machine::learning::SupModel;
+    ///
+    /// let mut lin_mod = LinRegressor::default();
+    /// let inputs = Matrix::new(3,1, vec![2.0, 3.0, 4.0]);
+    /// let targets = Vector::new(vec![5.0, 6.0, 9.0]);
+    ///
+    /// // The last sample counts as much as the others combined.
+    /// let weights = Vector::new(vec![1.0, 1.0, 2.0]);
+    ///
+    /// lin_mod.train_weighted(&inputs, &targets, &weights).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The number of weights differs from the number of samples.
+    /// - Some weight is negative or non-finite, or all weights are zero.
+    /// - Some input or target is NaN or infinite.
+    /// - The weighted normal equations are singular.
+    fn train_weighted(&mut self,
+                      inputs: &Matrix<f64>,
+                      targets: &Vector<f64>,
+                      weights: &Vector<f64>)
+                      -> LearningResult<()> {
+        validate_sample_weights(weights, inputs.rows())?;
+        self.fit(inputs, targets, Some(weights))
     }
 
     /// Predict output value from input data.
@@ -149,14 +515,34 @@ impl LinRegressor {
     /// let new_point = Matrix::new(1,1,vec![10.]);
     /// let _ = lin_mod.predict(&new_point).unwrap();
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// - Standardization is enabled and the inputs cannot be standardized.
     pub fn train_with_optimization(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) {
+        let standardizer = if self.standardize {
+            Some(LinRegressor::standardize_inputs(inputs).expect("Unable to standardize inputs."))
+        } else {
+            None
+        };
+
         let ones = Matrix::<f64>::ones(inputs.rows(), 1);
-        let full_inputs = ones.hcat(inputs);
+        let full_inputs = match standardizer {
+            Some((_, ref standardized)) => ones.hcat(standardized),
+            None => ones.hcat(inputs),
+        };
 
         let initial_params = vec![0.; full_inputs.cols()];
 
         let gd = GradientDesc::default();
-        let optimal_w = gd.optimize(self, &initial_params[..], &full_inputs, targets);
-        self.parameters = Some(Vector::new(optimal_w));
+        let optimal_w = Vector::new(gd.optimize(self, &initial_params[..], &full_inputs, targets));
+
+        self.parameters = match standardizer {
+            Some((ref standardizer, _)) => {
+                Some(LinRegressor::unstandardize_params(standardizer, optimal_w))
+            }
+            None => Some(optimal_w),
+        };
+        self.normal_factor = None;
     }
 }
@@ -32,26 +32,57 @@
 //!
 //! We could have been more specific about the learning of the model
 //! by using the `new` constructor instead. This allows us to provide
-//! a `GradientDesc` object with custom parameters.
+//! a `GradientDesc` object with custom parameters, or any other
+//! optimization algorithm such as `StochasticGD`.
+//!
+//! For data which does not fit in memory the model can be trained
+//! in chunks using `partial_fit`.
+//!
+//! ```
+//! use rusty_machine::learning::logistic_reg::LogisticRegressor;
+//! use rusty_machine::learning::optim::grad_desc::StochasticGD;
+//! use rusty_machine::linalg::Matrix;
+//! use rusty_machine::linalg::Vector;
+//!
+//! let sgd = StochasticGD::new(0.5, 0.3, 10).with_batch_size(2);
+//! let mut log_mod = LogisticRegressor::new(sgd);
+//!
+//! // Each call continues from the current parameters.
+//! log_mod.partial_fit(&Matrix::new(2,1,vec![1.0,7.0]), &Vector::new(vec![0.,1.])).unwrap();
+//! log_mod.partial_fit(&Matrix::new(2,1,vec![3.0,5.0]), &Vector::new(vec![0.,1.])).unwrap();
+//! ```
+//!
+//! The model can also be trained on, and predict, the class labels
+//! `0` and `1` directly using the `LabelClassifier` trait.
 
 use linalg::{Matrix, BaseMatrix};
 use linalg::Vector;
-use learning::{LearningResult, SupModel};
+use learning::{LearningResult, SupModel, LabelClassifier, ProbabilisticClassifier, validate_finite_matrix,
+               validate_finite_vector, validate_optimized, validate_sample_weights,
+               validate_labels, binary_label_targets};
+use learning::toolkit::numeric::xlogy;
 use learning::toolkit::activ_fn::{ActivationFunc, Sigmoid};
 use learning::toolkit::cost_fn::{CostFunc, CrossEntropyError};
 use learning::optim::grad_desc::GradientDesc;
 use learning::optim::{OptimAlgorithm, Optimizable};
-use learning::error::Error;
+use learning::error::{Error, ErrorKind};
+
+/// The default largest absolute gradient entry at which
+/// the optimization is reported to have converged.
+const DEFAULT_CONVERGENCE_CHECK_TOL: f64 = 1e-4;
 
 /// Logistic Regression Model.
 ///
 /// Contains option for optimized parameter.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct LogisticRegressor<A>
     where A: OptimAlgorithm<BaseLogisticRegressor>
 {
     base: BaseLogisticRegressor,
     alg: A,
+    convergence_check_tol: f64,
+    n_iter: usize,
+    converged: bool,
 }
 
 /// Constructs a default Logistic Regression model
@@ -61,6 +92,9 @@ impl Default for LogisticRegressor<GradientDesc> {
         LogisticRegressor {
             base: BaseLogisticRegressor::new(),
             alg: GradientDesc::default(),
+            convergence_check_tol: DEFAULT_CONVERGENCE_CHECK_TOL,
+            n_iter: 0,
+            converged: false,
         }
     }
 }
@@ -81,6 +115,9 @@ impl<A: OptimAlgorithm<BaseLogisticRegressor>> LogisticRegressor<A> {
         LogisticRegressor {
             base: BaseLogisticRegressor::new(),
             alg: alg,
+            convergence_check_tol: DEFAULT_CONVERGENCE_CHECK_TOL,
+            n_iter: 0,
+            converged: false,
         }
     }
 
@@ -90,6 +127,113 @@ impl<A: OptimAlgorithm<BaseLogisticRegressor>> LogisticRegressor<A> {
     pub fn parameters(&self) -> Option<&Vector<f64>> {
         self.base.parameters()
     }
+
+    /// Sets the gradient tolerance used to check for convergence after training.
+    ///
+    /// This is a diagnostic only. The optimization algorithm decides when
+    /// training stops, and the tolerance only changes the result of
+    /// `converged`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::logistic_reg::LogisticRegressor;
+    ///
+    /// let logistic_mod = LogisticRegressor::default().with_convergence_check_tol(1e-6);
+    /// assert_eq!(logistic_mod.convergence_check_tol(), 1e-6);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `tol` is not positive.
+    pub fn with_convergence_check_tol(mut self, tol: f64) -> Self {
+        assert!(tol > 0f64, "The gradient tolerance must be positive.");
+        self.convergence_check_tol = tol;
+        self
+    }
+
+    /// The gradient tolerance used to check for convergence after training.
+    ///
+    /// This is an absolute tolerance on each entry of the gradient of
+    /// the mean cross-entropy, so rescaling an input feature changes
+    /// whether a fit counts as converged. Defaults to `1e-4`.
+    pub fn convergence_check_tol(&self) -> f64 {
+        self.convergence_check_tol
+    }
+
+    /// Whether the last optimization converged.
+    ///
+    /// The optimization has converged if every entry of the gradient of
+    /// the cost at the fitted parameters is smaller than
+    /// `convergence_check_tol`. Training does not stop early when this
+    /// holds, so use the optimization algorithm's own iteration limit or
+    /// tolerance to shorten it. Returns `false` if the model has not been trained.
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    /// The number of iterations run by the last optimization.
+    ///
+    /// This counts the calls the algorithm makes to the callback of
+    /// `OptimAlgorithm::optimize_with_callback`, which depends on the
+    /// algorithm:
+    ///
+    /// - `GradientDesc` counts gradient steps, each over the full data.
+    /// - `StochasticGD`, `AdaGrad`, `RMSProp`, `Adam` and `Nadam` count
+    ///   passes through the data, each of which takes several steps.
+    /// - `ConjugateGD` counts successful line searches.
+    /// - Algorithms which do not override the callback report one iteration.
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+
+    /// Runs the optimization algorithm and records its convergence.
+    fn optimize(&mut self, start: &[f64], inputs: &Matrix<f64>, targets: &Vector<f64>) -> Vec<f64> {
+        let mut epochs = 0;
+        let params = self.alg.optimize_with_callback(&self.base, start, inputs, targets, |_, _| {
+            epochs += 1;
+        });
+
+        let (_, grad) = self.base.compute_grad(&params, inputs, targets);
+        self.n_iter = epochs;
+        self.converged = grad.iter().all(|g| g.abs() < self.convergence_check_tol);
+        params
+    }
+
+    /// Update the model using a new chunk of data.
+    ///
+    /// The optimization starts from the current parameters, or from
+    /// the usual initial parameters if the model has not been trained.
+    ///
+    /// # Failures
+    ///
+    /// - The inputs have a different number of columns to previous data.
+    /// - Some input or target is NaN or infinite.
+    /// - The optimization diverged.
+    pub fn partial_fit(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_vector("target", targets)?;
+
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        let full_inputs = ones.hcat(inputs);
+
+        let start = match self.base.parameters() {
+            Some(params) => {
+                if params.size() != full_inputs.cols() {
+                    return Err(Error::new(ErrorKind::InvalidData,
+                                          "Input data has a different number of columns to \
+                                           previously seen data."));
+                }
+                params.data().clone()
+            }
+            None => vec![0.5; full_inputs.cols()],
+        };
+
+        let optimal_w = self.optimize(&start, &full_inputs, targets);
+        validate_optimized(&self.base, &optimal_w, &full_inputs, targets)?;
+        self.base.set_parameters(Vector::new(optimal_w));
+        Ok(())
+    }
 }
 
 impl<A> SupModel<Matrix<f64>, Vector<f64>> for LogisticRegressor<A>
@@ -113,13 +257,80 @@ impl<A> SupModel<Matrix<f64>, Vector<f64>> for LogisticRegressor<A>
     ///
     /// logistic_mod.train(&inputs, &targets).unwrap();
     /// ```
+    ///
+    /// # Failures
+    ///
+    /// - Some input or target is NaN or infinite.
+    /// - The optimization diverged.
     fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_vector("target", targets)?;
+
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        let full_inputs = ones.hcat(inputs);
+
+        let initial_params = vec![0.5; full_inputs.cols()];
+
+        let optimal_w = self.optimize(&initial_params, &full_inputs, targets);
+        validate_optimized(&self.base, &optimal_w, &full_inputs, targets)?;
+        self.base.set_parameters(Vector::new(optimal_w));
+        Ok(())
+    }
+
+    /// Train the logistic regression model, weighting
+    /// the cross-entropy of each sample.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::logistic_reg::LogisticRegressor;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    /// use rusty_machine::learning::SupModel;
+    ///
+    /// let mut logistic_mod = LogisticRegressor::default();
+    /// let inputs = Matrix::new(4,1, vec![1.0, 2.0, 3.0, 4.0]);
+    /// let targets = Vector::new(vec![0.0, 1.0, 0.0, 1.0]);
+    /// let weights = Vector::new(vec![1.0, 0.5, 0.5, 1.0]);
+    ///
+    /// logistic_mod.train_weighted(&inputs, &targets, &weights).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - Some input or target is NaN or infinite.
+    /// - The number of weights differs from the number of samples.
+    /// - Some weight is negative or non-finite, or all weights are zero.
+    /// - The optimization diverged.
+    fn train_weighted(&mut self,
+                      inputs: &Matrix<f64>,
+                      targets: &Vector<f64>,
+                      weights: &Vector<f64>)
+                      -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_vector("target", targets)?;
+        validate_sample_weights(weights, inputs.rows())?;
+
         let ones = Matrix::<f64>::ones(inputs.rows(), 1);
         let full_inputs = ones.hcat(inputs);
 
         let initial_params = vec![0.5; full_inputs.cols()];
 
-        let optimal_w = self.alg.optimize(&self.base, &initial_params[..], &full_inputs, targets);
+        // The optimizers only pass the inputs and targets to the model, so the
+        // weights are appended as the last input column. This keeps each weight
+        // with its sample when stochastic optimizers select mini-batches.
+        //
+        // The weights are scaled to average one over the whole dataset, so a
+        // mini-batch whose weights are all zero has zero gradient.
+        let scale = inputs.rows() as f64 / weights.sum();
+        let weight_col = Matrix::new(inputs.rows(), 1, (weights * scale).into_vec());
+        let weighted_inputs = full_inputs.hcat(&weight_col);
+
+        self.base.weighted = true;
+        let optimal_w = self.optimize(&initial_params, &weighted_inputs, targets);
+        let diverged = validate_optimized(&self.base, &optimal_w, &weighted_inputs, targets);
+        self.base.weighted = false;
+        diverged?;
+
         self.base.set_parameters(Vector::new(optimal_w));
         Ok(())
     }
@@ -138,19 +349,52 @@ impl<A> SupModel<Matrix<f64>, Vector<f64>> for LogisticRegressor<A>
     }
 }
 
+/// Train and predict from the logistic regression model using
+/// the labels `0` and `1`.
+///
+/// A label of `1` is predicted when its probability is at least `0.5`.
+impl<A> LabelClassifier for LogisticRegressor<A>
+    where A: OptimAlgorithm<BaseLogisticRegressor>
+{
+    fn train_labels(&mut self, inputs: &Matrix<f64>, labels: &[usize]) -> LearningResult<()> {
+        validate_labels(labels, inputs.rows())?;
+        self.train(inputs, &binary_label_targets(labels, 0f64, 1f64)?)
+    }
+
+    fn predict_labels(&self, inputs: &Matrix<f64>) -> LearningResult<Vec<usize>> {
+        Ok(self.predict(inputs)?.iter().map(|&p| if p >= 0.5 { 1 } else { 0 }).collect())
+    }
+}
+
+/// The probabilities of the labels `0` and `1`, in that order.
+impl<A> ProbabilisticClassifier for LogisticRegressor<A>
+    where A: OptimAlgorithm<BaseLogisticRegressor>
+{
+    fn predict_proba(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        let probs = self.predict(inputs)?;
+        let data = probs.iter().flat_map(|&p| vec![1f64 - p, p]).collect::<Vec<f64>>();
+        Ok(Matrix::new(probs.size(), 2, data))
+    }
+}
+
 /// The Base Logistic Regression model.
 ///
 /// This struct cannot be instantianated and is used internally only.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct BaseLogisticRegressor {
     parameters: Option<Vector<f64>>,
+    /// Whether the last input column holds sample weights.
+    weighted: bool,
 }
 
 impl BaseLogisticRegressor {
     /// Construct a new BaseLogisticRegressor
     /// with parameters set to None.
     fn new() -> BaseLogisticRegressor {
-        BaseLogisticRegressor { parameters: None }
+        BaseLogisticRegressor {
+            parameters: None,
+            weighted: false,
+        }
     }
 }
 
@@ -174,6 +418,13 @@ impl BaseLogisticRegressor {
 /// X<sup>T</sup>(h(Xb) - y) / m
 ///
 /// where `h` is the sigmoid function and `b` the underlying model parameters.
+///
+/// When training with sample weights `w` the gradient is
+///
+/// X<sup>T</sup>(w * (h(Xb) - y)) / m
+///
+/// where the weights are scaled to average one over the whole
+/// training set.
 impl Optimizable for BaseLogisticRegressor {
     type Inputs = Matrix<f64>;
     type Targets = Vector<f64>;
@@ -184,6 +435,25 @@ impl Optimizable for BaseLogisticRegressor {
                     targets: &Vector<f64>)
                     -> (f64, Vec<f64>) {
 
+        if self.weighted {
+            let n_features = inputs.cols() - 1;
+            let weights = Vector::new(inputs.col(n_features).iter().cloned().collect::<Vec<f64>>());
+            let features = inputs.select_cols(&(0..n_features).collect::<Vec<usize>>());
+            let n_samples = inputs.rows() as f64;
+
+            let beta_vec = Vector::new(params.to_vec());
+            let outputs = (&features * beta_vec).apply(&Sigmoid::func);
+
+            let sample_costs = Vector::new(outputs.iter()
+                .zip(targets.iter())
+                .map(|(&o, &t)| xlogy(t, o) + xlogy(1f64 - t, 1f64 - o))
+                .collect::<Vec<f64>>());
+            let cost = -weights.dot(&sample_costs) / n_samples;
+            let grad = (features.transpose() * (outputs - targets).elemul(&weights)) / n_samples;
+
+            return (cost, grad.into_vec());
+        }
+
         let beta_vec = Vector::new(params.to_vec());
         let outputs = (inputs * beta_vec).apply(&Sigmoid::func);
 
@@ -0,0 +1,38 @@
+//! Model Information Module
+//!
+//! Contains `ModelInfo`, which summarizes the size of a model so
+//! that the memory use and prediction cost of models can be compared.
+//!
+//! Models which support it provide a `model_info` method.
+//! Quantities which do not apply to a model are `None`.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::nnet::NeuralNet;
+//!
+//! let net = NeuralNet::default(&[3, 5, 2]);
+//! let info = net.model_info();
+//!
+//! // Each linear layer has a bias term.
+//! assert_eq!(info.n_params, 4 * 5 + 6 * 2);
+//! assert_eq!(info.n_inputs, Some(3));
+//! assert_eq!(info.n_outputs, Some(2));
+//! assert_eq!(info.n_nodes, None);
+//! ```
+
+/// The size of a model.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModelInfo {
+    /// The number of learned parameters.
+    pub n_params: usize,
+    /// The number of input features, if known.
+    pub n_inputs: Option<usize>,
+    /// The number of outputs for each sample, if known.
+    pub n_outputs: Option<usize>,
+    /// The number of training samples stored by the model
+    /// and used for each prediction, for kernel models.
+    pub n_stored_samples: Option<usize>,
+    /// The total number of nodes, for tree models.
+    pub n_nodes: Option<usize>,
+}
@@ -0,0 +1,287 @@
+//! Multi-label Classification Module
+//!
+//! Contains the `BinaryRelevance` meta-classifier for problems
+//! where each sample can have any number of labels, such as tags.
+//!
+//! Binary relevance trains one binary classifier for each label,
+//! independently of the other labels. The targets are a matrix with
+//! one row per sample and one column per label, where `1` means the
+//! sample has the label and `0` that it does not.
+//!
+//! Any model which is trained on `0`/`1` targets and outputs a score
+//! which is at least `0.5` for the positive class can be used for the
+//! labels. The default is the `LogisticRegressor`, whose outputs are
+//! the probabilities of each label.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::multilabel::BinaryRelevance;
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::Matrix;
+//!
+//! let inputs = Matrix::new(4,2,vec![-2.0,-2.0, -2.0,2.0, 2.0,-2.0, 2.0,2.0]);
+//!
+//! // The first label is set when the first feature is positive,
+//! // the second when the second feature is positive.
+//! let targets = Matrix::new(4,2,vec![0.,0., 0.,1., 1.,0., 1.,1.]);
+//!
+//! let mut model = BinaryRelevance::default();
+//! model.train(&inputs, &targets).unwrap();
+//!
+//! let labels = model.predict(&Matrix::new(1,2,vec![3.0,-3.0])).unwrap();
+//! assert_eq!(labels.data(), &vec![1.0, 0.0]);
+//! ```
+
+use linalg::{Matrix, BaseMatrix, BaseMatrixMut, Vector};
+use learning::{LearningResult, SupModel};
+use learning::logistic_reg::LogisticRegressor;
+use learning::optim::grad_desc::GradientDesc;
+use learning::error::{Error, ErrorKind};
+
+/// Binary Relevance Multi-label Classifier
+#[derive(Debug)]
+pub struct BinaryRelevance<M = LogisticRegressor<GradientDesc>> {
+    /// The untrained classifier cloned for each label.
+    learner: M,
+    /// The trained classifier of each label.
+    classifiers: Vec<M>,
+}
+
+/// Constructs a binary relevance classifier using
+/// logistic regression for each label.
+impl Default for BinaryRelevance<LogisticRegressor<GradientDesc>> {
+    fn default() -> BinaryRelevance<LogisticRegressor<GradientDesc>> {
+        BinaryRelevance::new(LogisticRegressor::default())
+    }
+}
+
+impl<M> BinaryRelevance<M>
+    where M: SupModel<Matrix<f64>, Vector<f64>> + Clone
+{
+    /// Constructs an untrained binary relevance classifier using
+    /// copies of the given classifier for each label.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::multilabel::BinaryRelevance;
+    /// use rusty_machine::learning::logistic_reg::LogisticRegressor;
+    /// use rusty_machine::learning::optim::grad_desc::GradientDesc;
+    ///
+    /// let model = BinaryRelevance::new(LogisticRegressor::new(GradientDesc::new(0.5, 500)));
+    /// ```
+    pub fn new(learner: M) -> BinaryRelevance<M> {
+        BinaryRelevance {
+            learner,
+            classifiers: Vec::new(),
+        }
+    }
+
+    /// The trained classifier of each label.
+    pub fn classifiers(&self) -> &[M] {
+        &self.classifiers
+    }
+
+    /// The number of labels seen in training.
+    ///
+    /// Returns zero if the model has not been trained.
+    pub fn n_labels(&self) -> usize {
+        self.classifiers.len()
+    }
+
+    /// Computes the output of each label's classifier.
+    ///
+    /// The outputs have one row per sample and one column per label.
+    /// For the `LogisticRegressor` these are the label probabilities.
+    pub fn predict_proba(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        if self.classifiers.is_empty() {
+            return Err(Error::new_untrained());
+        }
+
+        let n_labels = self.classifiers.len();
+        let mut data = vec![0f64; inputs.rows() * n_labels];
+        for (j, classifier) in self.classifiers.iter().enumerate() {
+            let outputs = classifier.predict(inputs)?;
+            for (i, &p) in outputs.iter().enumerate() {
+                data[i * n_labels + j] = p;
+            }
+        }
+
+        Ok(Matrix::new(inputs.rows(), n_labels, data))
+    }
+
+    /// Checks the targets and trains one classifier for each label.
+    fn train_labels<F>(&mut self,
+                       inputs: &Matrix<f64>,
+                       targets: &Matrix<f64>,
+                       mut train: F)
+                       -> LearningResult<()>
+        where F: FnMut(&mut M, &Vector<f64>) -> LearningResult<()>
+    {
+        if targets.rows() != inputs.rows() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "There must be one row of targets per sample."));
+        }
+        if targets.cols() == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "The targets have no labels."));
+        }
+        if targets.iter().any(|&t| t != 0f64 && t != 1f64) {
+            return Err(Error::new(ErrorKind::InvalidData, "Targets must be either 0 or 1."));
+        }
+
+        let mut classifiers = Vec::with_capacity(targets.cols());
+        for j in 0..targets.cols() {
+            let label_targets = Vector::new(targets.col(j).iter().cloned().collect::<Vec<f64>>());
+            let mut classifier = self.learner.clone();
+            train(&mut classifier, &label_targets)?;
+            classifiers.push(classifier);
+        }
+
+        self.classifiers = classifiers;
+        Ok(())
+    }
+}
+
+impl<M> SupModel<Matrix<f64>, Matrix<f64>> for BinaryRelevance<M>
+    where M: SupModel<Matrix<f64>, Vector<f64>> + Clone
+{
+    /// Predict whether each sample has each label.
+    ///
+    /// A label is predicted, as `1`, when the output of its classifier
+    /// is at least `0.5`, and is otherwise `0`.
+    ///
+    /// Model must be trained before prediction can be made.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        Ok(self.predict_proba(inputs)?.apply(&|p| if p >= 0.5 { 1f64 } else { 0f64 }))
+    }
+
+    /// Train one classifier for each column of the targets.
+    ///
+    /// # Failures
+    ///
+    /// - The targets have a different number of rows to the inputs.
+    /// - The targets have no columns.
+    /// - Some target is not `0` or `1`.
+    /// - A classifier fails to train.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Matrix<f64>) -> LearningResult<()> {
+        self.train_labels(inputs, targets, |model, t| model.train(inputs, t))
+    }
+
+    /// Train one classifier for each column of the targets,
+    /// using the same sample weights for every label.
+    ///
+    /// # Failures
+    ///
+    /// - The targets have a different number of rows to the inputs.
+    /// - The targets have no columns.
+    /// - Some target is not `0` or `1`.
+    /// - A classifier fails to train, for example because it
+    ///   does not support sample weights.
+    fn train_weighted(&mut self,
+                      inputs: &Matrix<f64>,
+                      targets: &Matrix<f64>,
+                      weights: &Vector<f64>)
+                      -> LearningResult<()> {
+        self.train_labels(inputs, targets, |model, t| model.train_weighted(inputs, t, weights))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BinaryRelevance;
+    use analysis::score::{neg_hamming_loss, subset_accuracy};
+    use learning::SupModel;
+    use learning::logistic_reg::LogisticRegressor;
+    use learning::optim::grad_desc::GradientDesc;
+    use linalg::{Matrix, BaseMatrix, Vector};
+
+    /// Samples on a grid where the first label is set when
+    /// `x0 > 0.3` and the second label when `x1 < -0.2`.
+    fn two_label_data(offset: f64) -> (Matrix<f64>, Matrix<f64>) {
+        let mut inputs = Vec::new();
+        let mut targets = Vec::new();
+        for i in 0..12 {
+            for j in 0..12 {
+                let x0 = (i as f64 + offset) / 3.0 - 2.0;
+                let x1 = (j as f64 + offset) / 3.0 - 2.0;
+                inputs.push(x0);
+                inputs.push(x1);
+                targets.push(if x0 > 0.3 { 1.0 } else { 0.0 });
+                targets.push(if x1 < -0.2 { 1.0 } else { 0.0 });
+            }
+        }
+
+        (Matrix::new(144, 2, inputs), Matrix::new(144, 2, targets))
+    }
+
+    fn model() -> BinaryRelevance {
+        BinaryRelevance::new(LogisticRegressor::new(GradientDesc::new(1.0, 3000)))
+    }
+
+    #[test]
+    fn test_two_labels_predicted_independently() {
+        let (train_inputs, train_targets) = two_label_data(0.0);
+        let (test_inputs, test_targets) = two_label_data(0.25);
+
+        let mut model = model();
+        model.train(&train_inputs, &train_targets).unwrap();
+        assert_eq!(model.n_labels(), 2);
+
+        // Each classifier only depends on the feature of its label.
+        for (k, classifier) in model.classifiers().iter().enumerate() {
+            let params = classifier.parameters().unwrap();
+            assert!(params[2 - k].abs() < 0.1 * params[k + 1].abs(), "{:?}", params);
+        }
+
+        let outputs = model.predict(&test_inputs).unwrap();
+        assert!(subset_accuracy(&outputs, &test_targets) > 0.95);
+        assert!(neg_hamming_loss(&outputs, &test_targets) > -0.03);
+    }
+
+    #[test]
+    fn test_probabilities_match_labels() {
+        let (inputs, targets) = two_label_data(0.0);
+
+        let mut model = model();
+        model.train(&inputs, &targets).unwrap();
+
+        let probs = model.predict_proba(&inputs).unwrap();
+        let labels = model.predict(&inputs).unwrap();
+        assert_eq!(probs.cols(), 2);
+        for (p, l) in probs.iter().zip(labels.iter()) {
+            assert!(*p >= 0.0 && *p <= 1.0);
+            assert_eq!(*l, if *p >= 0.5 { 1.0 } else { 0.0 });
+        }
+    }
+
+    #[test]
+    fn test_weighted_matches_unweighted() {
+        let (inputs, targets) = two_label_data(0.0);
+
+        let mut unweighted = model();
+        unweighted.train(&inputs, &targets).unwrap();
+
+        let mut weighted = model();
+        weighted.train_weighted(&inputs, &targets, &Vector::ones(inputs.rows())).unwrap();
+
+        let diff = weighted.predict_proba(&inputs).unwrap() - unweighted.predict_proba(&inputs).unwrap();
+        assert!(diff.iter().all(|d| d.abs() < 1e-10));
+    }
+
+    #[test]
+    fn test_invalid_targets() {
+        let inputs = Matrix::new(2, 1, vec![1.0, 2.0]);
+
+        let mut model = BinaryRelevance::default();
+        assert!(model.train(&inputs, &Matrix::new(2, 1, vec![0.0, 2.0])).is_err());
+        assert!(model.train(&inputs, &Matrix::new(1, 2, vec![0.0, 1.0])).is_err());
+    }
+
+    #[test]
+    fn test_untrained() {
+        let model = BinaryRelevance::default();
+
+        assert!(model.predict(&Matrix::new(1, 1, vec![1.0])).is_err());
+    }
+}
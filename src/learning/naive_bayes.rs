@@ -1,6 +1,6 @@
 //! Naive Bayes Classifiers
 //!
-//! The classifier supports Gaussian, Bernoulli and Multinomial distributions.
+//! The classifier supports Gaussian, Bernoulli, Multinomial and Complement distributions.
 //!
 //! A naive Bayes classifier works by treating the features of each input as independent
 //! observations. Under this assumption we utilize Bayes' rule to compute the
@@ -40,9 +40,11 @@
 //! println!("Final outputs --\n{}", outputs);
 //! ```
 
-use linalg::{Matrix, Axes, BaseMatrix, BaseMatrixMut};
-use learning::{LearningResult, SupModel};
+use linalg::{Matrix, Vector, Axes, BaseMatrix, BaseMatrixMut};
+use learning::{LearningResult, SupModel, LabelClassifier, ProbabilisticClassifier, validate_finite_matrix,
+               validate_labels};
 use learning::error::{Error, ErrorKind};
+use learning::toolkit::numeric::row_log_sum_exp;
 use rulinalg::utils;
 
 use std::f64::consts::PI;
@@ -53,7 +55,9 @@ pub struct NaiveBayes<T: Distribution> {
     distr: Option<T>,
     cluster_count: Option<usize>,
     class_prior: Option<Vec<f64>>,
+    fixed_prior: Option<Vec<f64>>,
     class_counts: Vec<usize>,
+    var_smoothing: f64,
 }
 
 impl<T: Distribution> NaiveBayes<T> {
@@ -73,10 +77,81 @@ impl<T: Distribution> NaiveBayes<T> {
             distr: None,
             cluster_count: None,
             class_prior: None,
+            fixed_prior: None,
             class_counts: Vec::new(),
+            var_smoothing: 1e-9,
         }
     }
 
+    /// Set the fraction of the largest feature variance which is
+    /// added to the variance of every class and feature.
+    ///
+    /// This keeps the likelihood finite when a feature is constant
+    /// within a class. The default is `1e-9`. Only distributions with
+    /// variances, currently the `Gaussian`, are affected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::naive_bayes::{NaiveBayes, Gaussian};
+    ///
+    /// let model = NaiveBayes::<Gaussian>::new().with_var_smoothing(1e-6);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `var_smoothing` is negative.
+    pub fn with_var_smoothing(mut self, var_smoothing: f64) -> NaiveBayes<T> {
+        assert!(var_smoothing >= 0f64, "The variance smoothing must be non-negative.");
+        self.var_smoothing = var_smoothing;
+        self
+    }
+
+    /// Get the fraction of the largest feature variance
+    /// added to the class variances.
+    pub fn var_smoothing(&self) -> f64 {
+        self.var_smoothing
+    }
+
+    /// Create a new NaiveBayes model with fixed class priors.
+    ///
+    /// The given priors are used in place of the class proportions
+    /// in the training data. This is useful when the training data
+    /// is not representative, e.g. when it was deliberately balanced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::naive_bayes::{NaiveBayes, Gaussian};
+    /// use rusty_machine::linalg::Vector;
+    ///
+    /// // Create a new Gaussian Naive Bayes model for two classes,
+    /// // where the first class is known to be more common.
+    /// let model = NaiveBayes::<Gaussian>::with_priors(Vector::new(vec![0.8, 0.2])).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The priors are negative or do not sum to one.
+    ///
+    /// Training will also fail if the number of priors does not
+    /// match the number of classes in the targets.
+    pub fn with_priors(priors: Vector<f64>) -> LearningResult<NaiveBayes<T>> {
+        if priors.iter().any(|&p| p < 0f64) {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "Class priors must be non-negative."));
+        }
+
+        if (priors.sum() - 1f64).abs() > 1e-8 {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "Class priors must sum to one."));
+        }
+
+        let mut model = NaiveBayes::new();
+        model.fixed_prior = Some(priors.into_vec());
+        Ok(model)
+    }
+
     /// Get the cluster count for this model.
     ///
     /// Returns an option which is `None` until the model has been trained.
@@ -107,6 +182,9 @@ impl<T: Distribution> NaiveBayes<T> {
 impl<T: Distribution> SupModel<Matrix<f64>, Matrix<f64>> for NaiveBayes<T> {
     /// Train the model using inputs and targets.
     fn train(&mut self, inputs: &Matrix<f64>, targets: &Matrix<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_matrix("target", targets)?;
+
         self.distr = Some(T::from_model_params(targets.cols(), inputs.cols()));
         self.update_params(inputs, targets)
     }
@@ -133,6 +211,34 @@ impl<T: Distribution> SupModel<Matrix<f64>, Matrix<f64>> for NaiveBayes<T> {
     }
 }
 
+/// Train and predict from the Naive Bayes model using class labels.
+///
+/// The number of classes is one more than the largest label.
+impl<T: Distribution> LabelClassifier for NaiveBayes<T> {
+    fn train_labels(&mut self, inputs: &Matrix<f64>, labels: &[usize]) -> LearningResult<()> {
+        validate_labels(labels, inputs.rows())?;
+
+        let class_count = labels.iter().max().map_or(0, |&l| l + 1);
+        let mut targets = vec![0f64; labels.len() * class_count];
+        for (i, &l) in labels.iter().enumerate() {
+            targets[i * class_count + l] = 1f64;
+        }
+
+        self.train(inputs, &Matrix::new(labels.len(), class_count, targets))
+    }
+
+    fn predict_labels(&self, inputs: &Matrix<f64>) -> LearningResult<Vec<usize>> {
+        Ok(NaiveBayes::<T>::get_classes(self.get_log_probs(inputs)?))
+    }
+}
+
+/// The class probabilities given by `NaiveBayes::predict_proba`.
+impl<T: Distribution> ProbabilisticClassifier for NaiveBayes<T> {
+    fn predict_proba(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        NaiveBayes::<T>::predict_proba(self, inputs)
+    }
+}
+
 impl<T: Distribution> NaiveBayes<T> {
     /// Get the log-probabilities per class for each input.
     pub fn get_log_probs(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
@@ -145,10 +251,52 @@ impl<T: Distribution> NaiveBayes<T> {
         }
     }
 
+    /// Get the probability of each class for each input.
+    ///
+    /// Each row of the output sums to one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::naive_bayes::{NaiveBayes, Gaussian};
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{BaseMatrix, Matrix};
+    ///
+    /// let inputs = Matrix::new(4, 1, vec![1.0, 1.2, 3.0, 3.2]);
+    /// let targets = Matrix::new(4, 2, vec![1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0]);
+    ///
+    /// let mut model = NaiveBayes::<Gaussian>::new();
+    /// model.train(&inputs, &targets).unwrap();
+    ///
+    /// let probs = model.predict_proba(&Matrix::new(1, 1, vec![1.1])).unwrap();
+    /// assert!(probs[[0, 0]] > 0.9);
+    /// assert!((probs.sum() - 1.0).abs() < 1e-12);
+    /// ```
+    pub fn predict_proba(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        let mut log_probs = self.get_log_probs(inputs)?;
+        let log_norms = row_log_sum_exp(&log_probs);
+
+        for (mut row, norm) in log_probs.row_iter_mut().zip(log_norms.iter()) {
+            for x in row.iter_mut() {
+                *x = (*x - norm).exp();
+            }
+        }
+
+        Ok(log_probs)
+    }
+
     fn update_params(&mut self, inputs: &Matrix<f64>, targets: &Matrix<f64>) -> LearningResult<()> {
         let class_count = targets.cols();
         let total_data = inputs.rows();
 
+        if let Some(ref prior) = self.fixed_prior {
+            if prior.len() != class_count {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Number of class priors does not match the number of \
+                                       classes in the targets."));
+            }
+        }
+
         self.class_counts = vec![0; class_count];
         let mut class_data = vec![Vec::new(); class_count];
 
@@ -172,12 +320,23 @@ impl<T: Distribution> NaiveBayes<T> {
                 // Update the parameters within this class
                 try!(distr.update_params(&inputs.select_rows(&c), idx));
             }
-        }
 
-        let mut class_prior = Vec::with_capacity(class_count);
+            if self.var_smoothing > 0f64 && total_data > 1 {
+                let variances = inputs.variance(Axes::Row).map_err(|_| {
+                    Error::new(ErrorKind::InvalidData, "Cannot compute variance of inputs.")
+                })?;
+                let max_var = variances.iter().cloned().fold(0f64, f64::max);
+                distr.smooth_variances(self.var_smoothing * max_var);
+            }
+        }
 
-        // Compute the prior as the proportion in each class
-        class_prior.extend(self.class_counts.iter().map(|c| *c as f64 / total_data as f64));
+        let class_prior = match self.fixed_prior {
+            Some(ref prior) => prior.clone(),
+            None => {
+                // Compute the prior as the proportion in each class
+                self.class_counts.iter().map(|c| *c as f64 / total_data as f64).collect()
+            }
+        };
 
         self.class_prior = Some(class_prior);
         self.cluster_count = Some(class_count);
@@ -224,6 +383,13 @@ pub trait Distribution {
                      data: &Matrix<f64>,
                      class_prior: &[f64])
                      -> LearningResult<Matrix<f64>>;
+
+    /// Adds `epsilon` to the variance of every class and feature.
+    ///
+    /// This is called after the parameters of every class are updated.
+    /// The default implementation does nothing, for distributions
+    /// without variances.
+    fn smooth_variances(&mut self, _epsilon: f64) {}
 }
 
 /// The Gaussian Naive Bayes model distribution.
@@ -305,6 +471,12 @@ impl Distribution for Gaussian {
 
         Ok(Matrix::new(class_count, data.rows(), log_lik).transpose())
     }
+
+    fn smooth_variances(&mut self, epsilon: f64) {
+        for v in self.sigma.mut_data() {
+            *v += epsilon;
+        }
+    }
 }
 
 /// The Bernoulli Naive Bayes model distribution.
@@ -443,16 +615,102 @@ impl Distribution for Multinomial {
     }
 }
 
+/// The Complement Naive Bayes model distribution.
+///
+/// A variant of the Multinomial distribution for imbalanced data,
+/// described by [Rennie et al. (2003)](http://people.csail.mit.edu/jrennie/papers/icml03-nb.pdf).
+/// The weights of each class are estimated from the feature counts of
+/// all other classes:
+///
+///    w<sub>ki</sub> = log p<sub>~k,i</sub>
+///
+/// and an input is assigned to the class whose complement fits it worst,
+/// i.e. the class minimizing ∑<sub>i</sub> x<sub>i</sub> w<sub>ki</sub>.
+///
+/// As the class priors are the main source of bias on imbalanced
+/// data they are not used by this distribution.
+#[derive(Debug)]
+pub struct Complement {
+    feature_counts: Matrix<f64>,
+    pseudo_count: f64,
+}
+
+impl Complement {
+    /// The feature count matrix.
+    ///
+    /// A matrix of class by feature totals in the training data.
+    pub fn feature_counts(&self) -> &Matrix<f64> {
+        &self.feature_counts
+    }
+
+    /// The complement log probability matrix.
+    ///
+    /// A matrix of class by feature log-probabilities, estimated
+    /// from all classes except the class of each row.
+    pub fn complement_log_probs(&self) -> Matrix<f64> {
+        let totals = self.feature_counts.sum_rows();
+        let features = self.feature_counts.cols();
+
+        let mut log_probs = Vec::with_capacity(self.feature_counts.rows() * features);
+        for row in self.feature_counts.row_iter() {
+            let pseudo_fc = row.iter()
+                .zip(totals.iter())
+                .map(|(c, t)| t - c + self.pseudo_count)
+                .collect::<Vec<f64>>();
+            let pseudo_cc = pseudo_fc.iter().sum::<f64>();
+
+            log_probs.extend(pseudo_fc.into_iter().map(|x| x.ln() - pseudo_cc.ln()));
+        }
+
+        Matrix::new(self.feature_counts.rows(), features, log_probs)
+    }
+}
+
+impl Distribution for Complement {
+    fn from_model_params(class_count: usize, features: usize) -> Complement {
+        Complement {
+            feature_counts: Matrix::zeros(class_count, features),
+            pseudo_count: 1f64,
+        }
+    }
+
+    fn update_params(&mut self, data: &Matrix<f64>, class: usize) -> LearningResult<()> {
+        let features = data.cols();
+
+        // The complement depends on every class, so we only
+        // store the counts here.
+        let counts = data.sum_rows().into_vec();
+
+        for (i, item) in counts.iter().enumerate().take(features) {
+            self.feature_counts[[class, i]] = *item;
+        }
+
+        Ok(())
+    }
+
+    fn joint_log_lik(&self,
+                     data: &Matrix<f64>,
+                     _class_prior: &[f64])
+                     -> LearningResult<Matrix<f64>> {
+        Ok(-(data * self.complement_log_probs().transpose()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::NaiveBayes;
     use super::Gaussian;
     use super::Bernoulli;
     use super::Multinomial;
+    use super::Complement;
+
+    use learning::{LabelClassifier, ProbabilisticClassifier, SupModel};
+    use analysis::score::f1;
 
-    use learning::SupModel;
+    use rand::{Rng, StdRng, SeedableRng};
 
-    use linalg::Matrix;
+    use linalg::{Matrix, BaseMatrix, Vector};
+    use rulinalg::utils;
 
     #[test]
     fn test_gaussian() {
@@ -472,6 +730,89 @@ mod tests {
         assert_eq!(outputs.into_vec(), targets.into_vec());
     }
 
+    #[test]
+    fn test_gaussian_labels() {
+        let inputs = Matrix::new(6,
+                                 2,
+                                 vec![1.0, 1.1, 1.1, 0.9, 2.2, 2.3, 2.5, 2.7, 5.2, 4.3, 6.2, 7.3]);
+        let labels = vec![0, 0, 1, 1, 2, 2];
+
+        let mut model = NaiveBayes::<Gaussian>::new();
+        model.train_labels(&inputs, &labels).unwrap();
+
+        assert_eq!(model.cluster_count(), Some(&3));
+        assert_eq!(model.predict_labels(&inputs).unwrap(), labels);
+
+        // Matches training on the one-hot targets.
+        let outputs = model.predict(&inputs).unwrap();
+        let mut one_hot_model = NaiveBayes::<Gaussian>::new();
+        one_hot_model.train(&inputs, &one_hot(&labels, 3)).unwrap();
+        assert_eq!(one_hot_model.predict(&inputs).unwrap(), outputs);
+    }
+
+    #[test]
+    fn test_labels_wrong_length() {
+        let inputs = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+
+        let mut model = NaiveBayes::<Gaussian>::new();
+        assert!(model.train_labels(&inputs, &[0, 1]).is_err());
+        assert!(model.predict_labels(&inputs).is_err());
+    }
+
+    /// Two classes where the second feature is constant within each class.
+    fn constant_feature_data() -> (Matrix<f64>, Matrix<f64>) {
+        let inputs = Matrix::new(6, 2, vec![1.0, 0.0,
+                                            1.2, 0.0,
+                                            0.9, 0.0,
+                                            3.1, 1.0,
+                                            2.8, 1.0,
+                                            3.0, 1.0]);
+        let targets = Matrix::new(6, 2, vec![1.0, 0.0, 1.0, 0.0, 1.0, 0.0,
+                                             0.0, 1.0, 0.0, 1.0, 0.0, 1.0]);
+        (inputs, targets)
+    }
+
+    #[test]
+    fn test_gaussian_var_smoothing() {
+        let (inputs, targets) = constant_feature_data();
+
+        let mut model = NaiveBayes::<Gaussian>::new();
+        model.train(&inputs, &targets).unwrap();
+
+        let sigma = model.distr().unwrap().sigma();
+        assert!(sigma.iter().all(|&v| v > 0f64));
+
+        let log_probs = model.get_log_probs(&inputs).unwrap();
+        assert!(log_probs.iter().all(|x| !x.is_nan()));
+        assert!(log_probs.row_iter().all(|row| row.iter().any(|x| x.is_finite())));
+
+        let outputs = model.predict(&inputs).unwrap();
+        assert_eq!(outputs.into_vec(), targets.into_vec());
+
+        // New points are classified by the informative feature as well.
+        let new_inputs = Matrix::new(2, 2, vec![1.1, 0.0, 2.9, 1.0]);
+        let outputs = model.predict(&new_inputs).unwrap();
+        assert_eq!(outputs.into_vec(), vec![1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_gaussian_no_var_smoothing() {
+        let (inputs, targets) = constant_feature_data();
+
+        let mut model = NaiveBayes::<Gaussian>::new().with_var_smoothing(0.0);
+        model.train(&inputs, &targets).unwrap();
+
+        // Without smoothing the zero variances break the likelihood.
+        let log_probs = model.get_log_probs(&inputs).unwrap();
+        assert!(log_probs.iter().any(|x| !x.is_finite()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_negative_var_smoothing() {
+        let _ = NaiveBayes::<Gaussian>::new().with_var_smoothing(-1.0);
+    }
+
     #[test]
     fn test_bernoulli() {
         let inputs = Matrix::new(4,
@@ -502,4 +843,161 @@ mod tests {
         let outputs = model.predict(&inputs).unwrap();
         assert_eq!(outputs.into_vec(), targets.into_vec());
     }
+
+    #[test]
+    fn test_with_priors_shifts_predictions() {
+        let inputs = Matrix::new(4, 1, vec![0.0, 1.0, 3.0, 4.0]);
+        let targets = Matrix::new(4, 2, vec![1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0]);
+
+        // A point just closer to the first class.
+        let test_inputs = Matrix::new(1, 1, vec![1.9]);
+
+        let mut model = NaiveBayes::<Gaussian>::new();
+        model.train(&inputs, &targets).unwrap();
+        let outputs = model.predict(&test_inputs).unwrap();
+        assert_eq!(outputs.into_vec(), vec![1.0, 0.0]);
+
+        // Raising the prior of the second class changes the prediction.
+        let mut model = NaiveBayes::<Gaussian>::with_priors(Vector::new(vec![0.1, 0.9])).unwrap();
+        model.train(&inputs, &targets).unwrap();
+        assert_eq!(model.class_prior(), Some(&vec![0.1, 0.9]));
+
+        let outputs = model.predict(&test_inputs).unwrap();
+        assert_eq!(outputs.into_vec(), vec![0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_predict_proba_normalized() {
+        let inputs = Matrix::new(6, 1, vec![0.0, 0.5, 3.0, 3.5, 6.0, 6.5]);
+        let targets = Matrix::new(6, 3, vec![1.0, 0.0, 0.0,
+                                             1.0, 0.0, 0.0,
+                                             0.0, 1.0, 0.0,
+                                             0.0, 1.0, 0.0,
+                                             0.0, 0.0, 1.0,
+                                             0.0, 0.0, 1.0]);
+
+        let mut model = NaiveBayes::<Gaussian>::new();
+        model.train(&inputs, &targets).unwrap();
+
+        let test_inputs = Matrix::new(4, 1, vec![0.2, 1.8, 3.2, 100.0]);
+        let probs = model.predict_proba(&test_inputs).unwrap();
+        let classes = model.predict(&test_inputs).unwrap();
+
+        for (probs, classes) in probs.row_iter().zip(classes.row_iter()) {
+            assert!((probs.sum() - 1.0).abs() < 1e-12);
+            assert!(probs.iter().all(|p| p.is_finite() && *p >= 0.0));
+            assert_eq!(utils::argmax(probs.raw_slice()).0, utils::argmax(classes.raw_slice()).0);
+        }
+    }
+
+    #[test]
+    fn test_predict_with_reject() {
+        let inputs = Matrix::new(6, 1, vec![0.0, 0.5, 3.0, 3.5, 6.0, 6.5]);
+        let targets = Matrix::new(6, 3, vec![1.0, 0.0, 0.0,
+                                             1.0, 0.0, 0.0,
+                                             0.0, 1.0, 0.0,
+                                             0.0, 1.0, 0.0,
+                                             0.0, 0.0, 1.0,
+                                             0.0, 0.0, 1.0]);
+
+        let mut model = NaiveBayes::<Gaussian>::new();
+        model.train(&inputs, &targets).unwrap();
+
+        // Points near a class mean are confident, points
+        // halfway between two classes are not.
+        let test_inputs = Matrix::new(5, 1, vec![0.25, 1.75, 3.25, 4.75, 6.25]);
+        let classes = model.predict_with_reject(&test_inputs, 0.9).unwrap();
+        assert_eq!(classes.into_vec(), vec![Some(0), None, Some(1), None, Some(2)]);
+
+        // Nothing is rejected without a threshold.
+        let classes = model.predict_with_reject(&test_inputs, 0.0).unwrap();
+        assert!(classes.iter().all(|c| c.is_some()));
+
+        assert!(model.predict_with_reject(&test_inputs, 1.5).is_err());
+        assert!(model.predict_with_reject(&test_inputs, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_with_priors_invalid() {
+        assert!(NaiveBayes::<Gaussian>::with_priors(Vector::new(vec![0.5, 0.6])).is_err());
+        assert!(NaiveBayes::<Gaussian>::with_priors(Vector::new(vec![1.5, -0.5])).is_err());
+    }
+
+    #[test]
+    fn test_with_priors_wrong_class_count() {
+        let inputs = Matrix::new(4, 1, vec![0.0, 1.0, 3.0, 4.0]);
+        let targets = Matrix::new(4, 2, vec![1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0]);
+
+        let mut model = NaiveBayes::<Gaussian>::with_priors(Vector::new(vec![0.2, 0.3, 0.5])).unwrap();
+        assert!(model.train(&inputs, &targets).is_err());
+    }
+
+    /// Samples bag-of-words counts for each class, with the
+    /// given number of documents per class.
+    fn sample_documents(word_probs: &[Vec<f64>],
+                        docs_per_class: &[usize],
+                        rng: &mut StdRng)
+                        -> (Matrix<f64>, Vec<usize>) {
+        let vocab = word_probs[0].len();
+        let mut counts = Vec::new();
+        let mut labels = Vec::new();
+
+        for (class, (probs, &n)) in word_probs.iter().zip(docs_per_class).enumerate() {
+            for _ in 0..n {
+                let mut doc = vec![0f64; vocab];
+                for _ in 0..8 {
+                    let u = rng.next_f64();
+                    let mut cum = 0f64;
+                    let word = probs.iter().position(|p| { cum += *p; u < cum }).unwrap_or(vocab - 1);
+                    doc[word] += 1f64;
+                }
+                counts.extend(doc);
+                labels.push(class);
+            }
+        }
+
+        (Matrix::new(labels.len(), vocab, counts), labels)
+    }
+
+    fn one_hot(labels: &[usize], classes: usize) -> Matrix<f64> {
+        let mut data = vec![0f64; labels.len() * classes];
+        for (i, l) in labels.iter().enumerate() {
+            data[i * classes + l] = 1f64;
+        }
+        Matrix::new(labels.len(), classes, data)
+    }
+
+    fn macro_f1(outputs: &Matrix<f64>, labels: &[usize]) -> f64 {
+        let targets = one_hot(labels, outputs.cols());
+        let mut total = 0f64;
+        for c in 0..outputs.cols() {
+            let o = outputs.col(c).iter().cloned().collect::<Vec<f64>>();
+            let t = targets.col(c).iter().cloned().collect::<Vec<f64>>();
+            total += f1(o.iter(), t.iter());
+        }
+        total / outputs.cols() as f64
+    }
+
+    #[test]
+    fn test_complement_imbalanced() {
+        let word_probs = vec![vec![0.3, 0.25, 0.2, 0.1, 0.05, 0.04, 0.03, 0.03],
+                              vec![0.15, 0.15, 0.1, 0.3, 0.1, 0.1, 0.05, 0.05],
+                              vec![0.15, 0.15, 0.1, 0.05, 0.05, 0.1, 0.2, 0.2]];
+
+        let mut rng = StdRng::from_seed(&[1, 2, 3]);
+        let (train, train_labels) = sample_documents(&word_probs, &[200, 15, 15], &mut rng);
+        let (test, test_labels) = sample_documents(&word_probs, &[100, 100, 100], &mut rng);
+
+        let targets = one_hot(&train_labels, 3);
+
+        let mut multinomial = NaiveBayes::<Multinomial>::new();
+        multinomial.train(&train, &targets).unwrap();
+        let multinomial_f1 = macro_f1(&multinomial.predict(&test).unwrap(), &test_labels);
+
+        let mut complement = NaiveBayes::<Complement>::new();
+        complement.train(&train, &targets).unwrap();
+        let complement_f1 = macro_f1(&complement.predict(&test).unwrap(), &test_labels);
+
+        assert!(complement_f1 > multinomial_f1);
+    }
 }
@@ -45,18 +45,21 @@
 
 pub mod net_layer;
 
-use linalg::{Matrix, MatrixSlice};
+use linalg::{Matrix, MatrixSlice, BaseMatrix};
 use rulinalg::utils;
 
-use learning::{LearningResult, SupModel};
+use learning::{LearningResult, SupModel, predict_batched, validate_finite_matrix,
+               validate_optimized};
 use learning::error::{Error, ErrorKind};
+use learning::model_info::ModelInfo;
 use learning::toolkit::activ_fn;
 use learning::toolkit::activ_fn::ActivationFunc;
 use learning::toolkit::cost_fn;
 use learning::toolkit::cost_fn::CostFunc;
 use learning::toolkit::regularization::Regularization;
-use learning::optim::{Optimizable, OptimAlgorithm};
-use learning::optim::grad_desc::StochasticGD;
+use learning::optim::{EpochAlgorithm, Optimizable, OptimAlgorithm};
+use learning::optim::grad_desc::{Checkpoint, StochasticGD};
+use learning::optim::schedule::LearningRate;
 
 use self::net_layer::NetLayer;
 
@@ -86,8 +89,17 @@ impl<T, A> SupModel<Matrix<f64>, Matrix<f64>> for NeuralNet<T, A>
     }
 
     /// Train the model using gradient optimization and back propagation.
+    ///
+    /// # Failures
+    ///
+    /// - Some input or target is NaN or infinite.
+    /// - The optimization diverged.
     fn train(&mut self, inputs: &Matrix<f64>, targets: &Matrix<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_matrix("target", targets)?;
+
         let optimal_w = self.alg.optimize(&self.base, &self.base.weights, inputs, targets);
+        validate_optimized(&self.base, &optimal_w, inputs, targets)?;
         self.base.weights = optimal_w;
         Ok(())
     }
@@ -218,6 +230,113 @@ impl<T, A> NeuralNet<T, A>
             self
     }
 
+    /// Predicts the network outputs in batches of at most `batch_size` rows.
+    ///
+    /// This bounds the memory used by forward propagation on large
+    /// inputs. The outputs are the same as those of `predict`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::learning::nnet::NeuralNet;
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// let net = NeuralNet::default(&[2, 3, 1]);
+    /// let inputs = Matrix::new(1000, 2, vec![0.5; 2000]);
+    ///
+    /// let outputs = net.predict_batched(&inputs, 128).unwrap();
+    /// assert_eq!(outputs, net.predict(&inputs).unwrap());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `batch_size` is zero.
+    pub fn predict_batched(&self, inputs: &Matrix<f64>, batch_size: usize) -> LearningResult<Matrix<f64>> {
+        predict_batched(self, inputs, batch_size)
+    }
+
+    /// Sets whether the parameters of the layer at `idx` are updated
+    /// during training.
+    ///
+    /// The gradient is still propagated through a frozen layer, so the
+    /// layers before it continue to train. This can be used to fine-tune
+    /// some layers of a pretrained network while keeping the rest fixed.
+    /// All layers are trainable by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::nnet::NeuralNet;
+    ///
+    /// let mut net = NeuralNet::default(&[3, 4, 2]);
+    ///
+    /// // Freeze the first linear layer.
+    /// net.set_layer_trainable(0, false);
+    /// assert!(!net.is_layer_trainable(0));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - There is no layer at `idx`.
+    pub fn set_layer_trainable(&mut self, idx: usize, trainable: bool) {
+        assert!(idx < self.base.layers.len(), "There is no layer at index {}.", idx);
+        self.base.trainable[idx] = trainable;
+    }
+
+    /// Whether the parameters of the layer at `idx` are updated during training.
+    ///
+    /// # Panics
+    ///
+    /// - There is no layer at `idx`.
+    pub fn is_layer_trainable(&self, idx: usize) -> bool {
+        assert!(idx < self.base.layers.len(), "There is no layer at index {}.", idx);
+        self.base.trainable[idx]
+    }
+
+    /// Summarizes the size of the network.
+    ///
+    /// The number of parameters is the total over all layers. The input
+    /// and output sizes are taken from the first and last layers which
+    /// fix them, such as `Linear` layers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::nnet::NeuralNet;
+    ///
+    /// let net = NeuralNet::default(&[4, 3]);
+    /// assert_eq!(net.model_info().n_params, 5 * 3);
+    /// ```
+    pub fn model_info(&self) -> ModelInfo {
+        let layers = &self.base.layers;
+
+        ModelInfo {
+            n_params: self.base.weights.len(),
+            n_inputs: layers.iter().filter_map(|l| l.input_size()).next(),
+            n_outputs: layers.iter().rev().filter_map(|l| l.output_size()).next(),
+            n_stored_samples: None,
+            n_nodes: None,
+        }
+    }
+
+    /// Gets the optimization algorithm used to train the network.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::nnet::NeuralNet;
+    /// use rusty_machine::learning::optim::EpochAlgorithm;
+    ///
+    /// let net = NeuralNet::default(&[3, 2]);
+    ///
+    /// // The default optimizer makes at most 20 passes.
+    /// assert_eq!(net.optimizer().epochs(), 20);
+    /// ```
+    pub fn optimizer(&self) -> &A {
+        &self.alg
+    }
+
     /// Gets matrix of weights between specified layer and forward layer.
     ///
     /// # Examples
@@ -239,6 +358,284 @@ impl<T, A> NeuralNet<T, A>
     pub fn get_net_weights(&self, idx: usize) -> MatrixSlice<f64> {
         self.base.get_layer_weights(&self.base.weights[..], idx)
     }
+
+    /// Sets all of the weights in the network.
+    ///
+    /// The weights are given in the same flattened order used during
+    /// optimization, so this can be used to restore the parameters
+    /// saved by an optimizer checkpoint. Training continues from
+    /// these weights.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::nnet::NeuralNet;
+    ///
+    /// let layers = &[3, 2];
+    /// let mut net = NeuralNet::default(layers);
+    ///
+    /// // The net has a 4x2 weight matrix, including the bias term.
+    /// net.set_net_weights(&[0.5; 8]).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The number of weights does not match the network.
+    pub fn set_net_weights(&mut self, weights: &[f64]) -> LearningResult<()> {
+        if weights.len() != self.base.weights.len() {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  format!("Expected {} weights but got {}.",
+                                          self.base.weights.len(),
+                                          weights.len())));
+        }
+
+        self.base.weights = weights.to_vec();
+        Ok(())
+    }
+
+    /// Train the model and score it on a validation set after each epoch.
+    ///
+    /// The network's outputs on `val_inputs` are compared to `val_targets`
+    /// using `score` at the end of every pass through the training data.
+    /// Returns the score of each epoch, which can be used to plot a
+    /// learning curve. Evaluating the validation set does not change
+    /// the training.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::analysis::score::neg_mean_squared_error;
+    /// use rusty_machine::learning::nnet::NeuralNet;
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// let inputs = Matrix::new(4, 2, vec![0., 0., 0., 1., 1., 0., 1., 1.]);
+    /// let targets = Matrix::new(4, 1, vec![0., 1., 1., 1.]);
+    ///
+    /// let mut net = NeuralNet::default(&[2, 3, 1]);
+    /// let history = net.train_with_validation(&inputs, &targets,
+    ///                                         &inputs, &targets,
+    ///                                         neg_mean_squared_error).unwrap();
+    ///
+    /// // The default optimizer makes at most 20 passes.
+    /// assert!(history.len() <= 20);
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - Some input or target is NaN or infinite.
+    /// - The validation inputs do not match the network's input layer.
+    /// - The optimization diverged.
+    pub fn train_with_validation<S>(&mut self,
+                                    inputs: &Matrix<f64>,
+                                    targets: &Matrix<f64>,
+                                    val_inputs: &Matrix<f64>,
+                                    val_targets: &Matrix<f64>,
+                                    score: S)
+                                    -> LearningResult<Vec<f64>>
+        where S: Fn(&Matrix<f64>, &Matrix<f64>) -> f64
+    {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_matrix("target", targets)?;
+
+        let mut history = Vec::new();
+        let mut val_error = None;
+
+        let optimal_w = {
+            let base = &self.base;
+            let on_epoch = |_: usize, weights: &[f64]| {
+                if val_error.is_some() {
+                    return;
+                }
+
+                match base.forward_prop_with(weights, val_inputs) {
+                    Ok(outputs) => history.push(score(&outputs, val_targets)),
+                    Err(e) => val_error = Some(e),
+                }
+            };
+
+            self.alg.optimize_with_callback(base, &base.weights, inputs, targets, on_epoch)
+        };
+
+        if let Some(e) = val_error {
+            return Err(e);
+        }
+        validate_optimized(&self.base, &optimal_w, inputs, targets)?;
+
+        self.base.weights = optimal_w;
+        Ok(history)
+    }
+
+    /// Train the model, periodically saving the weights to a file.
+    ///
+    /// The saved weights can be read back with `load_checkpoint` and
+    /// restored using `set_net_weights`, e.g. to resume after a crash.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rusty_machine::learning::nnet::NeuralNet;
+    /// use rusty_machine::learning::optim::grad_desc::{Checkpoint, load_checkpoint};
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// let inputs = Matrix::new(4, 2, vec![0., 0., 0., 1., 1., 0., 1., 1.]);
+    /// let targets = Matrix::new(4, 1, vec![0., 1., 1., 1.]);
+    ///
+    /// // Save the weights after every 5 epochs.
+    /// let mut net = NeuralNet::default(&[2, 3, 1]);
+    /// net.train_with_checkpoint(&inputs, &targets, &Checkpoint::new(5, "weights.txt")).unwrap();
+    ///
+    /// let mut restored = NeuralNet::default(&[2, 3, 1]);
+    /// restored.set_net_weights(&load_checkpoint("weights.txt").unwrap()).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - Some input or target is NaN or infinite.
+    /// - A checkpoint file could not be written.
+    /// - The optimization diverged.
+    pub fn train_with_checkpoint(&mut self,
+                                 inputs: &Matrix<f64>,
+                                 targets: &Matrix<f64>,
+                                 checkpoint: &Checkpoint)
+                                 -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_matrix("target", targets)?;
+
+        let optimal_w = checkpoint.optimize(&self.alg, &self.base, &self.base.weights, inputs, targets)?;
+        validate_optimized(&self.base, &optimal_w, inputs, targets)?;
+        self.base.weights = optimal_w;
+        Ok(())
+    }
+}
+
+impl<T, A> NeuralNet<T, A>
+    where T: Criterion,
+          A: OptimAlgorithm<BaseNeuralNet<T>> + EpochAlgorithm
+{
+    /// Train the model for at most the given number of passes through the data.
+    ///
+    /// The optimizer makes as many parameter updates in each epoch as
+    /// it has mini-batches, so the number of updates depends on the
+    /// batch size. The number of epochs set on the optimizer is only
+    /// replaced for this training. As in `train`, the training stops
+    /// early once the cost stops changing.
+    ///
+    /// Returns the number of parameter updates made.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::nnet::{NeuralNet, BCECriterion};
+    /// use rusty_machine::learning::optim::grad_desc::StochasticGD;
+    /// use rusty_machine::learning::toolkit::activ_fn::Sigmoid;
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// let inputs = Matrix::new(4, 2, vec![0., 0., 0., 1., 1., 0., 1., 1.]);
+    /// let targets = Matrix::new(4, 1, vec![0., 1., 1., 1.]);
+    ///
+    /// // Two mini-batches of two points in each epoch.
+    /// let sgd = StochasticGD::new(0.1, 0.3, 100).with_batch_size(2);
+    /// let mut net = NeuralNet::mlp(&[2, 3, 1], BCECriterion::default(), sgd, Sigmoid);
+    ///
+    /// let steps = net.train_epochs(&inputs, &targets, 5).unwrap();
+    /// assert!(steps <= 10);
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - Some input or target is NaN or infinite.
+    /// - The optimization diverged.
+    pub fn train_epochs(&mut self,
+                        inputs: &Matrix<f64>,
+                        targets: &Matrix<f64>,
+                        epochs: usize)
+                        -> LearningResult<usize> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_matrix("target", targets)?;
+
+        let max_epochs = self.alg.epochs();
+        self.alg.set_epochs(epochs);
+
+        let mut completed = 0;
+        let optimal_w = self.alg.optimize_with_callback(&self.base,
+                                                        &self.base.weights,
+                                                        inputs,
+                                                        targets,
+                                                        |_, _| completed += 1);
+        self.alg.set_epochs(max_epochs);
+
+        validate_optimized(&self.base, &optimal_w, inputs, targets)?;
+        self.base.weights = optimal_w;
+        Ok(completed * self.alg.steps_per_epoch(inputs.rows()))
+    }
+}
+
+impl<T: Criterion, L: LearningRate> NeuralNet<T, StochasticGD<L>> {
+    /// Train the model, returning the order the training data was
+    /// visited in during each epoch.
+    ///
+    /// The mini-batches of each epoch are consecutive chunks of its order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::nnet::{NeuralNet, BCECriterion};
+    /// use rusty_machine::learning::optim::grad_desc::StochasticGD;
+    /// use rusty_machine::learning::toolkit::activ_fn::Sigmoid;
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// let inputs = Matrix::new(4, 2, vec![0., 0., 0., 1., 1., 0., 1., 1.]);
+    /// let targets = Matrix::new(4, 1, vec![0., 1., 1., 1.]);
+    ///
+    /// let sgd = StochasticGD::new(0.1, 0.3, 5).with_batch_size(2);
+    /// let mut net = NeuralNet::mlp(&[2, 3, 1], BCECriterion::default(), sgd, Sigmoid);
+    ///
+    /// let orders = net.train_recording_orders(&inputs, &targets).unwrap();
+    /// assert!(orders.len() <= 5);
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - Some input or target is NaN or infinite.
+    /// - The optimization diverged.
+    pub fn train_recording_orders(&mut self,
+                                  inputs: &Matrix<f64>,
+                                  targets: &Matrix<f64>)
+                                  -> LearningResult<Vec<Vec<usize>>> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_matrix("target", targets)?;
+
+        let (optimal_w, orders) =
+            self.alg.optimize_recording_orders(&self.base, &self.base.weights, inputs, targets);
+        validate_optimized(&self.base, &optimal_w, inputs, targets)?;
+        self.base.weights = optimal_w;
+        Ok(orders)
+    }
+
+    /// Train the model, visiting the training data in a fixed order
+    /// in every epoch instead of shuffling it.
+    ///
+    /// Together with `train_recording_orders` this reproduces the
+    /// mini-batches of a training run.
+    ///
+    /// # Failures
+    ///
+    /// - Some input or target is NaN or infinite.
+    /// - The order is not a permutation of the rows of the inputs.
+    /// - The optimization diverged.
+    pub fn train_in_order(&mut self,
+                          inputs: &Matrix<f64>,
+                          targets: &Matrix<f64>,
+                          order: &[usize])
+                          -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_matrix("target", targets)?;
+
+        let optimal_w = self.alg.optimize_in_order(&self.base, &self.base.weights, inputs, targets, order)?;
+        validate_optimized(&self.base, &optimal_w, inputs, targets)?;
+        self.base.weights = optimal_w;
+        Ok(())
+    }
 }
 
 /// Base Neural Network struct
@@ -248,6 +645,7 @@ impl<T, A> NeuralNet<T, A>
 pub struct BaseNeuralNet<T: Criterion> {
     layers: Vec<Box<NetLayer>>,
     weights: Vec<f64>,
+    trainable: Vec<bool>,
     criterion: T,
 }
 
@@ -267,6 +665,7 @@ impl<T: Criterion> BaseNeuralNet<T> {
         BaseNeuralNet {
             layers: Vec::new(),
             weights: Vec::new(),
+            trainable: Vec::new(),
             criterion: criterion
         }
     } 
@@ -277,6 +676,7 @@ impl<T: Criterion> BaseNeuralNet<T> {
         let mut mlp = BaseNeuralNet {
             layers: Vec::with_capacity(2*(layer_sizes.len()-1)),
             weights: Vec::new(),
+            trainable: Vec::with_capacity(2*(layer_sizes.len()-1)),
             criterion: criterion
         };
         for shape in layer_sizes.windows(2) {
@@ -290,6 +690,7 @@ impl<T: Criterion> BaseNeuralNet<T> {
     fn add<'a>(&'a mut self, layer: Box<NetLayer>) -> &'a mut BaseNeuralNet<T> {
         self.weights.extend_from_slice(&layer.default_params());
         self.layers.push(layer);
+        self.trainable.push(true);
         self
     }
 
@@ -374,9 +775,17 @@ impl<T: Criterion> BaseNeuralNet<T> {
             index -= layer.num_params();
 
             let grad_params = &mut gradients[index..index+layer.num_params()];
-            grad_params.copy_from_slice(layer.back_params(&out_grad, activation, result, params[i]).data());
-            
-            out_grad = layer.back_input(&out_grad, activation, result, params[i]);
+            out_grad = if self.trainable[i] {
+                let (param_grad, input_grad) = layer.back_prop(&out_grad, activation, result, params[i]);
+                grad_params.copy_from_slice(param_grad.data());
+                input_grad
+            } else {
+                // Frozen layers get no gradient, so the optimizers leave them unchanged
+                for g in grad_params.iter_mut() {
+                    *g = 0f64;
+                }
+                layer.back_input(&out_grad, activation, result, params[i])
+            };
         }
 
         let mut cost = self.criterion.cost(output, targets);
@@ -388,19 +797,36 @@ impl<T: Criterion> BaseNeuralNet<T> {
                                        self.criterion.reg_cost_grad(all_params).data(),
                                        |x, &y| *x = *x + y);
             cost += self.criterion.reg_cost(all_params);
+
+            let mut index = 0;
+            for (layer, &trainable) in self.layers.iter().zip(self.trainable.iter()) {
+                if !trainable {
+                    for g in &mut gradients[index..index + layer.num_params()] {
+                        *g = 0f64;
+                    }
+                }
+                index += layer.num_params();
+            }
         }
         (cost, gradients)
     }
 
     /// Forward propagation of the model weights to get the outputs.
     fn forward_prop(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        self.forward_prop_with(&self.weights, inputs)
+    }
+
+    /// Forward propagation of the given weights to get the outputs.
+    fn forward_prop_with(&self, weights: &[f64], inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
         if self.layers.is_empty() {
             return Ok(inputs.clone());
         }
 
+        assert_eq!(weights.len(), self.weights.len());
+
         let mut outputs = unsafe {
             let shape = self.layers[0].param_shape();
-            let slice = MatrixSlice::from_raw_parts(self.weights.as_ptr(),
+            let slice = MatrixSlice::from_raw_parts(weights.as_ptr(),
                                                     shape.0,
                                                     shape.1,
                                                     shape.1);
@@ -412,7 +838,7 @@ impl<T: Criterion> BaseNeuralNet<T> {
             let shape = layer.param_shape();
 
             let slice = unsafe {
-                MatrixSlice::from_raw_parts(self.weights.as_ptr().offset(index as isize),
+                MatrixSlice::from_raw_parts(weights.as_ptr().offset(index as isize),
                                             shape.0,
                                             shape.1,
                                             shape.1)
@@ -586,3 +1012,352 @@ impl MSECriterion {
         MSECriterion { regularization: regularization }
     }
 }
+
+/// The focal loss criterion.
+///
+/// Uses the Sigmoid activation function and the focal loss,
+/// which focuses the training on the examples which are
+/// misclassified. This helps when some classes are rare.
+#[derive(Clone, Debug)]
+pub struct FocalCriterion {
+    focal: cost_fn::FocalLoss,
+    regularization: Regularization<f64>,
+}
+
+impl Criterion for FocalCriterion {
+    type Cost = cost_fn::FocalLoss;
+
+    fn cost(&self, outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
+        self.focal.loss(outputs, targets)
+    }
+
+    fn cost_grad(&self, outputs: &Matrix<f64>, targets: &Matrix<f64>) -> Matrix<f64> {
+        self.focal.loss_grad(outputs, targets)
+    }
+
+    fn regularization(&self) -> Regularization<f64> {
+        self.regularization
+    }
+}
+
+/// Creates a focal loss criterion with `gamma = 2`, no class
+/// weights and no regularization.
+impl Default for FocalCriterion {
+    fn default() -> Self {
+        FocalCriterion::new(cost_fn::FocalLoss::default(), Regularization::None)
+    }
+}
+
+impl FocalCriterion {
+    /// Constructs a new FocalCriterion with the given focal loss and regularization.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::nnet::FocalCriterion;
+    /// use rusty_machine::learning::toolkit::cost_fn::FocalLoss;
+    /// use rusty_machine::learning::toolkit::regularization::Regularization;
+    ///
+    /// // Weight the rare second class three times as heavily as the first.
+    /// let focal = FocalLoss::new(2.0).with_alpha(vec![0.25, 0.75]);
+    /// let criterion = FocalCriterion::new(focal, Regularization::L2(0.1f64));
+    /// ```
+    pub fn new(focal: cost_fn::FocalLoss, regularization: Regularization<f64>) -> Self {
+        FocalCriterion {
+            focal,
+            regularization,
+        }
+    }
+
+    /// The focal loss used by the criterion.
+    pub fn focal_loss(&self) -> &cost_fn::FocalLoss {
+        &self.focal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NeuralNet;
+    use super::{BCECriterion, Criterion, FocalCriterion, MSECriterion};
+    use learning::SupModel;
+    use learning::error::ErrorKind;
+    use learning::optim::EpochAlgorithm;
+    use learning::optim::grad_desc::{Checkpoint, GradientDesc, StochasticGD, load_checkpoint};
+    use learning::toolkit::activ_fn::{Linear, Sigmoid};
+    use learning::toolkit::cost_fn::FocalLoss;
+    use learning::toolkit::regularization::Regularization;
+    use linalg::{Matrix, BaseMatrix};
+
+    use std::env;
+    use std::f64;
+    use std::fs;
+
+    /// The fraction of rows where the output is on the correct side of 0.5.
+    fn threshold_accuracy(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
+        let correct = outputs.iter()
+            .zip(targets.iter())
+            .filter(|&(o, t)| (*o > 0.5) == (*t > 0.5))
+            .count();
+        correct as f64 / outputs.rows() as f64
+    }
+
+    #[test]
+    fn validation_accuracy_increases() {
+        // Two separable clusters in the plane.
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for i in 0..20 {
+            let offset = (i % 5) as f64 * 0.1;
+            let (x, label) = if i < 10 { (-1.0 - offset, 0.) } else { (1.0 + offset, 1.) };
+            data.push(x);
+            data.push(0.3 * offset - 0.5);
+            labels.push(label);
+        }
+        let inputs = Matrix::new(20, 2, data);
+        let targets = Matrix::new(20, 1, labels);
+
+        let mut net = NeuralNet::mlp(&[2, 1], BCECriterion::default(), GradientDesc::new(0.05, 40), Sigmoid);
+
+        // Start from weights which classify every point as the first class.
+        net.set_net_weights(&[-2.0, 0.0, 0.0]).unwrap();
+
+        let history = net.train_with_validation(&inputs, &targets, &inputs, &targets, threshold_accuracy)
+            .unwrap();
+
+        assert_eq!(history.len(), 40);
+        assert!(history[0] < 1.0);
+        assert_eq!(*history.last().unwrap(), 1.0);
+        assert!(history.windows(2).all(|w| w[1] >= w[0]));
+    }
+
+    #[test]
+    fn focal_criterion_uses_parameters() {
+        let outputs = Matrix::new(2, 2, vec![0.9, 0.2, 0.4, 0.7]);
+        let targets = Matrix::new(2, 2, vec![1., 0., 0., 1.]);
+
+        let focal = FocalLoss::new(1.0).with_alpha(vec![0.5, 2.0]);
+        let criterion = FocalCriterion::new(focal.clone(), Regularization::None);
+
+        assert_eq!(criterion.cost(&outputs, &targets), focal.loss(&outputs, &targets));
+        assert_eq!(criterion.cost_grad(&outputs, &targets).into_vec(),
+                   focal.loss_grad(&outputs, &targets).into_vec());
+    }
+
+    #[test]
+    fn focal_criterion_trains() {
+        let inputs = Matrix::new(6, 1, vec![-3., -2., -1., 1., 2., 3.]);
+        let targets = Matrix::new(6, 1, vec![0., 0., 0., 1., 1., 1.]);
+
+        let mut net = NeuralNet::mlp(&[1, 1], FocalCriterion::default(), GradientDesc::new(0.5, 200), Sigmoid);
+        net.set_net_weights(&[0.0, 0.0]).unwrap();
+        net.train(&inputs, &targets).unwrap();
+
+        let outputs = net.predict(&inputs).unwrap();
+        assert_eq!(threshold_accuracy(&outputs, &targets), 1.0);
+    }
+
+    #[test]
+    fn train_epochs_counts_mini_batch_steps() {
+        let inputs = Matrix::new(10, 1, (0..10).map(|i| i as f64 / 10.0).collect::<Vec<f64>>());
+        let targets = Matrix::new(10, 1, vec![0., 0., 0., 0., 1., 0., 1., 1., 1., 1.]);
+
+        // Ten points in batches of four make three updates per epoch.
+        let sgd = StochasticGD::new(0.1, 0.1, 100).with_batch_size(4).with_seed(7);
+        let mut net = NeuralNet::mlp(&[1, 3, 1], BCECriterion::default(), sgd, Sigmoid);
+        assert_eq!(net.train_epochs(&inputs, &targets, 6).unwrap(), 18);
+        assert_eq!(net.optimizer().epochs(), 100);
+
+        // Full batch gradient descent makes one update per epoch.
+        let mut net = NeuralNet::mlp(&[1, 3, 1], BCECriterion::default(), GradientDesc::new(0.1, 100), Sigmoid);
+        assert_eq!(net.train_epochs(&inputs, &targets, 6).unwrap(), 6);
+    }
+
+    #[test]
+    fn validation_does_not_change_training() {
+        let inputs = Matrix::new(4, 2, vec![0., 0., 0., 1., 1., 0., 1., 1.]);
+        let targets = Matrix::new(4, 1, vec![0., 1., 1., 1.]);
+
+        let mut net = NeuralNet::mlp(&[2, 2, 1], BCECriterion::default(), GradientDesc::new(0.3, 10), Sigmoid);
+        net.set_net_weights(&[0.1, -0.2, 0.3, 0.4, -0.5, 0.6, 0.7, -0.8, 0.9]).unwrap();
+        let mut val_net = NeuralNet::mlp(&[2, 2, 1], BCECriterion::default(), GradientDesc::new(0.3, 10), Sigmoid);
+        val_net.set_net_weights(&[0.1, -0.2, 0.3, 0.4, -0.5, 0.6, 0.7, -0.8, 0.9]).unwrap();
+
+        net.train(&inputs, &targets).unwrap();
+        val_net.train_with_validation(&inputs, &targets, &inputs, &targets, threshold_accuracy).unwrap();
+
+        for i in 0..2 {
+            assert_eq!(net.get_net_weights(i).iter().collect::<Vec<_>>(),
+                       val_net.get_net_weights(i).iter().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn checkpoint_restores_weights() {
+        let path = env::temp_dir().join("rusty_machine_nnet_checkpoint.txt");
+        let _ = fs::remove_file(&path);
+
+        let inputs = Matrix::new(4, 2, vec![0., 0., 0., 1., 1., 0., 1., 1.]);
+        let targets = Matrix::new(4, 1, vec![0., 1., 1., 1.]);
+
+        let mut net = NeuralNet::mlp(&[2, 2, 1], BCECriterion::default(), GradientDesc::new(0.3, 10), Sigmoid);
+        net.train_with_checkpoint(&inputs, &targets, &Checkpoint::new(5, &path)).unwrap();
+
+        let mut restored = NeuralNet::mlp(&[2, 2, 1], BCECriterion::default(), GradientDesc::new(0.3, 10), Sigmoid);
+        restored.set_net_weights(&load_checkpoint(&path).unwrap()).unwrap();
+        assert_eq!(net.predict(&inputs).unwrap(), restored.predict(&inputs).unwrap());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn train_in_recorded_order() {
+        let inputs = Matrix::new(6, 1, vec![-3., -2., -1., 1., 2., 3.]);
+        let targets = Matrix::new(6, 1, vec![0., 0., 0., 1., 1., 1.]);
+        let sgd = StochasticGD::new(0.1, 0.3, 1).with_batch_size(2);
+
+        let mut net = NeuralNet::mlp(&[1, 1], BCECriterion::default(), sgd, Sigmoid);
+        net.set_net_weights(&[0.1, 0.2]).unwrap();
+        let orders = net.train_recording_orders(&inputs, &targets).unwrap();
+        assert_eq!(orders.len(), 1);
+
+        // Replaying the recorded order gives the same weights.
+        let mut replayed = NeuralNet::mlp(&[1, 1], BCECriterion::default(), sgd, Sigmoid);
+        replayed.set_net_weights(&[0.1, 0.2]).unwrap();
+        replayed.train_in_order(&inputs, &targets, &orders[0]).unwrap();
+        assert_eq!(net.get_net_weights(0).iter().collect::<Vec<_>>(),
+                   replayed.get_net_weights(0).iter().collect::<Vec<_>>());
+
+        assert!(replayed.train_in_order(&inputs, &targets, &[0, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn validation_mismatched_inputs() {
+        let inputs = Matrix::new(2, 2, vec![0., 0., 1., 1.]);
+        let targets = Matrix::new(2, 1, vec![0., 1.]);
+        let val_inputs = Matrix::new(2, 3, vec![0.; 6]);
+
+        let mut net = NeuralNet::mlp(&[2, 1], BCECriterion::default(), GradientDesc::new(0.3, 5), Sigmoid);
+        assert!(net.train_with_validation(&inputs, &targets, &val_inputs, &targets, threshold_accuracy)
+            .is_err());
+    }
+
+    #[test]
+    fn nan_input_is_reported() {
+        let inputs = Matrix::new(3, 2, vec![0., 1., 1., 0., 1., f64::NAN]);
+        let targets = Matrix::new(3, 1, vec![0., 1., 1.]);
+
+        let mut net = NeuralNet::mlp(&[2, 1], BCECriterion::default(), GradientDesc::new(0.3, 5), Sigmoid);
+        match net.train(&inputs, &targets) {
+            Err(e) => {
+                match *e.kind() {
+                    ErrorKind::InvalidData => {}
+                    _ => panic!("Unexpected error kind."),
+                }
+                assert_eq!(e.to_string(), "input row 2, column 1 is NaN");
+            }
+            Ok(_) => panic!("Training on NaN inputs should fail."),
+        }
+    }
+
+    #[test]
+    fn diverging_optimization_is_reported() {
+        // A learning rate this large makes the linear fit blow up.
+        let inputs = Matrix::new(4, 1, vec![10., 20., 30., 40.]);
+        let targets = Matrix::new(4, 1, vec![20., 40., 60., 80.]);
+
+        let mut net = NeuralNet::mlp(&[1, 1], MSECriterion::default(), GradientDesc::new(10.0, 1000), Linear);
+        match net.train(&inputs, &targets) {
+            Err(e) => {
+                match *e.kind() {
+                    ErrorKind::InvalidState => {}
+                    _ => panic!("Unexpected error kind."),
+                }
+                assert!(e.to_string().contains("diverged"));
+            }
+            Ok(_) => panic!("The optimization should diverge."),
+        }
+
+        // The network keeps its previous weights.
+        assert!(net.base.weights.iter().all(|w| w.is_finite()));
+    }
+
+    #[test]
+    fn batched_prediction_matches_predict() {
+        let inputs = Matrix::new(10, 2, (0..20).map(|x| x as f64 * 0.1 - 1.0).collect::<Vec<f64>>());
+        let net = NeuralNet::mlp(&[2, 4, 3], BCECriterion::default(), GradientDesc::default(), Sigmoid);
+
+        let outputs = net.predict(&inputs).unwrap();
+
+        // Batch sizes which do and do not divide the row count.
+        for &batch_size in &[1, 3, 5, 7, 10, 25] {
+            assert_eq!(net.predict_batched(&inputs, batch_size).unwrap(), outputs);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn batched_prediction_zero_batch_size() {
+        let inputs = Matrix::new(2, 2, vec![0.; 4]);
+        let net = NeuralNet::mlp(&[2, 1], BCECriterion::default(), GradientDesc::default(), Sigmoid);
+
+        let _ = net.predict_batched(&inputs, 0);
+    }
+
+    #[test]
+    fn frozen_layer_is_not_updated() {
+        let inputs = Matrix::new(4, 2, vec![0., 0., 0., 1., 1., 0., 1., 1.]);
+        let targets = Matrix::new(4, 1, vec![0., 1., 1., 0.]);
+
+        // Layers 0 and 2 are linear, layers 1 and 3 are sigmoids.
+        for &(frozen, trained) in &[(0, 2), (2, 0)] {
+            let mut net = NeuralNet::mlp(&[2, 3, 1], BCECriterion::default(), GradientDesc::new(0.5, 50), Sigmoid);
+            net.set_layer_trainable(frozen, false);
+
+            let frozen_before = net.get_net_weights(frozen).into_matrix();
+            let trained_before = net.get_net_weights(trained).into_matrix();
+            net.train(&inputs, &targets).unwrap();
+
+            assert_eq!(net.get_net_weights(frozen).into_matrix(), frozen_before);
+            // With the last layer frozen the first layer still trains,
+            // so the gradient reaches it through the frozen layer.
+            assert!(net.get_net_weights(trained).into_matrix() != trained_before);
+        }
+    }
+
+    #[test]
+    fn regularized_frozen_layer_is_not_updated() {
+        let inputs = Matrix::new(4, 2, vec![0., 0., 0., 1., 1., 0., 1., 1.]);
+        let targets = Matrix::new(4, 1, vec![0., 1., 1., 0.]);
+
+        let criterion = BCECriterion::new(Regularization::L2(0.5));
+        let mut net = NeuralNet::mlp(&[2, 3, 1], criterion, GradientDesc::new(0.5, 50), Sigmoid);
+        net.set_layer_trainable(0, false);
+        assert!(net.is_layer_trainable(2));
+
+        let before = net.get_net_weights(0).into_matrix();
+        net.train(&inputs, &targets).unwrap();
+        assert_eq!(net.get_net_weights(0).into_matrix(), before);
+    }
+
+    #[test]
+    #[should_panic]
+    fn trainable_flag_out_of_range() {
+        let mut net = NeuralNet::default(&[2, 1]);
+        net.set_layer_trainable(2, false);
+    }
+
+    #[test]
+    fn model_info_counts_layer_params() {
+        let mut net = NeuralNet::mlp(&[4, 6, 3, 2], MSECriterion::default(), GradientDesc::default(), Sigmoid);
+        net.add(Box::new(super::net_layer::Linear::without_bias(2, 5)));
+
+        let info = net.model_info();
+        let layer_params = net.base.layers.iter().map(|l| l.num_params()).sum::<usize>();
+
+        assert_eq!(info.n_params, layer_params);
+        assert_eq!(info.n_params, 5 * 6 + 7 * 3 + 4 * 2 + 2 * 5);
+        assert_eq!(info.n_inputs, Some(4));
+        assert_eq!(info.n_outputs, Some(5));
+        assert_eq!(info.n_stored_samples, None);
+    }
+}
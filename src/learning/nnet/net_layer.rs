@@ -1,6 +1,6 @@
 //! Neural Network Layers
 
-use linalg::{Matrix, MatrixSlice, BaseMatrix};
+use linalg::{Matrix, MatrixSlice, BaseMatrix, BaseMatrixMut};
 
 use learning::LearningResult;
 use learning::error::{Error, ErrorKind};
@@ -23,6 +23,13 @@ pub trait NetLayer : Debug {
     /// The gradient of the output of this layer with respect to its parameters
     fn back_params(&self, out_grad: &Matrix<f64>, input: &Matrix<f64>, output: &Matrix<f64>, params: MatrixSlice<f64>) -> Matrix<f64>;
 
+    /// The gradients of the output of this layer with respect to its parameters and its input
+    ///
+    /// Layers can override this to share work between `back_params` and `back_input`.
+    fn back_prop(&self, out_grad: &Matrix<f64>, input: &Matrix<f64>, output: &Matrix<f64>, params: MatrixSlice<f64>) -> (Matrix<f64>, Matrix<f64>) {
+        (self.back_params(out_grad, input, output, params), self.back_input(out_grad, input, output, params))
+    }
+
     /// The default value of the parameters of this layer before training
     fn default_params(&self) -> Vec<f64>;
 
@@ -34,6 +41,31 @@ pub trait NetLayer : Debug {
         let shape = self.param_shape();
         shape.0 * shape.1
     }
+
+    /// The number of columns of the input, if fixed by the layer
+    fn input_size(&self) -> Option<usize> {
+        None
+    }
+
+    /// The number of columns of the output, if fixed by the layer
+    fn output_size(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Methods for initializing the weights of a layer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Initializer {
+    /// Weights drawn from a gaussian distribution with 0 mean
+    /// and variance 2/(input_size+output_size)
+    Xavier,
+    /// Weights forming a random matrix with orthonormal columns,
+    /// or orthonormal rows if the layer has more outputs than inputs.
+    ///
+    /// Orthogonal weights preserve the norm of the gradients
+    /// passing through the layer, which helps to train deep networks.
+    /// Any bias term is initialized to zero.
+    Orthogonal,
 }
 
 /// Linear network layer
@@ -50,6 +82,8 @@ pub struct Linear {
     output_size: usize,
     /// Whether or not to include a bias term
     has_bias: bool,
+    /// How the weights are initialized
+    init: Initializer,
 }
 
 impl Linear {
@@ -58,7 +92,8 @@ impl Linear {
         Linear {
             input_size: input_size + 1, 
             output_size: output_size,
-            has_bias: true
+            has_bias: true,
+            init: Initializer::Xavier,
         }
     }
 
@@ -67,9 +102,56 @@ impl Linear {
         Linear {
             input_size: input_size, 
             output_size: output_size,
-            has_bias: false
+            has_bias: false,
+            init: Initializer::Xavier,
         }
     }
+
+    /// Set how the weights of the layer are initialized
+    ///
+    /// The default is `Initializer::Xavier`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::nnet::net_layer::{Linear, Initializer};
+    ///
+    /// let layer = Linear::new(10, 10).with_initializer(Initializer::Orthogonal);
+    /// ```
+    pub fn with_initializer(mut self, init: Initializer) -> Linear {
+        self.init = init;
+        self
+    }
+}
+
+/// Generates a random matrix with orthonormal columns, or orthonormal
+/// rows if there are more columns than rows
+///
+/// Uses the Q factor of the QR decomposition of a gaussian matrix.
+fn orthogonal_matrix(rows: usize, cols: usize) -> Matrix<f64> {
+    let (long, short) = if rows >= cols { (rows, cols) } else { (cols, rows) };
+    if short == 0 {
+        return Matrix::zeros(rows, cols);
+    }
+
+    let mut distro = Normal::new(0.0, 1.0);
+    let mut rng = thread_rng();
+    let gaussian = Matrix::new(long, short, (0..long * short).map(|_| distro.sample(&mut rng))
+                                                              .collect::<Vec<f64>>());
+
+    let (q, r) = gaussian.qr_decomp().expect("Failed to orthogonalize the weights.");
+
+    // Flip the signs of the columns to make the distribution uniform
+    let mut q = q.select_cols(&(0..short).collect::<Vec<usize>>());
+    for j in 0..short {
+        if r[[j, j]] < 0.0 {
+            for x in q.col_mut(j).iter_mut() {
+                *x = -*x;
+            }
+        }
+    }
+
+    if rows >= cols { q } else { q.transpose() }
 }
 
 fn remove_first_col(mat: Matrix<f64>) -> Matrix<f64>
@@ -137,19 +219,324 @@ impl NetLayer for Linear {
         }
     }
 
+    /// Initializes weights using the layer's `Initializer`
+    ///
+    /// By default uses Xavier initialization, with weights drawn from
+    /// gaussian distribution with 0 mean and variance 2/(input_size+output_size)
+    fn default_params(&self) -> Vec<f64> {
+        match self.init {
+            Initializer::Xavier => {
+                let mut distro = Normal::new(0.0, (2.0/(self.input_size+self.output_size) as f64).sqrt());
+                let mut rng = thread_rng();
+
+                (0..self.input_size*self.output_size).map(|_| distro.sample(&mut rng))
+                                                     .collect()
+            }
+            Initializer::Orthogonal => {
+                if self.has_bias {
+                    let weights = orthogonal_matrix(self.input_size - 1, self.output_size);
+                    Matrix::zeros(1, self.output_size).vcat(&weights).into_vec()
+                } else {
+                    orthogonal_matrix(self.input_size, self.output_size).into_vec()
+                }
+            }
+        }
+    }
+
+    fn param_shape(&self) -> (usize, usize) {
+        (self.input_size, self.output_size)
+    }
+
+    fn input_size(&self) -> Option<usize> {
+        if self.has_bias {
+            Some(self.input_size - 1)
+        } else {
+            Some(self.input_size)
+        }
+    }
+
+    fn output_size(&self) -> Option<usize> {
+        Some(self.output_size)
+    }
+}
+
+/// Recurrent network layer
+///
+/// Represents a simple recurrent layer which reads a sequence of steps
+/// and outputs its final hidden state. Starting from a zero state, each
+/// step updates the hidden state to `tanh(b + x W_x + h W_h)`.
+///
+/// Each row of the input holds one sequence padded to `seq_len` steps,
+/// laid out as `[m_1, ..., m_T, x_1, ..., x_T]`. The mask entry `m_t` is 1
+/// for a real step and 0 for padding. Padded steps leave the hidden state
+/// unchanged and get no gradient, so sequences of different lengths can
+/// be trained in one batch. The padding values must still be finite.
+///
+/// The parameters are a matrix of size (1 + I + N) x N, where I is the
+/// dimensionality of each step and N the size of the hidden state. The first
+/// row holds the bias, the next I rows the input weights and the last N rows
+/// the recurrent weights.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::learning::nnet::{NeuralNet, MSECriterion};
+/// use rusty_machine::learning::nnet::net_layer::{Linear, Recurrent};
+/// use rusty_machine::learning::optim::grad_desc::StochasticGD;
+/// use rusty_machine::learning::SupModel;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let layer = Recurrent::new(1, 4, 3);
+/// let sequences = vec![Matrix::new(2, 1, vec![1.0, 2.0]),
+///                      Matrix::new(3, 1, vec![1.0, 2.0, 3.0])];
+/// let inputs = layer.pack_sequences(&sequences).unwrap();
+/// let targets = Matrix::new(2, 1, vec![2.0, 3.0]);
+///
+/// let mut net = NeuralNet::new(MSECriterion::default(), StochasticGD::default());
+/// net.add(Box::new(layer))
+///    .add(Box::new(Linear::new(4, 1)));
+///
+/// net.train(&inputs, &targets).unwrap();
+/// let outputs = net.predict(&inputs).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Recurrent {
+    /// The number of dimensions of each step of the input
+    input_size: usize,
+    /// The number of dimensions of the hidden state
+    hidden_size: usize,
+    /// The number of steps in a padded sequence
+    seq_len: usize,
+}
+
+impl Recurrent {
+    /// Construct a new Recurrent layer
+    ///
+    /// # Panics
+    ///
+    /// - `seq_len` is zero.
+    pub fn new(input_size: usize, hidden_size: usize, seq_len: usize) -> Recurrent {
+        assert!(seq_len > 0, "The sequence length must be positive.");
+
+        Recurrent {
+            input_size,
+            hidden_size,
+            seq_len,
+        }
+    }
+
+    /// Packs sequences of different lengths into input rows for this layer
+    ///
+    /// Each sequence is a matrix with one row per step. Shorter sequences
+    /// are padded with zeros at the end, and the padding is masked out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::nnet::net_layer::Recurrent;
+    /// use rusty_machine::linalg::{Matrix, BaseMatrix};
+    ///
+    /// let layer = Recurrent::new(2, 4, 3);
+    /// let short = Matrix::new(1, 2, vec![1.0, 2.0]);
+    /// let long = Matrix::new(3, 2, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    ///
+    /// let inputs = layer.pack_sequences(&[short, long]).unwrap();
+    /// assert_eq!(inputs.row(0).raw_slice(),
+    ///            &[1.0, 0.0, 0.0, 1.0, 2.0, 0.0, 0.0, 0.0, 0.0]);
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - A sequence has the wrong number of columns.
+    /// - A sequence has more steps than the layer.
+    pub fn pack_sequences(&self, sequences: &[Matrix<f64>]) -> LearningResult<Matrix<f64>> {
+        let cols = self.seq_len * (1 + self.input_size);
+        let mut data = Vec::with_capacity(sequences.len() * cols);
+
+        for seq in sequences {
+            if seq.cols() != self.input_size {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "A sequence had the wrong number of columns"));
+            }
+            if seq.rows() > self.seq_len {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "A sequence had more steps than the layer"));
+            }
+
+            let padding = self.seq_len - seq.rows();
+            data.extend((0..self.seq_len).map(|t| if t < seq.rows() { 1.0 } else { 0.0 }));
+            data.extend_from_slice(seq.data());
+            data.extend((0..padding * self.input_size).map(|_| 0.0));
+        }
+
+        Ok(Matrix::new(sequences.len(), cols, data))
+    }
+
+    /// Whether step `t` of the sequence in row `i` is real, not padding
+    fn is_step(&self, input: &Matrix<f64>, i: usize, t: usize) -> bool {
+        input[[i, t]] > 0.5
+    }
+
+    /// The inputs of each sequence at step `t`
+    fn step_input(&self, input: &Matrix<f64>, t: usize) -> Matrix<f64> {
+        let start = self.seq_len + t * self.input_size;
+        input.select_cols(&(start..start + self.input_size).collect::<Vec<usize>>())
+    }
+
+    /// Splits the parameters into the bias, input weights and recurrent weights
+    fn split_params(&self, params: MatrixSlice<f64>) -> (Matrix<f64>, Matrix<f64>, Matrix<f64>) {
+        let split = self.input_size + 1;
+        let bias = params.select_rows(&[0]);
+        let w_x = params.select_rows(&(1..split).collect::<Vec<usize>>());
+        let w_h = params.select_rows(&(split..params.rows()).collect::<Vec<usize>>());
+        (bias, w_x, w_h)
+    }
+
+    /// The hidden states of each sequence before each step, followed by
+    /// the final hidden states
+    fn hidden_states(&self, input: &Matrix<f64>, params: MatrixSlice<f64>) -> Vec<Matrix<f64>> {
+        let (bias, w_x, w_h) = self.split_params(params);
+        let bias = &Matrix::ones(input.rows(), 1) * &bias;
+
+        let mut states = Vec::with_capacity(self.seq_len + 1);
+        states.push(Matrix::zeros(input.rows(), self.hidden_size));
+        for t in 0..self.seq_len {
+            let mut next = (&self.step_input(input, t) * &w_x + &states[t] * &w_h + &bias)
+                .apply(&f64::tanh);
+
+            // Padded steps keep the previous state
+            for i in 0..input.rows() {
+                if !self.is_step(input, i, t) {
+                    for j in 0..self.hidden_size {
+                        next[[i, j]] = states[t][[i, j]];
+                    }
+                }
+            }
+            states.push(next);
+        }
+        states
+    }
+
+    /// Backpropagates the output gradient through each step
+    ///
+    /// Returns the gradients with respect to the parameters and the input.
+    fn back_through_time(&self,
+                         out_grad: &Matrix<f64>,
+                         input: &Matrix<f64>,
+                         params: MatrixSlice<f64>)
+                         -> (Matrix<f64>, Matrix<f64>) {
+        debug_assert_eq!(input.rows(), out_grad.rows());
+        let states = self.hidden_states(input, params);
+        let (_, w_x, w_h) = self.split_params(params);
+        let (rows, size) = (input.rows(), self.hidden_size);
+
+        let mut bias_grad = Matrix::zeros(1, size);
+        let mut w_x_grad = Matrix::zeros(self.input_size, size);
+        let mut w_h_grad = Matrix::zeros(size, size);
+        let mut input_grad = Matrix::zeros(rows, input.cols());
+
+        // The gradient with respect to the hidden state after step t
+        let mut state_grad = out_grad.clone();
+        for t in (0..self.seq_len).rev() {
+            // The gradient before the tanh, which is zero for padded steps
+            let mut pre_grad = Matrix::zeros(rows, size);
+            for i in 0..rows {
+                if self.is_step(input, i, t) {
+                    for j in 0..size {
+                        let h = states[t + 1][[i, j]];
+                        pre_grad[[i, j]] = state_grad[[i, j]] * (1.0 - h * h);
+                        state_grad[[i, j]] = 0.0;
+                    }
+                }
+            }
+
+            bias_grad += Matrix::new(1, size, pre_grad.sum_rows().into_vec());
+            w_x_grad += &self.step_input(input, t).transpose() * &pre_grad;
+            w_h_grad += &states[t].transpose() * &pre_grad;
+
+            let step_grad = &pre_grad * &w_x.transpose();
+            let start = self.seq_len + t * self.input_size;
+            for i in 0..rows {
+                for k in 0..self.input_size {
+                    input_grad[[i, start + k]] = step_grad[[i, k]];
+                }
+            }
+
+            // Padded steps pass the gradient on unchanged
+            state_grad += &pre_grad * &w_h.transpose();
+        }
+
+        (bias_grad.vcat(&w_x_grad).vcat(&w_h_grad), input_grad)
+    }
+}
+
+impl NetLayer for Recurrent {
+    /// Computes the final hidden state of each sequence
+    ///
+    /// input should have dimensions N x T(1 + I)
+    /// where N is the number of sequences, T the number of steps
+    /// and I the dimensionality of each step
+    fn forward(&self, input: &Matrix<f64>, params: MatrixSlice<f64>) -> LearningResult<Matrix<f64>> {
+        if input.cols() != self.seq_len * (1 + self.input_size) {
+            return Err(Error::new(ErrorKind::InvalidData, "The input had the wrong number of columns"));
+        }
+        if params.rows() != 1 + self.input_size + self.hidden_size || params.cols() != self.hidden_size {
+            return Err(Error::new(ErrorKind::InvalidParameters, "The parameters had the wrong shape"));
+        }
+        for row in input.row_iter() {
+            if row.raw_slice()[..self.seq_len].iter().any(|&m| m != 0.0 && m != 1.0) {
+                return Err(Error::new(ErrorKind::InvalidData, "The input mask must be 0 or 1"));
+            }
+        }
+
+        Ok(self.hidden_states(input, params).pop().unwrap())
+    }
+
+    /// Backpropagates through each step, recomputing the hidden states
+    ///
+    /// The mask columns get zero gradient.
+    fn back_input(&self, out_grad: &Matrix<f64>, input: &Matrix<f64>, _: &Matrix<f64>, params: MatrixSlice<f64>) -> Matrix<f64> {
+        self.back_through_time(out_grad, input, params).1
+    }
+
+    /// Backpropagates through each step, recomputing the hidden states
+    fn back_params(&self, out_grad: &Matrix<f64>, input: &Matrix<f64>, _: &Matrix<f64>, params: MatrixSlice<f64>) -> Matrix<f64> {
+        self.back_through_time(out_grad, input, params).0
+    }
+
+    /// Backpropagates through each step once for both gradients
+    fn back_prop(&self, out_grad: &Matrix<f64>, input: &Matrix<f64>, _: &Matrix<f64>, params: MatrixSlice<f64>) -> (Matrix<f64>, Matrix<f64>) {
+        self.back_through_time(out_grad, input, params)
+    }
+
     /// Initializes weights using Xavier initialization
     ///
-    /// weights drawn from gaussian distribution with 0 mean and variance 2/(input_size+output_size)
+    /// The input and recurrent weights are drawn from gaussian distributions
+    /// with 0 mean and variance 2/(rows+N). The bias is initialized to zero.
     fn default_params(&self) -> Vec<f64> {
-        let mut distro = Normal::new(0.0, (2.0/(self.input_size+self.output_size) as f64).sqrt());
+        let size = self.hidden_size;
         let mut rng = thread_rng();
+        let mut xavier = |rows: usize| {
+            let mut distro = Normal::new(0.0, (2.0/(rows+size) as f64).sqrt());
+            Matrix::new(rows, size, (0..rows*size).map(|_| distro.sample(&mut rng))
+                                                  .collect::<Vec<f64>>())
+        };
+        let w_x = xavier(self.input_size);
+        let w_h = xavier(size);
 
-        (0..self.input_size*self.output_size).map(|_| distro.sample(&mut rng))
-                                             .collect()
+        Matrix::zeros(1, size).vcat(&w_x).vcat(&w_h).into_vec()
     }
 
     fn param_shape(&self) -> (usize, usize) {
-        (self.input_size, self.output_size)
+        (1 + self.input_size + self.hidden_size, self.hidden_size)
+    }
+
+    fn input_size(&self) -> Option<usize> {
+        Some(self.seq_len * (1 + self.input_size))
+    }
+
+    fn output_size(&self) -> Option<usize> {
+        Some(self.hidden_size)
     }
 }
 
@@ -183,3 +570,181 @@ impl<T: ActivationFunc> NetLayer for T {
         (0, 0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{NetLayer, Linear, Recurrent, Initializer};
+    use linalg::{Matrix, BaseMatrix};
+
+    /// Checks that the columns of the matrix are orthonormal.
+    fn assert_orthonormal_cols(mat: &Matrix<f64>) {
+        let gram = mat.transpose() * mat;
+        for i in 0..gram.rows() {
+            for j in 0..gram.cols() {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((gram[[i, j]] - expected).abs() < 1e-10,
+                        "entry ({}, {}) was {}", i, j, gram[[i, j]]);
+            }
+        }
+    }
+
+    #[test]
+    fn orthogonal_init_without_bias() {
+        let layer = Linear::without_bias(8, 5).with_initializer(Initializer::Orthogonal);
+        let (rows, cols) = layer.param_shape();
+        let weights = Matrix::new(rows, cols, layer.default_params());
+
+        assert_orthonormal_cols(&weights);
+    }
+
+    #[test]
+    fn orthogonal_init_with_bias() {
+        let layer = Linear::new(6, 6).with_initializer(Initializer::Orthogonal);
+        let (rows, cols) = layer.param_shape();
+        let params = Matrix::new(rows, cols, layer.default_params());
+
+        // The first row holds the bias terms.
+        assert!(params.row(0).iter().all(|&b| b == 0.0));
+
+        let weights = params.select_rows(&(1..rows).collect::<Vec<usize>>());
+        assert_orthonormal_cols(&weights);
+    }
+
+    #[test]
+    fn orthogonal_init_wide_layer() {
+        // With more outputs than inputs the rows are orthonormal instead.
+        let layer = Linear::without_bias(3, 7).with_initializer(Initializer::Orthogonal);
+        let (rows, cols) = layer.param_shape();
+        let weights = Matrix::new(rows, cols, layer.default_params());
+
+        assert_orthonormal_cols(&weights.transpose());
+    }
+
+    /// Fixed parameters for a recurrent layer, so the tests are repeatable.
+    fn recurrent_params(layer: &Recurrent) -> Matrix<f64> {
+        let (rows, cols) = layer.param_shape();
+        Matrix::new(rows, cols, (0..rows * cols).map(|i| ((i * 7 % 11) as f64 - 5.0) / 10.0)
+                                               .collect::<Vec<f64>>())
+    }
+
+    fn assert_close(a: &Matrix<f64>, b: &Matrix<f64>) {
+        assert_eq!((a.rows(), a.cols()), (b.rows(), b.cols()));
+        for (x, y) in a.data().iter().zip(b.data()) {
+            assert!((x - y).abs() < 1e-10, "{} != {}", x, y);
+        }
+    }
+
+    #[test]
+    fn recurrent_padding_ignored() {
+        let layer = Recurrent::new(2, 3, 4);
+        let params = recurrent_params(&layer);
+        let short = Matrix::new(2, 2, vec![0.5, -1.0, 2.0, 0.3]);
+
+        let mut padded = layer.pack_sequences(&[short.clone()]).unwrap();
+        let clean = layer.forward(&padded, params.as_slice()).unwrap();
+
+        // Replace the padding of the last two steps with junk
+        for j in 8..12 {
+            padded[[0, j]] = 7.0;
+        }
+        assert_close(&layer.forward(&padded, params.as_slice()).unwrap(), &clean);
+
+        let unpadded = Recurrent::new(2, 3, 2);
+        let input = unpadded.pack_sequences(&[short]).unwrap();
+        assert_close(&unpadded.forward(&input, params.as_slice()).unwrap(), &clean);
+    }
+
+    #[test]
+    fn recurrent_masked_batch_gradients() {
+        let layer = Recurrent::new(2, 3, 5);
+        let params = recurrent_params(&layer);
+
+        let short = Matrix::new(3, 2, vec![0.5, -1.0, 2.0, 0.3, -0.7, 1.1]);
+        let long = Matrix::new(5, 2, vec![1.0, 0.2, -0.4, 0.9, 1.5,
+                                          -2.0, 0.1, 0.6, -0.3, 0.8]);
+        let mut batch = layer.pack_sequences(&[short.clone(), long.clone()]).unwrap();
+        for j in 11..15 {
+            batch[[0, j]] = -3.0;
+        }
+        let out_grad = Matrix::new(2, 3, vec![0.3, -0.5, 1.0, -0.2, 0.4, 0.7]);
+
+        let output = layer.forward(&batch, params.as_slice()).unwrap();
+        let batch_params = layer.back_params(&out_grad, &batch, &output, params.as_slice());
+        let batch_input = layer.back_input(&out_grad, &batch, &output, params.as_slice());
+
+        // Each sequence on its own, at its own length
+        let mut alone_params = Matrix::zeros(params.rows(), params.cols());
+        for (i, seq) in [short, long].iter().enumerate() {
+            let alone = Recurrent::new(2, 3, seq.rows());
+            let input = alone.pack_sequences(&[seq.clone()]).unwrap();
+            let grad = out_grad.select_rows(&[i]);
+            let output = alone.forward(&input, params.as_slice()).unwrap();
+            assert_close(&output, &layer.forward(&batch, params.as_slice()).unwrap()
+                                        .select_rows(&[i]));
+
+            alone_params += alone.back_params(&grad, &input, &output, params.as_slice());
+
+            let input_grad = alone.back_input(&grad, &input, &output, params.as_slice());
+            for t in 0..seq.rows() {
+                assert_eq!(batch_input[[i, t]], 0.0);
+                for k in 0..2 {
+                    assert!((batch_input[[i, 5 + 2 * t + k]] -
+                             input_grad[[0, seq.rows() + 2 * t + k]]).abs() < 1e-10);
+                }
+            }
+            for j in 5 + 2 * seq.rows()..15 {
+                assert_eq!(batch_input[[i, j]], 0.0);
+            }
+        }
+
+        assert_close(&batch_params, &alone_params);
+
+        let (prop_params, prop_input) = layer.back_prop(&out_grad, &batch, &output, params.as_slice());
+        assert_close(&prop_params, &batch_params);
+        assert_close(&prop_input, &batch_input);
+    }
+
+    #[test]
+    fn recurrent_gradients_match_finite_differences() {
+        let layer = Recurrent::new(2, 2, 3);
+        let params = recurrent_params(&layer);
+        let input = layer.pack_sequences(&[Matrix::new(2, 2, vec![0.5, -1.0, 2.0, 0.3]),
+                                           Matrix::new(3, 2, vec![1.0, 0.2, -0.4, 0.9, 1.5, -2.0])])
+                         .unwrap();
+        let out_grad = Matrix::new(2, 2, vec![0.3, -0.5, 1.0, -0.2]);
+
+        // The cost is the sum of the outputs weighted by out_grad
+        let cost = |p: &Matrix<f64>| {
+            let output = layer.forward(&input, p.as_slice()).unwrap();
+            output.elemul(&out_grad).sum()
+        };
+
+        let output = layer.forward(&input, params.as_slice()).unwrap();
+        let grad = layer.back_params(&out_grad, &input, &output, params.as_slice());
+
+        let eps = 1e-6;
+        for i in 0..params.rows() {
+            for j in 0..params.cols() {
+                let mut plus = params.clone();
+                plus[[i, j]] += eps;
+                let mut minus = params.clone();
+                minus[[i, j]] -= eps;
+
+                let numeric = (cost(&plus) - cost(&minus)) / (2.0 * eps);
+                assert!((numeric - grad[[i, j]]).abs() < 1e-6,
+                        "entry ({}, {}): {} != {}", i, j, numeric, grad[[i, j]]);
+            }
+        }
+    }
+
+    #[test]
+    fn recurrent_invalid_mask() {
+        let layer = Recurrent::new(1, 2, 2);
+        let params = recurrent_params(&layer);
+        let input = Matrix::new(1, 4, vec![1.0, 0.5, 1.0, 2.0]);
+
+        assert!(layer.forward(&input, params.as_slice()).is_err());
+        assert!(layer.pack_sequences(&[Matrix::new(3, 1, vec![1.0, 2.0, 3.0])]).is_err());
+        assert!(layer.pack_sequences(&[Matrix::new(1, 2, vec![1.0, 2.0])]).is_err());
+    }
+}
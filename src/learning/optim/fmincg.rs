@@ -27,6 +27,8 @@
 //!
 //! - Conversion to Rust.
 //! - Length hard defaults to the max iterations.
+//! - Optional early stopping when the gradient norm is small.
+//! - Progress reporting after each successful line search.
 
 use learning::optim::{Optimizable, OptimAlgorithm};
 use linalg::Vector;
@@ -53,6 +55,22 @@ pub struct ConjugateGD {
 
     /// The default number of max iterations.
     pub iters: usize,
+    /// Stop once the norm of the gradient is smaller than `grad_tol`.
+    pub grad_tol: f64,
+}
+
+/// The progress of the conjugate gradient algorithm,
+/// reported after each successful line search.
+#[derive(Clone, Copy, Debug)]
+pub struct CGProgress<'a> {
+    /// The number of previous successful line searches.
+    pub iter: usize,
+    /// The current parameters.
+    pub params: &'a [f64],
+    /// The cost at the current parameters.
+    pub cost: f64,
+    /// The norm of the gradient at the current parameters.
+    pub grad_norm: f64,
 }
 
 /// The default Conjugate GD algorithm.
@@ -66,6 +84,8 @@ pub struct ConjugateGD {
 /// - max = 20
 /// - ration = 100
 /// - iters = 100
+/// - grad_tol = 0, so that the algorithm only stops
+///   after `iters` line searches or when a line search fails.
 impl Default for ConjugateGD {
     fn default() -> ConjugateGD {
         ConjugateGD {
@@ -76,6 +96,7 @@ impl Default for ConjugateGD {
             max: 20,
             ratio: 100.0,
             iters: 100,
+            grad_tol: 0f64,
         }
     }
 }
@@ -87,12 +108,81 @@ impl<M: Optimizable> OptimAlgorithm<M> for ConjugateGD {
                 inputs: &M::Inputs,
                 targets: &M::Targets)
                 -> Vec<f64> {
+        self.optimize_with_callback(model, start, inputs, targets, |_, _| {})
+    }
+
+    /// Calls `on_epoch` after each successful line search.
+    fn optimize_with_callback<F>(&self,
+                                 model: &M,
+                                 start: &[f64],
+                                 inputs: &M::Inputs,
+                                 targets: &M::Targets,
+                                 mut on_epoch: F)
+                                 -> Vec<f64>
+        where F: FnMut(usize, &[f64])
+    {
+        self.optimize_with_monitor(model, start, inputs, targets, |p| on_epoch(p.iter, p.params))
+    }
+}
+
+impl ConjugateGD {
+    /// Return the optimized parameters, calling `monitor` with the cost
+    /// and gradient norm after each successful line search.
+    ///
+    /// The costs reported are decreasing. Comparing the last gradient
+    /// norm with `grad_tol` shows whether the algorithm converged or
+    /// stopped at the iteration limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::Optimizable;
+    /// use rusty_machine::learning::optim::fmincg::ConjugateGD;
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// // Minimizes (x - 3)^2
+    /// struct Parabola;
+    ///
+    /// impl Optimizable for Parabola {
+    ///     type Inputs = Matrix<f64>;
+    ///     type Targets = Matrix<f64>;
+    ///
+    ///     fn compute_grad(&self, p: &[f64], _: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+    ///         ((p[0] - 3.0) * (p[0] - 3.0), vec![2.0 * (p[0] - 3.0)])
+    ///     }
+    /// }
+    ///
+    /// let cgd = ConjugateGD { grad_tol: 1e-8, ..ConjugateGD::default() };
+    /// let data = Matrix::zeros(1, 1);
+    ///
+    /// let mut costs = Vec::new();
+    /// let params = cgd.optimize_with_monitor(&Parabola, &[10.0], &data, &data,
+    ///                                        |p| costs.push(p.cost));
+    ///
+    /// assert!((params[0] - 3.0).abs() < 1e-6);
+    /// assert!(!costs.is_empty());
+    /// ```
+    pub fn optimize_with_monitor<M, F>(&self,
+                                       model: &M,
+                                       start: &[f64],
+                                       inputs: &M::Inputs,
+                                       targets: &M::Targets,
+                                       mut monitor: F)
+                                       -> Vec<f64>
+        where M: Optimizable,
+              F: FnMut(&CGProgress)
+    {
         let mut i = 0usize;
+        let mut n_success = 0usize;
         let mut ls_failed = false;
 
         let (mut f1, vec_df1) = model.compute_grad(start, inputs, targets);
         let mut df1 = Vector::new(vec_df1);
 
+        if df1.dot(&df1).sqrt() < self.grad_tol {
+            return start.to_vec();
+        }
+
         // The reduction in the function. Can also be specified as part of length
         let red = 1f64;
 
@@ -249,6 +339,19 @@ impl<M: Optimizable> OptimAlgorithm<M> for ConjugateGD {
 
                 d1 = d2;
                 ls_failed = false;
+
+                let grad_norm = df1.dot(&df1).sqrt();
+                monitor(&CGProgress {
+                    iter: n_success,
+                    params: x.data(),
+                    cost: f1,
+                    grad_norm,
+                });
+                n_success += 1;
+
+                if grad_norm < self.grad_tol {
+                    break;
+                }
             } else {
                 x = x0;
                 f1 = f0;
@@ -7,21 +7,190 @@
 //! Currently standard batch gradient descent is the only implemented
 //! optimization algorithm but there is flexibility to introduce new
 //! algorithms and git them into the same scheme easily.
+//!
+//! A `Checkpoint` runs any of the algorithms while periodically saving
+//! the current parameters to a file. The saved parameters can be read
+//! back with `load_checkpoint`.
+//!
+//! The step sizes of `GradientDesc` and `StochasticGD` can follow a
+//! schedule implementing `LearningRate`, using `with_schedule`.
+//!
+//! Each algorithm passes the parameters to the callback given to
+//! `optimize_with_callback` at the end of every pass through the data.
+//! The number of passes, and the number of updates in each pass, are
+//! available through the `EpochAlgorithm` trait.
 
-use learning::optim::{Optimizable, OptimAlgorithm};
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
+use learning::optim::{EpochAlgorithm, Optimizable, OptimAlgorithm};
+use learning::optim::schedule::LearningRate;
 use linalg::Vector;
 use linalg::{Matrix, BaseMatrix};
 use rulinalg::utils;
 
 use learning::toolkit::rand_utils;
 
+use rand::{SeedableRng, StdRng};
+
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
 const LEARNING_EPS: f64 = 1e-20;
 
+/// Periodically saves the parameters during optimization.
+///
+/// The parameters are saved at the end of every `every` epochs of the
+/// algorithm, which are passes through the data for the stochastic
+/// algorithms and single updates for `GradientDesc`. The file contains
+/// one parameter per line and can be read back using `load_checkpoint`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rusty_machine::learning::optim::Optimizable;
+/// use rusty_machine::learning::optim::grad_desc::{Checkpoint, GradientDesc};
+/// use rusty_machine::linalg::Matrix;
+///
+/// // Minimizes the squared distance of the parameters from 1.
+/// struct SqDist;
+///
+/// impl Optimizable for SqDist {
+///     type Inputs = Matrix<f64>;
+///     type Targets = Matrix<f64>;
+///
+///     fn compute_grad(&self, params: &[f64], _: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+///         let cost = params.iter().map(|p| (p - 1.0) * (p - 1.0)).sum();
+///         (cost, params.iter().map(|p| 2.0 * (p - 1.0)).collect())
+///     }
+/// }
+///
+/// let gd = GradientDesc::new(0.3, 10000);
+/// let checkpoint = Checkpoint::new(1000, "weights.txt");
+///
+/// let data = Matrix::zeros(1, 1);
+/// let params = checkpoint.optimize(&gd, &SqDist, &[0.0; 3], &data, &data).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    /// The number of epochs between saves.
+    every: usize,
+    /// The file the parameters are saved to.
+    path: PathBuf,
+}
+
+impl Checkpoint {
+    /// Constructs a checkpoint saving to `path` every `every` epochs.
+    ///
+    /// # Panics
+    ///
+    /// - `every` is zero.
+    pub fn new<P: AsRef<Path>>(every: usize, path: P) -> Checkpoint {
+        assert!(every > 0, "The checkpoint interval must be greater than 0.");
+
+        Checkpoint {
+            every,
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The number of epochs between saves.
+    pub fn every(&self) -> usize {
+        self.every
+    }
+
+    /// The file the parameters are saved to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Runs the optimization algorithm, saving the parameters
+    /// at the end of every `every` epochs.
+    ///
+    /// # Failures
+    ///
+    /// - A checkpoint file could not be written. The optimization
+    ///   still runs to the end, but no further checkpoints are saved.
+    pub fn optimize<M, A>(&self,
+                          alg: &A,
+                          model: &M,
+                          start: &[f64],
+                          inputs: &M::Inputs,
+                          targets: &M::Targets)
+                          -> LearningResult<Vec<f64>>
+        where M: Optimizable,
+              A: OptimAlgorithm<M>
+    {
+        let mut save_error = None;
+        let params = alg.optimize_with_callback(model, start, inputs, targets, |iter, params| {
+            if save_error.is_none() && iter % self.every == self.every - 1 {
+                save_error = self.save(params).err();
+            }
+        });
+
+        match save_error {
+            Some(e) => {
+                Err(Error::new(ErrorKind::Io,
+                               format!("Failed to write checkpoint file {}: {}",
+                                       self.path.display(),
+                                       e)))
+            }
+            None => Ok(params),
+        }
+    }
+
+    /// Writes the parameters to a temporary file and renames it over
+    /// the checkpoint, so an interrupted write never corrupts it.
+    fn save(&self, params: &[f64]) -> io::Result<()> {
+        let mut tmp_name = self.path.as_os_str().to_owned();
+        tmp_name.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_name);
+
+        {
+            let mut file = File::create(&tmp_path)?;
+            for p in params {
+                writeln!(file, "{:?}", p)?;
+            }
+            file.sync_all()?;
+        }
+
+        fs::rename(&tmp_path, &self.path)
+    }
+}
+
+/// Loads the parameters saved by a checkpointing optimization algorithm.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rusty_machine::learning::optim::grad_desc::load_checkpoint;
+///
+/// let params = load_checkpoint("weights.txt").unwrap();
+/// ```
+///
+/// # Failures
+///
+/// - The file cannot be read.
+/// - The file contains a line which is not a number.
+pub fn load_checkpoint<P: AsRef<Path>>(path: P) -> io::Result<Vec<f64>> {
+    let file = File::open(path)?;
+
+    BufReader::new(file)
+        .lines()
+        .map(|line| {
+            line?.trim().parse::<f64>().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        })
+        .collect()
+}
+
 /// Batch Gradient Descent algorithm
+///
+/// The step size is a constant `f64` by default,
+/// or any other `LearningRate` schedule.
 #[derive(Clone, Copy, Debug)]
-pub struct GradientDesc {
+pub struct GradientDesc<L = f64> {
     /// The step-size for the gradient descent steps.
-    alpha: f64,
+    alpha: L,
     /// The number of iterations to run.
     iters: usize,
 }
@@ -65,48 +234,147 @@ impl GradientDesc {
     }
 }
 
-impl<M: Optimizable> OptimAlgorithm<M> for GradientDesc {
+impl<L: LearningRate> GradientDesc<L> {
+    /// Construct a gradient descent algorithm whose step
+    /// size in each iteration is given by a schedule.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::GradientDesc;
+    /// use rusty_machine::learning::optim::schedule::CosineAnnealing;
+    ///
+    /// let gd = GradientDesc::with_schedule(CosineAnnealing::new(0.5, 0.01, 100), 1000);
+    /// ```
+    pub fn with_schedule(schedule: L, iters: usize) -> GradientDesc<L> {
+        GradientDesc {
+            alpha: schedule,
+            iters,
+        }
+    }
+}
+
+impl<L> EpochAlgorithm for GradientDesc<L> {
+    fn epochs(&self) -> usize {
+        self.iters
+    }
+
+    fn set_epochs(&mut self, epochs: usize) {
+        self.iters = epochs;
+    }
+
+    /// Each iteration makes one update using all of the data.
+    fn steps_per_epoch(&self, _n_rows: usize) -> usize {
+        1
+    }
+}
+
+impl<M: Optimizable, L: LearningRate> OptimAlgorithm<M> for GradientDesc<L> {
     fn optimize(&self,
                 model: &M,
                 start: &[f64],
                 inputs: &M::Inputs,
                 targets: &M::Targets)
                 -> Vec<f64> {
+        self.optimize_with_callback(model, start, inputs, targets, |_, _| {})
+    }
+
+    fn optimize_with_callback<F>(&self,
+                                 model: &M,
+                                 start: &[f64],
+                                 inputs: &M::Inputs,
+                                 targets: &M::Targets,
+                                 mut on_epoch: F)
+                                 -> Vec<f64>
+        where F: FnMut(usize, &[f64])
+    {
 
         // Create the initial optimal parameters
         let mut optimizing_val = Vector::new(start.to_vec());
         // The cost at the start of each iteration
         let mut start_iter_cost = 0f64;
 
-        for _ in 0..self.iters {
+        for iter in 0..self.iters {
             // Compute the cost and gradient for the current parameters
             let (cost, grad) = model.compute_grad(optimizing_val.data(), inputs, targets);
 
+            // Stop at the parameters where the cost diverged
+            if cost.is_nan() {
+                break;
+            }
+
             // Early stopping
             if (start_iter_cost - cost).abs() < LEARNING_EPS {
                 break;
             } else {
                 // Update the optimal parameters using gradient descent
-                optimizing_val = &optimizing_val - Vector::new(grad) * self.alpha;
+                optimizing_val = &optimizing_val - Vector::new(grad) * self.alpha.rate(iter);
                 // Update the latest cost
                 start_iter_cost = cost;
             }
+
+            on_epoch(iter, optimizing_val.data());
         }
         optimizing_val.into_vec()
     }
 }
 
+/// Data which can be split into batches of rows.
+///
+/// Used by the stochastic optimization algorithms to
+/// select the inputs and targets of each mini-batch.
+pub trait BatchData {
+    /// The number of rows in the data.
+    fn batch_rows(&self) -> usize;
+
+    /// Select the given rows from the data.
+    fn select_batch(&self, rows: &[usize]) -> Self;
+}
+
+impl BatchData for Matrix<f64> {
+    fn batch_rows(&self) -> usize {
+        self.rows()
+    }
+
+    fn select_batch(&self, rows: &[usize]) -> Matrix<f64> {
+        self.select_rows(rows)
+    }
+}
+
+impl BatchData for Vector<f64> {
+    fn batch_rows(&self) -> usize {
+        self.size()
+    }
+
+    fn select_batch(&self, rows: &[usize]) -> Vector<f64> {
+        Vector::new(rows.iter().map(|&i| self[i]).collect::<Vec<f64>>())
+    }
+}
+
 /// Stochastic Gradient Descent algorithm.
 ///
 /// Uses basic momentum to control the learning rate.
+///
+/// The data points are shuffled before each pass. The orders can be
+/// recorded with `optimize_recording_orders`, or replaced by a fixed
+/// order with `optimize_in_order`, to reproduce the mini-batches.
+///
+/// The step size is a constant `f64` by default,
+/// or any other `LearningRate` schedule.
 #[derive(Clone, Copy, Debug)]
-pub struct StochasticGD {
+pub struct StochasticGD<L = f64> {
     /// Controls the momentum of the descent
     alpha: f64,
     /// The square root of the raw learning rate.
-    mu: f64,
+    mu: L,
     /// The number of passes through the data.
     iters: usize,
+    /// The number of data points in each mini-batch.
+    batch_size: usize,
+    /// The number of mini-batches whose gradients are combined in each update.
+    accumulation_steps: usize,
+    /// The seed for shuffling the data points in each pass.
+    seed: Option<u64>,
 }
 
 /// The default Stochastic GD algorithm.
@@ -116,12 +384,17 @@ pub struct StochasticGD {
 /// - alpha = 0.1
 /// - mu = 0.1
 /// - iters = 20
+/// - batch_size = 1
+/// - accumulation_steps = 1
 impl Default for StochasticGD {
     fn default() -> StochasticGD {
         StochasticGD {
             alpha: 0.1,
             mu: 0.1,
             iters: 20,
+            batch_size: 1,
+            accumulation_steps: 1,
+            seed: None,
         }
     }
 }
@@ -147,50 +420,306 @@ impl StochasticGD {
             alpha: alpha,
             mu: mu,
             iters: iters,
+            batch_size: 1,
+            accumulation_steps: 1,
+            seed: None,
         }
     }
 }
 
-impl<M> OptimAlgorithm<M> for StochasticGD
-    where M: Optimizable<Inputs = Matrix<f64>, Targets = Matrix<f64>>
-{
-    fn optimize(&self,
-                model: &M,
-                start: &[f64],
-                inputs: &M::Inputs,
-                targets: &M::Targets)
-                -> Vec<f64> {
+impl<L: LearningRate> StochasticGD<L> {
+    /// Construct a stochastic gradient descent algorithm whose
+    /// step size `mu` in each pass is given by a schedule.
+    ///
+    /// As in `new`, the parameters move by `mu` times the momentum
+    /// term, which itself grows by `mu` times the gradient.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::StochasticGD;
+    /// use rusty_machine::learning::optim::schedule::CosineAnnealing;
+    ///
+    /// // Anneal the step size over cycles of 10 passes.
+    /// let schedule = CosineAnnealing::new(0.3, 0.01, 10).with_warm_restarts(true);
+    /// let sgd = StochasticGD::with_schedule(0.1, schedule, 50);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `alpha` is not greater than 0.
+    pub fn with_schedule(alpha: f64, schedule: L, iters: usize) -> StochasticGD<L> {
+        assert!(alpha > 0f64, "The momentum (alpha) must be greater than 0.");
+
+        StochasticGD {
+            alpha,
+            mu: schedule,
+            iters,
+            batch_size: 1,
+            accumulation_steps: 1,
+            seed: None,
+        }
+    }
+
+    /// Set the number of data points used in each update.
+    ///
+    /// By default each update uses a single data point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::StochasticGD;
+    ///
+    /// // Update the parameters using mini-batches of 10 points.
+    /// let sgd = StochasticGD::new(0.1, 0.3, 5).with_batch_size(10);
+    /// ```
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "The batch size must be greater than 0.");
+
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Combine the gradients of `steps` consecutive mini-batches
+    /// before each update.
+    ///
+    /// The gradients are averaged, weighting each mini-batch by its
+    /// size. For models whose gradient is the mean over the data points,
+    /// such as the neural networks, this gives the same updates as
+    /// mini-batches `steps` times larger while only computing the
+    /// gradient of one mini-batch at a time. The last update of each pass
+    /// uses any remaining mini-batches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::StochasticGD;
+    ///
+    /// // Update the parameters once every four mini-batches of 10 points,
+    /// // as with mini-batches of 40 points.
+    /// let sgd = StochasticGD::new(0.1, 0.3, 5).with_batch_size(10).with_accumulation_steps(4);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `steps` is zero.
+    pub fn with_accumulation_steps(mut self, steps: usize) -> Self {
+        assert!(steps > 0, "The number of accumulation steps must be greater than 0.");
+
+        self.accumulation_steps = steps;
+        self
+    }
+
+    /// The number of mini-batches whose gradients are combined in each update.
+    pub fn accumulation_steps(&self) -> usize {
+        self.accumulation_steps
+    }
+
+    /// Shuffle the data points using a random number generator seeded
+    /// with `seed`, so that repeated optimizations of the same model and
+    /// data visit the points in the same orders and give identical results.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::StochasticGD;
+    ///
+    /// let sgd = StochasticGD::new(0.1, 0.3, 5).with_seed(42);
+    /// ```
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The seed used to shuffle the data points, if any.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Optimize the model, also returning the order the data points
+    /// were visited in during each pass.
+    ///
+    /// The mini-batches of each pass are consecutive chunks of its order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::Optimizable;
+    /// use rusty_machine::learning::optim::grad_desc::StochasticGD;
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// // Minimizes the sum of the inputs times the parameter.
+    /// struct Linear;
+    ///
+    /// impl Optimizable for Linear {
+    ///     type Inputs = Matrix<f64>;
+    ///     type Targets = Matrix<f64>;
+    ///
+    ///     fn compute_grad(&self, p: &[f64], x: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+    ///         let sum = x.data().iter().sum::<f64>();
+    ///         (sum * p[0], vec![sum])
+    ///     }
+    /// }
+    ///
+    /// let sgd = StochasticGD::new(0.1, 0.1, 3).with_batch_size(2);
+    /// let data = Matrix::new(4, 1, vec![1.0, 2.0, 3.0, 4.0]);
+    ///
+    /// let (_, orders) = sgd.optimize_recording_orders(&Linear, &[0.0], &data, &data);
+    /// assert_eq!(orders.len(), 3);
+    ///
+    /// // The mini-batches of the first pass.
+    /// let batches = orders[0].chunks(2).collect::<Vec<_>>();
+    /// ```
+    pub fn optimize_recording_orders<M>(&self,
+                                        model: &M,
+                                        start: &[f64],
+                                        inputs: &M::Inputs,
+                                        targets: &M::Targets)
+                                        -> (Vec<f64>, Vec<Vec<usize>>)
+        where M: Optimizable,
+              M::Inputs: BatchData,
+              M::Targets: BatchData
+    {
+        let mut orders = Vec::new();
+        let params = self.run(model, start, inputs, targets, None, |_, _, order| {
+            orders.push(order.to_vec());
+        });
+        (params, orders)
+    }
+
+    /// Optimize the model, visiting the data points in a fixed order in
+    /// every pass instead of shuffling them.
+    ///
+    /// This makes the mini-batches reproducible, e.g. to debug a bad batch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::Optimizable;
+    /// use rusty_machine::learning::optim::grad_desc::StochasticGD;
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// // Minimizes the sum of the inputs times the parameter.
+    /// struct Linear;
+    ///
+    /// impl Optimizable for Linear {
+    ///     type Inputs = Matrix<f64>;
+    ///     type Targets = Matrix<f64>;
+    ///
+    ///     fn compute_grad(&self, p: &[f64], x: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+    ///         let sum = x.data().iter().sum::<f64>();
+    ///         (sum * p[0], vec![sum])
+    ///     }
+    /// }
+    ///
+    /// let sgd = StochasticGD::new(0.1, 0.1, 3);
+    /// let data = Matrix::new(4, 1, vec![1.0, 2.0, 3.0, 4.0]);
+    ///
+    /// // Visit the four data points in reverse order.
+    /// let params = sgd.optimize_in_order(&Linear, &[0.0], &data, &data, &[3, 2, 1, 0]).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The order is not a permutation of the row indices of the data.
+    pub fn optimize_in_order<M>(&self,
+                                model: &M,
+                                start: &[f64],
+                                inputs: &M::Inputs,
+                                targets: &M::Targets,
+                                order: &[usize])
+                                -> LearningResult<Vec<f64>>
+        where M: Optimizable,
+              M::Inputs: BatchData,
+              M::Targets: BatchData
+    {
+        let mut sorted = order.to_vec();
+        sorted.sort();
+        if !sorted.into_iter().eq(0..inputs.batch_rows()) {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  format!("The shuffle order must be a permutation of the {} data rows.",
+                                          inputs.batch_rows())));
+        }
+
+        Ok(self.run(model, start, inputs, targets, Some(order), |_, _, _| {}))
+    }
 
+    /// Runs the optimization, visiting the data points in `fixed_order`
+    /// in every pass if it is given and shuffling them otherwise.
+    ///
+    /// `on_pass` is called with the pass index, the parameters and the
+    /// order of the data points at the end of each pass.
+    fn run<M, F>(&self,
+                 model: &M,
+                 start: &[f64],
+                 inputs: &M::Inputs,
+                 targets: &M::Targets,
+                 fixed_order: Option<&[usize]>,
+                 mut on_pass: F)
+                 -> Vec<f64>
+        where M: Optimizable,
+              M::Inputs: BatchData,
+              M::Targets: BatchData,
+              F: FnMut(usize, &[f64], &[usize])
+    {
         // Create the initial optimal parameters
         let mut optimizing_val = Vector::new(start.to_vec());
         // Create the momentum based gradient distance
         let mut delta_w = Vector::zeros(start.len());
 
         // Set up the indices for permutation
-        let mut permutation = (0..inputs.rows()).collect::<Vec<_>>();
+        let mut permutation = match fixed_order {
+            Some(order) => order.to_vec(),
+            None => (0..inputs.batch_rows()).collect::<Vec<_>>(),
+        };
+        // A single seeded generator is used for every pass
+        let mut seeded_rng = self.seed.map(|seed| {
+            StdRng::from_seed(&[seed as u32 as usize, (seed >> 32) as usize])
+        });
         // The cost at the start of each iteration
         let mut start_iter_cost = 0f64;
 
-        for _ in 0..self.iters {
+        for iter in 0..self.iters {
             // The cost at the end of each stochastic gd pass
             let mut end_cost = 0f64;
             // Permute the indices
-            rand_utils::in_place_fisher_yates(&mut permutation);
-            for i in &permutation {
-                // Compute the cost and gradient for this data pair
-                let (cost, vec_data) = model.compute_grad(optimizing_val.data(),
-                                                          &inputs.select_rows(&[*i]),
-                                                          &targets.select_rows(&[*i]));
+            if fixed_order.is_none() {
+                match seeded_rng {
+                    Some(ref mut rng) => rand_utils::in_place_fisher_yates_with_rng(&mut permutation, rng),
+                    None => rand_utils::in_place_fisher_yates(&mut permutation),
+                }
+            }
+            // The step size for this pass
+            let mu = self.mu.rate(iter);
+            for group in permutation.chunks(self.batch_size * self.accumulation_steps) {
+                // Average the gradients of the batches in this group by their size
+                let mut grad = Vector::zeros(start.len());
+                for batch in group.chunks(self.batch_size) {
+                    // Compute the cost and gradient for this batch
+                    let (cost, vec_data) = model.compute_grad(optimizing_val.data(),
+                                                              &inputs.select_batch(batch),
+                                                              &targets.select_batch(batch));
+
+                    grad += Vector::new(vec_data) * batch.len() as f64;
+                    // Set the end cost (this is only used after the last iteration)
+                    end_cost += cost * batch.len() as f64;
+                }
+                grad /= group.len() as f64;
 
                 // Compute the difference in gradient using momentum
-                delta_w = Vector::new(vec_data) * self.mu + &delta_w * self.alpha;
+                delta_w = grad * mu + &delta_w * self.alpha;
                 // Update the parameters
-                optimizing_val = &optimizing_val - &delta_w * self.mu;
-                // Set the end cost (this is only used after the last iteration)
-                end_cost += cost;
+                optimizing_val = &optimizing_val - &delta_w * mu;
             }
 
-            end_cost /= inputs.rows() as f64;
+            end_cost /= inputs.batch_rows() as f64;
+
+            // Stop once the cost has diverged
+            if end_cost.is_nan() {
+                break;
+            }
+
+            on_pass(iter, optimizing_val.data(), &permutation);
 
             // Early stopping
             if (start_iter_cost - end_cost).abs() < LEARNING_EPS {
@@ -204,6 +733,50 @@ impl<M> OptimAlgorithm<M> for StochasticGD
     }
 }
 
+impl<L> EpochAlgorithm for StochasticGD<L> {
+    fn epochs(&self) -> usize {
+        self.iters
+    }
+
+    fn set_epochs(&mut self, epochs: usize) {
+        self.iters = epochs;
+    }
+
+    /// Each pass makes one update per group of accumulated mini-batches,
+    /// including a smaller final group.
+    fn steps_per_epoch(&self, n_rows: usize) -> usize {
+        n_rows.div_ceil(self.batch_size * self.accumulation_steps)
+    }
+}
+
+impl<M, L> OptimAlgorithm<M> for StochasticGD<L>
+    where L: LearningRate,
+          M: Optimizable,
+          M::Inputs: BatchData,
+          M::Targets: BatchData
+{
+    fn optimize(&self,
+                model: &M,
+                start: &[f64],
+                inputs: &M::Inputs,
+                targets: &M::Targets)
+                -> Vec<f64> {
+        self.optimize_with_callback(model, start, inputs, targets, |_, _| {})
+    }
+
+    fn optimize_with_callback<F>(&self,
+                                 model: &M,
+                                 start: &[f64],
+                                 inputs: &M::Inputs,
+                                 targets: &M::Targets,
+                                 mut on_epoch: F)
+                                 -> Vec<f64>
+        where F: FnMut(usize, &[f64])
+    {
+        self.run(model, start, inputs, targets, None, |iter, params, _| on_epoch(iter, params))
+    }
+}
+
 /// Adaptive Gradient Descent
 ///
 /// The adaptive gradient descent algorithm (Duchi et al. 2010).
@@ -249,6 +822,21 @@ impl Default for AdaGrad {
     }
 }
 
+impl EpochAlgorithm for AdaGrad {
+    fn epochs(&self) -> usize {
+        self.iters
+    }
+
+    fn set_epochs(&mut self, epochs: usize) {
+        self.iters = epochs;
+    }
+
+    /// Each pass makes one update per data point.
+    fn steps_per_epoch(&self, n_rows: usize) -> usize {
+        n_rows
+    }
+}
+
 impl<M: Optimizable<Inputs = Matrix<f64>, Targets = Matrix<f64>>> OptimAlgorithm<M> for AdaGrad {
     fn optimize(&self,
                 model: &M,
@@ -256,6 +844,18 @@ impl<M: Optimizable<Inputs = Matrix<f64>, Targets = Matrix<f64>>> OptimAlgorithm
                 inputs: &M::Inputs,
                 targets: &M::Targets)
                 -> Vec<f64> {
+        self.optimize_with_callback(model, start, inputs, targets, |_, _| {})
+    }
+
+    fn optimize_with_callback<F>(&self,
+                                 model: &M,
+                                 start: &[f64],
+                                 inputs: &M::Inputs,
+                                 targets: &M::Targets,
+                                 mut on_epoch: F)
+                                 -> Vec<f64>
+        where F: FnMut(usize, &[f64])
+    {
 
         // Initialize the adaptive scaling
         let mut ada_s = Vector::zeros(start.len());
@@ -267,7 +867,7 @@ impl<M: Optimizable<Inputs = Matrix<f64>, Targets = Matrix<f64>>> OptimAlgorithm
         // The cost at the start of each iteration
         let mut start_iter_cost = 0f64;
 
-        for _ in 0..self.iters {
+        for iter in 0..self.iters {
             // The cost at the end of each stochastic gd pass
             let mut end_cost = 0f64;
             // Permute the indices
@@ -291,6 +891,13 @@ impl<M: Optimizable<Inputs = Matrix<f64>, Targets = Matrix<f64>>> OptimAlgorithm
             }
             end_cost /= inputs.rows() as f64;
 
+            // Stop once the cost has diverged
+            if end_cost.is_nan() {
+                break;
+            }
+
+            on_epoch(iter, optimizing_val.data());
+
             // Early stopping
             if (start_iter_cost - end_cost).abs() < LEARNING_EPS {
                 break;
@@ -363,6 +970,21 @@ impl RMSProp {
     }
 }
 
+impl EpochAlgorithm for RMSProp {
+    fn epochs(&self) -> usize {
+        self.iters
+    }
+
+    fn set_epochs(&mut self, epochs: usize) {
+        self.iters = epochs;
+    }
+
+    /// Each pass makes one update per data point.
+    fn steps_per_epoch(&self, n_rows: usize) -> usize {
+        n_rows
+    }
+}
+
 impl<M> OptimAlgorithm<M> for RMSProp
     where M: Optimizable<Inputs = Matrix<f64>, Targets = Matrix<f64>> {
     fn optimize(&self,
@@ -371,6 +993,18 @@ impl<M> OptimAlgorithm<M> for RMSProp
                 inputs: &M::Inputs,
                 targets: &M::Targets)
                 -> Vec<f64> {
+        self.optimize_with_callback(model, start, inputs, targets, |_, _| {})
+    }
+
+    fn optimize_with_callback<F>(&self,
+                                 model: &M,
+                                 start: &[f64],
+                                 inputs: &M::Inputs,
+                                 targets: &M::Targets,
+                                 mut on_epoch: F)
+                                 -> Vec<f64>
+        where F: FnMut(usize, &[f64])
+    {
         // Initial parameters
         let mut params = Vector::new(start.to_vec());
         // Running average of squared gradients
@@ -381,7 +1015,7 @@ impl<M> OptimAlgorithm<M> for RMSProp
         // The cost from the previous iteration
         let mut prev_cost = 0f64;
 
-        for _ in 0..self.iters {
+        for iter in 0..self.iters {
             // The cost at end of each pass
             let mut end_cost = 0f64;
             // Permute the vertices
@@ -405,6 +1039,13 @@ impl<M> OptimAlgorithm<M> for RMSProp
             }
             end_cost /= inputs.rows() as f64;
 
+            // Stop once the cost has diverged
+            if end_cost.is_nan() {
+                break;
+            }
+
+            on_epoch(iter, params.data());
+
             // Early stopping
             if (prev_cost - end_cost).abs() < LEARNING_EPS {
                 break;
@@ -416,13 +1057,622 @@ impl<M> OptimAlgorithm<M> for RMSProp
     }
 }
 
-#[cfg(test)]
-mod tests {
-
-    use super::{GradientDesc, StochasticGD, AdaGrad, RMSProp};
+/// Adam
+///
+/// The Adam algorithm (Kingma and Ba 2014), which scales each step by
+/// running averages of the gradient and of the squared gradient.
+///
+/// With `amsgrad` set the step is scaled by the largest average of the
+/// squared gradient seen so far (Reddi et al. 2018). The effective
+/// learning rate then never increases, which fixes the failure of Adam
+/// to converge on problems where large gradients are rare.
+#[derive(Debug, Clone, Copy)]
+pub struct Adam {
+    /// The base step size of gradient descent steps
+    learning_rate: f64,
+    /// Decay rate of the running average of gradients
+    beta1: f64,
+    /// Decay rate of the running average of squared gradients
+    beta2: f64,
+    /// Small value used to avoid divide by zero
+    epsilon: f64,
+    /// The number of passes through the data
+    iters: usize,
+    /// Whether to use the maximum of past squared gradient averages
+    amsgrad: bool,
+}
 
-    #[test]
-    #[should_panic]
+/// The default Adam configuration
+///
+/// The defaults are:
+///
+/// - learning_rate = 0.001
+/// - beta1 = 0.9
+/// - beta2 = 0.999
+/// - epsilon = 1.0e-8
+/// - iters = 50
+/// - amsgrad = false
+impl Default for Adam {
+    fn default() -> Adam {
+        Adam {
+            learning_rate: 0.001,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1.0e-8,
+            iters: 50,
+            amsgrad: false,
+        }
+    }
+}
+
+impl Adam {
+    /// Construct an Adam algorithm.
+    ///
+    /// Requires learning rate, the decay rates of the gradient and
+    /// squared gradient averages, epsilon, and iteration count.
+    ///
+    /// #Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::Adam;
+    ///
+    /// let adam = Adam::new(0.01, 0.9, 0.999, 1e-8, 20);
+    /// ```
+    pub fn new(learning_rate: f64, beta1: f64, beta2: f64, epsilon: f64, iters: usize) -> Adam {
+        validate_adam_params(learning_rate, beta1, beta2, epsilon);
+
+        Adam {
+            learning_rate,
+            beta1,
+            beta2,
+            epsilon,
+            iters,
+            amsgrad: false,
+        }
+    }
+
+    /// Sets whether to use the AMSGrad variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::Adam;
+    ///
+    /// let adam = Adam::default().with_amsgrad(true);
+    /// assert!(adam.amsgrad());
+    /// ```
+    pub fn with_amsgrad(mut self, amsgrad: bool) -> Adam {
+        self.amsgrad = amsgrad;
+        self
+    }
+
+    /// Whether the AMSGrad variant is used.
+    pub fn amsgrad(&self) -> bool {
+        self.amsgrad
+    }
+}
+
+impl EpochAlgorithm for Adam {
+    fn epochs(&self) -> usize {
+        self.iters
+    }
+
+    fn set_epochs(&mut self, epochs: usize) {
+        self.iters = epochs;
+    }
+
+    /// Each pass makes one update per data point.
+    fn steps_per_epoch(&self, n_rows: usize) -> usize {
+        n_rows
+    }
+}
+
+impl<M> OptimAlgorithm<M> for Adam
+    where M: Optimizable<Inputs = Matrix<f64>, Targets = Matrix<f64>> {
+    fn optimize(&self,
+                model: &M,
+                start: &[f64],
+                inputs: &M::Inputs,
+                targets: &M::Targets)
+                -> Vec<f64> {
+        self.optimize_with_callback(model, start, inputs, targets, |_, _| {})
+    }
+
+    fn optimize_with_callback<F>(&self,
+                                 model: &M,
+                                 start: &[f64],
+                                 inputs: &M::Inputs,
+                                 targets: &M::Targets,
+                                 on_epoch: F)
+                                 -> Vec<f64>
+        where F: FnMut(usize, &[f64])
+    {
+        let config = MomentConfig {
+            learning_rate: self.learning_rate,
+            beta1: self.beta1,
+            beta2: self.beta2,
+            epsilon: self.epsilon,
+            iters: self.iters,
+            amsgrad: self.amsgrad,
+            nesterov: false,
+        };
+        config.optimize(model, start, inputs, targets, on_epoch)
+    }
+}
+
+/// Nadam
+///
+/// The Nadam algorithm (Dozat 2016), which is Adam with
+/// Nesterov momentum applied to the average of the gradients.
+#[derive(Debug, Clone, Copy)]
+pub struct Nadam {
+    /// The base step size of gradient descent steps
+    learning_rate: f64,
+    /// Decay rate of the running average of gradients
+    beta1: f64,
+    /// Decay rate of the running average of squared gradients
+    beta2: f64,
+    /// Small value used to avoid divide by zero
+    epsilon: f64,
+    /// The number of passes through the data
+    iters: usize,
+}
+
+/// The default Nadam configuration
+///
+/// The defaults are:
+///
+/// - learning_rate = 0.002
+/// - beta1 = 0.9
+/// - beta2 = 0.999
+/// - epsilon = 1.0e-8
+/// - iters = 50
+impl Default for Nadam {
+    fn default() -> Nadam {
+        Nadam {
+            learning_rate: 0.002,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1.0e-8,
+            iters: 50,
+        }
+    }
+}
+
+impl Nadam {
+    /// Construct a Nadam algorithm.
+    ///
+    /// Requires learning rate, the decay rates of the gradient and
+    /// squared gradient averages, epsilon, and iteration count.
+    ///
+    /// #Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::Nadam;
+    ///
+    /// let nadam = Nadam::new(0.01, 0.9, 0.999, 1e-8, 20);
+    /// ```
+    pub fn new(learning_rate: f64, beta1: f64, beta2: f64, epsilon: f64, iters: usize) -> Nadam {
+        validate_adam_params(learning_rate, beta1, beta2, epsilon);
+
+        Nadam {
+            learning_rate,
+            beta1,
+            beta2,
+            epsilon,
+            iters,
+        }
+    }
+}
+
+impl EpochAlgorithm for Nadam {
+    fn epochs(&self) -> usize {
+        self.iters
+    }
+
+    fn set_epochs(&mut self, epochs: usize) {
+        self.iters = epochs;
+    }
+
+    /// Each pass makes one update per data point.
+    fn steps_per_epoch(&self, n_rows: usize) -> usize {
+        n_rows
+    }
+}
+
+impl<M> OptimAlgorithm<M> for Nadam
+    where M: Optimizable<Inputs = Matrix<f64>, Targets = Matrix<f64>> {
+    fn optimize(&self,
+                model: &M,
+                start: &[f64],
+                inputs: &M::Inputs,
+                targets: &M::Targets)
+                -> Vec<f64> {
+        self.optimize_with_callback(model, start, inputs, targets, |_, _| {})
+    }
+
+    fn optimize_with_callback<F>(&self,
+                                 model: &M,
+                                 start: &[f64],
+                                 inputs: &M::Inputs,
+                                 targets: &M::Targets,
+                                 on_epoch: F)
+                                 -> Vec<f64>
+        where F: FnMut(usize, &[f64])
+    {
+        let config = MomentConfig {
+            learning_rate: self.learning_rate,
+            beta1: self.beta1,
+            beta2: self.beta2,
+            epsilon: self.epsilon,
+            iters: self.iters,
+            amsgrad: false,
+            nesterov: true,
+        };
+        config.optimize(model, start, inputs, targets, on_epoch)
+    }
+}
+
+/// Checks the parameters shared by Adam and Nadam.
+fn validate_adam_params(learning_rate: f64, beta1: f64, beta2: f64, epsilon: f64) {
+    assert!(0f64 < learning_rate, "The learning rate must be positive");
+    assert!((0f64..1f64).contains(&beta1), "Beta1 must be in [0, 1)");
+    assert!(0f64 < beta2 && beta2 < 1f64, "Beta2 must be between 0 and 1");
+    assert!(0f64 < epsilon, "Epsilon must be positive");
+}
+
+/// The update rule shared by Adam and its variants.
+struct MomentConfig {
+    learning_rate: f64,
+    beta1: f64,
+    beta2: f64,
+    epsilon: f64,
+    iters: usize,
+    amsgrad: bool,
+    nesterov: bool,
+}
+
+impl MomentConfig {
+    fn optimize<M, F>(&self,
+                      model: &M,
+                      start: &[f64],
+                      inputs: &Matrix<f64>,
+                      targets: &Matrix<f64>,
+                      mut on_epoch: F)
+                      -> Vec<f64>
+        where M: Optimizable<Inputs = Matrix<f64>, Targets = Matrix<f64>>,
+              F: FnMut(usize, &[f64])
+    {
+        let mut params = start.to_vec();
+        // Running averages of the gradient and squared gradient
+        let mut mean = vec![0f64; start.len()];
+        let mut sq_mean = vec![0f64; start.len()];
+        // The largest squared gradient average, used by AMSGrad
+        let mut max_sq_mean = vec![0f64; start.len()];
+
+        // Set up indices for permutation
+        let mut permutation = (0..inputs.rows()).collect::<Vec<_>>();
+        // The cost from the previous iteration
+        let mut prev_cost = 0f64;
+        // The number of updates made
+        let mut t = 0i32;
+
+        for iter in 0..self.iters {
+            // The cost at end of each pass
+            let mut end_cost = 0f64;
+            // Permute the vertices
+            rand_utils::in_place_fisher_yates(&mut permutation);
+            for i in &permutation {
+                let (cost, grad) = model.compute_grad(&params,
+                                                      &inputs.select_rows(&[*i]),
+                                                      &targets.select_rows(&[*i]));
+                t += 1;
+
+                // Bias corrections for the zero initial averages
+                let mean_correction = 1f64 - self.beta1.powi(t);
+                let next_mean_correction = 1f64 - self.beta1.powi(t + 1);
+                let sq_mean_correction = 1f64 - self.beta2.powi(t);
+
+                for (j, g) in grad.iter().enumerate() {
+                    mean[j] = self.beta1 * mean[j] + (1f64 - self.beta1) * g;
+                    sq_mean[j] = self.beta2 * sq_mean[j] + (1f64 - self.beta2) * g * g;
+
+                    let sq = if self.amsgrad {
+                        max_sq_mean[j] = max_sq_mean[j].max(sq_mean[j]);
+                        max_sq_mean[j]
+                    } else {
+                        sq_mean[j]
+                    };
+
+                    let step = if self.nesterov {
+                        // Look ahead to the next average of the gradients.
+                        self.beta1 * mean[j] / next_mean_correction +
+                        (1f64 - self.beta1) * g / mean_correction
+                    } else {
+                        mean[j] / mean_correction
+                    };
+
+                    params[j] -= self.learning_rate * step /
+                                 ((sq / sq_mean_correction).sqrt() + self.epsilon);
+                }
+
+                end_cost += cost;
+            }
+            end_cost /= inputs.rows() as f64;
+
+            // Stop once the cost has diverged
+            if end_cost.is_nan() {
+                break;
+            }
+
+            on_epoch(iter, &params);
+
+            // Early stopping
+            if (prev_cost - end_cost).abs() < LEARNING_EPS {
+                break;
+            } else {
+                prev_cost = end_cost;
+            }
+        }
+        params
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{Checkpoint, GradientDesc, StochasticGD, AdaGrad, RMSProp, Adam, Nadam, load_checkpoint};
+    use learning::error::ErrorKind;
+    use learning::optim::{EpochAlgorithm, Optimizable, OptimAlgorithm};
+    use learning::optim::schedule::{CosineAnnealing, LearningRate};
+    use linalg::{Matrix, BaseMatrix};
+
+    use std::cell::RefCell;
+    use std::env;
+    use std::fs;
+
+    /// Minimizes the squared distance of the parameters from a target.
+    struct SqDistModel {
+        target: Vec<f64>,
+    }
+
+    impl Optimizable for SqDistModel {
+        type Inputs = Matrix<f64>;
+        type Targets = Matrix<f64>;
+
+        fn compute_grad(&self, params: &[f64], _: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+            let diff = params.iter().zip(self.target.iter()).map(|(p, t)| p - t).collect::<Vec<f64>>();
+            (diff.iter().map(|d| d * d).sum(), diff.iter().map(|d| 2f64 * d).collect())
+        }
+    }
+
+    #[test]
+    fn gd_follows_schedule() {
+        let model = SqDistModel { target: vec![1.0] };
+        let schedule = CosineAnnealing::new(0.4, 0.05, 5).with_warm_restarts(true);
+        let gd = GradientDesc::with_schedule(schedule, 12);
+        let params = gd.optimize(&model, &[0.0], &Matrix::zeros(1, 1), &Matrix::zeros(1, 1));
+
+        // Each step scales the distance to the target by 1 - 2 * rate.
+        let distance = (0..12).map(|t| 1.0 - 2.0 * schedule.rate(t)).product::<f64>();
+        assert!((params[0] - (1.0 - distance)).abs() < 1e-12);
+
+        let constant = GradientDesc::with_schedule(0.1, 20);
+        assert_eq!(constant.optimize(&model, &[0.0], &Matrix::zeros(1, 1), &Matrix::zeros(1, 1)),
+                   GradientDesc::new(0.1, 20).optimize(&model, &[0.0], &Matrix::zeros(1, 1), &Matrix::zeros(1, 1)));
+    }
+
+    #[test]
+    fn sgd_follows_schedule() {
+        let model = SqDistModel { target: vec![1.0] };
+        let schedule = CosineAnnealing::new(0.4, 0.05, 4).with_warm_restarts(true);
+        let sgd = StochasticGD::with_schedule(0.2, schedule, 10);
+        let params = sgd.optimize(&model, &[0.0], &Matrix::zeros(1, 1), &Matrix::zeros(1, 1));
+
+        // With one data point there is one momentum update per pass.
+        let (mut param, mut delta) = (0f64, 0f64);
+        for t in 0..10 {
+            let mu = schedule.rate(t);
+            delta = 2.0 * (param - 1.0) * mu + delta * 0.2;
+            param -= delta * mu;
+        }
+        assert!((params[0] - param).abs() < 1e-12);
+
+        let constant = StochasticGD::with_schedule(0.2, 0.3, 10).with_seed(1);
+        let data = Matrix::zeros(3, 1);
+        assert_eq!(constant.optimize(&model, &[0.0], &data, &data),
+                   StochasticGD::new(0.2, 0.3, 10).with_seed(1).optimize(&model, &[0.0], &data, &data));
+    }
+
+    #[test]
+    fn boxed_algorithms() {
+        let model = SqDistModel { target: vec![1.0] };
+        let gd = GradientDesc::new(0.1, 50);
+        let sgd = StochasticGD::new(0.1, 0.3, 50).with_seed(3);
+        let algs: Vec<Box<dyn OptimAlgorithm<SqDistModel>>> = vec![Box::new(gd), Box::new(sgd)];
+
+        let data = Matrix::zeros(2, 1);
+        assert_eq!(algs[0].optimize(&model, &[0.0], &data, &data), gd.optimize(&model, &[0.0], &data, &data));
+        assert_eq!(algs[1].optimize(&model, &[0.0], &data, &data), sgd.optimize(&model, &[0.0], &data, &data));
+    }
+
+    #[test]
+    fn optimizers_are_copy_and_sync() {
+        fn assert_copy_sync<T: Copy + Sync>() {}
+
+        assert_copy_sync::<GradientDesc>();
+        assert_copy_sync::<GradientDesc<CosineAnnealing>>();
+        assert_copy_sync::<StochasticGD>();
+        assert_copy_sync::<StochasticGD<CosineAnnealing>>();
+    }
+
+    #[test]
+    fn gd_checkpoint() {
+        let path = env::temp_dir().join("rusty_machine_gd_checkpoint.txt");
+        let _ = fs::remove_file(&path);
+
+        let model = SqDistModel { target: vec![1.0, -2.0, 3.0] };
+        let gd = GradientDesc::new(0.1, 20);
+        let params = Checkpoint::new(5, &path)
+            .optimize(&gd, &model, &[0.0; 3], &Matrix::zeros(1, 1), &Matrix::zeros(1, 1))
+            .unwrap();
+        assert_eq!(params, gd.optimize(&model, &[0.0; 3], &Matrix::zeros(1, 1), &Matrix::zeros(1, 1)));
+
+        // The last checkpoint is written after the final iteration.
+        let saved = load_checkpoint(&path).unwrap();
+        assert_eq!(saved.len(), 3);
+        assert_eq!(saved, params);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn stochastic_gd_checkpoint() {
+        let path = env::temp_dir().join("rusty_machine_sgd_checkpoint.txt");
+        let _ = fs::remove_file(&path);
+
+        let model = SqDistModel { target: vec![1.0, -2.0] };
+        let sgd = StochasticGD::new(0.1, 0.1, 10);
+        Checkpoint::new(3, &path)
+            .optimize(&sgd, &model, &[0.0; 2], &Matrix::zeros(4, 1), &Matrix::zeros(4, 1))
+            .unwrap();
+
+        assert!(path.exists());
+        assert_eq!(load_checkpoint(&path).unwrap().len(), 2);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    /// Records the first input of each batch it computes the gradient for.
+    struct BatchRecorder {
+        batches: RefCell<Vec<Vec<f64>>>,
+    }
+
+    impl Optimizable for BatchRecorder {
+        type Inputs = Matrix<f64>;
+        type Targets = Matrix<f64>;
+
+        fn compute_grad(&self, params: &[f64], inputs: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+            self.batches.borrow_mut().push(inputs.col(0).iter().cloned().collect());
+            (1f64, vec![0f64; params.len()])
+        }
+    }
+
+    #[test]
+    fn stochastic_gd_fixed_shuffle_order() {
+        let model = BatchRecorder { batches: RefCell::new(Vec::new()) };
+        let inputs = Matrix::new(5, 1, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+
+        let sgd = StochasticGD::new(0.1, 0.1, 2).with_batch_size(2);
+        let _ = sgd.optimize_in_order(&model, &[0.0], &inputs, &inputs, &[3, 0, 4, 1, 2]).unwrap();
+
+        let expected = vec![vec![3.0, 0.0], vec![4.0, 1.0], vec![2.0]];
+        let batches = model.batches.borrow();
+        assert_eq!(batches.len(), 6);
+        assert_eq!(&batches[..3], &expected[..]);
+        assert_eq!(&batches[3..], &expected[..]);
+    }
+
+    #[test]
+    fn stochastic_gd_recorded_shuffle_orders() {
+        let model = BatchRecorder { batches: RefCell::new(Vec::new()) };
+        let inputs = Matrix::new(4, 1, vec![0.0, 1.0, 2.0, 3.0]);
+
+        let sgd = StochasticGD::new(0.1, 0.1, 3);
+        let (_, orders) = sgd.optimize_recording_orders(&model, &[0.0], &inputs, &inputs);
+
+        // Each pass visited the rows in its recorded order.
+        let batches = model.batches.borrow();
+        assert_eq!(orders.len() * 4, batches.len());
+        for (order, pass) in orders.iter().zip(batches.chunks(4)) {
+            assert_eq!(*order, pass.iter().map(|b| b[0] as usize).collect::<Vec<usize>>());
+        }
+    }
+
+    /// Least squares fit of a line through the origin.
+    struct LineModel;
+
+    impl Optimizable for LineModel {
+        type Inputs = Matrix<f64>;
+        type Targets = Matrix<f64>;
+
+        fn compute_grad(&self, params: &[f64], inputs: &Matrix<f64>, targets: &Matrix<f64>) -> (f64, Vec<f64>) {
+            let mut cost = 0f64;
+            let mut grad = 0f64;
+            for (x, y) in inputs.iter().zip(targets.iter()) {
+                let residual = params[0] * x - y;
+                cost += residual * residual;
+                grad += 2f64 * residual * x;
+            }
+            (cost, vec![grad])
+        }
+    }
+
+    #[test]
+    fn stochastic_gd_seeded_runs_identical() {
+        let inputs = Matrix::new(20, 1, (0..20).map(|i| i as f64 * 0.1).collect::<Vec<f64>>());
+        let targets = Matrix::new(20, 1, (0..20).map(|i| (i as f64 * 1.3).sin()).collect::<Vec<f64>>());
+
+        let run = |seed: u64| {
+            let sgd = StochasticGD::new(0.1, 0.1, 5).with_seed(seed);
+            let mut trajectory = Vec::new();
+            let params = sgd.optimize_with_callback(&LineModel, &[0.0], &inputs, &targets, |_, p| {
+                trajectory.push(p.to_vec());
+            });
+            let (_, orders) = sgd.optimize_recording_orders(&LineModel, &[0.0], &inputs, &targets);
+            (params, trajectory, orders)
+        };
+
+        let (params, trajectory, order) = run(7);
+        let (same_params, same_trajectory, same_order) = run(7);
+        let (other_params, _, other_order) = run(8);
+
+        assert_eq!(trajectory.len(), 5);
+        assert_eq!(trajectory, same_trajectory);
+        assert_eq!(params[0].to_bits(), same_params[0].to_bits());
+        assert_eq!(order, same_order);
+
+        assert!(order != other_order);
+        assert!(params[0].to_bits() != other_params[0].to_bits());
+    }
+
+    #[test]
+    fn stochastic_gd_invalid_shuffle_order() {
+        let model = BatchRecorder { batches: RefCell::new(Vec::new()) };
+        let inputs = Matrix::new(3, 1, vec![0.0, 1.0, 2.0]);
+
+        let sgd = StochasticGD::new(0.1, 0.1, 1);
+        for order in &[vec![0, 1, 1], vec![0, 1], vec![0, 1, 2, 3]] {
+            match sgd.optimize_in_order(&model, &[0.0], &inputs, &inputs, order) {
+                Err(e) => assert!(matches!(*e.kind(), ErrorKind::InvalidParameters)),
+                Ok(_) => panic!("{:?} is not a permutation of the rows.", order),
+            }
+        }
+        assert!(model.batches.borrow().is_empty());
+    }
+
+    #[test]
+    fn checkpoint_write_failure() {
+        let path = env::temp_dir().join("rusty_machine_missing_dir").join("weights.txt");
+
+        let model = SqDistModel { target: vec![1.0] };
+        let gd = GradientDesc::new(0.1, 10);
+        let res = Checkpoint::new(2, &path).optimize(&gd, &model, &[0.0], &Matrix::zeros(1, 1), &Matrix::zeros(1, 1));
+
+        match res {
+            Err(e) => assert!(matches!(*e.kind(), ErrorKind::Io)),
+            Ok(_) => panic!("The checkpoint should not be written."),
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn gd_zero_checkpoint_interval() {
+        let _ = Checkpoint::new(0, "weights.txt");
+    }
+
+    #[test]
+    #[should_panic]
     fn gd_neg_stepsize() {
         let _ = GradientDesc::new(-0.5, 0);
     }
@@ -468,4 +1718,169 @@ mod tests {
     fn rmsprop_neg_learning_rate() {
         let _ = RMSProp::new(0.5, -0.005, 1.0e-5, 0);
     }
+
+    /// The cost of each sample is its input times the parameter.
+    struct LinearLossModel;
+
+    impl Optimizable for LinearLossModel {
+        type Inputs = Matrix<f64>;
+        type Targets = Matrix<f64>;
+
+        fn compute_grad(&self, params: &[f64], inputs: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+            (inputs[[0, 0]] * params[0], vec![inputs[[0, 0]]])
+        }
+    }
+
+    #[test]
+    fn amsgrad_converges_where_adam_fails() {
+        // One sample in three has a large gradient pushing the parameter
+        // down, the others have small gradients pushing it up. The average
+        // gradient is positive so the parameter should decrease.
+        let data = (0..300).map(|i| if i % 3 == 0 { 10.0 } else { -1.0 }).collect::<Vec<f64>>();
+        let inputs = Matrix::new(300, 1, data);
+        let targets = Matrix::zeros(300, 1);
+
+        // Adam quickly forgets the rare large gradients and so takes
+        // full size steps in the wrong direction most of the time.
+        let adam = Adam::new(0.01, 0.0, 0.01, 1e-8, 5);
+        let params = adam.optimize(&LinearLossModel, &[0.0], &inputs, &targets);
+        assert!(params[0] > 1.0, "Adam moved to {}", params[0]);
+
+        let amsgrad = adam.with_amsgrad(true);
+        let params = amsgrad.optimize(&LinearLossModel, &[0.0], &inputs, &targets);
+        assert!(params[0] < -1.0, "AMSGrad moved to {}", params[0]);
+    }
+
+    #[test]
+    fn adam_and_nadam_convex_training() {
+        let model = SqDistModel { target: vec![1.0, -2.0, 3.0] };
+        let inputs = Matrix::zeros(10, 1);
+        let targets = Matrix::zeros(10, 1);
+
+        let adam = Adam::new(0.1, 0.9, 0.999, 1e-8, 100);
+        let nadam = Nadam::new(0.1, 0.9, 0.999, 1e-8, 100);
+        let amsgrad = adam.with_amsgrad(true);
+
+        for params in vec![adam.optimize(&model, &[0.0; 3], &inputs, &targets),
+                           nadam.optimize(&model, &[0.0; 3], &inputs, &targets),
+                           amsgrad.optimize(&model, &[0.0; 3], &inputs, &targets)] {
+            for (p, t) in params.iter().zip(model.target.iter()) {
+                assert!((p - t).abs() < 1e-2, "{} != {}", p, t);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn adam_invalid_beta1() {
+        let _ = Adam::new(0.01, 1.0, 0.999, 1e-8, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn nadam_neg_learning_rate() {
+        let _ = Nadam::new(-0.01, 0.9, 0.999, 1e-8, 0);
+    }
+
+    /// Counts the gradient computations, with a cost which never settles.
+    struct StepCounter {
+        steps: RefCell<usize>,
+    }
+
+    impl Optimizable for StepCounter {
+        type Inputs = Matrix<f64>;
+        type Targets = Matrix<f64>;
+
+        fn compute_grad(&self, params: &[f64], _: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+            *self.steps.borrow_mut() += 1;
+            (*self.steps.borrow() as f64, vec![0.0; params.len()])
+        }
+    }
+
+    /// The number of gradient computations made by the algorithm.
+    fn count_steps<A: OptimAlgorithm<StepCounter>>(alg: &A, n_rows: usize) -> usize {
+        let model = StepCounter { steps: RefCell::new(0) };
+        let _ = alg.optimize(&model, &[0.0], &Matrix::zeros(n_rows, 1), &Matrix::zeros(n_rows, 1));
+        let steps = *model.steps.borrow();
+        steps
+    }
+
+    #[test]
+    fn epochs_map_to_steps() {
+        let mut sgd = StochasticGD::new(0.1, 0.1, 20).with_batch_size(3);
+        sgd.set_epochs(5);
+        assert_eq!(sgd.epochs(), 5);
+        assert_eq!(sgd.steps_per_epoch(10), 4);
+        assert_eq!(count_steps(&sgd, 10), 20);
+
+        let mut gd = GradientDesc::new(0.1, 20);
+        gd.set_epochs(7);
+        assert_eq!(gd.steps_per_epoch(10), 1);
+        assert_eq!(count_steps(&gd, 10), 7);
+
+        let mut adagrad = AdaGrad::default();
+        adagrad.set_epochs(3);
+        assert_eq!(count_steps(&adagrad, 10), 3 * adagrad.steps_per_epoch(10));
+
+        let mut rms = RMSProp::default();
+        rms.set_epochs(3);
+        assert_eq!(count_steps(&rms, 10), 3 * rms.steps_per_epoch(10));
+
+        let mut adam = Adam::default();
+        adam.set_epochs(2);
+        assert_eq!(count_steps(&adam, 10), 2 * adam.steps_per_epoch(10));
+
+        let mut nadam = Nadam::default();
+        nadam.set_epochs(2);
+        assert_eq!(count_steps(&nadam, 10), 2 * nadam.steps_per_epoch(10));
+    }
+
+    /// Least squares regression through the origin, whose
+    /// gradient is the mean over the data points.
+    struct MeanSqModel;
+
+    impl Optimizable for MeanSqModel {
+        type Inputs = Matrix<f64>;
+        type Targets = Matrix<f64>;
+
+        fn compute_grad(&self, params: &[f64], inputs: &Matrix<f64>, targets: &Matrix<f64>) -> (f64, Vec<f64>) {
+            let n = inputs.rows() as f64;
+            let residuals = inputs.iter().zip(targets.iter()).map(|(x, t)| params[0] * x - t).collect::<Vec<f64>>();
+            let cost = residuals.iter().map(|r| r * r).sum::<f64>() / n;
+            let grad = residuals.iter().zip(inputs.iter()).map(|(r, x)| 2f64 * r * x).sum::<f64>() / n;
+            (cost, vec![grad])
+        }
+    }
+
+    #[test]
+    fn accumulated_gradients_match_large_batch() {
+        let inputs = Matrix::new(10, 1, (0..10).map(|i| i as f64 / 5.0).collect::<Vec<f64>>());
+        let targets = Matrix::new(10, 1, (0..10).map(|i| 1.5 * i as f64 / 5.0 + (i % 3) as f64 * 0.1).collect::<Vec<f64>>());
+        let order = vec![3, 8, 1, 6, 0, 9, 2, 5, 7, 4];
+
+        // Groups of six points, leaving a group of four in each pass.
+        let accumulated = StochasticGD::new(0.5, 0.3, 4).with_batch_size(2).with_accumulation_steps(3);
+        let large_batch = StochasticGD::new(0.5, 0.3, 4).with_batch_size(6);
+
+        assert_eq!(accumulated.steps_per_epoch(10), 2);
+        assert_eq!(count_steps(&accumulated, 10), 4 * 5);
+
+        let accumulated_params = accumulated.optimize_in_order(&MeanSqModel, &[0.0], &inputs, &targets, &order)
+            .unwrap();
+        let large_batch_params = large_batch.optimize_in_order(&MeanSqModel, &[0.0], &inputs, &targets, &order)
+            .unwrap();
+        assert!((accumulated_params[0] - large_batch_params[0]).abs() < 1e-12);
+
+        // The updates differ from those of the small batches alone.
+        let small_batch = StochasticGD::new(0.5, 0.3, 4).with_batch_size(2);
+        let small_batch_params = small_batch.optimize_in_order(&MeanSqModel, &[0.0], &inputs, &targets, &order)
+            .unwrap();
+        assert!((accumulated_params[0] - small_batch_params[0]).abs() > 1e-6);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_accumulation_steps() {
+        let _ = StochasticGD::default().with_accumulation_steps(0);
+    }
 }
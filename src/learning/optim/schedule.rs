@@ -0,0 +1,167 @@
+//! Learning Rate Schedules
+//!
+//! Contains the `LearningRate` trait, which gives the step size used
+//! by an optimization algorithm in each iteration, and schedules
+//! which change the step size as the optimization progresses.
+//!
+//! A plain `f64` is a constant learning rate. A schedule can be given
+//! to gradient descent using `GradientDesc::with_schedule`, or to
+//! stochastic gradient descent using `StochasticGD::with_schedule`.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::optim::schedule::{CosineAnnealing, LearningRate};
+//! use rusty_machine::learning::optim::grad_desc::GradientDesc;
+//!
+//! // Decay the rate from 0.5 to 0.01 over 50 iterations, then restart.
+//! let schedule = CosineAnnealing::new(0.5, 0.01, 50).with_warm_restarts(true);
+//! assert_eq!(schedule.rate(0), 0.5);
+//! assert_eq!(schedule.rate(50), 0.5);
+//!
+//! let gd = GradientDesc::with_schedule(schedule, 200);
+//! ```
+
+use std::f64;
+
+/// A learning rate which may change with the iteration.
+pub trait LearningRate {
+    /// The learning rate for the given iteration, counting from zero.
+    fn rate(&self, iter: usize) -> f64;
+}
+
+/// A constant learning rate.
+impl LearningRate for f64 {
+    fn rate(&self, _iter: usize) -> f64 {
+        *self
+    }
+}
+
+/// Cosine annealing learning rate schedule.
+///
+/// Within a cycle of `cycle_len` iterations the rate decays from
+/// `initial` to `min` following half a cosine:
+///
+/// min + (initial - min) (1 + cos(π t / cycle_len)) / 2
+///
+/// where `t` is the iteration within the cycle. Without warm restarts the
+/// rate stays at `min` after the first cycle. With warm restarts the rate
+/// is reset to `initial` at the start of each cycle, as in
+/// [SGDR](https://arxiv.org/abs/1608.03983).
+#[derive(Clone, Copy, Debug)]
+pub struct CosineAnnealing {
+    initial: f64,
+    min: f64,
+    cycle_len: usize,
+    warm_restarts: bool,
+}
+
+impl CosineAnnealing {
+    /// Constructs a cosine annealing schedule without warm restarts.
+    ///
+    /// # Panics
+    ///
+    /// - `min` is negative or greater than `initial`.
+    /// - `cycle_len` is zero.
+    pub fn new(initial: f64, min: f64, cycle_len: usize) -> CosineAnnealing {
+        assert!(min >= 0f64 && min <= initial,
+                "The minimum rate must be non-negative and at most the initial rate.");
+        assert!(cycle_len > 0, "The cycle length must be greater than 0.");
+
+        CosineAnnealing {
+            initial,
+            min,
+            cycle_len,
+            warm_restarts: false,
+        }
+    }
+
+    /// Sets whether the rate is reset to the initial rate at the start of each cycle.
+    pub fn with_warm_restarts(mut self, warm_restarts: bool) -> Self {
+        self.warm_restarts = warm_restarts;
+        self
+    }
+
+    /// The rate at the start of each cycle.
+    pub fn initial(&self) -> f64 {
+        self.initial
+    }
+
+    /// The rate at the end of each cycle.
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// The number of iterations in each cycle.
+    pub fn cycle_len(&self) -> usize {
+        self.cycle_len
+    }
+
+    /// Whether the rate is reset at the start of each cycle.
+    pub fn warm_restarts(&self) -> bool {
+        self.warm_restarts
+    }
+}
+
+impl LearningRate for CosineAnnealing {
+    fn rate(&self, iter: usize) -> f64 {
+        let t = if self.warm_restarts {
+            iter % self.cycle_len
+        } else if iter >= self.cycle_len {
+            return self.min;
+        } else {
+            iter
+        };
+
+        let progress = t as f64 / self.cycle_len as f64;
+        self.min + (self.initial - self.min) * (1f64 + (f64::consts::PI * progress).cos()) / 2f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CosineAnnealing, LearningRate};
+
+    use std::f64;
+
+    #[test]
+    fn test_constant_rate() {
+        assert_eq!(0.3.rate(0), 0.3);
+        assert_eq!(0.3.rate(1000), 0.3);
+    }
+
+    #[test]
+    fn test_cosine_follows_curve() {
+        let schedule = CosineAnnealing::new(1.0, 0.1, 20);
+
+        assert_eq!(schedule.rate(0), 1.0);
+        assert!((schedule.rate(10) - 0.55).abs() < 1e-12);
+        for t in 0..20 {
+            let expected = 0.1 + 0.45 * (1.0 + (f64::consts::PI * t as f64 / 20.0).cos());
+            assert!((schedule.rate(t) - expected).abs() < 1e-12);
+            assert!(schedule.rate(t + 1) < schedule.rate(t));
+        }
+
+        // Without restarts the rate stays at the minimum.
+        assert_eq!(schedule.rate(20), 0.1);
+        assert_eq!(schedule.rate(75), 0.1);
+    }
+
+    #[test]
+    fn test_cosine_warm_restarts() {
+        let schedule = CosineAnnealing::new(1.0, 0.1, 20).with_warm_restarts(true);
+
+        assert!(schedule.rate(19) < 0.11);
+        assert_eq!(schedule.rate(20), 1.0);
+        assert_eq!(schedule.rate(40), 1.0);
+        for t in 0..20 {
+            assert_eq!(schedule.rate(t), schedule.rate(t + 20));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cosine_invalid_min() {
+        let _ = CosineAnnealing::new(0.1, 0.5, 10);
+    }
+}
@@ -0,0 +1,460 @@
+//! Ordinal Regression module
+//!
+//! Contains an implementation of the proportional odds model, which
+//! predicts ordered categories such as ratings from 1 to 5.
+//!
+//! The model has a single coefficient vector shared by all categories
+//! and one threshold between each pair of neighboring categories. The
+//! probability that a sample falls in category `k` or below is
+//!
+//! P(y <= k | x) = sigmoid(threshold<sub>k</sub> - x<sup>T</sup>b)
+//!
+//! Unlike one-vs-rest classification this respects the ordering of the
+//! categories. The thresholds are kept in increasing order by fitting
+//! the log of the gap between each pair of neighbors.
+//!
+//! The categories are the distinct target values in increasing order.
+//! The thresholds play the role of the intercept, so no intercept
+//! column is added to the inputs.
+//!
+//! # Usage
+//!
+//! ```
+//! use rusty_machine::learning::ordinal_reg::OrdinalRegressor;
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::{Matrix, Vector};
+//!
+//! let inputs = Matrix::new(8, 1, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+//! let targets = Vector::new(vec![1.0, 1.0, 2.0, 1.0, 2.0, 3.0, 2.0, 3.0]);
+//!
+//! let mut model = OrdinalRegressor::default();
+//! model.train(&inputs, &targets).unwrap();
+//!
+//! // Larger inputs give higher categories.
+//! let outputs = model.predict(&Matrix::new(2, 1, vec![0.0, 10.0])).unwrap();
+//! assert_eq!(outputs.into_vec(), vec![1.0, 3.0]);
+//! ```
+
+use linalg::{Matrix, BaseMatrix, Vector};
+use learning::{LearningResult, SupModel, validate_finite_matrix, validate_finite_vector,
+               validate_optimized};
+use learning::toolkit::activ_fn::{ActivationFunc, Sigmoid};
+use learning::optim::fmincg::ConjugateGD;
+use learning::optim::{OptimAlgorithm, Optimizable};
+use learning::error::{Error, ErrorKind};
+
+/// The smallest probability used when taking logarithms.
+const MIN_PROB: f64 = 1e-300;
+
+/// Ordinal Regression Model.
+///
+/// Contains the fitted coefficients and thresholds.
+#[derive(Debug)]
+pub struct OrdinalRegressor<A>
+    where A: OptimAlgorithm<BaseOrdinalRegressor>
+{
+    base: BaseOrdinalRegressor,
+    alg: A,
+    categories: Option<Vec<f64>>,
+    coefficients: Option<Vector<f64>>,
+    thresholds: Option<Vector<f64>>,
+}
+
+/// Constructs a default Ordinal Regression model
+/// using conjugate gradient descent.
+impl Default for OrdinalRegressor<ConjugateGD> {
+    fn default() -> OrdinalRegressor<ConjugateGD> {
+        OrdinalRegressor::new(ConjugateGD::default())
+    }
+}
+
+impl<A: OptimAlgorithm<BaseOrdinalRegressor>> OrdinalRegressor<A> {
+    /// Constructs an untrained ordinal regression model.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::ordinal_reg::OrdinalRegressor;
+    /// use rusty_machine::learning::optim::grad_desc::GradientDesc;
+    ///
+    /// let model = OrdinalRegressor::new(GradientDesc::new(0.5, 1000));
+    /// ```
+    pub fn new(alg: A) -> OrdinalRegressor<A> {
+        OrdinalRegressor {
+            base: BaseOrdinalRegressor { n_categories: 0 },
+            alg,
+            categories: None,
+            coefficients: None,
+            thresholds: None,
+        }
+    }
+
+    /// The categories seen in training, in increasing order.
+    ///
+    /// Returns an option that is None if the model has not been trained.
+    pub fn categories(&self) -> Option<&Vec<f64>> {
+        self.categories.as_ref()
+    }
+
+    /// The coefficient of each feature.
+    ///
+    /// Returns an option that is None if the model has not been trained.
+    pub fn coefficients(&self) -> Option<&Vector<f64>> {
+        self.coefficients.as_ref()
+    }
+
+    /// The increasing thresholds between neighboring categories.
+    ///
+    /// Returns an option that is None if the model has not been trained.
+    pub fn thresholds(&self) -> Option<&Vector<f64>> {
+        self.thresholds.as_ref()
+    }
+
+    /// Predict the probability of each category for each input.
+    ///
+    /// The columns follow the order of `categories` and
+    /// each row sums to one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::ordinal_reg::OrdinalRegressor;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{BaseMatrix, Matrix, Vector};
+    ///
+    /// let inputs = Matrix::new(6, 1, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    /// let targets = Vector::new(vec![0.0, 1.0, 0.0, 1.0, 2.0, 2.0]);
+    ///
+    /// let mut model = OrdinalRegressor::default();
+    /// model.train(&inputs, &targets).unwrap();
+    ///
+    /// let probs = model.predict_proba(&inputs).unwrap();
+    /// assert_eq!(probs.cols(), 3);
+    /// assert!((probs.row(0).sum() - 1.0).abs() < 1e-12);
+    /// ```
+    pub fn predict_proba(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        match (self.coefficients.as_ref(), self.thresholds.as_ref()) {
+            (Some(coefficients), Some(thresholds)) => {
+                if inputs.cols() != coefficients.size() {
+                    return Err(Error::new(ErrorKind::InvalidData,
+                                          "The inputs have a different number of columns \
+                                           to the training data."));
+                }
+
+                let n_categories = thresholds.size() + 1;
+                let eta = inputs * coefficients;
+                let mut data = Vec::with_capacity(inputs.rows() * n_categories);
+                for e in eta.iter() {
+                    let mut lower = 0f64;
+                    for t in thresholds.iter() {
+                        let upper = Sigmoid::func(t - e);
+                        data.push(upper - lower);
+                        lower = upper;
+                    }
+                    data.push(1f64 - lower);
+                }
+
+                Ok(Matrix::new(inputs.rows(), n_categories, data))
+            }
+            _ => Err(Error::new_untrained()),
+        }
+    }
+}
+
+impl<A> SupModel<Matrix<f64>, Vector<f64>> for OrdinalRegressor<A>
+    where A: OptimAlgorithm<BaseOrdinalRegressor>
+{
+    /// Train the ordinal regression model.
+    ///
+    /// # Failures
+    ///
+    /// - Some input or target is NaN or infinite.
+    /// - The inputs and targets have different numbers of samples.
+    /// - The targets take fewer than two distinct values.
+    /// - The optimization diverged.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_vector("target", targets)?;
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "There must be one target per sample."));
+        }
+
+        let mut categories = targets.data().clone();
+        categories.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        categories.dedup();
+        if categories.len() < 2 {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The targets must contain at least two categories."));
+        }
+
+        let indices = Vector::new(targets.iter()
+            .map(|t| categories.binary_search_by(|c| c.partial_cmp(t).unwrap()).unwrap() as f64)
+            .collect::<Vec<f64>>());
+
+        self.base.n_categories = categories.len();
+        let start = initial_params(&indices, inputs.cols(), categories.len());
+        let params = self.alg.optimize(&self.base, &start, inputs, &indices);
+        validate_optimized(&self.base, &params, inputs, &indices)?;
+
+        let (coefficients, thresholds) = split_params(&params, inputs.cols());
+        self.coefficients = Some(Vector::new(coefficients.to_vec()));
+        self.thresholds = Some(Vector::new(thresholds));
+        self.categories = Some(categories);
+        Ok(())
+    }
+
+    /// Predict the most probable category of each input.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        let probs = self.predict_proba(inputs)?;
+        let categories = self.categories.as_ref().ok_or_else(Error::new_untrained)?;
+
+        Ok(Vector::new(probs.row_iter()
+            .map(|row| {
+                let (idx, _) = row.iter()
+                    .enumerate()
+                    .fold((0, f64::NEG_INFINITY),
+                          |best, (i, p)| if *p > best.1 { (i, *p) } else { best });
+                categories[idx]
+            })
+            .collect::<Vec<f64>>()))
+    }
+}
+
+/// Starts the thresholds at the logits of the cumulative
+/// category proportions, with zero coefficients.
+fn initial_params(indices: &Vector<f64>, n_features: usize, n_categories: usize) -> Vec<f64> {
+    let n = indices.size() as f64;
+    let mut counts = vec![0f64; n_categories];
+    for idx in indices.iter() {
+        counts[*idx as usize] += 1f64;
+    }
+
+    let mut params = vec![0f64; n_features];
+    let mut cumulative = 0f64;
+    let mut prev = 0f64;
+    for (k, count) in counts.iter().take(n_categories - 1).enumerate() {
+        // Smoothing keeps the logits finite for empty categories.
+        cumulative += *count;
+        let p = (cumulative + 0.5) / (n + 1f64);
+        let threshold = (p / (1f64 - p)).ln();
+
+        if k == 0 {
+            params.push(threshold);
+        } else {
+            params.push((threshold - prev).max(1e-3).ln());
+        }
+        prev = threshold;
+    }
+
+    params
+}
+
+/// Splits the optimized parameters into the coefficients
+/// and the increasing thresholds.
+fn split_params(params: &[f64], n_features: usize) -> (&[f64], Vec<f64>) {
+    let (coefficients, gaps) = params.split_at(n_features);
+
+    let mut thresholds = Vec::with_capacity(gaps.len());
+    for (k, g) in gaps.iter().enumerate() {
+        if k == 0 {
+            thresholds.push(*g);
+        } else {
+            let prev = thresholds[k - 1];
+            thresholds.push(prev + g.exp());
+        }
+    }
+
+    (coefficients, thresholds)
+}
+
+/// The Base Ordinal Regression model.
+///
+/// This struct cannot be instantiated and is used internally only.
+#[derive(Debug)]
+pub struct BaseOrdinalRegressor {
+    n_categories: usize,
+}
+
+/// Computing the negative log-likelihood of the proportional odds model.
+///
+/// The parameters are the coefficients followed by the first threshold and
+/// the log of each gap between consecutive thresholds. The targets are the
+/// category indices.
+impl Optimizable for BaseOrdinalRegressor {
+    type Inputs = Matrix<f64>;
+    type Targets = Vector<f64>;
+
+    fn compute_grad(&self,
+                    params: &[f64],
+                    inputs: &Matrix<f64>,
+                    targets: &Vector<f64>)
+                    -> (f64, Vec<f64>) {
+        let n_features = inputs.cols();
+        let n_thresholds = self.n_categories - 1;
+        let (coefficients, thresholds) = split_params(params, n_features);
+
+        let eta = inputs * Vector::new(coefficients.to_vec());
+
+        let mut cost = 0f64;
+        let mut grad_eta = vec![0f64; inputs.rows()];
+        let mut grad_thresholds = vec![0f64; n_thresholds];
+
+        for (i, (e, t)) in eta.iter().zip(targets.iter()).enumerate() {
+            let k = *t as usize;
+
+            // The cumulative probability and density at each side of the category.
+            let (upper, upper_density) = if k < n_thresholds {
+                let s = Sigmoid::func(thresholds[k] - e);
+                (s, s * (1f64 - s))
+            } else {
+                (1f64, 0f64)
+            };
+            let (lower, lower_density) = if k > 0 {
+                let s = Sigmoid::func(thresholds[k - 1] - e);
+                (s, s * (1f64 - s))
+            } else {
+                (0f64, 0f64)
+            };
+
+            let prob = (upper - lower).max(MIN_PROB);
+            cost -= prob.ln();
+
+            grad_eta[i] = (upper_density - lower_density) / prob;
+            if k < n_thresholds {
+                grad_thresholds[k] -= upper_density / prob;
+            }
+            if k > 0 {
+                grad_thresholds[k - 1] += lower_density / prob;
+            }
+        }
+
+        let n = inputs.rows() as f64;
+        let mut grad = (inputs.transpose() * Vector::new(grad_eta)).into_vec();
+
+        // Every threshold above a gap moves with it.
+        let mut suffix = 0f64;
+        let mut grad_gaps = vec![0f64; n_thresholds];
+        for k in (0..n_thresholds).rev() {
+            suffix += grad_thresholds[k];
+            grad_gaps[k] = if k == 0 {
+                suffix
+            } else {
+                suffix * params[n_features + k].exp()
+            };
+        }
+        grad.extend(grad_gaps);
+
+        (cost / n, grad.into_iter().map(|g| g / n).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OrdinalRegressor, BaseOrdinalRegressor};
+    use learning::SupModel;
+    use learning::optim::Optimizable;
+    use linalg::{Matrix, BaseMatrix, Vector};
+
+    use rand::{Rng, StdRng, SeedableRng};
+
+    /// Samples from a proportional odds model with coefficient 2
+    /// and thresholds -2, 0 and 1.5, labelled 1 to 4.
+    fn ordinal_data(n: usize) -> (Matrix<f64>, Vector<f64>) {
+        let seed: &[_] = &[3, 1, 4];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+
+        let mut inputs = Vec::with_capacity(n);
+        let mut targets = Vec::with_capacity(n);
+        for _ in 0..n {
+            let x = rng.gen_range(-3.0, 3.0);
+            // Logistic noise on the latent score.
+            let u: f64 = rng.gen_range(1e-9, 1.0 - 1e-9);
+            let latent = 2.0 * x + (u / (1.0 - u)).ln();
+
+            let category = [-2.0, 0.0, 1.5].iter().filter(|t| latent > **t).count();
+            inputs.push(x);
+            targets.push(category as f64 + 1.0);
+        }
+
+        (Matrix::new(n, 1, inputs), Vector::new(targets))
+    }
+
+    #[test]
+    fn test_thresholds_increasing() {
+        let (inputs, targets) = ordinal_data(1000);
+
+        let mut model = OrdinalRegressor::default();
+        model.train(&inputs, &targets).unwrap();
+
+        assert_eq!(model.categories().unwrap(), &vec![1.0, 2.0, 3.0, 4.0]);
+
+        let thresholds = model.thresholds().unwrap();
+        assert_eq!(thresholds.size(), 3);
+        for pair in thresholds.data().windows(2) {
+            assert!(pair[0] < pair[1]);
+        }
+
+        // The fit recovers the generating parameters.
+        for (t, expected) in thresholds.iter().zip([-2.0, 0.0, 1.5].iter()) {
+            assert!((t - expected).abs() < 0.5, "threshold {} != {}", t, expected);
+        }
+        assert!((model.coefficients().unwrap()[0] - 2.0).abs() < 0.3);
+    }
+
+    #[test]
+    fn test_predictions() {
+        let (inputs, targets) = ordinal_data(500);
+
+        let mut model = OrdinalRegressor::default();
+        model.train(&inputs, &targets).unwrap();
+
+        let probs = model.predict_proba(&inputs).unwrap();
+        assert_eq!(probs.cols(), 4);
+        for row in probs.row_iter() {
+            assert!((row.sum() - 1.0).abs() < 1e-12);
+            assert!(row.iter().all(|p| *p >= 0.0));
+        }
+
+        let outputs = model.predict(&Matrix::new(3, 1, vec![-3.0, 0.4, 3.0])).unwrap();
+        assert_eq!(outputs.into_vec(), vec![1.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_gradient_matches_finite_differences() {
+        let (inputs, targets) = ordinal_data(30);
+        let indices = targets.apply(&|t| t - 1.0);
+        let base = BaseOrdinalRegressor { n_categories: 4 };
+        let params = vec![0.7, -1.0, 0.3, -0.2];
+
+        let (_, grad) = base.compute_grad(&params, &inputs, &indices);
+        for j in 0..params.len() {
+            let mut plus = params.clone();
+            let mut minus = params.clone();
+            plus[j] += 1e-6;
+            minus[j] -= 1e-6;
+
+            let numeric = (base.compute_grad(&plus, &inputs, &indices).0 -
+                           base.compute_grad(&minus, &inputs, &indices).0) / 2e-6;
+            assert!((numeric - grad[j]).abs() < 1e-6, "{} != {}", numeric, grad[j]);
+        }
+    }
+
+    #[test]
+    fn test_single_category() {
+        let inputs = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+        let targets = Vector::new(vec![2.0, 2.0, 2.0]);
+
+        let mut model = OrdinalRegressor::default();
+        assert!(model.train(&inputs, &targets).is_err());
+    }
+
+    #[test]
+    fn test_untrained() {
+        let model = OrdinalRegressor::default();
+        let inputs = Matrix::new(1, 1, vec![1.0]);
+
+        assert!(model.predict(&inputs).is_err());
+        assert!(model.predict_proba(&inputs).is_err());
+    }
+}
@@ -0,0 +1,503 @@
+//! Principal Component Analysis Module
+//!
+//! Contains implementations of batch and incremental
+//! Principal Component Analysis.
+//!
+//! Both models project their inputs onto the leading principal
+//! components using the `predict` method of `UnSupModel`.
+//!
+//! # Usage
+//!
+//! ```
+//! use rusty_machine::linalg::{Matrix, BaseMatrix};
+//! use rusty_machine::learning::pca::PCA;
+//! use rusty_machine::learning::UnSupModel;
+//!
+//! let inputs = Matrix::new(4, 2, vec![1.0, 2.0,
+//!                                     2.0, 4.1,
+//!                                     3.0, 5.9,
+//!                                     4.0, 8.0]);
+//!
+//! // Keep only the first principal component.
+//! let mut model = PCA::new(1);
+//! model.train(&inputs).unwrap();
+//!
+//! // Project the inputs onto the component.
+//! let projected = model.predict(&inputs).unwrap();
+//! assert_eq!(projected.cols(), 1);
+//! ```
+//!
+//! # Incremental PCA
+//!
+//! When the data does not fit in memory it can be fed to an
+//! `IncrementalPCA` in chunks using `partial_fit`. Each call
+//! updates the running mean and a low-rank representation of
+//! the data seen so far.
+//!
+//! ```
+//! use rusty_machine::linalg::{Matrix, BaseMatrix};
+//! use rusty_machine::learning::pca::IncrementalPCA;
+//! use rusty_machine::learning::UnSupModel;
+//!
+//! let chunk_a = Matrix::new(3, 2, vec![1.0, 2.0, 2.0, 4.1, 3.0, 5.9]);
+//! let chunk_b = Matrix::new(3, 2, vec![4.0, 8.0, 5.0, 10.2, 6.0, 11.9]);
+//!
+//! let mut model = IncrementalPCA::new(1);
+//! model.partial_fit(&chunk_a).unwrap();
+//! model.partial_fit(&chunk_b).unwrap();
+//!
+//! let projected = model.predict(&chunk_a).unwrap();
+//! assert_eq!(projected.cols(), 1);
+//! ```
+
+use linalg::{Matrix, BaseMatrix, Axes, Vector};
+use learning::{LearningResult, UnSupModel, validate_finite_matrix};
+use learning::error::{Error, ErrorKind};
+
+/// Principal Component Analysis model.
+///
+/// The components are computed from the singular value
+/// decomposition of the centered training data.
+#[derive(Debug)]
+pub struct PCA {
+    n_components: usize,
+    means: Option<Vector<f64>>,
+    components: Option<Matrix<f64>>,
+    explained_variance_ratio: Option<Vector<f64>>,
+}
+
+impl PCA {
+    /// Constructs an untrained PCA model which keeps
+    /// `n_components` principal components.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::pca::PCA;
+    ///
+    /// let model = PCA::new(2);
+    /// ```
+    pub fn new(n_components: usize) -> PCA {
+        PCA {
+            n_components,
+            means: None,
+            components: None,
+            explained_variance_ratio: None,
+        }
+    }
+
+    /// The number of components kept by the model.
+    pub fn n_components(&self) -> usize {
+        self.n_components
+    }
+
+    /// The column means of the training data.
+    pub fn means(&self) -> Option<&Vector<f64>> {
+        self.means.as_ref()
+    }
+
+    /// The principal components, one per row, ordered
+    /// by decreasing explained variance.
+    pub fn components(&self) -> Option<&Matrix<f64>> {
+        self.components.as_ref()
+    }
+
+    /// The proportion of the total variance explained
+    /// by each component.
+    pub fn explained_variance_ratio(&self) -> Option<&Vector<f64>> {
+        self.explained_variance_ratio.as_ref()
+    }
+}
+
+impl UnSupModel<Matrix<f64>, Matrix<f64>> for PCA {
+    /// Train the model by computing the principal components of the inputs.
+    fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_components(self.n_components, inputs.rows(), inputs.cols())?;
+
+        let means = inputs.mean(Axes::Row);
+        let centered = center(inputs, &means);
+        let total_ss = centered.data().iter().fold(0f64, |acc, x| acc + x * x);
+
+        let (s, _, v) = centered.svd()?;
+        let (singular_values, components) = truncate(&s, &v, self.n_components);
+
+        self.explained_variance_ratio = Some(variance_ratio(&singular_values, total_ss));
+        self.means = Some(means);
+        self.components = Some(components);
+        Ok(())
+    }
+
+    /// Project the inputs onto the principal components.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        match (self.means.as_ref(), self.components.as_ref()) {
+            (Some(means), Some(components)) => project(inputs, means, components),
+            _ => Err(Error::new_untrained()),
+        }
+    }
+}
+
+/// Incremental Principal Component Analysis model.
+///
+/// The model is updated one chunk at a time, keeping only the
+/// running mean and the leading singular values and vectors in
+/// memory. Each update follows the incremental SVD of
+/// [Ross et al. (2008)](http://www.cs.toronto.edu/~dross/ivt/RossLimLinYang_ijcv.pdf).
+///
+/// The components are an approximation of those found by `PCA`
+/// on the full data. The approximation is exact when all
+/// components are kept.
+#[derive(Debug)]
+pub struct IncrementalPCA {
+    n_components: usize,
+    n_samples_seen: usize,
+    means: Option<Vector<f64>>,
+    components: Option<Matrix<f64>>,
+    singular_values: Option<Vector<f64>>,
+    explained_variance_ratio: Option<Vector<f64>>,
+    total_ss: f64,
+}
+
+impl IncrementalPCA {
+    /// Constructs an untrained incremental PCA model which keeps
+    /// `n_components` principal components.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::pca::IncrementalPCA;
+    ///
+    /// let model = IncrementalPCA::new(2);
+    /// ```
+    pub fn new(n_components: usize) -> IncrementalPCA {
+        IncrementalPCA {
+            n_components,
+            n_samples_seen: 0,
+            means: None,
+            components: None,
+            singular_values: None,
+            explained_variance_ratio: None,
+            total_ss: 0f64,
+        }
+    }
+
+    /// The number of components kept by the model.
+    pub fn n_components(&self) -> usize {
+        self.n_components
+    }
+
+    /// The number of samples the model has been fitted to.
+    pub fn n_samples_seen(&self) -> usize {
+        self.n_samples_seen
+    }
+
+    /// The column means of the data seen so far.
+    pub fn means(&self) -> Option<&Vector<f64>> {
+        self.means.as_ref()
+    }
+
+    /// The principal components, one per row, ordered
+    /// by decreasing explained variance.
+    pub fn components(&self) -> Option<&Matrix<f64>> {
+        self.components.as_ref()
+    }
+
+    /// The proportion of the total variance explained
+    /// by each component.
+    pub fn explained_variance_ratio(&self) -> Option<&Vector<f64>> {
+        self.explained_variance_ratio.as_ref()
+    }
+
+    /// Update the model with a new chunk of data.
+    ///
+    /// # Failures
+    ///
+    /// - The chunk has a different number of columns to previous chunks.
+    /// - The first chunk has fewer rows than `n_components`.
+    /// - `n_components` exceeds the number of columns.
+    /// - Some entry of the chunk is NaN or infinite.
+    pub fn partial_fit(&mut self, chunk: &Matrix<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", chunk)?;
+
+        let chunk_means = chunk.mean(Axes::Row);
+        let centered = center(chunk, &chunk_means);
+        let chunk_ss = centered.data().iter().fold(0f64, |acc, x| acc + x * x);
+
+        let (stacked, means, total_ss) = match (self.means.as_ref(),
+                                                self.components.as_ref(),
+                                                self.singular_values.as_ref()) {
+            (Some(means), Some(components), Some(singular_values)) => {
+                if chunk.cols() != means.size() {
+                    return Err(Error::new(ErrorKind::InvalidData,
+                                          "Chunk has a different number of columns to \
+                                           previously seen data."));
+                }
+
+                let n = self.n_samples_seen as f64;
+                let m = chunk.rows() as f64;
+
+                let mean_diff = means - &chunk_means;
+                let correction = Matrix::new(1,
+                                             mean_diff.size(),
+                                             (mean_diff.clone() * (n * m / (n + m)).sqrt())
+                                                 .into_vec());
+
+                // Stack the current low-rank representation, the new
+                // centered data and a correction for the shifted mean.
+                let stacked = (Matrix::from_diag(singular_values.data()) * components)
+                    .vcat(&centered)
+                    .vcat(&correction);
+
+                let new_means = (means * n + &chunk_means * m) / (n + m);
+                let total_ss = self.total_ss + chunk_ss + mean_diff.dot(&mean_diff) * n * m / (n + m);
+
+                (stacked, new_means, total_ss)
+            }
+            _ => {
+                validate_components(self.n_components, chunk.rows(), chunk.cols())?;
+                (centered, chunk_means, chunk_ss)
+            }
+        };
+
+        let (s, _, v) = stacked.svd()?;
+        let (singular_values, components) = truncate(&s, &v, self.n_components);
+
+        self.explained_variance_ratio = Some(variance_ratio(&singular_values, total_ss));
+        self.n_samples_seen += chunk.rows();
+        self.means = Some(means);
+        self.components = Some(components);
+        self.singular_values = Some(singular_values);
+        self.total_ss = total_ss;
+        Ok(())
+    }
+}
+
+impl UnSupModel<Matrix<f64>, Matrix<f64>> for IncrementalPCA {
+    /// Train the model from scratch on the inputs.
+    ///
+    /// Any previously fitted chunks are discarded.
+    fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        *self = IncrementalPCA::new(self.n_components);
+        self.partial_fit(inputs)
+    }
+
+    /// Project the inputs onto the principal components.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        match (self.means.as_ref(), self.components.as_ref()) {
+            (Some(means), Some(components)) => project(inputs, means, components),
+            _ => Err(Error::new_untrained()),
+        }
+    }
+}
+
+fn validate_components(n_components: usize, rows: usize, cols: usize) -> LearningResult<()> {
+    if n_components == 0 {
+        Err(Error::new(ErrorKind::InvalidParameters,
+                       "The number of components must be greater than zero."))
+    } else if n_components > cols {
+        Err(Error::new(ErrorKind::InvalidParameters,
+                       "The number of components cannot exceed the number of columns."))
+    } else if n_components > rows {
+        Err(Error::new(ErrorKind::InvalidData,
+                       "The number of rows must be at least the number of components."))
+    } else {
+        Ok(())
+    }
+}
+
+/// Subtract the column means from each row of the inputs.
+fn center(inputs: &Matrix<f64>, means: &Vector<f64>) -> Matrix<f64> {
+    let mut data = inputs.data().clone();
+    for row in data.chunks_mut(inputs.cols()) {
+        for (x, m) in row.iter_mut().zip(means.data().iter()) {
+            *x -= *m;
+        }
+    }
+    Matrix::new(inputs.rows(), inputs.cols(), data)
+}
+
+/// Keep the leading singular values and the matching right
+/// singular vectors, with the vectors stored as rows.
+fn truncate(s: &Matrix<f64>, v: &Matrix<f64>, k: usize) -> (Vector<f64>, Matrix<f64>) {
+    let singular_values = Vector::new(s.diag().take(k).cloned().collect::<Vec<f64>>());
+    let cols = (0..k).collect::<Vec<usize>>();
+    (singular_values, v.select_cols(&cols).transpose())
+}
+
+fn variance_ratio(singular_values: &Vector<f64>, total_ss: f64) -> Vector<f64> {
+    if total_ss > 0f64 {
+        singular_values.clone().apply(&|s| s * s / total_ss)
+    } else {
+        Vector::zeros(singular_values.size())
+    }
+}
+
+fn project(inputs: &Matrix<f64>,
+           means: &Vector<f64>,
+           components: &Matrix<f64>)
+           -> LearningResult<Matrix<f64>> {
+    if inputs.cols() != means.size() {
+        return Err(Error::new(ErrorKind::InvalidData,
+                              "Input data has a different number of columns to the \
+                               training data."));
+    }
+
+    Ok(center(inputs, means) * components.transpose())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PCA, IncrementalPCA};
+    use linalg::{Matrix, BaseMatrix};
+    use rulinalg::norm::Euclidean;
+    use learning::UnSupModel;
+
+    use rand::{StdRng, SeedableRng};
+    use rand::distributions::{Normal, IndependentSample};
+
+    /// Samples with very different variances along a
+    /// rotated set of axes.
+    fn sample_data(n: usize) -> Matrix<f64> {
+        let seed: &[_] = &[1, 2, 3];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let normal = Normal::new(0., 1.);
+
+        let scales = Matrix::from_diag(&[6.0, 3.0, 0.5, 0.1]);
+        let rotation = Matrix::new(4, 4, vec![0.5, 0.5, 0.5, 0.5,
+                                              0.5, -0.5, 0.5, -0.5,
+                                              0.5, 0.5, -0.5, -0.5,
+                                              0.5, -0.5, -0.5, 0.5]);
+
+        let raw = Matrix::new(n, 4, (0..n * 4).map(|_| normal.ind_sample(&mut rng)).collect::<Vec<f64>>());
+        let offset = Matrix::new(n, 4, vec![1.0, -2.0, 3.0, 0.5].iter().cycle().take(n * 4).cloned().collect::<Vec<f64>>());
+        raw * scales * rotation + offset
+    }
+
+    fn assert_same_components(a: &Matrix<f64>, b: &Matrix<f64>, tol: f64) {
+        assert_eq!(a.rows(), b.rows());
+        for (row_a, row_b) in a.row_iter().zip(b.row_iter()) {
+            // Components are only defined up to sign.
+            let dot = row_a.iter().zip(row_b.iter()).fold(0f64, |acc, (x, y)| acc + x * y);
+            assert!((dot.abs() - 1.0).abs() < tol, "component mismatch: |dot| = {}", dot.abs());
+        }
+    }
+
+    #[test]
+    fn test_pca_explained_variance() {
+        let inputs = sample_data(200);
+
+        let mut model = PCA::new(4);
+        model.train(&inputs).unwrap();
+
+        let ratio = model.explained_variance_ratio().unwrap();
+        assert!((ratio.sum() - 1.0).abs() < 1e-10);
+        assert!(ratio[0] > ratio[1] && ratio[1] > ratio[2] && ratio[2] > ratio[3]);
+        assert!(ratio[0] > 0.7);
+    }
+
+    #[test]
+    fn test_pca_predict_shape() {
+        let inputs = sample_data(50);
+
+        let mut model = PCA::new(2);
+        model.train(&inputs).unwrap();
+
+        let projected = model.predict(&inputs).unwrap();
+        assert_eq!(projected.rows(), 50);
+        assert_eq!(projected.cols(), 2);
+
+        // The projections of the training data are centered.
+        for j in 0..2 {
+            let mean = projected.col(j).iter().fold(0f64, |acc, x| acc + x) / 50.0;
+            assert!(mean.abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_incremental_matches_batch() {
+        let inputs = sample_data(200);
+
+        let mut batch = PCA::new(2);
+        batch.train(&inputs).unwrap();
+
+        let mut incremental = IncrementalPCA::new(2);
+        for i in 0..4 {
+            let rows = (i * 50..(i + 1) * 50).collect::<Vec<usize>>();
+            incremental.partial_fit(&inputs.select_rows(&rows)).unwrap();
+        }
+        assert_eq!(incremental.n_samples_seen(), 200);
+
+        let batch_means = batch.means().unwrap();
+        let inc_means = incremental.means().unwrap();
+        assert!((batch_means - inc_means).norm(Euclidean) < 1e-10);
+
+        assert_same_components(batch.components().unwrap(),
+                               incremental.components().unwrap(),
+                               1e-3);
+
+        let batch_ratio = batch.explained_variance_ratio().unwrap();
+        let inc_ratio = incremental.explained_variance_ratio().unwrap();
+        assert!((batch_ratio - inc_ratio).norm(Euclidean) < 1e-3);
+    }
+
+    #[test]
+    fn test_incremental_all_components_exact() {
+        let inputs = sample_data(120);
+
+        let mut batch = PCA::new(4);
+        batch.train(&inputs).unwrap();
+
+        let mut incremental = IncrementalPCA::new(4);
+        for i in 0..3 {
+            let rows = (i * 40..(i + 1) * 40).collect::<Vec<usize>>();
+            incremental.partial_fit(&inputs.select_rows(&rows)).unwrap();
+        }
+
+        assert_same_components(batch.components().unwrap(),
+                               incremental.components().unwrap(),
+                               1e-8);
+
+        let batch_ratio = batch.explained_variance_ratio().unwrap();
+        let inc_ratio = incremental.explained_variance_ratio().unwrap();
+        assert!((batch_ratio - inc_ratio).norm(Euclidean) < 1e-8);
+    }
+
+    #[test]
+    fn test_incremental_train_resets() {
+        let inputs = sample_data(60);
+
+        let mut model = IncrementalPCA::new(2);
+        model.partial_fit(&inputs).unwrap();
+        model.train(&inputs).unwrap();
+
+        assert_eq!(model.n_samples_seen(), 60);
+    }
+
+    #[test]
+    fn test_incremental_wrong_cols() {
+        let mut model = IncrementalPCA::new(1);
+        model.partial_fit(&Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 5.0])).unwrap();
+
+        let res = model.partial_fit(&Matrix::new(1, 3, vec![1.0, 2.0, 3.0]));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_too_many_components() {
+        let inputs = Matrix::new(3, 2, vec![1.0, 2.0, 3.0, 5.0, 4.0, 4.0]);
+
+        let mut model = PCA::new(3);
+        assert!(model.train(&inputs).is_err());
+
+        let mut model = IncrementalPCA::new(3);
+        assert!(model.partial_fit(&inputs).is_err());
+    }
+
+    #[test]
+    fn test_untrained_predict() {
+        let inputs = Matrix::new(1, 2, vec![1.0, 2.0]);
+
+        assert!(PCA::new(1).predict(&inputs).is_err());
+        assert!(IncrementalPCA::new(1).predict(&inputs).is_err());
+    }
+}
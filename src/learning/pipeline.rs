@@ -0,0 +1,320 @@
+//! Pipeline module
+//!
+//! Contains the `Pipeline`, which chains data transformations
+//! and a supervised model into a single model.
+//!
+//! Each step of the pipeline is a `TransformFitter`. Training the
+//! pipeline fits each step on the output of the previous step and then
+//! trains the model on the output of the last step. Predicting applies
+//! the same fitted transformers before calling the model. The statistics
+//! of the transformers, such as the column means of a `Standardizer`,
+//! therefore only ever come from the training data.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::data::transforms::{MinMaxFitter, StandardizerFitter};
+//! use rusty_machine::learning::lin_reg::LinRegressor;
+//! use rusty_machine::learning::pipeline::Pipeline;
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::{Matrix, Vector};
+//!
+//! let inputs = Matrix::new(4, 1, vec![1.0, 2.0, 3.0, 4.0]);
+//! let targets = Vector::new(vec![3.0, 5.0, 7.0, 9.0]);
+//!
+//! // Standardize the inputs, then scale them to [0, 1], then fit a line.
+//! let mut model = Pipeline::new(LinRegressor::default())
+//!                          .with_step(StandardizerFitter::default())
+//!                          .with_step(MinMaxFitter::default());
+//!
+//! model.train(&inputs, &targets).unwrap();
+//!
+//! let outputs = model.predict(&Matrix::new(1, 1, vec![5.0])).unwrap();
+//! assert!((outputs[0] - 11.0).abs() < 1e-8);
+//! ```
+
+use std::marker::PhantomData;
+
+use data::transforms::{Transformer, TransformFitter};
+use linalg::{Matrix, Vector};
+use learning::{LearningResult, SupModel};
+use learning::error::Error;
+
+/// A sequence of transformations which is fitted to training data.
+///
+/// This is implemented for `()`, the empty sequence, for a
+/// `FittedStep`, and for pairs of steps applied one after the other.
+pub trait PipelineStep {
+    /// Fits the step to the inputs and returns the transformed inputs.
+    fn fit_transform(&mut self, inputs: Matrix<f64>) -> LearningResult<Matrix<f64>>;
+
+    /// Transforms the inputs using the fitted step.
+    fn transform(&self, inputs: Matrix<f64>) -> LearningResult<Matrix<f64>>;
+}
+
+impl PipelineStep for () {
+    fn fit_transform(&mut self, inputs: Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        Ok(inputs)
+    }
+
+    fn transform(&self, inputs: Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        Ok(inputs)
+    }
+}
+
+impl<A: PipelineStep, B: PipelineStep> PipelineStep for (A, B) {
+    fn fit_transform(&mut self, inputs: Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        let inputs = self.0.fit_transform(inputs)?;
+        self.1.fit_transform(inputs)
+    }
+
+    fn transform(&self, inputs: Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        let inputs = self.0.transform(inputs)?;
+        self.1.transform(inputs)
+    }
+}
+
+/// A fitter and the transformer it produced from the last training data.
+///
+/// `Transformer::transform` takes `&mut self`, so the fitted transformer
+/// is cloned for each call to `transform`. This leaves it unchanged,
+/// and a fitted pipeline can be shared between threads.
+#[derive(Debug)]
+pub struct FittedStep<F, T> {
+    fitter: F,
+    transformer: Option<T>,
+}
+
+impl<F, T> FittedStep<F, T> {
+    /// Constructs an unfitted step.
+    pub fn new(fitter: F) -> FittedStep<F, T> {
+        FittedStep {
+            fitter,
+            transformer: None,
+        }
+    }
+
+    /// The fitter used to create the transformer.
+    pub fn fitter(&self) -> &F {
+        &self.fitter
+    }
+
+    /// Consumes the step, returning the fitted transformer.
+    ///
+    /// Returns `None` if the step has not been fitted.
+    pub fn into_transformer(self) -> Option<T> {
+        self.transformer
+    }
+}
+
+impl<F, T> PipelineStep for FittedStep<F, T>
+    where F: TransformFitter<Matrix<f64>, T> + Clone,
+          T: Transformer<Matrix<f64>> + Clone
+{
+    fn fit_transform(&mut self, inputs: Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        let transformer = self.fitter.clone().fit(&inputs)?;
+        let outputs = transformer.clone().transform(inputs)?;
+        self.transformer = Some(transformer);
+        Ok(outputs)
+    }
+
+    fn transform(&self, inputs: Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        match self.transformer {
+            Some(ref transformer) => transformer.clone().transform(inputs),
+            None => Err(Error::new_untrained()),
+        }
+    }
+}
+
+/// A sequence of transformations followed by a supervised model.
+///
+/// See the module description for more information.
+#[derive(Debug)]
+pub struct Pipeline<S, M, U> {
+    steps: S,
+    model: M,
+    targets: PhantomData<U>,
+}
+
+impl<M, U> Pipeline<(), M, U>
+    where M: SupModel<Matrix<f64>, U>
+{
+    /// Constructs a pipeline with no transformations before the model.
+    pub fn new(model: M) -> Pipeline<(), M, U> {
+        Pipeline {
+            steps: (),
+            model,
+            targets: PhantomData,
+        }
+    }
+}
+
+impl<S, M, U> Pipeline<S, M, U>
+    where S: PipelineStep,
+          M: SupModel<Matrix<f64>, U>
+{
+    /// Adds a transformation after the existing ones.
+    ///
+    /// The fitter is cloned each time the pipeline is trained.
+    pub fn with_step<F, T>(self, fitter: F) -> Pipeline<(S, FittedStep<F, T>), M, U>
+        where F: TransformFitter<Matrix<f64>, T> + Clone,
+              T: Transformer<Matrix<f64>> + Clone
+    {
+        Pipeline {
+            steps: (self.steps, FittedStep::new(fitter)),
+            model: self.model,
+            targets: PhantomData,
+        }
+    }
+
+    /// The transformations applied before the model.
+    ///
+    /// The steps are nested pairs in the order they were added.
+    pub fn steps(&self) -> &S {
+        &self.steps
+    }
+
+    /// The model trained on the transformed inputs.
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// Applies the fitted transformations to the inputs.
+    pub fn transform(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        self.steps.transform(inputs.clone())
+    }
+}
+
+impl<S, M, U> SupModel<Matrix<f64>, U> for Pipeline<S, M, U>
+    where S: PipelineStep,
+          M: SupModel<Matrix<f64>, U>
+{
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<U> {
+        let transformed = self.transform(inputs)?;
+        self.model.predict(&transformed)
+    }
+
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &U) -> LearningResult<()> {
+        let transformed = self.steps.fit_transform(inputs.clone())?;
+        self.model.train(&transformed, targets)
+    }
+
+    fn train_weighted(&mut self,
+                      inputs: &Matrix<f64>,
+                      targets: &U,
+                      weights: &Vector<f64>)
+                      -> LearningResult<()> {
+        let transformed = self.steps.fit_transform(inputs.clone())?;
+        self.model.train_weighted(&transformed, targets, weights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pipeline;
+    use data::transforms::{Transformer, TransformFitter, StandardizerFitter, PolynomialFitter};
+    use learning::SupModel;
+    use learning::lin_reg::LinRegressor;
+    use linalg::{Matrix, Vector};
+
+    use std::sync::Arc;
+    use std::thread;
+
+    fn curve_data() -> (Matrix<f64>, Vector<f64>) {
+        let xs = (0..30).map(|i| 10.0 + i as f64 * 0.5).collect::<Vec<f64>>();
+        let ys = xs.iter().map(|x| (x * 0.4).sin() * 3.0 + 0.1 * x).collect::<Vec<f64>>();
+        (Matrix::new(30, 1, xs), Vector::new(ys))
+    }
+
+    fn expander() -> PolynomialFitter {
+        PolynomialFitter::new(3)
+    }
+
+    #[test]
+    fn test_pipeline_matches_manual_steps() {
+        let (inputs, targets) = curve_data();
+        let new_inputs = Matrix::new(3, 1, vec![11.25, 17.0, 30.0]);
+
+        let mut pipeline = Pipeline::new(LinRegressor::default())
+            .with_step(StandardizerFitter::default())
+            .with_step(expander());
+        pipeline.train(&inputs, &targets).unwrap();
+        let outputs = pipeline.predict(&new_inputs).unwrap();
+
+        let mut standardizer = StandardizerFitter::default().fit(&inputs).unwrap();
+        let standardized = standardizer.transform(inputs).unwrap();
+        let mut features = expander().fit(&standardized).unwrap();
+        let expanded = features.transform(standardized).unwrap();
+        let mut model = LinRegressor::default();
+        model.train(&expanded, &targets).unwrap();
+
+        let new_features = features.transform(standardizer.transform(new_inputs).unwrap()).unwrap();
+        let expected = model.predict(&new_features).unwrap();
+
+        assert_eq!(outputs, expected);
+        assert_eq!(pipeline.model().parameters(), model.parameters());
+    }
+
+    #[test]
+    fn test_pipeline_uses_training_statistics() {
+        let (inputs, targets) = curve_data();
+
+        let mut pipeline = Pipeline::new(LinRegressor::default())
+            .with_step(StandardizerFitter::default());
+        pipeline.train(&inputs, &targets).unwrap();
+
+        // A single row cannot be standardized on its own, so
+        // the fitted standardizer must be reused.
+        let outputs = pipeline.predict(&Matrix::new(1, 1, vec![10.0])).unwrap();
+        assert_eq!(outputs.size(), 1);
+
+        let mut standardizer = StandardizerFitter::default().fit(&inputs).unwrap();
+        let expected = standardizer.transform(Matrix::new(1, 1, vec![10.0])).unwrap();
+        assert_eq!(pipeline.transform(&Matrix::new(1, 1, vec![10.0])).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_pipeline_without_steps() {
+        let (inputs, targets) = curve_data();
+
+        let mut pipeline = Pipeline::new(LinRegressor::default());
+        pipeline.train(&inputs, &targets).unwrap();
+
+        let mut model = LinRegressor::default();
+        model.train(&inputs, &targets).unwrap();
+
+        assert_eq!(pipeline.predict(&inputs).unwrap(), model.predict(&inputs).unwrap());
+    }
+
+    #[test]
+    fn test_pipeline_untrained() {
+        let pipeline = Pipeline::new(LinRegressor::default())
+            .with_step(StandardizerFitter::default());
+
+        assert!(pipeline.predict(&Matrix::new(1, 1, vec![1.0])).is_err());
+    }
+
+    #[test]
+    fn test_pipeline_shared_between_threads() {
+        let (inputs, targets) = curve_data();
+
+        let mut pipeline = Pipeline::new(LinRegressor::default())
+            .with_step(StandardizerFitter::default())
+            .with_step(expander());
+        pipeline.train(&inputs, &targets).unwrap();
+        let expected = pipeline.predict(&inputs).unwrap();
+
+        let pipeline = Arc::new(pipeline);
+        let handles = (0..2)
+            .map(|_| {
+                let pipeline = pipeline.clone();
+                let inputs = inputs.clone();
+                thread::spawn(move || pipeline.predict(&inputs).unwrap())
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+    }
+}
@@ -0,0 +1,316 @@
+//! Quantile Regression module
+//!
+//! Contains an implementation of linear quantile regression.
+//!
+//! Rather than the conditional mean of the targets, the model estimates
+//! a conditional quantile `tau`. For example `tau = 0.9` gives a line
+//! with roughly 90% of the targets below it. Fitting the models for two
+//! quantiles gives a prediction interval without assuming anything
+//! about the distribution of the noise.
+//!
+//! The model minimizes the pinball loss, which weights positive residuals
+//! by `tau` and negative residuals by `1 - tau`. It is fit by iteratively
+//! reweighted least squares. Each weight is the pinball weight of the
+//! residual divided by its size, and residuals are bounded away from zero
+//! to keep the weights finite. This minimizes a smoothed pinball loss
+//! which matches the exact loss away from the fitted line.
+//!
+//! The regressor will automatically add the intercept term
+//! so you do not need to format the input matrices yourself.
+//!
+//! # Usage
+//!
+//! ```
+//! use rusty_machine::learning::quantile_reg::QuantileRegressor;
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::{Matrix, Vector};
+//!
+//! let inputs = Matrix::new(5,1,vec![1.0,2.0,3.0,4.0,5.0]);
+//! let targets = Vector::new(vec![1.2,1.9,3.1,4.5,4.9]);
+//!
+//! // Estimate the 90th percentile of the targets.
+//! let mut upper = QuantileRegressor::new(0.9, 100);
+//! upper.train(&inputs, &targets).unwrap();
+//!
+//! let upper_bound = upper.predict(&inputs).unwrap();
+//! ```
+
+use linalg::{Matrix, BaseMatrix, Vector};
+use learning::{LearningResult, SupModel, validate_finite_matrix, validate_finite_vector};
+use learning::error::Error;
+use learning::lin_reg::weighted_least_squares;
+
+/// Relative change in the parameters at which the iterations stop.
+const CONVERGENCE_TOL: f64 = 1e-8;
+
+/// Residuals are bounded below by this amount, relative to the
+/// scale of the targets, when computing the weights.
+const RESIDUAL_FLOOR: f64 = 1e-6;
+
+/// Quantile Regression Model.
+///
+/// Contains the quantile to estimate and
+/// an option for the fitted parameters.
+#[derive(Debug)]
+pub struct QuantileRegressor {
+    /// The quantile to estimate.
+    tau: f64,
+    /// The maximum number of reweighting iterations.
+    max_iters: usize,
+    /// The parameters for the regression model.
+    parameters: Option<Vector<f64>>,
+}
+
+/// The default quantile regressor.
+///
+/// The defaults are:
+///
+/// - tau = 0.5
+/// - max_iters = 100
+///
+/// This estimates the conditional median.
+impl Default for QuantileRegressor {
+    fn default() -> QuantileRegressor {
+        QuantileRegressor {
+            tau: 0.5,
+            max_iters: 100,
+            parameters: None,
+        }
+    }
+}
+
+impl QuantileRegressor {
+    /// Constructs an untrained quantile regressor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::quantile_reg::QuantileRegressor;
+    ///
+    /// let model = QuantileRegressor::new(0.1, 50);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `tau` is not strictly between 0 and 1.
+    pub fn new(tau: f64, max_iters: usize) -> QuantileRegressor {
+        assert!(tau > 0f64 && tau < 1f64, "Tau must be strictly between 0 and 1.");
+
+        QuantileRegressor {
+            tau,
+            max_iters,
+            parameters: None,
+        }
+    }
+
+    /// The quantile estimated by the model.
+    pub fn tau(&self) -> f64 {
+        self.tau
+    }
+
+    /// The maximum number of reweighting iterations.
+    pub fn max_iters(&self) -> usize {
+        self.max_iters
+    }
+
+    /// Get the parameters from the model.
+    ///
+    /// The first parameter is the intercept.
+    ///
+    /// Returns an option that is None if the model has not been trained.
+    pub fn parameters(&self) -> Option<&Vector<f64>> {
+        self.parameters.as_ref()
+    }
+
+    /// Computes the weight of each residual in the next least squares fit.
+    fn pinball_weights(&self, residuals: &Vector<f64>, floor: f64) -> Vec<f64> {
+        residuals.iter()
+            .map(|&r| {
+                let w = if r >= 0f64 { self.tau } else { 1f64 - self.tau };
+                w / r.abs().max(floor)
+            })
+            .collect()
+    }
+}
+
+/// The pinball loss of the residuals for the quantile `tau`.
+fn pinball_loss(residuals: &Vector<f64>, tau: f64) -> f64 {
+    residuals.iter()
+        .map(|&r| if r >= 0f64 { tau * r } else { (tau - 1f64) * r })
+        .sum()
+}
+
+impl SupModel<Matrix<f64>, Vector<f64>> for QuantileRegressor {
+    /// Train the quantile regression model.
+    ///
+    /// Takes training data and output values as input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::quantile_reg::QuantileRegressor;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    /// use rusty_machine::learning::SupModel;
+    ///
+    /// let mut quantile_mod = QuantileRegressor::default();
+    /// let inputs = Matrix::new(3,1, vec![2.0, 3.0, 4.0]);
+    /// let targets = Vector::new(vec![5.0, 6.0, 7.0]);
+    ///
+    /// quantile_mod.train(&inputs, &targets).unwrap();
+    /// ```
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_vector("target", targets)?;
+
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        let full_inputs = ones.hcat(inputs);
+
+        let target_scale = targets.iter().map(|t| t.abs()).fold(0f64, f64::max);
+        let floor = RESIDUAL_FLOOR * target_scale.max(1f64);
+
+        // Start from the ordinary least squares solution.
+        let mut params = weighted_least_squares(&full_inputs, targets, &vec![1f64; inputs.rows()])?;
+        let mut loss = pinball_loss(&(targets - &full_inputs * &params), self.tau);
+
+        for _ in 0..self.max_iters {
+            let residuals = targets - &full_inputs * &params;
+            let weights = self.pinball_weights(&residuals, floor);
+
+            let new_params = weighted_least_squares(&full_inputs, targets, &weights)?;
+            let new_loss = pinball_loss(&(targets - &full_inputs * &new_params), self.tau);
+
+            // Rounding in the nearly singular late iterations
+            // can make the fit worse, so keep the best one.
+            if new_loss > loss {
+                break;
+            }
+
+            let change = (&new_params - &params).iter().map(|x| x.abs()).fold(0f64, f64::max);
+            let size = params.iter().map(|x| x.abs()).fold(0f64, f64::max);
+            params = new_params;
+            loss = new_loss;
+
+            if change <= CONVERGENCE_TOL * size.max(1f64) {
+                break;
+            }
+        }
+
+        self.parameters = Some(params);
+        Ok(())
+    }
+
+    /// Predict output value from input data.
+    ///
+    /// Model must be trained before prediction can be made.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let Some(ref v) = self.parameters {
+            let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+            let full_inputs = ones.hcat(inputs);
+            Ok(full_inputs * v)
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QuantileRegressor, pinball_loss};
+    use learning::SupModel;
+    use linalg::{Matrix, Vector};
+
+    /// Points above the line `y = 2x + 1`, with noise spread
+    /// evenly over `[0, 3)`.
+    fn noisy_line() -> (Matrix<f64>, Vector<f64>) {
+        let n = 400;
+        let xs = (0..n).map(|i| i as f64 * 0.05).collect::<Vec<f64>>();
+        let ys = xs.iter()
+            .enumerate()
+            .map(|(i, x)| 2.0 * x + 1.0 + 3.0 * (i as f64 * 0.618_033_988_7).fract())
+            .collect::<Vec<f64>>();
+
+        (Matrix::new(n, 1, xs), Vector::new(ys))
+    }
+
+    fn fraction_below(model: &QuantileRegressor, inputs: &Matrix<f64>, targets: &Vector<f64>) -> f64 {
+        let outputs = model.predict(inputs).unwrap();
+        let below = targets.iter().zip(outputs.iter()).filter(|&(t, o)| t < o).count();
+        below as f64 / targets.size() as f64
+    }
+
+    #[test]
+    fn test_upper_quantile_coverage() {
+        let (inputs, targets) = noisy_line();
+
+        let mut model = QuantileRegressor::new(0.9, 200);
+        model.train(&inputs, &targets).unwrap();
+
+        assert!((fraction_below(&model, &inputs, &targets) - 0.9).abs() < 0.02);
+
+        // The 90th percentile of the noise is 2.7.
+        let params = model.parameters().unwrap();
+        assert!((params[0] - 3.7).abs() < 0.1);
+        assert!((params[1] - 2.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_quantiles_are_ordered() {
+        let (inputs, targets) = noisy_line();
+
+        let mut predictions = Vec::new();
+        for &tau in &[0.1, 0.5, 0.9] {
+            let mut model = QuantileRegressor::new(tau, 200);
+            model.train(&inputs, &targets).unwrap();
+
+            assert!((fraction_below(&model, &inputs, &targets) - tau).abs() < 0.02);
+            predictions.push(model.predict(&inputs).unwrap());
+        }
+
+        for w in predictions.windows(2) {
+            assert!(w[0].iter().zip(w[1].iter()).all(|(lo, hi)| lo < hi));
+        }
+    }
+
+    #[test]
+    fn test_improves_least_squares_loss() {
+        let (inputs, targets) = noisy_line();
+
+        let mut model = QuantileRegressor::new(0.9, 0);
+        model.train(&inputs, &targets).unwrap();
+        let ols_loss = pinball_loss(&(&targets - model.predict(&inputs).unwrap()), 0.9);
+
+        let mut model = QuantileRegressor::new(0.9, 200);
+        model.train(&inputs, &targets).unwrap();
+        let loss = pinball_loss(&(&targets - model.predict(&inputs).unwrap()), 0.9);
+
+        assert!(loss < ols_loss);
+    }
+
+    #[test]
+    fn test_quantile_exact_fit() {
+        let inputs = Matrix::new(3, 1, vec![2.0, 3.0, 4.0]);
+        let targets = Vector::new(vec![5.0, 6.0, 7.0]);
+
+        let mut model = QuantileRegressor::new(0.75, 100);
+        model.train(&inputs, &targets).unwrap();
+
+        let outputs = model.predict(&inputs).unwrap();
+        for (o, t) in outputs.iter().zip(targets.iter()) {
+            assert!((o - t).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_quantile_untrained() {
+        let model = QuantileRegressor::default();
+        let inputs = Matrix::new(1, 1, vec![1.0]);
+
+        assert!(model.predict(&inputs).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_quantile_invalid_tau() {
+        let _ = QuantileRegressor::new(1.0, 10);
+    }
+}
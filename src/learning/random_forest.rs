@@ -0,0 +1,813 @@
+//! Random Forest module
+//!
+//! Contains an implementation of random forest regression.
+//!
+//! The forest averages the predictions of regression trees, each
+//! trained on a bootstrap sample of the data. Every split of a tree
+//! considers only a random subset of the features, which makes the
+//! trees differ from one another so that averaging them reduces the
+//! variance of the predictions.
+//!
+//! The forest can also keep the targets of the samples in each leaf.
+//! It then answers quantile queries from the empirical distribution
+//! of the targets sharing leaves with an input, as in quantile
+//! regression forests. Querying two quantiles gives a prediction
+//! interval without assuming anything about the distribution of the
+//! noise.
+//!
+//! # Usage
+//!
+//! ```
+//! use rusty_machine::learning::random_forest::RandomForest;
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::{Matrix, BaseMatrix, Vector};
+//!
+//! let inputs = Matrix::new(8,1,vec![1.0,2.0,3.0,4.0,5.0,6.0,7.0,8.0]);
+//! let targets = Vector::new(vec![1.1,1.9,3.2,3.9,5.1,6.0,6.8,8.1]);
+//!
+//! let mut forest = RandomForest::new(20).with_min_leaf_size(2).with_leaf_samples();
+//! forest.train(&inputs, &targets).unwrap();
+//!
+//! let mean = forest.predict(&inputs).unwrap();
+//!
+//! // The 10th, 50th and 90th percentiles for each input.
+//! let quantiles = forest.predict_quantiles(&inputs, &[0.1, 0.5, 0.9]).unwrap();
+//! assert_eq!(quantiles.cols(), 3);
+//! ```
+
+use linalg::{Matrix, BaseMatrix, Vector};
+use learning::{LearningResult, SupModel, validate_finite_matrix, validate_finite_vector};
+use learning::error::{Error, ErrorKind};
+use learning::model_info::ModelInfo;
+use learning::toolkit::rand_utils;
+
+use rand::{self, Rng, SeedableRng, StdRng};
+
+use std::cmp::Ordering;
+
+/// A node of a regression tree.
+#[derive(Clone, Debug)]
+enum Node {
+    /// Inputs with `feature` at most `threshold` go to `left`.
+    Split {
+        feature: usize,
+        threshold: f64,
+        left: usize,
+        right: usize,
+    },
+    /// The mean of the bootstrap targets in the leaf, and the
+    /// training targets falling into it if the forest keeps them.
+    Leaf { mean: f64, samples: Vec<f64> },
+}
+
+/// A regression tree, with the root as the first node.
+#[derive(Clone, Debug)]
+struct RegressionTree {
+    nodes: Vec<Node>,
+}
+
+impl RegressionTree {
+    /// The index of the leaf which the input falls into.
+    fn leaf_index(&self, input: &[f64]) -> usize {
+        let mut index = 0;
+        while let Node::Split { feature, threshold, left, right } = self.nodes[index] {
+            index = if input[feature] <= threshold { left } else { right };
+        }
+        index
+    }
+
+    /// The leaf which the input falls into.
+    fn leaf(&self, input: &[f64]) -> &Node {
+        &self.nodes[self.leaf_index(input)]
+    }
+
+    /// Writes the subtree at `index` as nested if/else statements.
+    fn write_text(&self, index: usize, depth: usize, text: &mut String) {
+        let indent = "    ".repeat(depth);
+        match self.nodes[index] {
+            Node::Split { feature, threshold, left, right } => {
+                text.push_str(&format!("{}if x[{}] <= {}:\n", indent, feature, threshold));
+                self.write_text(left, depth + 1, text);
+                text.push_str(&format!("{}else:\n", indent));
+                self.write_text(right, depth + 1, text);
+            }
+            Node::Leaf { mean, .. } => text.push_str(&format!("{}predict {}\n", indent, mean)),
+        }
+    }
+
+    /// Describes the tree as nested if/else statements.
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+        self.write_text(0, 0, &mut text);
+        text
+    }
+
+    /// Describes the tree in the DOT language of Graphviz.
+    fn to_graphviz(&self) -> String {
+        let mut dot = String::from("digraph RegressionTree {\n");
+        dot.push_str("    node [shape=box];\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            match *node {
+                Node::Split { feature, threshold, .. } => {
+                    dot.push_str(&format!("    n{} [label=\"x[{}] <= {}\"];\n", i, feature, threshold));
+                }
+                Node::Leaf { mean, .. } => {
+                    dot.push_str(&format!("    n{} [label=\"predict {}\"];\n", i, mean));
+                }
+            }
+        }
+        for (i, node) in self.nodes.iter().enumerate() {
+            if let Node::Split { left, right, .. } = *node {
+                dot.push_str(&format!("    n{} -> n{} [label=\"true\"];\n", i, left));
+                dot.push_str(&format!("    n{} -> n{} [label=\"false\"];\n", i, right));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// The settings used to grow each tree.
+struct TreeBuilder<'a, R: 'a> {
+    inputs: &'a Matrix<f64>,
+    targets: &'a Vector<f64>,
+    max_depth: usize,
+    min_leaf_size: usize,
+    max_features: usize,
+    rng: &'a mut R,
+    nodes: Vec<Node>,
+}
+
+impl<'a, R: Rng> TreeBuilder<'a, R> {
+    /// Grows the subtree of the given rows, returning its index.
+    fn build(&mut self, rows: &mut [usize], depth: usize) -> usize {
+        let index = self.nodes.len();
+        let split = if depth < self.max_depth && rows.len() >= 2 * self.min_leaf_size {
+            self.best_split(rows)
+        } else {
+            None
+        };
+
+        match split {
+            Some((feature, threshold)) => {
+                // Reserve the node so that the root stays first.
+                self.nodes.push(Node::Leaf {
+                    mean: 0f64,
+                    samples: Vec::new(),
+                });
+
+                let inputs = self.inputs;
+                rows.sort_by(|&a, &b| {
+                    inputs[[a, feature]].partial_cmp(&inputs[[b, feature]]).unwrap_or(Ordering::Equal)
+                });
+                let n_left = rows.iter().take_while(|&&r| inputs[[r, feature]] <= threshold).count();
+                let (lower, upper) = rows.split_at_mut(n_left);
+
+                let left = self.build(lower, depth + 1);
+                let right = self.build(upper, depth + 1);
+                self.nodes[index] = Node::Split {
+                    feature,
+                    threshold,
+                    left,
+                    right,
+                };
+            }
+            None => {
+                let mean = rows.iter().map(|&r| self.targets[r]).sum::<f64>() / rows.len() as f64;
+                self.nodes.push(Node::Leaf {
+                    mean,
+                    samples: Vec::new(),
+                });
+            }
+        }
+        index
+    }
+
+    /// The split over a random subset of the features which most
+    /// reduces the sum of squared errors, if any split does.
+    fn best_split(&mut self, rows: &mut [usize]) -> Option<(usize, f64)> {
+        let n = rows.len();
+        let total = rows.iter().map(|&r| self.targets[r]).sum::<f64>();
+        let total_sq = rows.iter().map(|&r| self.targets[r] * self.targets[r]).sum::<f64>();
+        // Splits must improve on the error of a single leaf.
+        let mut best_error = total_sq - total * total / n as f64;
+        let mut best = None;
+
+        let mut features = (0..self.inputs.cols()).collect::<Vec<usize>>();
+        rand_utils::in_place_fisher_yates_with_rng(&mut features, self.rng);
+
+        for &feature in features.iter().take(self.max_features) {
+            let inputs = self.inputs;
+            rows.sort_by(|&a, &b| {
+                inputs[[a, feature]].partial_cmp(&inputs[[b, feature]]).unwrap_or(Ordering::Equal)
+            });
+
+            let mut left_sum = 0f64;
+            let mut left_sq = 0f64;
+            for i in 0..n - 1 {
+                let t = self.targets[rows[i]];
+                left_sum += t;
+                left_sq += t * t;
+
+                let n_left = i + 1;
+                let value = inputs[[rows[i], feature]];
+                let next = inputs[[rows[i + 1], feature]];
+                if n_left < self.min_leaf_size || n - n_left < self.min_leaf_size || value == next {
+                    continue;
+                }
+
+                let right_sum = total - left_sum;
+                let error = (left_sq - left_sum * left_sum / n_left as f64) +
+                            (total_sq - left_sq - right_sum * right_sum / (n - n_left) as f64);
+                if error < best_error - 1e-12 * best_error.abs() {
+                    best_error = error;
+                    best = Some((feature, (value + next) / 2f64));
+                }
+            }
+        }
+        best
+    }
+}
+
+/// Random Forest Regression Model.
+///
+/// Contains the settings for growing the trees and
+/// an option for the trained trees.
+#[derive(Clone, Debug)]
+pub struct RandomForest {
+    /// The number of trees in the forest.
+    n_trees: usize,
+    /// The maximum depth of each tree.
+    max_depth: usize,
+    /// The minimum number of samples in each leaf.
+    min_leaf_size: usize,
+    /// The number of features considered at each split.
+    max_features: Option<usize>,
+    /// Whether the leaves keep the targets of their samples.
+    keep_leaf_samples: bool,
+    /// The seed for the bootstrap samples and feature subsets.
+    seed: Option<u64>,
+    /// The trained trees, and the number of features they were trained on.
+    trees: Option<(Vec<RegressionTree>, usize)>,
+}
+
+/// The default random forest.
+///
+/// The defaults are:
+///
+/// - n_trees = 100
+/// - max_depth = unlimited
+/// - min_leaf_size = 5
+/// - max_features = a third of the features, rounded up
+/// - no leaf samples are kept
+impl Default for RandomForest {
+    fn default() -> RandomForest {
+        RandomForest::new(100)
+    }
+}
+
+impl RandomForest {
+    /// Constructs an untrained random forest with `n_trees` trees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::random_forest::RandomForest;
+    ///
+    /// let forest = RandomForest::new(50);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `n_trees` is zero.
+    pub fn new(n_trees: usize) -> RandomForest {
+        assert!(n_trees > 0, "The forest must have at least one tree.");
+
+        RandomForest {
+            n_trees,
+            max_depth: usize::MAX,
+            min_leaf_size: 5,
+            max_features: None,
+            keep_leaf_samples: false,
+            seed: None,
+            trees: None,
+        }
+    }
+
+    /// Limit the depth of each tree.
+    ///
+    /// A depth of zero gives trees with a single leaf.
+    pub fn with_max_depth(mut self, max_depth: usize) -> RandomForest {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Set the minimum number of samples in each leaf.
+    ///
+    /// Larger leaves give smoother predictions and
+    /// more reliable quantile estimates.
+    ///
+    /// # Panics
+    ///
+    /// - `min_leaf_size` is zero.
+    pub fn with_min_leaf_size(mut self, min_leaf_size: usize) -> RandomForest {
+        assert!(min_leaf_size > 0, "The minimum leaf size must be greater than 0.");
+
+        self.min_leaf_size = min_leaf_size;
+        self
+    }
+
+    /// Set the number of features considered at each split.
+    ///
+    /// All of the features are considered if there are fewer than `max_features`.
+    ///
+    /// # Panics
+    ///
+    /// - `max_features` is zero.
+    pub fn with_max_features(mut self, max_features: usize) -> RandomForest {
+        assert!(max_features > 0, "The number of features per split must be greater than 0.");
+
+        self.max_features = Some(max_features);
+        self
+    }
+
+    /// Keep the targets of the samples in each leaf,
+    /// so that the forest can predict quantiles.
+    ///
+    /// The forest then stores every training target in each tree.
+    pub fn with_leaf_samples(mut self) -> RandomForest {
+        self.keep_leaf_samples = true;
+        self
+    }
+
+    /// Draw the bootstrap samples and feature subsets using a random
+    /// number generator seeded with `seed`, so that training on the
+    /// same data always gives the same forest.
+    pub fn with_seed(mut self, seed: u64) -> RandomForest {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The number of trees in the forest.
+    pub fn n_trees(&self) -> usize {
+        self.n_trees
+    }
+
+    /// Whether the leaves keep the targets of their samples.
+    pub fn keeps_leaf_samples(&self) -> bool {
+        self.keep_leaf_samples
+    }
+
+    /// Describes a trained tree of the forest as nested if/else statements.
+    ///
+    /// Inputs take the first branch of a split if their feature is at
+    /// most the threshold. Each leaf predicts the mean of its targets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::random_forest::RandomForest;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let inputs = Matrix::new(8,1,vec![1.0,1.0,1.0,1.0,5.0,5.0,5.0,5.0]);
+    /// let targets = Vector::new(vec![1.0,1.0,1.0,1.0,3.0,3.0,3.0,3.0]);
+    ///
+    /// let mut forest = RandomForest::new(2).with_min_leaf_size(1).with_seed(3);
+    /// forest.train(&inputs, &targets).unwrap();
+    ///
+    /// assert_eq!(forest.tree_to_text(0).unwrap(),
+    ///            "if x[0] <= 3:\n    predict 1\nelse:\n    predict 3\n");
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The model has not been trained.
+    /// - `tree` is not less than the number of trees.
+    pub fn tree_to_text(&self, tree: usize) -> LearningResult<String> {
+        Ok(self.tree(tree)?.to_text())
+    }
+
+    /// Describes a trained tree of the forest in the DOT language of Graphviz.
+    ///
+    /// The graph has a node for each split and leaf, and each split has
+    /// an edge to its two children labelled with whether the split
+    /// condition holds.
+    ///
+    /// # Failures
+    ///
+    /// - The model has not been trained.
+    /// - `tree` is not less than the number of trees.
+    pub fn tree_to_graphviz(&self, tree: usize) -> LearningResult<String> {
+        Ok(self.tree(tree)?.to_graphviz())
+    }
+
+    /// The trained tree at the given index.
+    fn tree(&self, index: usize) -> LearningResult<&RegressionTree> {
+        let (ref trees, _) = *self.trees.as_ref().ok_or_else(Error::new_untrained)?;
+
+        trees.get(index).ok_or_else(|| {
+            Error::new(ErrorKind::InvalidParameters,
+                       format!("The forest has {} trees, so there is no tree {}.", trees.len(), index))
+        })
+    }
+
+    /// The trained trees, checking that the inputs have the right number of columns.
+    fn trained_trees(&self, inputs: &Matrix<f64>) -> LearningResult<&[RegressionTree]> {
+        let (ref trees, n_features) = *self.trees.as_ref().ok_or_else(Error::new_untrained)?;
+
+        if inputs.cols() != n_features {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  format!("The inputs have {} columns but the forest was trained \
+                                           on {}.",
+                                          inputs.cols(),
+                                          n_features)));
+        }
+        Ok(trees)
+    }
+
+    /// Predict quantiles of the target for each input.
+    ///
+    /// Each tree gives equal weight to the leaf containing the input,
+    /// shared between the targets kept in that leaf. The result has a
+    /// row for each input and a column for each quantile, holding the
+    /// smallest kept target whose cumulative weight reaches the quantile.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::random_forest::RandomForest;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let inputs = Matrix::new(6,1,vec![1.0,2.0,3.0,4.0,5.0,6.0]);
+    /// let targets = Vector::new(vec![1.0,2.0,3.0,4.0,5.0,6.0]);
+    ///
+    /// let mut forest = RandomForest::new(10).with_min_leaf_size(3).with_leaf_samples();
+    /// forest.train(&inputs, &targets).unwrap();
+    ///
+    /// let interval = forest.predict_quantiles(&inputs, &[0.05, 0.95]).unwrap();
+    /// assert!(interval[[0, 0]] <= interval[[0, 1]]);
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The model has not been trained.
+    /// - The model was trained without keeping the leaf samples.
+    /// - A quantile is outside `[0, 1]`.
+    /// - The inputs have a different number of columns to the training inputs.
+    pub fn predict_quantiles(&self,
+                             inputs: &Matrix<f64>,
+                             quantiles: &[f64])
+                             -> LearningResult<Matrix<f64>> {
+        let trees = self.trained_trees(inputs)?;
+        if !self.keep_leaf_samples {
+            return Err(Error::new(ErrorKind::InvalidState,
+                                  "The forest was not trained to keep the leaf samples."));
+        }
+        if let Some(q) = quantiles.iter().find(|q| !(**q >= 0f64 && **q <= 1f64)) {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  format!("The quantile {} is not between 0 and 1.", q)));
+        }
+
+        let mut data = Vec::with_capacity(inputs.rows() * quantiles.len());
+        for row in inputs.row_iter() {
+            // The kept targets and their weights, in increasing order.
+            let mut weighted = Vec::new();
+            for tree in trees {
+                if let Node::Leaf { ref samples, .. } = *tree.leaf(row.raw_slice()) {
+                    let weight = 1f64 / (samples.len() * trees.len()) as f64;
+                    weighted.extend(samples.iter().map(|&t| (t, weight)));
+                }
+            }
+            weighted.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+            for &q in quantiles {
+                let mut cumulative = 0f64;
+                let mut value = weighted[weighted.len() - 1].0;
+                for &(t, w) in &weighted {
+                    cumulative += w;
+                    // Allow for rounding in the sum of the weights.
+                    if cumulative >= q - 1e-12 {
+                        value = t;
+                        break;
+                    }
+                }
+                data.push(value);
+            }
+        }
+
+        Ok(Matrix::new(inputs.rows(), quantiles.len(), data))
+    }
+
+    /// Summarizes the size of the trained forest.
+    ///
+    /// Each split has a feature and a threshold, and each leaf a mean.
+    /// The nodes are counted over all of the trees. The stored samples
+    /// are the targets kept in the leaves.
+    ///
+    /// # Failures
+    ///
+    /// - The model has not been trained.
+    pub fn model_info(&self) -> LearningResult<ModelInfo> {
+        let (ref trees, n_features) = *self.trees.as_ref().ok_or_else(Error::new_untrained)?;
+
+        let n_nodes = trees.iter().map(|t| t.nodes.len()).sum();
+        let n_params = trees.iter()
+            .flat_map(|t| t.nodes.iter())
+            .map(|n| match *n {
+                Node::Leaf { .. } => 1,
+                Node::Split { .. } => 2,
+            })
+            .sum();
+        let n_samples = trees.iter()
+            .flat_map(|t| t.nodes.iter())
+            .map(|n| match *n {
+                Node::Leaf { ref samples, .. } => samples.len(),
+                Node::Split { .. } => 0,
+            })
+            .sum();
+
+        Ok(ModelInfo {
+            n_params,
+            n_inputs: Some(n_features),
+            n_outputs: Some(1),
+            n_stored_samples: if self.keep_leaf_samples { Some(n_samples) } else { None },
+            n_nodes: Some(n_nodes),
+        })
+    }
+
+    /// Grows the trees using the given random number generator.
+    fn train_with_rng<R: Rng>(&mut self,
+                              inputs: &Matrix<f64>,
+                              targets: &Vector<f64>,
+                              rng: &mut R) {
+        let n_features = inputs.cols();
+        let max_features = self.max_features.unwrap_or_else(|| n_features.div_ceil(3)).clamp(1, n_features.max(1));
+
+        let mut trees = Vec::with_capacity(self.n_trees);
+        for _ in 0..self.n_trees {
+            let mut rows = (0..inputs.rows()).map(|_| rng.gen_range(0, inputs.rows())).collect::<Vec<usize>>();
+
+            let mut builder = TreeBuilder {
+                inputs,
+                targets,
+                max_depth: self.max_depth,
+                min_leaf_size: self.min_leaf_size,
+                max_features,
+                rng: &mut *rng,
+                nodes: Vec::new(),
+            };
+            builder.build(&mut rows, 0);
+            let mut tree = RegressionTree { nodes: builder.nodes };
+
+            // Every training sample is kept, not only those in the bootstrap
+            // sample, which avoids underestimating the spread of the leaves.
+            if self.keep_leaf_samples {
+                for (row, &target) in inputs.row_iter().zip(targets.iter()) {
+                    let leaf = tree.leaf_index(row.raw_slice());
+                    if let Node::Leaf { ref mut samples, .. } = tree.nodes[leaf] {
+                        samples.push(target);
+                    }
+                }
+            }
+            trees.push(tree);
+        }
+
+        self.trees = Some((trees, n_features));
+    }
+}
+
+impl SupModel<Matrix<f64>, Vector<f64>> for RandomForest {
+    /// Train the forest on the inputs and targets.
+    ///
+    /// Each tree is grown on a bootstrap sample of the rows.
+    ///
+    /// # Failures
+    ///
+    /// - The inputs or targets contain non-finite values.
+    /// - The inputs are empty.
+    /// - The inputs and targets have different numbers of rows.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_vector("target", targets)?;
+
+        if inputs.rows() == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "The inputs are empty."));
+        }
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The inputs and targets must have the same number of rows."));
+        }
+
+        match self.seed {
+            Some(seed) => {
+                let mut rng = StdRng::from_seed(&[seed as u32 as usize, (seed >> 32) as usize]);
+                self.train_with_rng(inputs, targets, &mut rng);
+            }
+            None => self.train_with_rng(inputs, targets, &mut rand::thread_rng()),
+        }
+        Ok(())
+    }
+
+    /// Predict the mean target of each input.
+    ///
+    /// The prediction is the average of the leaf means of the trees.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        let trees = self.trained_trees(inputs)?;
+
+        Ok(Vector::new(inputs.row_iter()
+            .map(|row| {
+                trees.iter()
+                    .map(|tree| match *tree.leaf(row.raw_slice()) {
+                        Node::Leaf { mean, .. } => mean,
+                        Node::Split { .. } => unreachable!(),
+                    })
+                    .sum::<f64>() / trees.len() as f64
+            })
+            .collect::<Vec<f64>>()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RandomForest;
+    use learning::SupModel;
+    use learning::error::ErrorKind;
+    use linalg::{Matrix, BaseMatrix, Vector};
+
+    use rand::{Rng, SeedableRng, StdRng};
+    use rand::distributions::normal::StandardNormal;
+
+    /// Targets of `2x` with noise whose standard deviation grows with `x`.
+    fn heteroscedastic_data(n: usize, rng: &mut StdRng) -> (Matrix<f64>, Vector<f64>) {
+        let xs = (0..n).map(|_| rng.gen::<f64>() * 4.0).collect::<Vec<f64>>();
+        let ys = xs.iter()
+            .map(|&x| {
+                let StandardNormal(noise) = rng.gen::<StandardNormal>();
+                2.0 * x + (0.1 + 0.5 * x) * noise
+            })
+            .collect::<Vec<f64>>();
+        (Matrix::new(n, 1, xs), Vector::new(ys))
+    }
+
+    #[test]
+    fn test_predict_fits_step() {
+        let inputs = Matrix::new(20, 1, (0..20).map(|i| i as f64).collect::<Vec<f64>>());
+        let targets = Vector::new((0..20).map(|i| if i < 10 { 1.0 } else { 5.0 }).collect::<Vec<f64>>());
+
+        let mut forest = RandomForest::new(20).with_min_leaf_size(2).with_seed(3);
+        forest.train(&inputs, &targets).unwrap();
+
+        let outputs = forest.predict(&Matrix::new(2, 1, vec![2.0, 17.0])).unwrap();
+        assert!((outputs[0] - 1.0).abs() < 1e-10);
+        assert!((outputs[1] - 5.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_seeded_forests_match() {
+        let mut rng = StdRng::from_seed(&[1, 2]);
+        let (inputs, targets) = heteroscedastic_data(100, &mut rng);
+
+        let mut a = RandomForest::new(5).with_seed(11);
+        let mut b = RandomForest::new(5).with_seed(11);
+        a.train(&inputs, &targets).unwrap();
+        b.train(&inputs, &targets).unwrap();
+
+        assert_eq!(a.predict(&inputs).unwrap(), b.predict(&inputs).unwrap());
+    }
+
+    #[test]
+    fn test_quantile_interval_coverage() {
+        let mut rng = StdRng::from_seed(&[4, 5, 6]);
+        let (train_inputs, train_targets) = heteroscedastic_data(1000, &mut rng);
+        let (test_inputs, test_targets) = heteroscedastic_data(500, &mut rng);
+
+        let mut forest = RandomForest::new(50).with_min_leaf_size(20).with_leaf_samples().with_seed(7);
+        forest.train(&train_inputs, &train_targets).unwrap();
+
+        let intervals = forest.predict_quantiles(&test_inputs, &[0.05, 0.5, 0.95]).unwrap();
+        let covered = test_targets.iter()
+            .zip(intervals.row_iter())
+            .filter(|&(t, ref q)| q[0] <= *t && *t <= q[2])
+            .count();
+        let coverage = covered as f64 / 500.0;
+        assert!(coverage > 0.85 && coverage < 0.95, "coverage was {}", coverage);
+
+        for q in intervals.row_iter() {
+            assert!(q[0] <= q[1] && q[1] <= q[2]);
+        }
+
+        // The intervals widen with the noise.
+        let narrow = forest.predict_quantiles(&Matrix::new(2, 1, vec![0.5, 3.5]), &[0.05, 0.95]).unwrap();
+        assert!(narrow[[1, 1]] - narrow[[1, 0]] > 2.0 * (narrow[[0, 1]] - narrow[[0, 0]]));
+    }
+
+    #[test]
+    fn test_quantiles_need_leaf_samples() {
+        let inputs = Matrix::new(4, 1, vec![1.0, 2.0, 3.0, 4.0]);
+        let targets = Vector::new(vec![1.0, 2.0, 3.0, 4.0]);
+
+        let mut forest = RandomForest::new(3).with_min_leaf_size(1);
+        forest.train(&inputs, &targets).unwrap();
+        assert!(forest.model_info().unwrap().n_stored_samples.is_none());
+
+        let err = forest.predict_quantiles(&inputs, &[0.5]).unwrap_err();
+        assert!(matches!(*err.kind(), ErrorKind::InvalidState));
+    }
+
+    #[test]
+    fn test_quantiles_out_of_range() {
+        let inputs = Matrix::new(4, 1, vec![1.0, 2.0, 3.0, 4.0]);
+        let targets = Vector::new(vec![1.0, 2.0, 3.0, 4.0]);
+
+        let mut forest = RandomForest::new(3).with_min_leaf_size(1).with_leaf_samples();
+        forest.train(&inputs, &targets).unwrap();
+        assert_eq!(forest.model_info().unwrap().n_stored_samples, Some(12));
+
+        let err = forest.predict_quantiles(&inputs, &[0.5, 1.5]).unwrap_err();
+        assert!(matches!(*err.kind(), ErrorKind::InvalidParameters));
+    }
+
+    #[test]
+    fn test_model_info_counts_nodes() {
+        let inputs = Matrix::new(6, 1, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let targets = Vector::new(vec![1.0, 4.0, 2.0, 8.0, 5.0, 7.0]);
+
+        let mut forest = RandomForest::new(4).with_min_leaf_size(1);
+        assert!(forest.model_info().is_err());
+        forest.train(&inputs, &targets).unwrap();
+
+        // Each tree has one more leaf than splits.
+        let info = forest.model_info().unwrap();
+        let n_nodes = info.n_nodes.unwrap();
+        let n_splits = (n_nodes - 4) / 2;
+        assert_eq!(n_nodes, 2 * n_splits + 4);
+        assert!(n_splits > 0);
+        assert_eq!(info.n_params, 2 * n_splits + (n_splits + 4));
+        assert_eq!(info.n_inputs, Some(1));
+    }
+
+    #[test]
+    fn test_tree_export() {
+        let inputs = Matrix::new(8, 1, vec![1.0, 1.0, 1.0, 1.0, 5.0, 5.0, 5.0, 5.0]);
+        let targets = Vector::new(vec![0.0, 0.0, 0.0, 0.0, 10.0, 10.0, 10.0, 10.0]);
+
+        let mut forest = RandomForest::new(2).with_min_leaf_size(1).with_seed(3);
+        assert!(forest.tree_to_text(0).is_err());
+        forest.train(&inputs, &targets).unwrap();
+
+        assert_eq!(forest.tree_to_text(0).unwrap(),
+                   "if x[0] <= 3:\n    predict 0\nelse:\n    predict 10\n");
+
+        let dot = forest.tree_to_graphviz(0).unwrap();
+        assert!(dot.starts_with("digraph RegressionTree {"));
+        assert!(dot.contains("n0 [label=\"x[0] <= 3\"];"));
+        assert!(dot.contains("n1 [label=\"predict 0\"];"));
+        assert!(dot.contains("n2 [label=\"predict 10\"];"));
+        assert!(dot.contains("n0 -> n1 [label=\"true\"];"));
+        assert!(dot.contains("n0 -> n2 [label=\"false\"];"));
+        assert!(dot.trim_end().ends_with('}'));
+
+        let err = forest.tree_to_text(2).unwrap_err();
+        assert!(matches!(*err.kind(), ErrorKind::InvalidParameters));
+        assert!(forest.tree_to_graphviz(2).is_err());
+    }
+
+    #[test]
+    fn test_nested_tree_text() {
+        let inputs = Matrix::new(6, 1, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let targets = Vector::new(vec![0.0, 0.0, 5.0, 5.0, 20.0, 20.0]);
+
+        let mut forest = RandomForest::new(3).with_min_leaf_size(1).with_seed(11);
+        forest.train(&inputs, &targets).unwrap();
+
+        // Every line of a child is indented once more than its split.
+        for i in 0..3 {
+            let text = forest.tree_to_text(i).unwrap();
+            let n_nodes = forest.trees.as_ref().unwrap().0[i].nodes.len();
+            assert_eq!(text.matches("predict").count(), (n_nodes + 1) / 2);
+            for line in text.lines() {
+                let indent = line.len() - line.trim_start().len();
+                assert_eq!(indent % 4, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_untrained_and_wrong_columns() {
+        let mut forest = RandomForest::new(3).with_leaf_samples();
+        let inputs = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+        assert!(forest.predict(&inputs).is_err());
+        assert!(forest.predict_quantiles(&inputs, &[0.5]).is_err());
+
+        forest.train(&inputs, &Vector::new(vec![1.0, 2.0])).unwrap();
+        assert!(forest.predict(&Matrix::new(1, 3, vec![1.0, 2.0, 3.0])).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_trees() {
+        let _ = RandomForest::new(0);
+    }
+}
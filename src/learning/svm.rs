@@ -5,7 +5,8 @@
 //!
 //! The SVM models currently only support binary classification.
 //! The model inputs should be a matrix and the training targets are
-//! in the form of a vector of `-1`s and `1`s.
+//! in the form of a vector of `-1`s and `1`s. Alternatively the
+//! `LabelClassifier` trait trains on, and predicts, the labels `0` and `1`.
 //!
 //! # Examples
 //!
@@ -36,9 +37,11 @@
 use linalg::{Matrix, BaseMatrix};
 use linalg::Vector;
 
-use learning::toolkit::kernel::{Kernel, SquaredExp};
-use learning::{LearningResult, SupModel};
+use learning::toolkit::kernel::{Kernel, SquaredExp, validate_gram_matrix};
+use learning::{LearningResult, SupModel, LabelClassifier, validate_finite_matrix, validate_finite_vector,
+               validate_labels, binary_label_targets};
 use learning::error::{Error, ErrorKind};
+use learning::model_info::ModelInfo;
 
 use rand;
 use rand::Rng;
@@ -121,52 +124,336 @@ impl<K: Kernel> SVM<K> {
     }
 }
 
+impl<K: Kernel> SVM<K> {
+    /// Compute the signed distance of each input from the separating plane.
+    ///
+    /// The sign of the distance gives the predicted class. The distances
+    /// are not probabilities, but can be mapped to probabilities using a
+    /// `PlattScaler` from the `calibration` module.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::svm::SVM;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let inputs = Matrix::new(4,1,vec![1.0,3.0,5.0,7.0]);
+    /// let targets = Vector::new(vec![-1.,-1.,1.,1.]);
+    ///
+    /// let mut svm_mod = SVM::default();
+    /// svm_mod.train(&inputs, &targets).unwrap();
+    ///
+    /// let scores = svm_mod.decision_function(&inputs).unwrap();
+    /// ```
+    pub fn decision_function(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        let full_inputs = ones.hcat(inputs);
+
+        match (self.alpha.as_ref(), self.train_inputs.as_ref(), self.train_targets.as_ref()) {
+            (Some(alpha), Some(train_inputs), Some(train_targets)) => {
+                let ker_mat = self.ker_mat(&full_inputs, train_inputs)?;
+                let weight_vec = alpha.elemul(train_targets) / self.lambda;
+
+                Ok(ker_mat * weight_vec)
+            }
+            (Some(_), None, _) => {
+                Err(Error::new(ErrorKind::InvalidState,
+                               "The model was trained on a precomputed kernel matrix."))
+            }
+            _ => Err(Error::new_untrained()),
+        }
+    }
+}
+
 /// Train the model using the Pegasos algorithm and
 /// predict the model output from new data.
 impl<K: Kernel> SupModel<Matrix<f64>, Vector<f64>> for SVM<K> {
     fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        let plane_dist = self.decision_function(inputs)?;
+        Ok(plane_dist.apply(&|d| d.signum()))
+    }
+
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        validate_finite_matrix("input", inputs)?;
+        validate_finite_vector("target", targets)?;
+
         let ones = Matrix::<f64>::ones(inputs.rows(), 1);
         let full_inputs = ones.hcat(inputs);
 
-        if let (&Some(ref alpha), &Some(ref train_inputs), &Some(ref train_targets)) =
-               (&self.alpha, &self.train_inputs, &self.train_targets) {
-            let ker_mat = try!(self.ker_mat(&full_inputs, train_inputs));
-            let weight_vec = alpha.elemul(train_targets) / self.lambda;
+        let alpha = self.pegasos(targets, |i, j| {
+            self.ker.kernel(full_inputs.row(i).raw_slice(), full_inputs.row(j).raw_slice())
+        });
 
-            let plane_dist = ker_mat * weight_vec;
+        self.alpha = Some(alpha);
+        self.train_inputs = Some(full_inputs);
+        self.train_targets = Some(targets.clone());
 
-            Ok(plane_dist.apply(&|d| d.signum()))
-        } else {
-            Err(Error::new_untrained())
+        Ok(())
+    }
+}
+
+/// Train and predict from the SVM using the labels `0` and `1`,
+/// which correspond to the targets `-1` and `1`.
+impl<K: Kernel> LabelClassifier for SVM<K> {
+    fn train_labels(&mut self, inputs: &Matrix<f64>, labels: &[usize]) -> LearningResult<()> {
+        validate_labels(labels, inputs.rows())?;
+        self.train(inputs, &binary_label_targets(labels, -1f64, 1f64)?)
+    }
+
+    fn predict_labels(&self, inputs: &Matrix<f64>) -> LearningResult<Vec<usize>> {
+        Ok(self.predict(inputs)?.iter().map(|&p| if p > 0f64 { 1 } else { 0 }).collect())
+    }
+}
+
+impl<K: Kernel> SVM<K> {
+    /// Train the model using a precomputed kernel matrix.
+    ///
+    /// The `gram` matrix holds the kernel between each pair of training
+    /// samples and must be square and symmetric. The model's own kernel
+    /// is not used, and no bias column is added to the samples.
+    ///
+    /// A model trained this way must make predictions with
+    /// `predict_precomputed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::svm::SVM;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let gram = Matrix::new(4, 4, vec![1.0, 0.8, 0.1, 0.0,
+    ///                                   0.8, 1.0, 0.2, 0.1,
+    ///                                   0.1, 0.2, 1.0, 0.8,
+    ///                                   0.0, 0.1, 0.8, 1.0]);
+    /// let targets = Vector::new(vec![-1., -1., 1., 1.]);
+    ///
+    /// let mut svm_mod = SVM::default();
+    /// svm_mod.train_precomputed(&gram, &targets).unwrap();
+    ///
+    /// // The kernel between a new sample and each training sample.
+    /// let cross_gram = Matrix::new(1, 4, vec![0.0, 0.1, 0.7, 0.9]);
+    /// let output = svm_mod.predict_precomputed(&cross_gram).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The Gram matrix is not square and symmetric.
+    /// - The Gram matrix and targets have different sizes.
+    pub fn train_precomputed(&mut self, gram: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        validate_gram_matrix(gram)?;
+        if gram.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The Gram matrix and targets have different sizes."));
         }
+        validate_finite_vector("target", targets)?;
+
+        let alpha = self.pegasos(targets, |i, j| gram[[i, j]]);
+
+        self.alpha = Some(alpha);
+        self.train_inputs = None;
+        self.train_targets = Some(targets.clone());
+
+        Ok(())
     }
 
-    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
-        let n = inputs.rows();
+    /// Compute the signed distance from the separating plane using a
+    /// precomputed kernel matrix.
+    ///
+    /// Each row of `cross_gram` holds the kernel between a new sample
+    /// and each training sample. Requires the model to be trained with
+    /// `train_precomputed`.
+    pub fn decision_function_precomputed(&self, cross_gram: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        match (self.alpha.as_ref(), self.train_inputs.as_ref(), self.train_targets.as_ref()) {
+            (Some(alpha), None, Some(train_targets)) => {
+                if cross_gram.cols() != alpha.size() {
+                    return Err(Error::new(ErrorKind::InvalidData,
+                                          "The kernel matrix must have a column for each training sample."));
+                }
+
+                let weight_vec = alpha.elemul(train_targets) / self.lambda;
+                Ok(cross_gram * weight_vec)
+            }
+            (Some(_), Some(_), _) => {
+                Err(Error::new(ErrorKind::InvalidState,
+                               "The model was not trained on a precomputed kernel matrix."))
+            }
+            _ => Err(Error::new_untrained()),
+        }
+    }
+
+    /// Predict the classes using a precomputed kernel matrix.
+    ///
+    /// See `decision_function_precomputed` for the expected input.
+    pub fn predict_precomputed(&self, cross_gram: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        let plane_dist = self.decision_function_precomputed(cross_gram)?;
+        Ok(plane_dist.apply(&|d| d.signum()))
+    }
+
+    /// Summarizes the size of the trained model.
+    ///
+    /// The model has one dual coefficient per training sample, and every
+    /// stored training sample is used to compute each prediction. The
+    /// number of inputs is unknown for a model trained on a precomputed
+    /// kernel matrix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::svm::SVM;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let inputs = Matrix::new(4,2,vec![1.0,0.0,3.0,1.0,5.0,0.0,7.0,1.0]);
+    /// let targets = Vector::new(vec![-1.,-1.,1.,1.]);
+    ///
+    /// let mut svm_mod = SVM::default();
+    /// svm_mod.train(&inputs, &targets).unwrap();
+    ///
+    /// let info = svm_mod.model_info().unwrap();
+    /// assert_eq!(info.n_stored_samples, Some(4));
+    /// assert_eq!(info.n_inputs, Some(2));
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The model has not been trained.
+    pub fn model_info(&self) -> LearningResult<ModelInfo> {
+        let alpha = self.alpha.as_ref().ok_or_else(Error::new_untrained)?;
+
+        Ok(ModelInfo {
+            n_params: alpha.size(),
+            // The training inputs include the bias column
+            n_inputs: self.train_inputs.as_ref().map(|m| m.cols() - 1),
+            n_outputs: Some(1),
+            n_stored_samples: Some(alpha.size()),
+            n_nodes: None,
+        })
+    }
+
+    /// Runs the Pegasos iterations given the kernel between
+    /// the `i`th and `j`th training samples.
+    fn pegasos<F: Fn(usize, usize) -> f64>(&self, targets: &Vector<f64>, ker: F) -> Vector<f64> {
+        let n = targets.size();
 
         let mut rng = rand::thread_rng();
 
         let mut alpha = vec![0f64; n];
 
-        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
-        let full_inputs = ones.hcat(inputs);
-
         for t in 0..self.optim_iters {
             let i = rng.gen_range(0, n);
-            let row_i = full_inputs.select_rows(&[i]);
-            let sum = full_inputs.row_iter()
-                .fold(0f64, |sum, row| sum + self.ker.kernel(row_i.data(), row.raw_slice())) *
-                      targets[i] / (self.lambda * (t as f64));
+            let sum = (0..n).fold(0f64, |sum, j| sum + ker(i, j)) * targets[i] /
+                      (self.lambda * (t as f64));
 
             if sum < 1f64 {
                 alpha[i] += 1f64;
             }
         }
 
-        self.alpha = Some(Vector::new(alpha) / (self.optim_iters as f64));
-        self.train_inputs = Some(full_inputs);
-        self.train_targets = Some(targets.clone());
+        Vector::new(alpha) / (self.optim_iters as f64)
+    }
+}
 
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::SVM;
+    use learning::{LabelClassifier, SupModel};
+    use learning::toolkit::kernel::{Kernel, SquaredExp};
+    use linalg::{Matrix, Vector, BaseMatrix};
+
+    /// The kernel between each row of `m1` and each row of `m2`,
+    /// after adding the bias column used when training on features.
+    fn biased_ker_mat<K: Kernel>(ker: &K, m1: &Matrix<f64>, m2: &Matrix<f64>) -> Matrix<f64> {
+        let m1 = Matrix::<f64>::ones(m1.rows(), 1).hcat(m1);
+        let m2 = Matrix::<f64>::ones(m2.rows(), 1).hcat(m2);
+
+        let mut data = Vec::with_capacity(m1.rows() * m2.rows());
+        for row1 in m1.row_iter() {
+            for row2 in m2.row_iter() {
+                data.push(ker.kernel(row1.raw_slice(), row2.raw_slice()));
+            }
+        }
+        Matrix::new(m1.rows(), m2.rows(), data)
+    }
+
+    #[test]
+    fn test_precomputed_matches_features() {
+        let inputs = Matrix::new(8, 2, vec![0.0, 0.5, 1.0, 0.0, 0.5, 1.5, 1.5, 1.0,
+                                            5.0, 5.5, 6.0, 5.0, 5.5, 6.5, 6.5, 6.0]);
+        let targets = Vector::new(vec![-1., -1., -1., -1., 1., 1., 1., 1.]);
+        let new_inputs = Matrix::new(4, 2, vec![0.2, 0.2, 6.2, 5.8, 1.0, 1.0, 5.0, 6.0]);
+        let ker = SquaredExp::new(2.0, 1.0);
+
+        let mut feature_svm = SVM::new(ker, 0.3);
+        feature_svm.optim_iters = 1000;
+        feature_svm.train(&inputs, &targets).unwrap();
+
+        let mut gram_svm = SVM::new(ker, 0.3);
+        gram_svm.optim_iters = 1000;
+        gram_svm.train_precomputed(&biased_ker_mat(&ker, &inputs, &inputs), &targets).unwrap();
+
+        // Pegasos samples at random, so only the signs are compared.
+        let cross_gram = biased_ker_mat(&ker, &new_inputs, &inputs);
+        let expected = Vector::new(vec![-1., 1., -1., 1.]);
+        assert_eq!(feature_svm.predict(&new_inputs).unwrap(), expected);
+        assert_eq!(gram_svm.predict_precomputed(&cross_gram).unwrap(), expected);
+
+        let feature_dist = feature_svm.decision_function(&new_inputs).unwrap();
+        let gram_dist = gram_svm.decision_function_precomputed(&cross_gram).unwrap();
+        for (f, g) in feature_dist.iter().zip(gram_dist.iter()) {
+            assert_eq!(f.signum(), g.signum());
+        }
+
+        // Each model only accepts its own kind of input.
+        assert!(feature_svm.predict_precomputed(&cross_gram).is_err());
+        assert!(gram_svm.predict(&new_inputs).is_err());
+    }
+
+    #[test]
+    fn test_integer_labels() {
+        let inputs = Matrix::new(6, 1, vec![1.0, 2.0, 3.0, 6.0, 7.0, 8.0]);
+        let labels = vec![0, 0, 0, 1, 1, 1];
+
+        let mut svm = SVM::default();
+        svm.optim_iters = 500;
+        svm.train_labels(&inputs, &labels).unwrap();
+
+        assert_eq!(svm.predict_labels(&inputs).unwrap(), labels);
+
+        let outputs = svm.predict(&inputs).unwrap();
+        assert_eq!(outputs, Vector::new(vec![-1.0, -1.0, -1.0, 1.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn test_integer_labels_round_trip() {
+        let inputs = Matrix::new(8, 2, vec![0.0, 0.5, 1.0, 0.0, 0.5, 1.5, 1.5, 1.0,
+                                            5.0, 5.5, 6.0, 5.0, 5.5, 6.5, 6.5, 6.0]);
+        let labels = vec![0, 0, 0, 0, 1, 1, 1, 1];
+
+        let mut svm = SVM::default();
+        svm.optim_iters = 1000;
+        svm.train_labels(&inputs, &labels).unwrap();
+
+        // The predicted labels agree with the signs of the outputs.
+        let new_inputs = Matrix::new(4, 2, vec![0.2, 0.2, 6.2, 5.8, 1.0, 1.0, 5.0, 6.0]);
+        let predicted = svm.predict_labels(&new_inputs).unwrap();
+        let outputs = svm.predict(&new_inputs).unwrap();
+        assert_eq!(predicted, vec![0, 1, 0, 1]);
+        for (&label, &output) in predicted.iter().zip(outputs.iter()) {
+            assert_eq!(output, if label == 1 { 1.0 } else { -1.0 });
+        }
+
+        // Retraining on the predicted labels reproduces them.
+        svm.train_labels(&inputs, &svm.predict_labels(&inputs).unwrap()).unwrap();
+        assert_eq!(svm.predict_labels(&inputs).unwrap(), labels);
+    }
+
+    #[test]
+    fn test_integer_labels_not_binary() {
+        let inputs = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+
+        let mut svm = SVM::default();
+        assert!(svm.train_labels(&inputs, &[0, 2, 1]).is_err());
+        assert!(svm.predict_labels(&inputs).is_err());
     }
 }
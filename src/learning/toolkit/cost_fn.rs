@@ -8,8 +8,9 @@
 //! You can also create your own custom cost functions for use in your models.
 //! Just create a struct implementing the `CostFunc` trait.
 
-use linalg::{Matrix, BaseMatrix, BaseMatrixMut};
+use linalg::{Matrix, BaseMatrix};
 use linalg::Vector;
+use learning::toolkit::numeric::xlogy;
 
 /// Trait for cost functions in models.
 pub trait CostFunc<T> {
@@ -62,15 +63,9 @@ pub struct CrossEntropyError;
 
 impl CostFunc<Matrix<f64>> for CrossEntropyError {
     fn cost(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
-        // The cost for a single
-        let log_inv_output = (-outputs + 1f64).apply(&ln);
-        let log_output = outputs.clone().apply(&ln);
-
-        let mat_cost = targets.elemul(&log_output) + (-targets + 1f64).elemul(&log_inv_output);
-
         let n = outputs.rows();
 
-        -(mat_cost.sum()) / (n as f64)
+        cross_entropy_sum(outputs.data(), targets.data()) / (n as f64)
     }
 
     fn grad_cost(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> Matrix<f64> {
@@ -80,15 +75,9 @@ impl CostFunc<Matrix<f64>> for CrossEntropyError {
 
 impl CostFunc<Vector<f64>> for CrossEntropyError {
     fn cost(outputs: &Vector<f64>, targets: &Vector<f64>) -> f64 {
-        // The cost for a single
-        let log_inv_output = (-outputs + 1f64).apply(&ln);
-        let log_output = outputs.clone().apply(&ln);
-
-        let mat_cost = targets.elemul(&log_output) + (-targets + 1f64).elemul(&log_inv_output);
-
         let n = outputs.size();
 
-        -(mat_cost.sum()) / (n as f64)
+        cross_entropy_sum(outputs.data(), targets.data()) / (n as f64)
     }
 
     fn grad_cost(outputs: &Vector<f64>, targets: &Vector<f64>) -> Vector<f64> {
@@ -96,7 +85,261 @@ impl CostFunc<Vector<f64>> for CrossEntropyError {
     }
 }
 
-/// Logarithm for applying within cost function.
-fn ln(x: f64) -> f64 {
-    x.ln()
+/// The focal loss cost function.
+///
+/// A variant of the cross entropy for imbalanced classification, where
+/// the many easy examples would otherwise dominate the cost. For an
+/// output `p` with target `t` the cost is
+///
+/// -&alpha;<sub>j</sub> [t (1-p)<sup>&gamma;</sup> ln(p) + (1-t) p<sup>&gamma;</sup> ln(1-p)]
+///
+/// averaged over the rows, where `j` is the column of the output. The
+/// factors (1-p)<sup>&gamma;</sup> and p<sup>&gamma;</sup> down-weight
+/// examples which are already classified confidently and correctly.
+/// The focusing parameter &gamma; is non-negative, and with &gamma; = 0
+/// and no class weights the cost is the `CrossEntropyError`.
+///
+/// The outputs should be probabilities, such as from a sigmoid or
+/// softmax output layer. The optional weights &alpha;<sub>j</sub>
+/// balance the classes, and are one when not given.
+///
+/// The `CostFunc` implementation uses the common default of &gamma; = 2
+/// without class weights. Use the `FocalCriterion` of the `nnet` module to
+/// train a neural network with other parameters.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::learning::toolkit::cost_fn::FocalLoss;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let focal = FocalLoss::new(2.0).with_alpha(vec![0.25, 0.75]);
+///
+/// let outputs = Matrix::new(2, 2, vec![0.9, 0.1, 0.3, 0.7]);
+/// let targets = Matrix::new(2, 2, vec![1.0, 0.0, 0.0, 1.0]);
+///
+/// let cost = focal.loss(&outputs, &targets);
+/// let grad = focal.loss_grad(&outputs, &targets);
+/// ```
+#[derive(Clone, Debug)]
+pub struct FocalLoss {
+    gamma: f64,
+    alpha: Option<Vec<f64>>,
+}
+
+/// Constructs a focal loss with `gamma = 2` and no class weights.
+impl Default for FocalLoss {
+    fn default() -> FocalLoss {
+        FocalLoss::new(2f64)
+    }
+}
+
+impl FocalLoss {
+    /// Constructs a focal loss with the focusing parameter `gamma`
+    /// and no class weights.
+    ///
+    /// # Panics
+    ///
+    /// - `gamma` is negative or not finite.
+    pub fn new(gamma: f64) -> FocalLoss {
+        assert!(gamma >= 0f64 && gamma.is_finite(),
+                "The focusing parameter must be finite and non-negative.");
+
+        FocalLoss {
+            gamma,
+            alpha: None,
+        }
+    }
+
+    /// Sets the weight of each output column, which should
+    /// have one entry per class.
+    ///
+    /// # Panics
+    ///
+    /// - Some weight is negative or not finite.
+    pub fn with_alpha(mut self, alpha: Vec<f64>) -> Self {
+        assert!(alpha.iter().all(|a| *a >= 0f64 && a.is_finite()),
+                "The class weights must be finite and non-negative.");
+
+        self.alpha = Some(alpha);
+        self
+    }
+
+    /// The focusing parameter.
+    pub fn gamma(&self) -> f64 {
+        self.gamma
+    }
+
+    /// The weight of each class, if given.
+    pub fn alpha(&self) -> Option<&[f64]> {
+        self.alpha.as_ref().map(|a| &a[..])
+    }
+
+    /// The weight of each output column.
+    fn class_weights(&self, cols: usize) -> Vec<f64> {
+        match self.alpha {
+            Some(ref alpha) => {
+                assert_eq!(alpha.len(), cols, "There must be one class weight per output column.");
+                alpha.clone()
+            }
+            None => vec![1f64; cols],
+        }
+    }
+
+    /// The focal loss of the outputs against the targets.
+    ///
+    /// # Panics
+    ///
+    /// - Class weights were given, but not one per output column.
+    pub fn loss(&self, outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
+        let weights = self.class_weights(outputs.cols());
+        let g = self.gamma;
+
+        let total = outputs.iter()
+            .zip(targets.iter())
+            .enumerate()
+            .map(|(i, (&p, &t))| {
+                let w = weights[i % outputs.cols()];
+                w * (xlogy(t * (1f64 - p).powf(g), p) + xlogy((1f64 - t) * p.powf(g), 1f64 - p))
+            })
+            .sum::<f64>();
+
+        -total / outputs.rows() as f64
+    }
+
+    /// The gradient of the focal loss with respect to the outputs.
+    ///
+    /// As with the `CrossEntropyError` the gradient is for the
+    /// summed cost, without averaging over the rows.
+    ///
+    /// # Panics
+    ///
+    /// - Class weights were given, but not one per output column.
+    pub fn loss_grad(&self, outputs: &Matrix<f64>, targets: &Matrix<f64>) -> Matrix<f64> {
+        let weights = self.class_weights(outputs.cols());
+        let g = self.gamma;
+
+        let grad = outputs.iter()
+            .zip(targets.iter())
+            .enumerate()
+            .map(|(i, (&p, &t))| {
+                let w = weights[i % outputs.cols()];
+
+                // The derivatives of the focusing factors, which vanish when gamma is 0.
+                let (pos_focus, neg_focus) = if g == 0f64 {
+                    (0f64, 0f64)
+                } else {
+                    (g * (1f64 - p).powf(g - 1f64) * p.ln(), g * p.powf(g - 1f64) * (1f64 - p).ln())
+                };
+
+                let pos = (1f64 - p).powf(g) / p - pos_focus;
+                let neg = neg_focus - p.powf(g) / (1f64 - p);
+                -w * (t * pos + (1f64 - t) * neg)
+            })
+            .collect::<Vec<f64>>();
+
+        Matrix::new(outputs.rows(), outputs.cols(), grad)
+    }
+}
+
+impl CostFunc<Matrix<f64>> for FocalLoss {
+    fn cost(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
+        FocalLoss::default().loss(outputs, targets)
+    }
+
+    fn grad_cost(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> Matrix<f64> {
+        FocalLoss::default().loss_grad(outputs, targets)
+    }
+}
+
+/// The summed cross entropy of the outputs against the targets.
+///
+/// Outputs saturated at exactly 0 or 1 give a finite cost when
+/// the target agrees with them.
+fn cross_entropy_sum(outputs: &[f64], targets: &[f64]) -> f64 {
+    -outputs.iter()
+        .zip(targets.iter())
+        .map(|(&o, &t)| xlogy(t, o) + xlogy(1f64 - t, 1f64 - o))
+        .sum::<f64>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CostFunc, CrossEntropyError, FocalLoss};
+    use linalg::{Matrix, BaseMatrix};
+
+    fn test_outputs() -> (Matrix<f64>, Matrix<f64>) {
+        let outputs = Matrix::new(3, 2, vec![0.9, 0.2, 0.35, 0.6, 0.05, 0.99]);
+        let targets = Matrix::new(3, 2, vec![1.0, 0.0, 1.0, 0.0, 0.0, 1.0]);
+        (outputs, targets)
+    }
+
+    #[test]
+    fn test_focal_easy_example_gradient() {
+        // A confidently correct example and a badly wrong one.
+        let easy = Matrix::new(1, 1, vec![0.95]);
+        let hard = Matrix::new(1, 1, vec![0.1]);
+        let target = Matrix::new(1, 1, vec![1.0]);
+
+        let focal = FocalLoss::new(2.0);
+        let easy_ratio = focal.loss_grad(&easy, &target)[[0, 0]] /
+                         CrossEntropyError::grad_cost(&easy, &target)[[0, 0]];
+        let hard_ratio = focal.loss_grad(&hard, &target)[[0, 0]] /
+                         CrossEntropyError::grad_cost(&hard, &target)[[0, 0]];
+
+        assert!(easy_ratio > 0.0 && easy_ratio < 0.01, "{}", easy_ratio);
+        assert!(hard_ratio > 0.5, "{}", hard_ratio);
+
+        assert!(focal.loss(&easy, &target) < 0.01 * CrossEntropyError::cost(&easy, &target));
+    }
+
+    #[test]
+    fn test_focal_gradient_matches_finite_differences() {
+        let (outputs, targets) = test_outputs();
+        let focal = FocalLoss::new(1.5).with_alpha(vec![0.25, 0.75]);
+
+        let grad = focal.loss_grad(&outputs, &targets);
+        let eps = 1e-6;
+        for i in 0..outputs.data().len() {
+            let mut plus = outputs.clone();
+            plus.mut_data()[i] += eps;
+            let mut minus = outputs.clone();
+            minus.mut_data()[i] -= eps;
+
+            // The gradient is of the summed, not averaged, cost.
+            let numeric = (focal.loss(&plus, &targets) - focal.loss(&minus, &targets)) / (2.0 * eps) *
+                          outputs.rows() as f64;
+            assert!((grad.data()[i] - numeric).abs() < 1e-6, "{} != {}", grad.data()[i], numeric);
+        }
+    }
+
+    #[test]
+    fn test_focal_zero_gamma_is_cross_entropy() {
+        let (outputs, targets) = test_outputs();
+        let focal = FocalLoss::new(0.0);
+
+        assert!((focal.loss(&outputs, &targets) - CrossEntropyError::cost(&outputs, &targets)).abs() < 1e-12);
+
+        let diff = focal.loss_grad(&outputs, &targets) - CrossEntropyError::grad_cost(&outputs, &targets);
+        assert!(diff.iter().all(|d| d.abs() < 1e-10));
+    }
+
+    #[test]
+    fn test_focal_class_weights() {
+        let (outputs, targets) = test_outputs();
+        let unweighted = FocalLoss::default();
+        let weighted = FocalLoss::default().with_alpha(vec![2.0, 0.0]);
+
+        // Only the first column contributes, with twice the weight.
+        let first_col = FocalLoss::default().loss(&outputs.select_cols(&[0]), &targets.select_cols(&[0]));
+        assert!((weighted.loss(&outputs, &targets) - 2.0 * first_col).abs() < 1e-12);
+        assert_eq!(FocalLoss::cost(&outputs, &targets), unweighted.loss(&outputs, &targets));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_focal_wrong_alpha_length() {
+        let (outputs, targets) = test_outputs();
+        let _ = FocalLoss::default().with_alpha(vec![1.0]).loss(&outputs, &targets);
+    }
 }
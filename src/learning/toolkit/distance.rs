@@ -0,0 +1,484 @@
+//! Module for distance metrics
+//!
+//! Provides the `Metric` trait and the `pairwise_distances` function,
+//! which computes the distances between all pairs of rows in two matrices.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::toolkit::distance::{pairwise_distances, Euclidean};
+//! use rusty_machine::linalg::{BaseMatrix, Matrix};
+//!
+//! let a = Matrix::new(2, 2, vec![0.0, 0.0, 1.0, 1.0]);
+//! let b = Matrix::new(3, 2, vec![3.0, 4.0, 0.0, 1.0, 1.0, 1.0]);
+//!
+//! let distances = pairwise_distances(&a, &b, &Euclidean);
+//!
+//! assert_eq!(distances.rows(), 2);
+//! assert_eq!(distances.cols(), 3);
+//! assert!((distances[[0, 0]] - 5.0).abs() < 1e-12);
+//! ```
+//!
+//! The `Mahalanobis` distance accounts for the scale and correlation
+//! of the features. Models which support it take a `DistanceMetric`,
+//! which either gives the covariance or estimates it from the
+//! training inputs.
+
+use linalg::{Matrix, BaseMatrix, BaseMatrixMut, Axes, Vector};
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
+use rulinalg::utils;
+
+/// The Metric trait
+///
+/// Requires a function computing the distance between two vectors.
+pub trait Metric {
+    /// The distance between two vectors.
+    fn distance(&self, x1: &[f64], x2: &[f64]) -> f64;
+
+    /// The distances between each row of `a` and each row of `b`.
+    ///
+    /// Metrics can override this with a faster method than
+    /// computing each distance separately.
+    fn pairwise(&self, a: &Matrix<f64>, b: &Matrix<f64>) -> Matrix<f64> {
+        let mut data = Vec::with_capacity(a.rows() * b.rows());
+        for row_a in a.row_iter() {
+            for row_b in b.row_iter() {
+                data.push(self.distance(row_a.raw_slice(), row_b.raw_slice()));
+            }
+        }
+
+        Matrix::new(a.rows(), b.rows(), data)
+    }
+}
+
+/// Computes the distances between each row of `a` and each row of `b`.
+///
+/// The entry `[i, j]` of the output is the distance between
+/// row `i` of `a` and row `j` of `b`.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::learning::toolkit::distance::{pairwise_distances, Manhattan};
+/// use rusty_machine::linalg::Matrix;
+///
+/// let a = Matrix::new(2, 2, vec![0.0, 0.0, 1.0, 2.0]);
+///
+/// let distances = pairwise_distances(&a, &a, &Manhattan);
+/// assert_eq!(distances.into_vec(), vec![0.0, 3.0, 3.0, 0.0]);
+/// ```
+///
+/// # Panics
+///
+/// - `a` and `b` have different numbers of columns.
+pub fn pairwise_distances<M: Metric>(a: &Matrix<f64>, b: &Matrix<f64>, metric: &M) -> Matrix<f64> {
+    assert_eq!(a.cols(), b.cols(), "The inputs must have the same number of columns.");
+
+    metric.pairwise(a, b)
+}
+
+/// The squared Euclidean distances between the rows of two matrices.
+///
+/// Each distance is summed from the coordinate differences. The expansion
+/// ||x - y||<sup>2</sup> = ||x||<sup>2</sup> + ||y||<sup>2</sup> - 2x<sup>T</sup>y
+/// is faster but cancels catastrophically for points far from the origin.
+fn sq_euclidean_pairwise(a: &Matrix<f64>, b: &Matrix<f64>) -> Matrix<f64> {
+    let mut data = Vec::with_capacity(a.rows() * b.rows());
+    for row_a in a.row_iter() {
+        for row_b in b.row_iter() {
+            let d = row_a.raw_slice()
+                .iter()
+                .zip(row_b.raw_slice().iter())
+                .fold(0f64, |acc, (x, y)| acc + (x - y) * (x - y));
+            data.push(d);
+        }
+    }
+
+    Matrix::new(a.rows(), b.rows(), data)
+}
+
+/// The Euclidean distance.
+///
+/// d(x,y) = ||x-y||
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Euclidean;
+
+impl Metric for Euclidean {
+    fn distance(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        SquaredEuclidean.distance(x1, x2).sqrt()
+    }
+
+    fn pairwise(&self, a: &Matrix<f64>, b: &Matrix<f64>) -> Matrix<f64> {
+        sq_euclidean_pairwise(a, b).apply(&|d| d.sqrt())
+    }
+}
+
+/// The squared Euclidean distance.
+///
+/// d(x,y) = ||x-y||<sup>2</sup>
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SquaredEuclidean;
+
+impl Metric for SquaredEuclidean {
+    fn distance(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        assert_eq!(x1.len(), x2.len());
+
+        let diff = Vector::new(utils::vec_bin_op(x1, x2, |x, y| x - y));
+        diff.dot(&diff)
+    }
+
+    fn pairwise(&self, a: &Matrix<f64>, b: &Matrix<f64>) -> Matrix<f64> {
+        sq_euclidean_pairwise(a, b)
+    }
+}
+
+/// The Manhattan distance.
+///
+/// d(x,y) = &sum;<sub>i</sub> |x<sub>i</sub> - y<sub>i</sub>|
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Manhattan;
+
+impl Metric for Manhattan {
+    fn distance(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        assert_eq!(x1.len(), x2.len());
+
+        x1.iter().zip(x2.iter()).map(|(x, y)| (x - y).abs()).sum()
+    }
+}
+
+/// The Mahalanobis distance.
+///
+/// d(x,y) = ((x-y)<sup>T</sup> &Sigma;<sup>-1</sup> (x-y))<sup>1/2</sup>
+///
+/// where &Sigma; is a covariance matrix. With the Cholesky factorization
+/// &Sigma; = LL<sup>T</sup> this is the Euclidean distance between
+/// L<sup>-1</sup>x and L<sup>-1</sup>y, so the inverse of the factor is
+/// computed once and reused for every distance.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::learning::toolkit::distance::{Mahalanobis, Metric};
+/// use rusty_machine::linalg::Matrix;
+///
+/// // The first feature has a variance of 4.
+/// let metric = Mahalanobis::new(Matrix::new(2, 2, vec![4.0, 0.0, 0.0, 1.0])).unwrap();
+///
+/// assert!((metric.distance(&[0.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-12);
+/// assert!((metric.distance(&[0.0, 0.0], &[0.0, 2.0]) - 2.0).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Mahalanobis {
+    covariance: Matrix<f64>,
+    /// The lower triangular Cholesky factor of the covariance.
+    chol: Matrix<f64>,
+    /// The inverse of the Cholesky factor.
+    inv_chol: Matrix<f64>,
+}
+
+impl Mahalanobis {
+    /// Constructs the Mahalanobis distance for the given covariance.
+    ///
+    /// # Failures
+    ///
+    /// - The covariance is not square.
+    /// - The covariance is not symmetric positive definite.
+    pub fn new(covariance: Matrix<f64>) -> LearningResult<Mahalanobis> {
+        if covariance.rows() != covariance.cols() {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "The covariance matrix must be square."));
+        }
+        for i in 0..covariance.rows() {
+            for j in 0..i {
+                let (a, b) = (covariance[[i, j]], covariance[[j, i]]);
+                // The same relative tolerance as `validate_gram_matrix`,
+                // written so that NaN entries are also rejected.
+                let close = (a - b).abs() <= 1e-10 * (1f64 + a.abs().max(b.abs()));
+                if !close {
+                    return Err(Error::new(ErrorKind::InvalidParameters,
+                                          "The covariance matrix must be symmetric."));
+                }
+            }
+        }
+
+        let not_pd = || {
+            Error::new(ErrorKind::InvalidParameters,
+                       "The covariance matrix must be positive definite.")
+        };
+        let chol = covariance.cholesky().map_err(|_| not_pd())?;
+        if chol.iter().any(|x| !x.is_finite()) || chol.diag().any(|&x| x <= 0f64) {
+            return Err(not_pd());
+        }
+        let inv_chol = chol.clone().inverse().map_err(|_| not_pd())?;
+
+        Ok(Mahalanobis {
+            covariance,
+            chol,
+            inv_chol,
+        })
+    }
+
+    /// Constructs the Mahalanobis distance for the sample
+    /// covariance of the rows of `inputs`.
+    ///
+    /// # Failures
+    ///
+    /// - There are fewer than two rows.
+    /// - The sample covariance is not positive definite, for example
+    ///   because some feature is constant.
+    pub fn from_data(inputs: &Matrix<f64>) -> LearningResult<Mahalanobis> {
+        if inputs.rows() < 2 {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Cannot estimate the covariance of fewer than two rows."));
+        }
+
+        let means = inputs.mean(Axes::Row);
+        let mut centered = inputs.clone();
+        for mut row in centered.row_iter_mut() {
+            utils::in_place_vec_bin_op(row.raw_slice_mut(), means.data(), |x, &m| *x -= m);
+        }
+
+        let covariance = (centered.transpose() * &centered) / (inputs.rows() - 1) as f64;
+        Mahalanobis::new(covariance)
+    }
+
+    /// The covariance matrix.
+    pub fn covariance(&self) -> &Matrix<f64> {
+        &self.covariance
+    }
+
+    /// Maps each row x to L<sup>-1</sup>x, so that the Euclidean distance
+    /// between the mapped rows is the Mahalanobis distance.
+    ///
+    /// # Panics
+    ///
+    /// - The inputs have a different number of columns to the covariance.
+    pub fn whiten(&self, inputs: &Matrix<f64>) -> Matrix<f64> {
+        assert_eq!(inputs.cols(), self.chol.rows(),
+                   "The inputs must have one column per covariance row.");
+        inputs * self.inv_chol.transpose()
+    }
+
+    /// Maps whitened rows back to the original features.
+    ///
+    /// This is the inverse of `whiten`.
+    ///
+    /// # Panics
+    ///
+    /// - The inputs have a different number of columns to the covariance.
+    pub fn unwhiten(&self, inputs: &Matrix<f64>) -> Matrix<f64> {
+        assert_eq!(inputs.cols(), self.chol.rows(),
+                   "The inputs must have one column per covariance row.");
+        inputs * self.chol.transpose()
+    }
+}
+
+impl Metric for Mahalanobis {
+    fn distance(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        assert_eq!(x1.len(), x2.len());
+
+        let diff = Vector::new(utils::vec_bin_op(x1, x2, |x, y| x - y));
+        let whitened = &self.inv_chol * diff;
+        whitened.dot(&whitened).sqrt()
+    }
+
+    fn pairwise(&self, a: &Matrix<f64>, b: &Matrix<f64>) -> Matrix<f64> {
+        sq_euclidean_pairwise(&self.whiten(a), &self.whiten(b)).apply(&|d| d.sqrt())
+    }
+}
+
+/// The distance used by a model to compare samples.
+#[derive(Clone, Debug)]
+pub enum DistanceMetric {
+    /// The Euclidean distance.
+    Euclidean,
+    /// The Mahalanobis distance with the given covariance.
+    Mahalanobis(Matrix<f64>),
+    /// The Mahalanobis distance with the sample covariance
+    /// of the training inputs.
+    EstimatedMahalanobis,
+}
+
+/// The default metric is `Euclidean`.
+impl Default for DistanceMetric {
+    fn default() -> DistanceMetric {
+        DistanceMetric::Euclidean
+    }
+}
+
+impl DistanceMetric {
+    /// The Mahalanobis distance to use for the given training inputs,
+    /// or `None` for the Euclidean distance.
+    ///
+    /// # Failures
+    ///
+    /// - The covariance is invalid or cannot be estimated.
+    /// - The covariance has a different size to the rows of the inputs.
+    pub fn fit(&self, inputs: &Matrix<f64>) -> LearningResult<Option<Mahalanobis>> {
+        let metric = match *self {
+            DistanceMetric::Euclidean => return Ok(None),
+            DistanceMetric::Mahalanobis(ref covariance) => Mahalanobis::new(covariance.clone())?,
+            DistanceMetric::EstimatedMahalanobis => Mahalanobis::from_data(inputs)?,
+        };
+
+        if metric.covariance().rows() != inputs.cols() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The covariance must have one row per input column."));
+        }
+        Ok(Some(metric))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pairwise_distances, Metric, Euclidean, SquaredEuclidean, Manhattan};
+    use super::{Mahalanobis, DistanceMetric};
+    use linalg::{Matrix, BaseMatrix};
+
+    fn naive_distances<F: Fn(&[f64], &[f64]) -> f64>(a: &Matrix<f64>, b: &Matrix<f64>, dist: F) -> Matrix<f64> {
+        let mut data = Vec::new();
+        for i in 0..a.rows() {
+            for j in 0..b.rows() {
+                data.push(dist(a.row(i).raw_slice(), b.row(j).raw_slice()));
+            }
+        }
+        Matrix::new(a.rows(), b.rows(), data)
+    }
+
+    fn assert_close(m1: &Matrix<f64>, m2: &Matrix<f64>) {
+        assert_eq!(m1.rows(), m2.rows());
+        assert_eq!(m1.cols(), m2.cols());
+        for (x, y) in m1.iter().zip(m2.iter()) {
+            assert!((x - y).abs() < 1e-10, "{} != {}", x, y);
+        }
+    }
+
+    fn test_inputs() -> (Matrix<f64>, Matrix<f64>) {
+        let a = Matrix::new(4, 3, vec![0.0, 0.0, 0.0,
+                                       1.0, -2.0, 0.5,
+                                       3.0, 4.0, -1.0,
+                                       -0.5, 0.25, 2.0]);
+        let b = Matrix::new(3, 3, vec![1.0, -2.0, 0.5,
+                                       2.0, 2.0, 2.0,
+                                       -1.0, 0.0, 3.0]);
+        (a, b)
+    }
+
+    #[test]
+    fn test_sq_euclidean_matches_naive() {
+        let (a, b) = test_inputs();
+        let naive = naive_distances(&a, &b, |x, y| {
+            x.iter().zip(y.iter()).map(|(p, q)| (p - q) * (p - q)).sum()
+        });
+
+        assert_close(&pairwise_distances(&a, &b, &SquaredEuclidean), &naive);
+        assert_close(&SquaredEuclidean.pairwise(&a, &b), &naive_distances(&a, &b, |x, y| {
+            SquaredEuclidean.distance(x, y)
+        }));
+    }
+
+    #[test]
+    fn test_euclidean_far_from_origin() {
+        let a = Matrix::new(2, 1, vec![1.7e9, 1.7e9 + 1.0]);
+        let b = Matrix::new(1, 1, vec![1.7e9 + 3.0]);
+
+        let distances = pairwise_distances(&a, &b, &Euclidean);
+        assert_eq!(distances.into_vec(), vec![3.0, 2.0]);
+    }
+
+    #[test]
+    fn test_euclidean_matches_naive() {
+        let (a, b) = test_inputs();
+        let naive = naive_distances(&a, &b, |x, y| {
+            x.iter().zip(y.iter()).map(|(p, q)| (p - q) * (p - q)).sum::<f64>().sqrt()
+        });
+
+        let distances = pairwise_distances(&a, &b, &Euclidean);
+        assert_close(&distances, &naive);
+
+        // Identical rows are exactly zero distance apart.
+        assert_eq!(distances[[1, 0]], 0.0);
+    }
+
+    #[test]
+    fn test_manhattan_matches_naive() {
+        let (a, b) = test_inputs();
+        let naive = naive_distances(&a, &b, |x, y| {
+            x.iter().zip(y.iter()).map(|(p, q)| (p - q).abs()).sum()
+        });
+
+        assert_close(&pairwise_distances(&a, &b, &Manhattan), &naive);
+    }
+
+    #[test]
+    fn test_mahalanobis_matches_naive() {
+        let (a, b) = test_inputs();
+        let covariance = Matrix::new(3, 3, vec![2.0, 0.6, 0.1,
+                                                0.6, 1.0, -0.3,
+                                                0.1, -0.3, 0.5]);
+        let inverse = covariance.clone().inverse().unwrap();
+        let metric = Mahalanobis::new(covariance).unwrap();
+
+        let naive = naive_distances(&a, &b, |x, y| {
+            let diff = Matrix::new(3, 1, x.iter().zip(y.iter()).map(|(p, q)| p - q).collect::<Vec<f64>>());
+            (diff.transpose() * &inverse * diff)[[0, 0]].sqrt()
+        });
+
+        assert_close(&pairwise_distances(&a, &b, &metric), &naive);
+        assert_close(&metric.unwhiten(&metric.whiten(&a)), &a);
+        assert!((metric.distance(a.row(2).raw_slice(), b.row(1).raw_slice()) - naive[[2, 1]]).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_mahalanobis_identity_is_euclidean() {
+        let (a, b) = test_inputs();
+        let metric = Mahalanobis::new(Matrix::identity(3)).unwrap();
+
+        assert_close(&metric.pairwise(&a, &b), &Euclidean.pairwise(&a, &b));
+    }
+
+    #[test]
+    fn test_mahalanobis_from_data() {
+        let inputs = Matrix::new(4, 2, vec![0.0, 1.0, 2.0, 1.0, 0.0, 3.0, 2.0, 3.0]);
+        let metric = Mahalanobis::from_data(&inputs).unwrap();
+
+        let expected = Matrix::new(2, 2, vec![4.0 / 3.0, 0.0, 0.0, 4.0 / 3.0]);
+        assert_close(metric.covariance(), &expected);
+
+        assert!(DistanceMetric::EstimatedMahalanobis.fit(&inputs).unwrap().is_some());
+        assert!(DistanceMetric::Euclidean.fit(&inputs).unwrap().is_none());
+        assert!(DistanceMetric::Mahalanobis(Matrix::identity(3)).fit(&inputs).is_err());
+    }
+
+    #[test]
+    fn test_mahalanobis_invalid_covariance() {
+        assert!(Mahalanobis::new(Matrix::new(2, 3, vec![1.0; 6])).is_err());
+        assert!(Mahalanobis::new(Matrix::new(2, 2, vec![1.0, 0.5, 0.0, 1.0])).is_err());
+        assert!(Mahalanobis::new(Matrix::new(2, 2, vec![1.0, 2.0, 2.0, 1.0])).is_err());
+        assert!(Mahalanobis::new(Matrix::new(2, 2, vec![1.0, 0.0, 0.0, 0.0])).is_err());
+
+        // A constant feature has no variance.
+        let inputs = Matrix::new(3, 2, vec![1.0, 5.0, 2.0, 5.0, 3.0, 5.0]);
+        assert!(Mahalanobis::from_data(&inputs).is_err());
+    }
+
+    #[test]
+    fn test_mahalanobis_symmetry_tolerance_is_relative() {
+        // Rounding of a large covariance breaks the symmetry by more than 1e-10.
+        let covariance = Matrix::new(2, 2, vec![1e8, 5e7, 5e7 + 1e-6, 1e8]);
+        assert!(Mahalanobis::new(covariance).is_ok());
+
+        let covariance = Matrix::new(2, 2, vec![1.0, 0.5, 0.5 + 1e-6, 1.0]);
+        assert!(Mahalanobis::new(covariance).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mismatched_columns() {
+        let a = Matrix::new(2, 2, vec![0.0; 4]);
+        let b = Matrix::new(2, 3, vec![0.0; 6]);
+
+        let _ = pairwise_distances(&a, &b, &Euclidean);
+    }
+}
@@ -0,0 +1,329 @@
+//! Module for k-d trees
+//!
+//! Provides the `KDTree`, a spatial index over the rows of a matrix
+//! which answers nearest neighbour and radius queries under the
+//! Euclidean distance without comparing against every row.
+//!
+//! The tree splits the points at the median of one coordinate at each
+//! level, cycling through the coordinates. Queries skip the subtrees
+//! which cannot contain a point closer than the current candidates.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::toolkit::kd_tree::KDTree;
+//! use rusty_machine::linalg::Matrix;
+//!
+//! let points = Matrix::new(4, 2, vec![0.0, 0.0, 1.0, 0.0, 0.0, 3.0, 5.0, 5.0]);
+//! let tree = KDTree::new(points);
+//!
+//! // The two closest points to (0.2, 0.1).
+//! let nearest = tree.nearest(&[0.2, 0.1], 2);
+//! assert_eq!(nearest[0].0, 0);
+//! assert_eq!(nearest[1].0, 1);
+//!
+//! // All points within a distance of 3.
+//! let within = tree.within_radius(&[0.0, 0.0], 3.0);
+//! assert_eq!(within.iter().map(|&(i, _)| i).collect::<Vec<usize>>(), vec![0, 1, 2]);
+//! ```
+
+use linalg::{Matrix, BaseMatrix};
+use learning::toolkit::distance::{Metric, Euclidean};
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// A node of the tree, holding one point.
+#[derive(Clone, Debug)]
+struct Node {
+    /// The row of the point in the data.
+    index: usize,
+    /// The coordinate on which the children are split.
+    dim: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+/// A neighbour found by a query, ordered by distance and then by index.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Candidate {
+    dist: f64,
+    index: usize,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        self.dist
+            .partial_cmp(&other.dist)
+            .unwrap_or(Ordering::Equal)
+            .then(self.index.cmp(&other.index))
+    }
+}
+
+/// A k-d tree over the rows of a matrix.
+///
+/// Query results are pairs of the row index and its Euclidean
+/// distance to the query point, sorted by distance.
+#[derive(Clone, Debug)]
+pub struct KDTree {
+    data: Matrix<f64>,
+    nodes: Vec<Node>,
+    root: Option<usize>,
+}
+
+impl KDTree {
+    /// Constructs a k-d tree over the rows of `data`.
+    ///
+    /// The data should not contain non-finite values.
+    pub fn new(data: Matrix<f64>) -> KDTree {
+        let mut tree = KDTree {
+            data,
+            nodes: Vec::new(),
+            root: None,
+        };
+
+        let mut indices = (0..tree.data.rows()).collect::<Vec<usize>>();
+        if tree.data.cols() > 0 {
+            tree.root = tree.build(&mut indices, 0);
+        }
+        tree
+    }
+
+    /// Builds the subtree of the given points, returning its root.
+    fn build(&mut self, indices: &mut [usize], depth: usize) -> Option<usize> {
+        if indices.is_empty() {
+            return None;
+        }
+
+        let dim = depth % self.data.cols();
+        {
+            let data = &self.data;
+            indices.sort_by(|&a, &b| {
+                data[[a, dim]].partial_cmp(&data[[b, dim]]).unwrap_or(Ordering::Equal)
+            });
+        }
+
+        let mid = indices.len() / 2;
+        let index = indices[mid];
+        let (lower, upper) = indices.split_at_mut(mid);
+        let left = self.build(lower, depth + 1);
+        let right = self.build(&mut upper[1..], depth + 1);
+
+        self.nodes.push(Node {
+            index,
+            dim,
+            left,
+            right,
+        });
+        Some(self.nodes.len() - 1)
+    }
+
+    /// The points in the tree, one per row.
+    pub fn data(&self) -> &Matrix<f64> {
+        &self.data
+    }
+
+    /// The number of points in the tree.
+    pub fn len(&self) -> usize {
+        self.data.rows()
+    }
+
+    /// Whether the tree has no points.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The distance from `point` to the point of a node.
+    fn distance(&self, point: &[f64], node: &Node) -> f64 {
+        Euclidean.distance(point, self.data.row(node.index).raw_slice())
+    }
+
+    /// The signed distance from `point` to the splitting plane of a node.
+    fn split_offset(&self, point: &[f64], node: &Node) -> f64 {
+        point[node.dim] - self.data[[node.index, node.dim]]
+    }
+
+    /// The `k` points closest to `point`.
+    ///
+    /// Fewer points are returned if the tree has fewer than `k` points.
+    /// Points at the same distance are ordered by their row index.
+    ///
+    /// # Panics
+    ///
+    /// - `point` has a different length to the rows of the data.
+    pub fn nearest(&self, point: &[f64], k: usize) -> Vec<(usize, f64)> {
+        assert_eq!(point.len(), self.data.cols(), "The point must have one entry per column.");
+
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.nearest_in(self.root, point, k, &mut heap);
+        }
+
+        heap.into_sorted_vec().into_iter().map(|c| (c.index, c.dist)).collect()
+    }
+
+    fn nearest_in(&self,
+                  node: Option<usize>,
+                  point: &[f64],
+                  k: usize,
+                  heap: &mut BinaryHeap<Candidate>) {
+        let node = match node {
+            Some(n) => &self.nodes[n],
+            None => return,
+        };
+
+        heap.push(Candidate {
+            dist: self.distance(point, node),
+            index: node.index,
+        });
+        if heap.len() > k {
+            heap.pop();
+        }
+
+        let offset = self.split_offset(point, node);
+        let (near, far) = if offset < 0f64 {
+            (node.left, node.right)
+        } else {
+            (node.right, node.left)
+        };
+
+        self.nearest_in(near, point, k, heap);
+        // The far side can only hold a closer point if the
+        // splitting plane is closer than the current k-th point.
+        let kth_dist = heap.peek().map_or(0f64, |c| c.dist);
+        if heap.len() < k || offset.abs() <= kth_dist {
+            self.nearest_in(far, point, k, heap);
+        }
+    }
+
+    /// All points whose distance to `point` is at most `radius`.
+    ///
+    /// # Panics
+    ///
+    /// - `point` has a different length to the rows of the data.
+    pub fn within_radius(&self, point: &[f64], radius: f64) -> Vec<(usize, f64)> {
+        assert_eq!(point.len(), self.data.cols(), "The point must have one entry per column.");
+
+        let mut found = Vec::new();
+        self.within_radius_in(self.root, point, radius, &mut found);
+        found.sort();
+
+        found.into_iter().map(|c| (c.index, c.dist)).collect()
+    }
+
+    fn within_radius_in(&self,
+                        node: Option<usize>,
+                        point: &[f64],
+                        radius: f64,
+                        found: &mut Vec<Candidate>) {
+        let node = match node {
+            Some(n) => &self.nodes[n],
+            None => return,
+        };
+
+        let dist = self.distance(point, node);
+        if dist <= radius {
+            found.push(Candidate {
+                dist,
+                index: node.index,
+            });
+        }
+
+        // Points on the left are at least `offset` away, and
+        // points on the right at least `-offset`.
+        let offset = self.split_offset(point, node);
+        if offset <= radius {
+            self.within_radius_in(node.left, point, radius, found);
+        }
+        if -offset <= radius {
+            self.within_radius_in(node.right, point, radius, found);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KDTree;
+    use learning::toolkit::distance::{Metric, Euclidean};
+    use linalg::{Matrix, BaseMatrix};
+
+    fn test_points() -> Matrix<f64> {
+        let data = (0..150).map(|i| ((i * 37 % 101) as f64 * 0.61).sin() * 5.0).collect::<Vec<f64>>();
+        Matrix::new(50, 3, data)
+    }
+
+    /// The distances from `point` to every row, sorted.
+    fn brute_force(points: &Matrix<f64>, point: &[f64]) -> Vec<(usize, f64)> {
+        let mut dists = points.row_iter()
+            .map(|r| Euclidean.distance(point, r.raw_slice()))
+            .enumerate()
+            .collect::<Vec<(usize, f64)>>();
+        dists.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap().then(a.0.cmp(&b.0)));
+        dists
+    }
+
+    #[test]
+    fn test_nearest_matches_brute_force() {
+        let points = test_points();
+        let tree = KDTree::new(points.clone());
+        assert_eq!(tree.len(), 50);
+
+        for query in &[[0.0, 0.0, 0.0], [4.0, -1.0, 2.5], [-6.0, 6.0, -6.0]] {
+            let expected = brute_force(&points, query);
+            for &k in &[1, 5, 50, 60] {
+                let nearest = tree.nearest(query, k);
+                assert_eq!(nearest.len(), k.min(50));
+                assert_eq!(&nearest[..], &expected[..k.min(50)]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_within_radius_matches_brute_force() {
+        let points = test_points();
+        let tree = KDTree::new(points.clone());
+
+        for query in &[[0.0, 0.0, 0.0], [4.0, -1.0, 2.5]] {
+            for &r in &[0.0, 1.5, 4.0, 100.0] {
+                let expected = brute_force(&points, query)
+                    .into_iter()
+                    .filter(|&(_, d)| d <= r)
+                    .collect::<Vec<(usize, f64)>>();
+                assert_eq!(tree.within_radius(query, r), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_duplicate_points() {
+        let tree = KDTree::new(Matrix::new(4, 1, vec![1.0, 1.0, 1.0, 2.0]));
+
+        let nearest = tree.nearest(&[1.0], 3);
+        assert_eq!(nearest, vec![(0, 0.0), (1, 0.0), (2, 0.0)]);
+        assert_eq!(tree.within_radius(&[1.0], 0.0).len(), 3);
+    }
+
+    #[test]
+    fn test_empty_tree() {
+        let tree = KDTree::new(Matrix::new(0, 2, Vec::new()));
+
+        assert!(tree.is_empty());
+        assert!(tree.nearest(&[0.0, 0.0], 3).is_empty());
+        assert!(tree.within_radius(&[0.0, 0.0], 1.0).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_wrong_point_length() {
+        let tree = KDTree::new(Matrix::new(2, 2, vec![0.0, 1.0, 2.0, 3.0]));
+        let _ = tree.nearest(&[0.0], 1);
+    }
+}
@@ -4,10 +4,13 @@
 
 use std::ops::{Add, Mul};
 
-use linalg::Vector;
+use linalg::{Matrix, BaseMatrix, Vector};
 use linalg::norm::{Euclidean, VectorNorm, VectorMetric};
 use rulinalg::utils;
 
+use learning::{LearningResult, validate_finite_matrix};
+use learning::error::{Error, ErrorKind};
+
 /// The Kernel trait
 ///
 /// Requires a function mapping two vectors to a scalar.
@@ -511,3 +514,129 @@ impl Kernel for RationalQuadratic {
         (1f64 + diff.dot(&diff) / (2f64 * self.alpha * self.ls * self.ls)).powf(-self.alpha)
     }
 }
+
+/// The Cosine Kernel.
+///
+/// k(x,y) = x<sup>T</sup>y / (||x|| ||y||)
+///
+/// The kernel is zero if either vector is zero.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Cosine;
+
+impl Kernel for Cosine {
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::kernel;
+    /// use rusty_machine::learning::toolkit::kernel::Kernel;
+    ///
+    /// let ker = kernel::Cosine;
+    ///
+    /// // Parallel vectors have similarity one regardless of length.
+    /// assert!((ker.kernel(&[1.,2.,3.], &[2.,4.,6.]) - 1.0).abs() < 1e-12);
+    /// assert_eq!(ker.kernel(&[1.,0.], &[0.,1.]), 0.0);
+    /// ```
+    fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        let norms = utils::dot(x1, x1).sqrt() * utils::dot(x2, x2).sqrt();
+
+        if norms == 0f64 {
+            0f64
+        } else {
+            utils::dot(x1, x2) / norms
+        }
+    }
+}
+
+/// Checks that a precomputed kernel matrix can be used for training.
+///
+/// The Gram matrix of a kernel between the training samples must be
+/// square, finite and symmetric, up to a small relative tolerance.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::learning::toolkit::kernel::validate_gram_matrix;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let gram = Matrix::new(2, 2, vec![1.0, 0.5, 0.5, 1.0]);
+/// assert!(validate_gram_matrix(&gram).is_ok());
+///
+/// let not_gram = Matrix::new(2, 2, vec![1.0, 0.5, 0.2, 1.0]);
+/// assert!(validate_gram_matrix(&not_gram).is_err());
+/// ```
+pub fn validate_gram_matrix(gram: &Matrix<f64>) -> LearningResult<()> {
+    if gram.rows() != gram.cols() {
+        return Err(Error::new(ErrorKind::InvalidData, "The Gram matrix must be square."));
+    }
+
+    validate_finite_matrix("Gram matrix", gram)?;
+
+    for i in 0..gram.rows() {
+        for j in 0..i {
+            let (a, b) = (gram[[i, j]], gram[[j, i]]);
+            let close = (a - b).abs() <= 1e-10 * (1f64 + a.abs().max(b.abs()));
+            if !close {
+                return Err(Error::new(ErrorKind::InvalidData, "The Gram matrix must be symmetric."));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Kernel, Cosine, validate_gram_matrix};
+    use linalg::Matrix;
+
+    #[test]
+    fn test_cosine_kernel() {
+        let ker = Cosine;
+        let x = [1.0, -2.0, 0.5];
+        let y = [0.3, 0.4, -1.2];
+
+        let expected = (0.3 - 0.8 - 0.6) / ((5.25f64).sqrt() * (1.69f64).sqrt());
+        assert!((ker.kernel(&x, &y) - expected).abs() < 1e-12);
+        assert_eq!(ker.kernel(&x, &y), ker.kernel(&y, &x));
+
+        // Only the direction of the vectors matters.
+        assert!((ker.kernel(&x, &[3.0, -6.0, 1.5]) - 1.0).abs() < 1e-12);
+        assert!((ker.kernel(&x, &[-0.5, 1.0, -0.25]) + 1.0).abs() < 1e-12);
+        assert!((ker.kernel(&[2.0, -4.0, 1.0], &y) - expected).abs() < 1e-12);
+
+        assert_eq!(ker.kernel(&x, &[0.0, 0.0, 0.0]), 0.0);
+        assert_eq!(ker.kernel(&[0.0, 0.0, 0.0], &[0.0, 0.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_cosine_gram_matrix() {
+        let points = [[1.0, 0.0], [0.6, 0.8], [-3.0, 1.0], [0.0, 0.0]];
+        let mut data = Vec::with_capacity(16);
+        for p in &points {
+            for q in &points {
+                data.push(Cosine.kernel(p, q));
+            }
+        }
+        let gram = Matrix::new(4, 4, data);
+
+        assert!(validate_gram_matrix(&gram).is_ok());
+        for i in 0..3 {
+            assert!((gram[[i, i]] - 1.0).abs() < 1e-12);
+        }
+        assert!(gram.data().iter().all(|k| k.abs() <= 1.0 + 1e-12));
+    }
+
+    #[test]
+    fn test_gram_matrix_non_finite() {
+        use std::f64;
+
+        for &bad in &[f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            // A non-finite entry on the diagonal passes the symmetry check.
+            let diag = Matrix::new(2, 2, vec![bad, 0.5, 0.5, 1.0]);
+            assert!(validate_gram_matrix(&diag).is_err());
+
+            let off_diag = Matrix::new(2, 2, vec![1.0, bad, bad, 1.0]);
+            assert!(validate_gram_matrix(&off_diag).is_err());
+        }
+    }
+}
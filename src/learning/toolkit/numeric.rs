@@ -0,0 +1,136 @@
+//! Utility functions for numerical computation.
+//!
+//! This module provides numerically stable routines which are
+//! shared by the learning modules.
+
+use linalg::{Matrix, BaseMatrix, Vector};
+
+use std::f64;
+
+/// Computes `ln(sum(exp(x)))` for the values in a slice.
+///
+/// The maximum is subtracted before exponentiating, so large
+/// values do not overflow and very negative values do not all
+/// underflow to zero.
+///
+/// Returns negative infinity if the slice is empty or every
+/// value is negative infinity.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::learning::toolkit::numeric::log_sum_exp;
+///
+/// // The naive computation overflows here.
+/// let lse = log_sum_exp(&[1000.0, 1000.0]);
+/// assert!((lse - (1000.0 + 2f64.ln())).abs() < 1e-10);
+/// ```
+pub fn log_sum_exp(row: &[f64]) -> f64 {
+    let max = row.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+    // Subtracting an infinite maximum would produce NaN.
+    if max.is_infinite() {
+        return max;
+    }
+
+    max + row.iter().map(|x| (x - max).exp()).sum::<f64>().ln()
+}
+
+/// Computes `x * ln(y)`, taking the result to be zero when `x` is zero.
+///
+/// This is the convention used in entropies, where a term with no
+/// weight contributes nothing even if `y` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::learning::toolkit::numeric::xlogy;
+///
+/// assert_eq!(xlogy(0.0, 0.0), 0.0);
+/// assert!((xlogy(2.0, 1f64.exp()) - 2.0).abs() < 1e-12);
+/// ```
+pub fn xlogy(x: f64, y: f64) -> f64 {
+    if x == 0f64 {
+        0f64
+    } else {
+        x * y.ln()
+    }
+}
+
+/// Computes the log-sum-exp of each row of a matrix.
+///
+/// See `log_sum_exp` for details.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::learning::toolkit::numeric::row_log_sum_exp;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let mat = Matrix::new(2, 2, vec![0.0, 0.0, -1000.0, -1000.0]);
+/// let lse = row_log_sum_exp(&mat);
+///
+/// assert!((lse[1] - (-1000.0 + 2f64.ln())).abs() < 1e-10);
+/// ```
+pub fn row_log_sum_exp(mat: &Matrix<f64>) -> Vector<f64> {
+    Vector::new(mat.row_iter().map(|row| log_sum_exp(row.raw_slice())).collect::<Vec<f64>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linalg::Matrix;
+
+    use std::f64;
+
+    fn naive_log_sum_exp(row: &[f64]) -> f64 {
+        row.iter().map(|x| x.exp()).sum::<f64>().ln()
+    }
+
+    #[test]
+    fn test_log_sum_exp_moderate() {
+        let rows: Vec<Vec<f64>> = vec![vec![0.0], vec![1.0, 2.0, 3.0], vec![-5.0, 0.5, 10.0, -2.0]];
+
+        for row in rows {
+            assert!((log_sum_exp(&row) - naive_log_sum_exp(&row)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_log_sum_exp_large() {
+        assert!(naive_log_sum_exp(&[800.0, 800.0]).is_infinite());
+
+        let lse = log_sum_exp(&[800.0, 800.0]);
+        assert!((lse - (800.0 + 2f64.ln())).abs() < 1e-10);
+
+        let lse = log_sum_exp(&[-800.0, -801.0]);
+        assert!((lse - (-800.0 + (1.0 + (-1f64).exp()).ln())).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_log_sum_exp_infinite() {
+        assert_eq!(log_sum_exp(&[f64::NEG_INFINITY, f64::NEG_INFINITY]), f64::NEG_INFINITY);
+        assert_eq!(log_sum_exp(&[]), f64::NEG_INFINITY);
+        assert_eq!(log_sum_exp(&[f64::NEG_INFINITY, 0.0]), 0.0);
+        assert_eq!(log_sum_exp(&[f64::INFINITY, 0.0]), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_xlogy() {
+        assert_eq!(xlogy(0.0, 0.0), 0.0);
+        assert_eq!(xlogy(0.0, 5.0), 0.0);
+        assert_eq!(xlogy(1.0, 0.0), f64::NEG_INFINITY);
+        assert!((xlogy(3.0, 2.0) - 3.0 * 2f64.ln()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_row_log_sum_exp() {
+        let mat = Matrix::new(3, 2, vec![1.0, 2.0, 900.0, 900.0, f64::NEG_INFINITY, f64::NEG_INFINITY]);
+        let lse = row_log_sum_exp(&mat);
+
+        assert_eq!(lse.size(), 3);
+        assert!((lse[0] - naive_log_sum_exp(&[1.0, 2.0])).abs() < 1e-12);
+        assert!((lse[1] - (900.0 + 2f64.ln())).abs() < 1e-10);
+        assert_eq!(lse[2], f64::NEG_INFINITY);
+    }
+}
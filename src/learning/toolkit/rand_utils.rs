@@ -3,6 +3,8 @@
 //! This module provides sampling and shuffling which are used
 //! within the learning modules.
 
+use linalg::{Matrix, BaseMatrix, Vector};
+
 use rand::{Rng, thread_rng};
 
 /// ```
@@ -97,8 +99,32 @@ pub fn fisher_yates<T: Copy>(arr: &[T]) -> Vec<T> {
 /// rand_utils::in_place_fisher_yates(&mut a);
 /// ```
 pub fn in_place_fisher_yates<T>(arr: &mut [T]) {
+    in_place_fisher_yates_with_rng(arr, &mut thread_rng());
+}
+
+/// The in place Fisher-Yates shuffle, drawing from the
+/// given random number generator.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate rand;
+/// # extern crate rusty_machine;
+/// use rusty_machine::learning::toolkit::rand_utils;
+/// use rand::{SeedableRng, StdRng};
+///
+/// # fn main() {
+/// let mut a = (0..5).collect::<Vec<_>>();
+/// let mut b = a.clone();
+///
+/// // Shuffles with the same seed give the same permutation
+/// rand_utils::in_place_fisher_yates_with_rng(&mut a, &mut StdRng::from_seed(&[1, 2, 3]));
+/// rand_utils::in_place_fisher_yates_with_rng(&mut b, &mut StdRng::from_seed(&[1, 2, 3]));
+/// assert_eq!(a, b);
+/// # }
+/// ```
+pub fn in_place_fisher_yates_with_rng<T, R: Rng>(arr: &mut [T], rng: &mut R) {
     let n = arr.len();
-    let mut rng = thread_rng();
 
     for i in 0..n {
         // Swap i with a random point after it
@@ -107,9 +133,142 @@ pub fn in_place_fisher_yates<T>(arr: &mut [T]) {
     }
 }
 
+/// Samples rows of the inputs and targets with replacement.
+///
+/// Returns `n` rows of the inputs alongside their matching targets,
+/// drawn using the given random number generator.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate rand;
+/// # extern crate rusty_machine;
+///
+/// use rusty_machine::learning::toolkit::rand_utils;
+/// use rusty_machine::linalg::{Matrix, Vector};
+/// use rand::{StdRng, SeedableRng};
+///
+/// # fn main() {
+/// let inputs = Matrix::new(3, 2, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+/// let targets = Vector::new(vec![0.0, 1.0, 1.0]);
+///
+/// let mut rng = StdRng::from_seed(&[1, 2, 3]);
+/// let (sample_inputs, sample_targets) = rand_utils::bootstrap_sample(&inputs, &targets, 5, &mut rng);
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// - The inputs and targets have different numbers of rows.
+/// - The inputs are empty.
+pub fn bootstrap_sample<T, R>(inputs: &Matrix<f64>,
+                              targets: &Vector<T>,
+                              n: usize,
+                              rng: &mut R)
+                              -> (Matrix<f64>, Vector<T>)
+    where T: Copy,
+          R: Rng
+{
+    assert!(inputs.rows() == targets.size(),
+            "Inputs and targets must have the same number of rows.");
+    assert!(inputs.rows() > 0, "Cannot sample from empty inputs.");
+
+    let idxs = (0..n).map(|_| rng.gen_range(0, inputs.rows())).collect::<Vec<usize>>();
+    select_samples(inputs, targets, &idxs)
+}
+
+/// Samples rows of the inputs and targets with replacement,
+/// preserving the proportion of each class in the targets.
+///
+/// Each class is sampled separately from its own rows.
+/// The number of samples per class is rounded so that `n`
+/// rows are returned in total. The rows are grouped by class
+/// in the order the classes first appear in the targets.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate rand;
+/// # extern crate rusty_machine;
+///
+/// use rusty_machine::learning::toolkit::rand_utils;
+/// use rusty_machine::linalg::{Matrix, Vector};
+/// use rand::{StdRng, SeedableRng};
+///
+/// # fn main() {
+/// let inputs = Matrix::new(4, 1, vec![1.0, 2.0, 3.0, 4.0]);
+/// let targets = Vector::new(vec![0, 0, 0, 1]);
+///
+/// let mut rng = StdRng::from_seed(&[1, 2, 3]);
+/// let (_, sample_targets) = rand_utils::stratified_bootstrap_sample(&inputs, &targets, 8, &mut rng);
+///
+/// // Exactly a quarter of the sampled targets are class 1.
+/// assert_eq!(sample_targets.iter().filter(|&&t| t == 1).count(), 2);
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// - The inputs and targets have different numbers of rows.
+/// - The inputs are empty.
+pub fn stratified_bootstrap_sample<T, R>(inputs: &Matrix<f64>,
+                                         targets: &Vector<T>,
+                                         n: usize,
+                                         rng: &mut R)
+                                         -> (Matrix<f64>, Vector<T>)
+    where T: Copy + PartialEq,
+          R: Rng
+{
+    assert!(inputs.rows() == targets.size(),
+            "Inputs and targets must have the same number of rows.");
+    assert!(inputs.rows() > 0, "Cannot sample from empty inputs.");
+
+    // Group the row indices by class.
+    let mut classes: Vec<(T, Vec<usize>)> = Vec::new();
+    for (i, t) in targets.iter().enumerate() {
+        match classes.iter_mut().find(|c| c.0 == *t) {
+            Some(c) => c.1.push(i),
+            None => classes.push((*t, vec![i])),
+        }
+    }
+
+    // Allocate samples to each class by the largest remainder method.
+    let total = targets.size();
+    let mut counts = classes.iter().map(|c| c.1.len() * n / total).collect::<Vec<usize>>();
+    let allocated = counts.iter().sum::<usize>();
+
+    let mut by_remainder = (0..classes.len()).collect::<Vec<usize>>();
+    by_remainder.sort_by(|&a, &b| {
+        let rem_a = classes[a].1.len() * n % total;
+        let rem_b = classes[b].1.len() * n % total;
+        rem_b.cmp(&rem_a)
+    });
+    for &k in by_remainder.iter().take(n - allocated) {
+        counts[k] += 1;
+    }
+
+    let mut idxs = Vec::with_capacity(n);
+    for (c, count) in classes.iter().zip(counts) {
+        for _ in 0..count {
+            idxs.push(c.1[rng.gen_range(0, c.1.len())]);
+        }
+    }
+
+    select_samples(inputs, targets, &idxs)
+}
+
+fn select_samples<T: Copy>(inputs: &Matrix<f64>,
+                           targets: &Vector<T>,
+                           idxs: &[usize])
+                           -> (Matrix<f64>, Vector<T>) {
+    let sample_targets = idxs.iter().map(|&i| targets[i]).collect::<Vec<T>>();
+    (inputs.select_rows(idxs), Vector::new(sample_targets))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::{StdRng, SeedableRng};
 
     #[test]
     fn test_reservoir_sample() {
@@ -141,4 +300,65 @@ mod tests {
             assert!(a.contains(&val));
         }
     }
+
+    #[test]
+    fn test_bootstrap_sample() {
+        let inputs = Matrix::new(5, 2, (0..10).map(|x| x as f64).collect::<Vec<_>>());
+        let targets = Vector::new(vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+
+        let mut rng = StdRng::from_seed(&[1, 2, 3]);
+        let (sample_inputs, sample_targets) = bootstrap_sample(&inputs, &targets, 12, &mut rng);
+
+        assert_eq!(sample_inputs.rows(), 12);
+        assert_eq!(sample_targets.size(), 12);
+
+        // Each sampled row still matches its target.
+        for (row, t) in sample_inputs.row_iter().zip(sample_targets.iter()) {
+            assert_eq!(row[0], 2.0 * t);
+            assert_eq!(row[1], 2.0 * t + 1.0);
+        }
+    }
+
+    #[test]
+    fn test_stratified_bootstrap_sample() {
+        let targets = Vector::new((0..100).map(|i| if i < 70 { 0 } else if i < 90 { 1 } else { 2 })
+            .collect::<Vec<usize>>());
+        let inputs = Matrix::new(100, 1, targets.iter().map(|&t| t as f64).collect::<Vec<_>>());
+
+        let mut rng = StdRng::from_seed(&[1, 2, 3]);
+        let (sample_inputs, sample_targets) = stratified_bootstrap_sample(&inputs, &targets, 50, &mut rng);
+
+        assert_eq!(sample_inputs.rows(), 50);
+        assert_eq!(sample_targets.size(), 50);
+
+        let count = |c| sample_targets.iter().filter(|&&t| t == c).count();
+        assert_eq!(count(0), 35);
+        assert_eq!(count(1), 10);
+        assert_eq!(count(2), 5);
+
+        for (row, t) in sample_inputs.row_iter().zip(sample_targets.iter()) {
+            assert_eq!(row[0], *t as f64);
+        }
+    }
+
+    #[test]
+    fn test_stratified_bootstrap_rounding() {
+        let inputs = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+        let targets = Vector::new(vec![0, 1, 2]);
+
+        let mut rng = StdRng::from_seed(&[1, 2, 3]);
+        let (_, sample_targets) = stratified_bootstrap_sample(&inputs, &targets, 7, &mut rng);
+
+        assert_eq!(sample_targets.size(), 7);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bootstrap_sample_mismatched() {
+        let inputs = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+        let targets = Vector::new(vec![0, 1]);
+
+        let mut rng = StdRng::from_seed(&[1, 2, 3]);
+        let _ = bootstrap_sample(&inputs, &targets, 2, &mut rng);
+    }
 }
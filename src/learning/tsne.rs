@@ -0,0 +1,540 @@
+//! t-distributed Stochastic Neighbor Embedding Module
+//!
+//! Contains an implementation of t-SNE, which embeds high-dimensional
+//! data in two or three dimensions for visualization.
+//!
+//! The similarity of each pair of samples is modelled by a Gaussian in
+//! the input space, with a width chosen for each sample so that the
+//! distribution over its neighbors has the requested perplexity. In the
+//! embedding the similarities follow a Student-t distribution. The
+//! embedding is found by minimizing the Kullback-Leibler divergence
+//! between the two with gradient descent. Momentum is used throughout
+//! and the input similarities are exaggerated in the early iterations,
+//! which helps clusters to form.
+//!
+//! t-SNE computes an embedding of a fixed set of samples and cannot map
+//! new samples onto an existing embedding. `train` stores the embedding
+//! of the training data, while `predict` embeds its inputs from scratch.
+//! The embedding starts from a random position so each run differs.
+//!
+//! # Usage
+//!
+//! ```
+//! use rusty_machine::linalg::{Matrix, BaseMatrix};
+//! use rusty_machine::learning::tsne::TSNE;
+//! use rusty_machine::learning::UnSupModel;
+//!
+//! let inputs = Matrix::new(6, 3, vec![0.0, 0.1, 0.0,
+//!                                     0.1, 0.0, 0.1,
+//!                                     0.0, 0.0, 0.1,
+//!                                     5.0, 5.1, 5.0,
+//!                                     5.1, 5.0, 5.1,
+//!                                     5.0, 5.0, 5.1]);
+//!
+//! // The perplexity must be less than the number of samples.
+//! let mut model = TSNE::new(2)
+//!     .with_perplexity(2.0)
+//!     .with_learning_rate(10.0)
+//!     .with_iters(300);
+//! model.train(&inputs).unwrap();
+//!
+//! let embedding = model.embedding().unwrap();
+//! assert_eq!(embedding.rows(), 6);
+//! assert_eq!(embedding.cols(), 2);
+//! ```
+
+use linalg::{Matrix, BaseMatrix};
+use learning::{LearningResult, UnSupModel, validate_finite_matrix};
+use learning::error::{Error, ErrorKind};
+use learning::toolkit::distance::{pairwise_distances, SquaredEuclidean};
+
+use rand::{Rng, thread_rng};
+use rand::distributions::normal::StandardNormal;
+
+use std::f64;
+
+/// The factor by which the input similarities are exaggerated.
+const EARLY_EXAGGERATION: f64 = 12.0;
+
+/// The number of iterations with exaggerated similarities.
+const EXAGGERATION_ITERS: usize = 250;
+
+/// The momentum used during and after the exaggeration.
+const INITIAL_MOMENTUM: f64 = 0.5;
+const FINAL_MOMENTUM: f64 = 0.8;
+
+/// The smallest per-parameter gain on the learning rate.
+const MIN_GAIN: f64 = 0.01;
+
+/// The standard deviation of the initial embedding.
+const INITIAL_SCALE: f64 = 1e-2;
+
+/// Bounds the search for each Gaussian's precision.
+const ENTROPY_TOL: f64 = 1e-5;
+const PRECISION_SEARCH_ITERS: usize = 100;
+
+/// The smallest joint similarity, which avoids taking the log of zero.
+const MIN_AFFINITY: f64 = 1e-12;
+
+/// t-SNE model.
+///
+/// Contains the optimization parameters and, once trained,
+/// the embedding of the training data.
+#[derive(Debug)]
+pub struct TSNE {
+    n_components: usize,
+    perplexity: f64,
+    learning_rate: f64,
+    iters: usize,
+    embedding: Option<Matrix<f64>>,
+    kl_divergence: Option<f64>,
+}
+
+impl TSNE {
+    /// Constructs an untrained t-SNE model which embeds
+    /// the data in `n_components` dimensions.
+    ///
+    /// The defaults are:
+    ///
+    /// - perplexity = 30
+    /// - learning_rate = 200
+    /// - iters = 1000
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::tsne::TSNE;
+    ///
+    /// let model = TSNE::new(2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `n_components` is zero.
+    pub fn new(n_components: usize) -> TSNE {
+        assert!(n_components > 0, "The embedding must have at least one dimension.");
+
+        TSNE {
+            n_components,
+            perplexity: 30.0,
+            learning_rate: 200.0,
+            iters: 1000,
+            embedding: None,
+            kl_divergence: None,
+        }
+    }
+
+    /// Sets the perplexity, roughly the number of neighbors each
+    /// sample considers.
+    ///
+    /// # Panics
+    ///
+    /// - `perplexity` is not positive.
+    pub fn with_perplexity(mut self, perplexity: f64) -> TSNE {
+        assert!(perplexity > 0f64, "The perplexity must be positive.");
+        self.perplexity = perplexity;
+        self
+    }
+
+    /// Sets the learning rate of the gradient descent.
+    ///
+    /// Small datasets need a smaller learning rate than the default,
+    /// otherwise the embedding spreads out without forming clusters.
+    ///
+    /// # Panics
+    ///
+    /// - `learning_rate` is not positive.
+    pub fn with_learning_rate(mut self, learning_rate: f64) -> TSNE {
+        assert!(learning_rate > 0f64, "The learning rate must be positive.");
+        self.learning_rate = learning_rate;
+        self
+    }
+
+    /// Sets the number of gradient descent iterations.
+    pub fn with_iters(mut self, iters: usize) -> TSNE {
+        self.iters = iters;
+        self
+    }
+
+    /// The number of dimensions of the embedding.
+    pub fn n_components(&self) -> usize {
+        self.n_components
+    }
+
+    /// The perplexity of the input similarities.
+    pub fn perplexity(&self) -> f64 {
+        self.perplexity
+    }
+
+    /// The learning rate of the gradient descent.
+    pub fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    /// The number of gradient descent iterations.
+    pub fn iters(&self) -> usize {
+        self.iters
+    }
+
+    /// The embedding of the training data, one row per sample.
+    pub fn embedding(&self) -> Option<&Matrix<f64>> {
+        self.embedding.as_ref()
+    }
+
+    /// The Kullback-Leibler divergence between the input
+    /// and embedded similarities of the training data.
+    pub fn kl_divergence(&self) -> Option<f64> {
+        self.kl_divergence
+    }
+
+    /// Computes the embedding of the inputs and its KL divergence.
+    fn embed(&self, inputs: &Matrix<f64>) -> LearningResult<(Matrix<f64>, f64)> {
+        validate_finite_matrix("input", inputs)?;
+
+        let n = inputs.rows();
+        if n < 2 {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "At least two samples are needed to compute an embedding."));
+        }
+        if self.perplexity >= n as f64 {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The perplexity must be less than the number of samples."));
+        }
+
+        let p = joint_affinities(&pairwise_distances(inputs, inputs, &SquaredEuclidean),
+                                 self.perplexity);
+
+        let d = self.n_components;
+        let mut rng = thread_rng();
+        let mut y = (0..n * d)
+            .map(|_| {
+                let StandardNormal(z) = rng.gen::<StandardNormal>();
+                z * INITIAL_SCALE
+            })
+            .collect::<Vec<f64>>();
+        let mut update = vec![0f64; n * d];
+        let mut gains = vec![1f64; n * d];
+
+        for iter in 0..self.iters {
+            let (exaggeration, momentum) = if iter < EXAGGERATION_ITERS {
+                (EARLY_EXAGGERATION, INITIAL_MOMENTUM)
+            } else {
+                (1f64, FINAL_MOMENTUM)
+            };
+
+            let grad = kl_gradient(&p, &y, d, exaggeration);
+
+            for k in 0..n * d {
+                // Grow the gain while the previous update still moves
+                // downhill, and shrink it once the update overshoots.
+                gains[k] = if (grad[k] > 0f64) == (update[k] > 0f64) {
+                    (gains[k] * 0.8).max(MIN_GAIN)
+                } else {
+                    gains[k] + 0.2
+                };
+
+                update[k] = momentum * update[k] - self.learning_rate * gains[k] * grad[k];
+                y[k] += update[k];
+            }
+
+            center(&mut y, d);
+        }
+
+        let kl = kl_divergence(&p, &y, d);
+        Ok((Matrix::new(n, d, y), kl))
+    }
+}
+
+/// Computes the symmetric joint similarities from the squared distances.
+fn joint_affinities(distances: &Matrix<f64>, perplexity: f64) -> Vec<f64> {
+    let n = distances.rows();
+    let mut conditional = vec![0f64; n * n];
+    for i in 0..n {
+        let probs = calibrate_row(distances.row(i).raw_slice(), i, perplexity);
+        conditional[i * n..(i + 1) * n].copy_from_slice(&probs);
+    }
+
+    let mut joint = vec![0f64; n * n];
+    for i in 0..n {
+        for j in 0..n {
+            if i != j {
+                let p = (conditional[i * n + j] + conditional[j * n + i]) / (2f64 * n as f64);
+                joint[i * n + j] = p.max(MIN_AFFINITY);
+            }
+        }
+    }
+
+    joint
+}
+
+/// Finds the Gaussian similarities from sample `i` to the others
+/// whose entropy matches the log of the perplexity.
+///
+/// The precision of the Gaussian is found by bisection.
+fn calibrate_row(distances: &[f64], i: usize, perplexity: f64) -> Vec<f64> {
+    let target = perplexity.ln();
+
+    // Shifting by the nearest distance keeps the exponentials in range.
+    let nearest = distances.iter()
+        .enumerate()
+        .filter(|&(j, _)| j != i)
+        .map(|(_, d)| *d)
+        .fold(f64::INFINITY, f64::min);
+
+    let mut beta = 1f64;
+    let mut lower = 0f64;
+    let mut upper = f64::INFINITY;
+    let mut probs = vec![0f64; distances.len()];
+
+    for _ in 0..PRECISION_SEARCH_ITERS {
+        let mut sum = 0f64;
+        let mut weighted = 0f64;
+        for (j, (p, d)) in probs.iter_mut().zip(distances.iter()).enumerate() {
+            *p = if j == i { 0f64 } else { (-(d - nearest) * beta).exp() };
+            sum += *p;
+            weighted += *p * (d - nearest);
+        }
+
+        for p in &mut probs {
+            *p /= sum;
+        }
+
+        let entropy = sum.ln() + beta * weighted / sum;
+        let diff = entropy - target;
+        if diff.abs() < ENTROPY_TOL {
+            break;
+        }
+
+        // A high entropy means the Gaussian is too wide.
+        if diff > 0f64 {
+            lower = beta;
+            beta = if upper.is_infinite() { beta * 2f64 } else { (beta + upper) / 2f64 };
+        } else {
+            upper = beta;
+            beta = (beta + lower) / 2f64;
+        }
+    }
+
+    probs
+}
+
+/// Computes the Student-t kernel between each pair of embedded points.
+///
+/// Returns the kernel values and their sum, with zeros on the diagonal.
+fn student_t_kernel(y: &[f64], d: usize) -> (Vec<f64>, f64) {
+    let n = y.len() / d;
+    let mut kernel = vec![0f64; n * n];
+    let mut total = 0f64;
+
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let sq_dist = (0..d).map(|k| (y[i * d + k] - y[j * d + k]).powi(2)).sum::<f64>();
+            let w = 1f64 / (1f64 + sq_dist);
+            kernel[i * n + j] = w;
+            kernel[j * n + i] = w;
+            total += 2f64 * w;
+        }
+    }
+
+    (kernel, total)
+}
+
+/// The gradient of the KL divergence with respect to the embedding.
+fn kl_gradient(p: &[f64], y: &[f64], d: usize, exaggeration: f64) -> Vec<f64> {
+    let n = y.len() / d;
+    let (kernel, total) = student_t_kernel(y, d);
+
+    let mut grad = vec![0f64; n * d];
+    for i in 0..n {
+        for j in 0..n {
+            if i == j {
+                continue;
+            }
+
+            let w = kernel[i * n + j];
+            let force = 4f64 * (exaggeration * p[i * n + j] - w / total) * w;
+            for k in 0..d {
+                grad[i * d + k] += force * (y[i * d + k] - y[j * d + k]);
+            }
+        }
+    }
+
+    grad
+}
+
+/// The KL divergence between the input and embedded similarities.
+fn kl_divergence(p: &[f64], y: &[f64], d: usize) -> f64 {
+    let n = y.len() / d;
+    let (kernel, total) = student_t_kernel(y, d);
+
+    let mut kl = 0f64;
+    for i in 0..n {
+        for j in 0..n {
+            if i != j {
+                let p_ij = p[i * n + j];
+                let q_ij = (kernel[i * n + j] / total).max(MIN_AFFINITY);
+                kl += p_ij * (p_ij / q_ij).ln();
+            }
+        }
+    }
+
+    kl
+}
+
+/// Subtracts the mean from each dimension of the embedding.
+fn center(y: &mut [f64], d: usize) {
+    let n = y.len() / d;
+    for k in 0..d {
+        let mean = (0..n).map(|i| y[i * d + k]).sum::<f64>() / n as f64;
+        for i in 0..n {
+            y[i * d + k] -= mean;
+        }
+    }
+}
+
+impl UnSupModel<Matrix<f64>, Matrix<f64>> for TSNE {
+    /// Train the model by computing the embedding of the inputs.
+    ///
+    /// # Failures
+    ///
+    /// - Some input is NaN or infinite.
+    /// - There are fewer than two samples.
+    /// - The perplexity is not less than the number of samples.
+    fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        let (embedding, kl) = self.embed(inputs)?;
+        self.embedding = Some(embedding);
+        self.kl_divergence = Some(kl);
+        Ok(())
+    }
+
+    /// Compute a new embedding of the inputs.
+    ///
+    /// The embedding from training is not reused, as t-SNE
+    /// cannot place new samples within an existing embedding.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        self.embed(inputs).map(|(embedding, _)| embedding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TSNE, calibrate_row, joint_affinities};
+    use learning::UnSupModel;
+    use learning::toolkit::distance::{pairwise_distances, SquaredEuclidean};
+    use linalg::{Matrix, BaseMatrix};
+
+    use rand::{Rng, StdRng, SeedableRng};
+    use rand::distributions::normal::StandardNormal;
+
+    /// Three tight clusters in ten dimensions.
+    fn cluster_data() -> (Matrix<f64>, Vec<usize>) {
+        let seed: &[_] = &[1, 2, 3];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for c in 0..3 {
+            for _ in 0..12 {
+                for k in 0..10 {
+                    let StandardNormal(z) = rng.gen::<StandardNormal>();
+                    let center = if k % 3 == c { 10.0 } else { 0.0 };
+                    data.push(center + 0.5 * z);
+                }
+                labels.push(c);
+            }
+        }
+
+        (Matrix::new(36, 10, data), labels)
+    }
+
+    #[test]
+    fn test_clusters_remain_separated() {
+        let (inputs, labels) = cluster_data();
+
+        // The default learning rate is too large for so few samples.
+        let mut model = TSNE::new(2).with_perplexity(5.0).with_learning_rate(10.0).with_iters(500);
+        model.train(&inputs).unwrap();
+
+        let embedding = model.embedding().unwrap();
+        assert_eq!(embedding.rows(), 36);
+        assert_eq!(embedding.cols(), 2);
+
+        let distances = pairwise_distances(embedding, embedding, &SquaredEuclidean);
+
+        // Every point is closer to all of its own cluster
+        // than to any point of another cluster.
+        for i in 0..36 {
+            let row = distances.row(i);
+            let mut within = 0f64;
+            let mut between = ::std::f64::INFINITY;
+            for (j, d) in row.iter().enumerate() {
+                if labels[j] == labels[i] {
+                    within = within.max(*d);
+                } else {
+                    between = between.min(*d);
+                }
+            }
+            assert!(within < between, "point {} is not separated", i);
+        }
+
+        assert!(model.kl_divergence().unwrap() >= 0f64);
+    }
+
+    #[test]
+    fn test_calibrated_perplexity() {
+        let (inputs, _) = cluster_data();
+        let distances = pairwise_distances(&inputs, &inputs, &SquaredEuclidean);
+
+        for &perplexity in &[2.0, 5.0, 20.0] {
+            let probs = calibrate_row(distances.row(0).raw_slice(), 0, perplexity);
+            let entropy = -probs.iter().filter(|p| **p > 0.0).map(|p| p * p.ln()).sum::<f64>();
+
+            assert_eq!(probs[0], 0.0);
+            assert!((probs.iter().sum::<f64>() - 1.0).abs() < 1e-10);
+            assert!((entropy.exp() - perplexity).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_joint_affinities_symmetric() {
+        let (inputs, _) = cluster_data();
+        let distances = pairwise_distances(&inputs, &inputs, &SquaredEuclidean);
+        let p = joint_affinities(&distances, 5.0);
+
+        let n = inputs.rows();
+        for i in 0..n {
+            assert_eq!(p[i * n + i], 0.0);
+            for j in 0..n {
+                assert_eq!(p[i * n + j], p[j * n + i]);
+            }
+        }
+        assert!((p.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_embedding_dimensions() {
+        let (inputs, _) = cluster_data();
+
+        let model = TSNE::new(3).with_perplexity(5.0).with_iters(50);
+        let embedding = model.predict(&inputs).unwrap();
+
+        assert_eq!(embedding.rows(), 36);
+        assert_eq!(embedding.cols(), 3);
+        assert!(model.embedding().is_none());
+    }
+
+    #[test]
+    fn test_perplexity_too_large() {
+        let inputs = Matrix::new(4, 2, vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+
+        let mut model = TSNE::new(2).with_perplexity(4.0);
+        assert!(model.train(&inputs).is_err());
+        assert!(model.embedding().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_learning_rate() {
+        let _ = TSNE::new(2).with_learning_rate(0.0);
+    }
+}
@@ -0,0 +1,350 @@
+//! Voting Ensemble Module
+//!
+//! Contains the `VotingClassifier`, which combines the predictions
+//! of several trained classifiers.
+//!
+//! Each classifier implements `ProbabilisticClassifier`, so any mix of
+//! models which predict class probabilities can be combined. With hard
+//! voting each model votes for its most probable class. With soft voting
+//! the class probabilities of the models are averaged, so confident
+//! models count for more. Each model can be given a weight.
+//!
+//! The models must already be trained and predict the same classes.
+//!
+//! # Usage
+//!
+//! ```
+//! use rusty_machine::learning::voting::{Voting, VotingClassifier};
+//! use rusty_machine::learning::knn::{KNNClassifier, Weighting};
+//! use rusty_machine::learning::logistic_reg::LogisticRegressor;
+//! use rusty_machine::learning::naive_bayes::{NaiveBayes, Gaussian};
+//! use rusty_machine::learning::LabelClassifier;
+//! use rusty_machine::linalg::Matrix;
+//!
+//! let inputs = Matrix::new(6,1,vec![1.0,1.5,2.0,4.0,4.5,5.0]);
+//! let labels = vec![0,0,0,1,1,1];
+//!
+//! let mut log_mod = LogisticRegressor::default();
+//! log_mod.train_labels(&inputs, &labels).unwrap();
+//!
+//! let mut nb_mod = NaiveBayes::<Gaussian>::new();
+//! nb_mod.train_labels(&inputs, &labels).unwrap();
+//!
+//! let mut knn_mod = KNNClassifier::new(3, Weighting::Uniform);
+//! knn_mod.train_labels(&inputs, &labels).unwrap();
+//!
+//! // Average the probabilities, counting logistic regression twice.
+//! let ensemble = VotingClassifier::new(Voting::Soft)
+//!     .with_weighted_model(log_mod, 2.0)
+//!     .with_model(nb_mod)
+//!     .with_model(knn_mod);
+//!
+//! let classes = ensemble.predict_labels(&Matrix::new(2,1,vec![1.2,4.8])).unwrap();
+//! assert_eq!(classes, vec![0, 1]);
+//! ```
+
+use linalg::{Matrix, BaseMatrix};
+use learning::{LearningResult, ProbabilisticClassifier};
+use learning::error::{Error, ErrorKind};
+
+use std::fmt;
+
+/// How the predictions of the models are combined.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Voting {
+    /// Each model votes for its most probable class.
+    Hard,
+    /// The class probabilities of the models are averaged.
+    Soft,
+}
+
+/// Voting Ensemble Classifier
+pub struct VotingClassifier {
+    models: Vec<Box<dyn ProbabilisticClassifier>>,
+    weights: Vec<f64>,
+    voting: Voting,
+}
+
+impl fmt::Debug for VotingClassifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("VotingClassifier")
+            .field("n_models", &self.models.len())
+            .field("weights", &self.weights)
+            .field("voting", &self.voting)
+            .finish()
+    }
+}
+
+impl VotingClassifier {
+    /// Constructs an ensemble without any models.
+    pub fn new(voting: Voting) -> VotingClassifier {
+        VotingClassifier {
+            models: Vec::new(),
+            weights: Vec::new(),
+            voting,
+        }
+    }
+
+    /// Adds a trained model to the ensemble with a weight of one.
+    pub fn with_model<M: ProbabilisticClassifier + 'static>(self, model: M) -> Self {
+        self.with_weighted_model(model, 1f64)
+    }
+
+    /// Adds a trained model to the ensemble with the given weight.
+    ///
+    /// # Panics
+    ///
+    /// - `weight` is negative or not finite.
+    pub fn with_weighted_model<M: ProbabilisticClassifier + 'static>(mut self, model: M, weight: f64) -> Self {
+        assert!(weight >= 0f64 && weight.is_finite(),
+                "The model weight must be finite and non-negative.");
+
+        self.models.push(Box::new(model));
+        self.weights.push(weight);
+        self
+    }
+
+    /// How the predictions of the models are combined.
+    pub fn voting(&self) -> Voting {
+        self.voting
+    }
+
+    /// The weight of each model, in the order they were added.
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// The number of models in the ensemble.
+    pub fn n_models(&self) -> usize {
+        self.models.len()
+    }
+
+    /// Predict the class of each input.
+    ///
+    /// This is the class with the largest combined vote or
+    /// probability. Ties go to the smallest class.
+    ///
+    /// # Failures
+    ///
+    /// - As for `predict_proba`.
+    pub fn predict_labels(&self, inputs: &Matrix<f64>) -> LearningResult<Vec<usize>> {
+        let probs = self.predict_proba(inputs)?;
+
+        Ok(probs.row_iter().map(|row| argmax(row.raw_slice())).collect())
+    }
+}
+
+/// The index of the largest value, taking the first of any ties.
+fn argmax(values: &[f64]) -> usize {
+    let mut best = 0;
+    for (i, &v) in values.iter().enumerate() {
+        if v > values[best] {
+            best = i;
+        }
+    }
+    best
+}
+
+/// The weighted share of the votes for each class with hard voting,
+/// or the weighted mean of the class probabilities with soft voting.
+///
+/// # Failures
+///
+/// - The ensemble has no models.
+/// - The model weights are all zero.
+/// - A model fails to predict.
+/// - The models predict different numbers of classes.
+impl ProbabilisticClassifier for VotingClassifier {
+    fn predict_proba(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        if self.models.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidState, "The ensemble has no models."));
+        }
+
+        let total_weight = self.weights.iter().sum::<f64>();
+        if total_weight == 0f64 {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "The model weights must not all be zero."));
+        }
+
+        let mut combined: Option<Matrix<f64>> = None;
+        for (model, &weight) in self.models.iter().zip(self.weights.iter()) {
+            let mut probs = model.predict_proba(inputs)?;
+
+            if let Voting::Hard = self.voting {
+                let mut votes = Matrix::zeros(probs.rows(), probs.cols());
+                for (i, row) in probs.row_iter().enumerate() {
+                    votes[[i, argmax(row.raw_slice())]] = 1f64;
+                }
+                probs = votes;
+            }
+
+            combined = match combined {
+                None => Some(probs * weight),
+                Some(c) => {
+                    if c.cols() != probs.cols() {
+                        return Err(Error::new(ErrorKind::InvalidData,
+                                              "The models predict different numbers of classes."));
+                    }
+                    Some(c + probs * weight)
+                }
+            };
+        }
+
+        Ok(combined.expect("The ensemble has at least one model.") / total_weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Voting, VotingClassifier};
+    use learning::{LabelClassifier, LearningResult, ProbabilisticClassifier};
+    use learning::knn::{KNNClassifier, Weighting};
+    use learning::logistic_reg::LogisticRegressor;
+    use learning::naive_bayes::{NaiveBayes, Gaussian};
+    use learning::optim::grad_desc::GradientDesc;
+    use linalg::{Matrix, BaseMatrix};
+
+    /// A logistic regression which only sees one feature.
+    struct OnFeature {
+        feature: usize,
+        model: LogisticRegressor<GradientDesc>,
+    }
+
+    impl OnFeature {
+        fn train(feature: usize, inputs: &Matrix<f64>, labels: &[usize]) -> OnFeature {
+            let mut model = LogisticRegressor::new(GradientDesc::new(1.0, 500));
+            model.train_labels(&inputs.select_cols(&[feature]), labels).unwrap();
+            OnFeature { feature, model }
+        }
+    }
+
+    impl ProbabilisticClassifier for OnFeature {
+        fn predict_proba(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+            self.model.predict_proba(&inputs.select_cols(&[self.feature]))
+        }
+    }
+
+    /// Points on a grid labelled by whether most of their features are positive.
+    fn majority_data() -> (Matrix<f64>, Vec<usize>) {
+        let values = [-1.25, -0.75, -0.25, 0.25, 0.75, 1.25];
+        let mut inputs = Vec::new();
+        let mut labels = Vec::new();
+        for &a in &values {
+            for &b in &values {
+                for &c in &values {
+                    inputs.extend_from_slice(&[a, b, c]);
+                    let positive = [a, b, c].iter().filter(|&&x| x > 0.0).count();
+                    labels.push(if positive >= 2 { 1 } else { 0 });
+                }
+            }
+        }
+        (Matrix::new(labels.len(), 3, inputs), labels)
+    }
+
+    fn accuracy(predictions: &[usize], labels: &[usize]) -> f64 {
+        let correct = predictions.iter().zip(labels.iter()).filter(|&(p, l)| p == l).count();
+        correct as f64 / labels.len() as f64
+    }
+
+    fn majority_ensemble(voting: Voting, inputs: &Matrix<f64>, labels: &[usize]) -> VotingClassifier {
+        (0..3).fold(VotingClassifier::new(voting),
+                    |ensemble, f| ensemble.with_model(OnFeature::train(f, inputs, labels)))
+    }
+
+    #[test]
+    fn test_ensemble_beats_each_model() {
+        let (inputs, labels) = majority_data();
+
+        // Each model only sees one feature, so is right three times in four.
+        for f in 0..3 {
+            let model = OnFeature::train(f, &inputs, &labels);
+            let predictions = model.model.predict_labels(&inputs.select_cols(&[f])).unwrap();
+            assert!((accuracy(&predictions, &labels) - 0.75).abs() < 1e-12);
+        }
+
+        let soft = majority_ensemble(Voting::Soft, &inputs, &labels);
+        let soft_accuracy = accuracy(&soft.predict_labels(&inputs).unwrap(), &labels);
+        assert!(soft_accuracy > 0.85, "{}", soft_accuracy);
+
+        // The majority of the votes is the label.
+        let hard = majority_ensemble(Voting::Hard, &inputs, &labels);
+        assert_eq!(hard.predict_labels(&inputs).unwrap(), labels);
+    }
+
+    #[test]
+    fn test_probabilities_are_weighted_means() {
+        let (inputs, labels) = majority_data();
+        let first = OnFeature::train(0, &inputs, &labels);
+        let second = OnFeature::train(1, &inputs, &labels);
+        let expected = (first.predict_proba(&inputs).unwrap() * 3.0 + second.predict_proba(&inputs).unwrap()) / 4.0;
+
+        let ensemble = VotingClassifier::new(Voting::Soft)
+            .with_weighted_model(first, 3.0)
+            .with_model(second);
+        assert_eq!(ensemble.weights(), &[3.0, 1.0]);
+
+        let probs = ensemble.predict_proba(&inputs).unwrap();
+        assert!((probs - expected).iter().all(|d| d.abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_hard_voting_with_weights() {
+        let (inputs, labels) = majority_data();
+
+        // A model with most of the weight decides every vote.
+        let ensemble = VotingClassifier::new(Voting::Hard)
+            .with_model(OnFeature::train(0, &inputs, &labels))
+            .with_weighted_model(OnFeature::train(1, &inputs, &labels), 5.0)
+            .with_model(OnFeature::train(2, &inputs, &labels));
+
+        let single = OnFeature::train(1, &inputs, &labels);
+        let expected = single.model.predict_labels(&inputs.select_cols(&[1])).unwrap();
+        assert_eq!(ensemble.predict_labels(&inputs).unwrap(), expected);
+
+        let votes = ensemble.predict_proba(&inputs).unwrap();
+        assert!(votes.iter().all(|&v| [0.0, 1.0 / 7.0, 2.0 / 7.0, 5.0 / 7.0, 6.0 / 7.0, 1.0]
+            .iter()
+            .any(|w| (v - w).abs() < 1e-12)));
+    }
+
+    #[test]
+    fn test_mixed_models() {
+        let inputs = Matrix::new(9, 1, vec![0.0, 0.5, 1.0, 3.0, 3.5, 4.0, 6.0, 6.5, 7.0]);
+        let labels = vec![0, 0, 0, 1, 1, 1, 2, 2, 2];
+
+        let mut nb_mod = NaiveBayes::<Gaussian>::new();
+        nb_mod.train_labels(&inputs, &labels).unwrap();
+        let mut knn_mod = KNNClassifier::new(3, Weighting::Uniform);
+        knn_mod.train_labels(&inputs, &labels).unwrap();
+
+        let ensemble = VotingClassifier::new(Voting::Soft).with_model(nb_mod).with_model(knn_mod);
+        assert_eq!(ensemble.n_models(), 2);
+
+        let test_inputs = Matrix::new(3, 1, vec![0.2, 3.6, 6.9]);
+        let probs = ensemble.predict_proba(&test_inputs).unwrap();
+        assert_eq!(probs.cols(), 3);
+        for row in probs.row_iter() {
+            assert!((row.sum() - 1.0).abs() < 1e-12);
+        }
+        assert_eq!(ensemble.predict_labels(&test_inputs).unwrap(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_invalid_ensembles() {
+        let inputs = Matrix::new(4, 1, vec![0.0, 1.0, 5.0, 6.0]);
+        assert!(VotingClassifier::new(Voting::Soft).predict_labels(&inputs).is_err());
+
+        let mut two_classes = NaiveBayes::<Gaussian>::new();
+        two_classes.train_labels(&inputs, &[0, 0, 1, 1]).unwrap();
+        let mut three_classes = NaiveBayes::<Gaussian>::new();
+        three_classes.train_labels(&inputs, &[0, 0, 2, 2]).unwrap();
+
+        let mismatched = VotingClassifier::new(Voting::Soft).with_model(two_classes).with_model(three_classes);
+        assert!(mismatched.predict_labels(&inputs).is_err());
+
+        let mut model = NaiveBayes::<Gaussian>::new();
+        model.train_labels(&inputs, &[0, 0, 1, 1]).unwrap();
+        let unweighted = VotingClassifier::new(Voting::Hard).with_weighted_model(model, 0.0);
+        assert!(unweighted.predict_proba(&inputs).is_err());
+    }
+}
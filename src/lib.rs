@@ -16,15 +16,26 @@
 //! The currently supported techniques are:
 //!
 //! - Linear Regression
+//! - Bayesian Linear Regression
+//! - Huber Regression
+//! - Quantile Regression
 //! - Logistic Regression
+//! - Ordinal Regression
 //! - Generalized Linear Models
 //! - K-Means Clustering
+//! - K-Nearest Neighbours Classification
 //! - Neural Networks
 //! - Gaussian Process Regression
 //! - Support Vector Machines
 //! - Gaussian Mixture Models
 //! - Naive Bayes Classifiers
 //! - DBSCAN
+//! - AdaBoost
+//! - Random Forests
+//! - Voting Ensembles
+//! - Multi-label Classification
+//! - Principal Component Analysis
+//! - t-SNE
 //!
 //! ### linalg
 //!
@@ -132,14 +143,28 @@ pub mod learning {
     pub mod glm;
     pub mod gmm;
     pub mod lin_reg;
+    pub mod bayes_lin_reg;
+    pub mod huber_reg;
+    pub mod quantile_reg;
     pub mod logistic_reg;
+    pub mod ordinal_reg;
     pub mod k_means;
+    pub mod knn;
     pub mod nnet;
     pub mod gp;
     pub mod svm;
     pub mod naive_bayes;
+    pub mod adaboost;
+    pub mod random_forest;
+    pub mod voting;
+    pub mod calibration;
+    pub mod multilabel;
+    pub mod pipeline;
+    pub mod pca;
+    pub mod tsne;
 
     pub mod error;
+    pub mod model_info;
 
     /// A new type which provides clean access to the learning errors
     pub type LearningResult<T> = Result<T, error::Error>;
@@ -151,6 +176,289 @@ pub mod learning {
 
         /// Train the model using inputs and targets.
         fn train(&mut self, inputs: &T, targets: &U) -> LearningResult<()>;
+
+        /// Train the model using inputs, targets and a non-negative weight
+        /// for each sample. Samples with larger weights have more influence
+        /// on the fit.
+        ///
+        /// The default implementation returns an error, as not every
+        /// model supports sample weights.
+        fn train_weighted(&mut self,
+                          _inputs: &T,
+                          _targets: &U,
+                          _weights: &::linalg::Vector<f64>)
+                          -> LearningResult<()> {
+            Err(error::Error::new(error::ErrorKind::UnsupportedOperation,
+                                  "This model does not support sample weights."))
+        }
+    }
+
+    /// Trait for classifiers which are trained on, and predict,
+    /// integer class labels.
+    ///
+    /// The labels are `0, 1, ...` with one label per sample. The model
+    /// converts them to its own target encoding, such as one-hot rows,
+    /// internally. The `LabelEncoder` can map other categories to labels.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::LabelClassifier;
+    /// use rusty_machine::learning::naive_bayes::{NaiveBayes, Gaussian};
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// let inputs = Matrix::new(6, 1, vec![1.0, 1.2, 3.0, 3.2, 5.0, 5.2]);
+    /// let labels = vec![0, 0, 1, 1, 2, 2];
+    ///
+    /// let mut model = NaiveBayes::<Gaussian>::new();
+    /// model.train_labels(&inputs, &labels).unwrap();
+    ///
+    /// let classes = model.predict_labels(&Matrix::new(2, 1, vec![0.9, 5.1])).unwrap();
+    /// assert_eq!(classes, vec![0, 2]);
+    /// ```
+    pub trait LabelClassifier {
+        /// Train the classifier using inputs and the class label of each input.
+        fn train_labels(&mut self, inputs: &::linalg::Matrix<f64>, labels: &[usize]) -> LearningResult<()>;
+
+        /// Predict the class label of each input.
+        fn predict_labels(&self, inputs: &::linalg::Matrix<f64>) -> LearningResult<Vec<usize>>;
+    }
+
+    /// Trait for classifiers which predict the probability of each class.
+    ///
+    /// The probabilities have one row per input and one column per
+    /// class label `0, 1, ...`, and each row sums to one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::{LabelClassifier, ProbabilisticClassifier};
+    /// use rusty_machine::learning::naive_bayes::{NaiveBayes, Gaussian};
+    /// use rusty_machine::linalg::{BaseMatrix, Matrix};
+    ///
+    /// let inputs = Matrix::new(4, 1, vec![1.0, 1.2, 3.0, 3.2]);
+    /// let labels = vec![0, 0, 1, 1];
+    ///
+    /// let mut model = NaiveBayes::<Gaussian>::new();
+    /// model.train_labels(&inputs, &labels).unwrap();
+    ///
+    /// let probs = ProbabilisticClassifier::predict_proba(&model, &Matrix::new(1, 1, vec![3.1])).unwrap();
+    /// assert_eq!(probs.cols(), 2);
+    /// assert!(probs[[0, 1]] > 0.9);
+    /// ```
+    pub trait ProbabilisticClassifier {
+        /// Predict the probability of each class for each input.
+        fn predict_proba(&self, inputs: &::linalg::Matrix<f64>) -> LearningResult<::linalg::Matrix<f64>>;
+
+        /// Predict the class of each input, abstaining when unsure.
+        ///
+        /// Returns the index of the most probable class for each input, or
+        /// `None` if its probability is below `min_prob`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use rusty_machine::learning::ProbabilisticClassifier;
+        /// use rusty_machine::learning::naive_bayes::{NaiveBayes, Gaussian};
+        /// use rusty_machine::learning::SupModel;
+        /// use rusty_machine::linalg::Matrix;
+        ///
+        /// let inputs = Matrix::new(4, 1, vec![1.0, 1.2, 3.0, 3.2]);
+        /// let targets = Matrix::new(4, 2, vec![1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0]);
+        ///
+        /// let mut model = NaiveBayes::<Gaussian>::new();
+        /// model.train(&inputs, &targets).unwrap();
+        ///
+        /// // The middle input lies between the two classes.
+        /// let new_inputs = Matrix::new(3, 1, vec![1.1, 2.1, 3.1]);
+        /// let classes = model.predict_with_reject(&new_inputs, 0.9).unwrap();
+        /// assert_eq!(classes.into_vec(), vec![Some(0), None, Some(1)]);
+        /// ```
+        ///
+        /// # Failures
+        ///
+        /// - `min_prob` is not between 0 and 1.
+        /// - The probabilities cannot be predicted, e.g. the model has not been trained.
+        fn predict_with_reject(&self,
+                               inputs: &::linalg::Matrix<f64>,
+                               min_prob: f64)
+                               -> LearningResult<::linalg::Vector<Option<usize>>> {
+            use linalg::BaseMatrix;
+
+            if !(0f64..=1f64).contains(&min_prob) {
+                return Err(error::Error::new(error::ErrorKind::InvalidParameters,
+                                             "The minimum probability must be between 0 and 1."));
+            }
+
+            let probs = self.predict_proba(inputs)?;
+            let classes = probs.row_iter()
+                .map(|row| {
+                    let (class, prob) = ::rulinalg::utils::argmax(row.raw_slice());
+                    if prob >= min_prob { Some(class) } else { None }
+                })
+                .collect::<Vec<Option<usize>>>();
+
+            Ok(::linalg::Vector::new(classes))
+        }
+    }
+
+    /// Checks that there is one label per sample.
+    fn validate_labels(labels: &[usize], n_samples: usize) -> LearningResult<()> {
+        if labels.len() != n_samples {
+            return Err(error::Error::new(error::ErrorKind::InvalidData,
+                                         "There must be one label per sample."));
+        }
+        Ok(())
+    }
+
+    /// Maps the labels `0` and `1` of a binary classifier
+    /// to the targets `negative` and `positive`.
+    fn binary_label_targets(labels: &[usize],
+                            negative: f64,
+                            positive: f64)
+                            -> LearningResult<::linalg::Vector<f64>> {
+        labels.iter()
+            .map(|&l| match l {
+                0 => Ok(negative),
+                1 => Ok(positive),
+                _ => {
+                    Err(error::Error::new(error::ErrorKind::InvalidData,
+                                          "This classifier only supports the labels 0 and 1."))
+                }
+            })
+            .collect::<LearningResult<Vec<f64>>>()
+            .map(::linalg::Vector::new)
+    }
+
+    /// Predict the outputs of a model in batches of at most `batch_size` rows.
+    ///
+    /// Only one batch and its outputs are held in memory by the model at
+    /// a time, which bounds the memory used for intermediate results.
+    /// The output is the same as a single call to `predict`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::{predict_batched, SupModel};
+    /// use rusty_machine::learning::nnet::NeuralNet;
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// let net = NeuralNet::default(&[2, 3, 1]);
+    /// let inputs = Matrix::new(5, 2, vec![0.5; 10]);
+    ///
+    /// let outputs = predict_batched(&net, &inputs, 2).unwrap();
+    /// assert_eq!(outputs, net.predict(&inputs).unwrap());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `batch_size` is zero.
+    pub fn predict_batched<M>(model: &M,
+                              inputs: &::linalg::Matrix<f64>,
+                              batch_size: usize)
+                              -> LearningResult<::linalg::Matrix<f64>>
+        where M: SupModel<::linalg::Matrix<f64>, ::linalg::Matrix<f64>>
+    {
+        use linalg::{BaseMatrix, Matrix, MatrixSlice};
+
+        assert!(batch_size > 0, "The batch size must be positive.");
+
+        if inputs.rows() <= batch_size {
+            return model.predict(inputs);
+        }
+
+        let mut data = Vec::new();
+        let mut cols = 0;
+        let mut start = 0;
+        while start < inputs.rows() {
+            let rows = batch_size.min(inputs.rows() - start);
+            let batch = MatrixSlice::from_matrix(inputs, [start, 0], rows, inputs.cols()).into_matrix();
+            let outputs = model.predict(&batch)?;
+
+            cols = outputs.cols();
+            data.extend_from_slice(outputs.data());
+            start += rows;
+        }
+
+        Ok(Matrix::new(inputs.rows(), cols, data))
+    }
+
+    /// Checks that there is one finite, non-negative weight
+    /// per sample and that some weight is positive.
+    fn validate_sample_weights(weights: &::linalg::Vector<f64>, n_samples: usize) -> LearningResult<()> {
+        if weights.size() != n_samples {
+            return Err(error::Error::new(error::ErrorKind::InvalidData,
+                                         "There must be one weight per sample."));
+        }
+
+        if weights.iter().any(|w| !w.is_finite() || *w < 0f64) {
+            return Err(error::Error::new(error::ErrorKind::InvalidData,
+                                         "Sample weights must be finite and non-negative."));
+        }
+
+        if weights.iter().all(|w| *w == 0f64) {
+            return Err(error::Error::new(error::ErrorKind::InvalidData,
+                                         "Some sample weight must be positive."));
+        }
+
+        Ok(())
+    }
+
+    /// Describes a value which is not finite.
+    fn non_finite_name(x: f64) -> &'static str {
+        if x.is_nan() { "NaN" } else { "infinite" }
+    }
+
+    /// Checks that every entry of the matrix is finite.
+    ///
+    /// The error names the first bad entry, e.g. "input row 42, column 3 is NaN".
+    pub(crate) fn validate_finite_matrix(name: &str, mat: &::linalg::Matrix<f64>) -> LearningResult<()> {
+        use linalg::BaseMatrix;
+
+        match mat.iter().position(|x| !x.is_finite()) {
+            Some(idx) => {
+                Err(error::Error::new(error::ErrorKind::InvalidData,
+                                      format!("{} row {}, column {} is {}",
+                                              name,
+                                              idx / mat.cols(),
+                                              idx % mat.cols(),
+                                              non_finite_name(mat.data()[idx]))))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that every entry of the vector is finite.
+    ///
+    /// The error names the first bad entry, e.g. "target 7 is infinite".
+    fn validate_finite_vector(name: &str, vec: &::linalg::Vector<f64>) -> LearningResult<()> {
+        match vec.iter().position(|x| !x.is_finite()) {
+            Some(idx) => {
+                Err(error::Error::new(error::ErrorKind::InvalidData,
+                                      format!("{} {} is {}", name, idx, non_finite_name(vec[idx]))))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that the cost of the optimized parameters is not NaN.
+    ///
+    /// The optimizers stop as soon as the cost becomes NaN, so a
+    /// diverged optimization is caught here with one extra
+    /// evaluation of the cost.
+    fn validate_optimized<M: optim::Optimizable>(model: &M,
+                                                 params: &[f64],
+                                                 inputs: &M::Inputs,
+                                                 targets: &M::Targets)
+                                                 -> LearningResult<()> {
+        let (cost, _) = model.compute_grad(params, inputs, targets);
+        if cost.is_nan() {
+            Err(error::Error::new(error::ErrorKind::InvalidState,
+                                  "The optimization diverged: the cost is NaN. \
+                                   Try a smaller learning rate."))
+        } else {
+            Ok(())
+        }
     }
 
     /// Trait for unsupervised model.
@@ -185,23 +493,70 @@ pub mod learning {
             /// Return the optimized parameter using gradient optimization.
             ///
             /// Takes in a set of starting parameters and related model data.
+            ///
+            /// Algorithms should stop early if the cost becomes NaN,
+            /// as further iterations cannot recover.
             fn optimize(&self,
                         model: &M,
                         start: &[f64],
                         inputs: &M::Inputs,
                         targets: &M::Targets)
                         -> Vec<f64>;
+
+            /// Return the optimized parameters, calling `on_epoch` with the
+            /// epoch index and current parameters after each pass.
+            ///
+            /// The callback only observes the parameters and does not
+            /// affect the optimization. Algorithms without a notion of
+            /// epochs call it once with the final parameters.
+            ///
+            /// The method is generic over the callback, so it is not
+            /// available on `OptimAlgorithm` trait objects.
+            fn optimize_with_callback<F>(&self,
+                                         model: &M,
+                                         start: &[f64],
+                                         inputs: &M::Inputs,
+                                         targets: &M::Targets,
+                                         mut on_epoch: F)
+                                         -> Vec<f64>
+                where F: FnMut(usize, &[f64]),
+                      Self: Sized
+            {
+                let params = self.optimize(model, start, inputs, targets);
+                on_epoch(0, &params);
+                params
+            }
+        }
+
+        /// Trait for optimization algorithms which make passes, or epochs,
+        /// through the training data.
+        ///
+        /// The algorithms stop early once the cost stops changing, so
+        /// the number of epochs is a maximum.
+        pub trait EpochAlgorithm {
+            /// The maximum number of passes through the data.
+            fn epochs(&self) -> usize;
+
+            /// Sets the maximum number of passes through the data.
+            fn set_epochs(&mut self, epochs: usize);
+
+            /// The number of parameter updates in each pass through `n_rows` data points.
+            fn steps_per_epoch(&self, n_rows: usize) -> usize;
         }
 
         pub mod grad_desc;
         pub mod fmincg;
+        pub mod schedule;
     }
 
     /// Module for learning tools.
     pub mod toolkit {
         pub mod activ_fn;
         pub mod cost_fn;
+        pub mod distance;
+        pub mod kd_tree;
         pub mod kernel;
+        pub mod numeric;
         pub mod rand_utils;
         pub mod regularization;
     }
@@ -219,6 +574,8 @@ pub mod stats {
 pub mod analysis {
     pub mod confusion_matrix;
     pub mod cross_validation;
+    pub mod importance;
+    pub mod report;
     pub mod score;
 }
 
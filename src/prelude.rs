@@ -7,6 +7,8 @@ pub use linalg::Vector;
 pub use linalg::Axes;
 
 pub use learning::SupModel;
+pub use learning::LabelClassifier;
+pub use learning::ProbabilisticClassifier;
 pub use learning::UnSupModel;
 
 #[cfg(test)]
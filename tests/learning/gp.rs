@@ -1,7 +1,8 @@
-use rm::linalg::Matrix;
+use rm::linalg::{Matrix, BaseMatrix};
 use rm::linalg::Vector;
 use rm::learning::SupModel;
-use rm::learning::gp::GaussianProcess;
+use rm::learning::gp::{GaussianProcess, MultiOutputGaussianProcess, ConstMean, LinearMean};
+use rm::learning::toolkit::kernel::{Kernel, SquaredExp};
 
 #[test]
 fn test_default_gp() {
@@ -17,3 +18,184 @@ fn test_default_gp() {
 
 	let _outputs = gp.predict(&test_inputs).unwrap();
 }
+
+#[test]
+fn test_predict_interval_widths() {
+	let mut gp = GaussianProcess::default();
+	gp.noise = 1e-2f64;
+
+	let inputs = Matrix::new(5,1,vec![0.,1.,2.,3.,4.]);
+	let targets = Vector::new(vec![0.,1.,2.,1.,0.]);
+
+	gp.train(&inputs, &targets).unwrap();
+
+	let test_inputs = Matrix::new(3,1,vec![0.5,2.5,6.0]);
+	let (post_mean, post_var) = gp.get_posterior(&test_inputs).unwrap();
+
+	let (lower_95, upper_95) = gp.predict_interval(&test_inputs, 0.95).unwrap();
+	let (lower_68, upper_68) = gp.predict_interval(&test_inputs, 0.68).unwrap();
+
+	for i in 0..3 {
+		// Both intervals are centered on the posterior mean.
+		assert!(((lower_95[i] + upper_95[i]) / 2. - post_mean[i]).abs() < 1e-10);
+		assert!(((lower_68[i] + upper_68[i]) / 2. - post_mean[i]).abs() < 1e-10);
+
+		assert!(upper_95[i] - lower_95[i] > upper_68[i] - lower_68[i]);
+
+		let std_dev = post_var[[i, i]].sqrt();
+		assert!(((upper_95[i] - post_mean[i]) / std_dev - 1.959964).abs() < 1e-5);
+	}
+}
+
+#[test]
+fn test_predict_interval_invalid_confidence() {
+	let mut gp = GaussianProcess::default();
+	gp.noise = 1e-2f64;
+
+	let inputs = Matrix::new(3,1,vec![0.,1.,2.]);
+	let targets = Vector::new(vec![0.,1.,0.]);
+
+	gp.train(&inputs, &targets).unwrap();
+
+	assert!(gp.predict_interval(&inputs, 0.).is_err());
+	assert!(gp.predict_interval(&inputs, 1.).is_err());
+	assert!(gp.predict_interval(&inputs, 1.5).is_err());
+}
+
+#[test]
+fn test_predict_interval_untrained() {
+	let gp = GaussianProcess::default();
+
+	let inputs = Matrix::new(1,1,vec![0.]);
+	assert!(gp.predict_interval(&inputs, 0.95).is_err());
+}
+
+#[test]
+fn test_multi_output_matches_single_output() {
+	let inputs = Matrix::new(6,1,vec![0.,1.,2.,3.,4.,5.]);
+	let targets = Matrix::new(6,2,vec![0.,5.,
+	                                   1.,4.,
+	                                   2.,3.,
+	                                   1.,3.,
+	                                   0.,4.,
+	                                   1.,5.]);
+
+	let mut multi_gp = MultiOutputGaussianProcess::default();
+	multi_gp.noise = 0.5f64;
+	multi_gp.train(&inputs, &targets).unwrap();
+
+	let test_inputs = Matrix::new(4,1,vec![0.5,2.2,4.7,7.0]);
+	let outputs = multi_gp.predict(&test_inputs).unwrap();
+
+	assert_eq!(outputs.rows(), 4);
+	assert_eq!(outputs.cols(), 2);
+
+	for j in 0..2 {
+		let mut gp = GaussianProcess::default();
+		gp.noise = 0.5f64;
+
+		let col_targets = Vector::new(targets.col(j).iter().cloned().collect::<Vec<f64>>());
+		gp.train(&inputs, &col_targets).unwrap();
+
+		let single_outputs = gp.predict(&test_inputs).unwrap();
+		for i in 0..4 {
+			assert!((outputs[[i, j]] - single_outputs[i]).abs() < 1e-10);
+		}
+	}
+}
+
+#[test]
+fn test_multi_output_mismatched_rows() {
+	let mut multi_gp = MultiOutputGaussianProcess::default();
+	multi_gp.noise = 0.5f64;
+
+	let inputs = Matrix::new(3,1,vec![0.,1.,2.]);
+	let targets = Matrix::new(2,2,vec![0.,1.,2.,3.]);
+
+	assert!(multi_gp.train(&inputs, &targets).is_err());
+}
+
+#[test]
+fn test_linear_mean_extrapolates_trend() {
+	let inputs = Matrix::new(10,1,vec![0.,1.,2.,3.,4.,5.,6.,7.,8.,9.]);
+	let targets = Vector::new(inputs.iter().map(|&x: &f64| 2. * x + 1. + (3. * x).sin() * 0.2).collect::<Vec<f64>>());
+
+	let mean = LinearMean::fit(&inputs, &targets).unwrap();
+	assert!((mean.weights()[0] - 2.).abs() < 0.1);
+	assert!((mean.bias() - 1.).abs() < 0.5);
+
+	let mut linear_gp = GaussianProcess::new(SquaredExp::default(), mean, 1e-2);
+	linear_gp.train(&inputs, &targets).unwrap();
+
+	let mut const_gp = GaussianProcess::new(SquaredExp::default(), ConstMean::default(), 1e-2);
+	const_gp.train(&inputs, &targets).unwrap();
+
+	// Far beyond the training range.
+	let test_inputs = Matrix::new(2,1,vec![20.,30.]);
+	let linear_outputs = linear_gp.predict(&test_inputs).unwrap();
+	let const_outputs = const_gp.predict(&test_inputs).unwrap();
+
+	assert!((linear_outputs[0] - 41.).abs() < 1.);
+	assert!((linear_outputs[1] - 61.).abs() < 1.);
+
+	// The constant mean reverts to zero away from the data.
+	assert!(const_outputs[0].abs() < 1e-3);
+	assert!(const_outputs[1].abs() < 1e-3);
+}
+
+#[test]
+fn test_linear_mean_mismatched_rows() {
+	let inputs = Matrix::new(3,1,vec![0.,1.,2.]);
+	let targets = Vector::new(vec![0.,1.]);
+
+	assert!(LinearMean::fit(&inputs, &targets).is_err());
+}
+
+fn gram_matrix<K: Kernel>(ker: &K, m1: &Matrix<f64>, m2: &Matrix<f64>) -> Matrix<f64> {
+	let mut data = Vec::with_capacity(m1.rows() * m2.rows());
+	for row1 in m1.row_iter() {
+		for row2 in m2.row_iter() {
+			data.push(ker.kernel(row1.raw_slice(), row2.raw_slice()));
+		}
+	}
+	Matrix::new(m1.rows(), m2.rows(), data)
+}
+
+#[test]
+fn test_precomputed_matches_features() {
+	let ker = SquaredExp::new(1.5, 2.);
+	let inputs = Matrix::new(6,2,vec![0.,1.,1.,0.5,2.,2.,3.,1.5,4.,0.,5.,3.]);
+	let targets = Vector::new(vec![0.,1.,3.,2.,1.,4.]);
+	let test_inputs = Matrix::new(3,2,vec![0.5,0.5,2.5,1.,4.5,2.]);
+
+	let mut gp = GaussianProcess::new(ker, ConstMean::default(), 1e-2);
+	gp.train(&inputs, &targets).unwrap();
+	let outputs = gp.predict(&test_inputs).unwrap();
+
+	let mut pre_gp = GaussianProcess::new(ker, ConstMean::default(), 1e-2);
+	pre_gp.train_precomputed(&gram_matrix(&ker, &inputs, &inputs), &targets).unwrap();
+	let pre_outputs = pre_gp.predict_precomputed(&gram_matrix(&ker, &test_inputs, &inputs)).unwrap();
+
+	for (o, p) in outputs.iter().zip(pre_outputs.iter()) {
+		assert!((o - p).abs() < 1e-10);
+	}
+
+	// Each model only accepts the input format it was trained with.
+	assert!(gp.predict_precomputed(&gram_matrix(&ker, &test_inputs, &inputs)).is_err());
+	assert!(pre_gp.predict(&test_inputs).is_err());
+}
+
+#[test]
+fn test_precomputed_invalid_gram() {
+	let mut gp = GaussianProcess::default();
+	let targets = Vector::new(vec![0.,1.]);
+
+	let not_square = Matrix::new(2,3,vec![1.,0.,0.,0.,1.,0.]);
+	assert!(gp.train_precomputed(&not_square, &targets).is_err());
+
+	let not_symmetric = Matrix::new(2,2,vec![1.,0.5,0.,1.]);
+	assert!(gp.train_precomputed(&not_symmetric, &targets).is_err());
+
+	let wrong_size = Matrix::new(3,3,vec![1.,0.,0.,0.,1.,0.,0.,0.,1.]);
+	assert!(gp.train_precomputed(&wrong_size, &targets).is_err());
+}
@@ -1,7 +1,10 @@
 use rm::linalg::Matrix;
 use rm::learning::UnSupModel;
 use rm::learning::k_means::KMeansClassifier;
-use rm::learning::k_means::{Forgy, RandomPartition, KPlusPlus};
+use rm::learning::k_means::{Forgy, RandomPartition, KPlusPlus, Initializer};
+use rm::learning::toolkit::distance::DistanceMetric;
+use rm::learning::LearningResult;
+use rm::linalg::BaseMatrix;
 
 #[test]
 fn test_model_default() {
@@ -99,3 +102,135 @@ fn test_two_centroids() {
     assert!(classes.data().iter().take(3).all(|x| *x == class_a));
     assert!(classes.data().iter().skip(3).all(|x| *x == class_b));
 }
+
+#[test]
+fn test_nan_inputs() {
+    let mut model = KMeansClassifier::<KPlusPlus>::new(2);
+    let inputs = Matrix::new(3, 2, vec![1.0, 2.0, ::std::f64::NAN, 3.0, 1.0, 4.0]);
+
+    let err = model.train(&inputs).unwrap_err();
+    assert_eq!(err.to_string(), "input row 1, column 0 is NaN");
+}
+
+#[test]
+fn test_model_convergence() {
+    let inputs = Matrix::new(6, 2, vec![0.0, 0.0, 0.5, 0.2, 0.1, 0.6,
+                                        8.0, 8.0, 8.4, 7.9, 7.7, 8.3]);
+
+    let mut model = KMeansClassifier::new_specified(2, 1, Forgy);
+    model.train(&inputs).unwrap();
+    assert!(!model.converged());
+    assert_eq!(model.n_iter(), 1);
+
+    model.set_iters(100);
+    model.train(&inputs).unwrap();
+    assert!(model.converged());
+    assert!(model.n_iter() > 1 && model.n_iter() < 100);
+
+    // Any change in cost is within a huge tolerance.
+    model.set_tol(1e6);
+    model.train(&inputs).unwrap();
+    assert!(model.converged());
+    assert_eq!(model.n_iter(), 1);
+}
+
+/// Uses the first `k` rows as the initial centroids.
+#[derive(Debug)]
+struct FirstRows;
+
+impl Initializer for FirstRows {
+    fn init_centroids(&self, k: usize, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        Ok(inputs.select_rows(&(0..k).collect::<Vec<usize>>()))
+    }
+}
+
+/// Standard normal samples from a linear congruential generator
+/// and the Box-Muller transform.
+fn standard_normals(n: usize) -> Vec<f64> {
+    let mut state = 12345u64;
+    let mut uniform = || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((state >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+    };
+
+    (0..n)
+        .map(|_| (-2.0 * uniform().ln()).sqrt() * (2.0 * ::std::f64::consts::PI * uniform()).cos())
+        .collect()
+}
+
+/// The within-group covariance of `correlated_groups`.
+fn group_covariance() -> Matrix<f64> {
+    // Variances of 64 and 0.04 along the diagonals.
+    Matrix::new(2, 2, vec![32.02, 31.98, 31.98, 32.02])
+}
+
+/// Two interleaved groups of correlated Gaussian samples. Both are
+/// spread widely along the direction (1, 1), and they are separated
+/// by 2 along the direction (1, -1) across which they are narrow.
+fn correlated_groups() -> (Matrix<f64>, Vec<usize>) {
+    let n = 200;
+    let normals = standard_normals(2 * n);
+
+    let mut data = Vec::with_capacity(2 * n);
+    let mut groups = Vec::with_capacity(n);
+    for i in 0..n {
+        let group = i % 2;
+        let along = 8.0 * normals[2 * i];
+        let across = 0.2 * normals[2 * i + 1] + if group == 0 { -1.0 } else { 1.0 };
+
+        data.push((along + across) / 2f64.sqrt());
+        data.push((along - across) / 2f64.sqrt());
+        groups.push(group);
+    }
+
+    (Matrix::new(n, 2, data), groups)
+}
+
+/// The proportion of samples clustered with their own group.
+fn cluster_accuracy(classes: &[usize], groups: &[usize]) -> f64 {
+    let same = classes.iter().zip(groups.iter()).filter(|&(c, g)| c == g).count();
+    same.max(groups.len() - same) as f64 / groups.len() as f64
+}
+
+#[test]
+fn test_mahalanobis_recovers_correlated_groups() {
+    let (inputs, groups) = correlated_groups();
+
+    let mut euclidean = KMeansClassifier::new_specified(2, 100, FirstRows);
+    euclidean.train(&inputs).unwrap();
+    let euclidean_classes = euclidean.predict(&inputs).unwrap();
+    assert!(cluster_accuracy(euclidean_classes.data(), &groups) < 0.75);
+
+    let mut mahalanobis = KMeansClassifier::new_specified(2, 100, FirstRows)
+        .with_metric(DistanceMetric::Mahalanobis(group_covariance()));
+    mahalanobis.train(&inputs).unwrap();
+    let mahalanobis_classes = mahalanobis.predict(&inputs).unwrap();
+    assert_eq!(cluster_accuracy(mahalanobis_classes.data(), &groups), 1.0);
+
+    // The centroids are the group means in the original features.
+    let centroids = mahalanobis.centroids().as_ref().unwrap();
+    for c in 0..2 {
+        let across = (centroids[[c, 0]] - centroids[[c, 1]]) / 2f64.sqrt();
+        assert!((across.abs() - 1.0).abs() < 0.1, "{}", centroids);
+    }
+}
+
+#[test]
+fn test_estimated_mahalanobis() {
+    let (inputs, groups) = correlated_groups();
+
+    let mut model = KMeansClassifier::new_specified(2, 100, FirstRows)
+        .with_metric(DistanceMetric::EstimatedMahalanobis);
+    model.train(&inputs).unwrap();
+
+    let classes = model.predict(&inputs).unwrap();
+    assert!(cluster_accuracy(classes.data(), &groups) > 0.95);
+}
+
+#[test]
+fn test_mahalanobis_wrong_size() {
+    let (inputs, _) = correlated_groups();
+
+    let mut model = KMeansClassifier::new(2).with_metric(DistanceMetric::Mahalanobis(Matrix::identity(3)));
+    assert!(model.train(&inputs).is_err());
+}
@@ -1,7 +1,8 @@
-use rm::linalg::Matrix;
+use rm::linalg::{Matrix, BaseMatrix};
 use rm::linalg::Vector;
 use rm::learning::SupModel;
-use rm::learning::lin_reg::LinRegressor;
+use rm::learning::error::ErrorKind;
+use rm::learning::lin_reg::{LinRegressor, Solver};
 use libnum::abs;
 
 #[test]
@@ -58,7 +59,10 @@ fn test_regression_datasets_trees() {
     let mut lin_mod = LinRegressor::default();
     lin_mod.train(&trees.data(), &trees.target()).unwrap();
     let params = lin_mod.parameters().unwrap();
-    assert_eq!(params, &Vector::new(vec![-57.98765891838409, 4.708160503017506, 0.3392512342447438]));
+    let expected_params = vec![-57.98765891838409, 4.708160503017506, 0.3392512342447438];
+    for (p, e) in params.iter().zip(expected_params.iter()) {
+        assert!(abs(p - e) < 1e-10);
+    }
 
     let predicted = lin_mod.predict(&trees.data()).unwrap();
     let expected = vec![4.837659653793278, 4.55385163347481, 4.816981265588826, 15.874115228921276,
@@ -69,5 +73,252 @@ fn test_regression_datasets_trees() {
                         34.388184394951004, 36.008318964043994, 35.38525970948079, 41.76899799551756,
                         44.87770231764652, 50.942867757643015, 52.223751092491256, 53.42851282520877,
                         53.899328875510534, 53.899328875510534, 68.51530482306926];
-    assert_eq!(predicted, Vector::new(expected));
-}
\ No newline at end of file
+    assert_eq!(predicted.size(), expected.len());
+    for (p, e) in predicted.iter().zip(expected.iter()) {
+        assert!(abs(p - e) < 1e-10);
+    }
+}
+
+/// Generates noiseless data with one large, low variance feature and one tiny feature.
+fn badly_scaled_data() -> (Matrix<f64>, Vector<f64>) {
+    let n = 50;
+    let mut data = Vec::with_capacity(2 * n);
+    let mut targets = Vec::with_capacity(n);
+
+    for i in 0..n {
+        let x1 = 1e6 + (i as f64 * 0.7).sin() * 10.0;
+        let x2 = 1e-4 * (i as f64 * 1.3).cos();
+        data.push(x1);
+        data.push(x2);
+        targets.push(2.0 + 0.5 * x1 + 3e4 * x2);
+    }
+
+    (Matrix::new(n, 2, data), Vector::new(targets))
+}
+
+#[test]
+fn test_regression_standardization() {
+    let (inputs, targets) = badly_scaled_data();
+    let true_params = [2.0, 0.5, 3e4];
+
+    let mut raw_mod = LinRegressor::default();
+    raw_mod.train(&inputs, &targets).unwrap();
+
+    let mut std_mod = LinRegressor::with_standardization(true);
+    std_mod.train(&inputs, &targets).unwrap();
+
+    let raw_outputs = raw_mod.predict(&inputs).unwrap();
+    let std_outputs = std_mod.predict(&inputs).unwrap();
+
+    for (r, s) in raw_outputs.iter().zip(std_outputs.iter()) {
+        assert!(abs(r - s) < 1e-3);
+    }
+
+    let raw_params = raw_mod.parameters().unwrap();
+    let std_params = std_mod.parameters().unwrap();
+
+    for i in 0..3 {
+        assert!(abs(std_params[i] - true_params[i]) < 1e-4);
+    }
+
+    // The intercept is badly determined without standardization.
+    assert!(abs(std_params[0] - true_params[0]) < abs(raw_params[0] - true_params[0]));
+}
+
+#[test]
+fn test_regression_standardization_constant_column() {
+    let mut lin_mod = LinRegressor::with_standardization(true);
+    let inputs = Matrix::new(3, 2, vec![1.0, 2.0, 1.0, 3.0, 1.0, 4.0]);
+    let targets = Vector::new(vec![5.0, 6.0, 7.0]);
+
+    assert!(lin_mod.train(&inputs, &targets).is_err());
+}
+
+/// Two groups of points on the lines `y = x` and `y = 3x`.
+fn two_line_data() -> (Matrix<f64>, Vector<f64>) {
+    let xs = (0..20).map(|i| (i % 10) as f64).collect::<Vec<f64>>();
+    let ys = xs.iter().enumerate().map(|(i, x)| if i < 10 { *x } else { 3.0 * x }).collect::<Vec<f64>>();
+
+    (Matrix::new(20, 1, xs), Vector::new(ys))
+}
+
+#[test]
+fn test_weighted_regression() {
+    let (inputs, targets) = two_line_data();
+
+    let mut lin_mod = LinRegressor::default();
+    lin_mod.train(&inputs, &targets).unwrap();
+    assert!(abs(lin_mod.parameters().unwrap()[1] - 2.0) < 1e-8);
+
+    // Upweighting the steeper line pulls the slope towards it.
+    let weights = Vector::new((0..20).map(|i| if i < 10 { 1.0 } else { 9.0 }).collect::<Vec<f64>>());
+    let mut weighted_mod = LinRegressor::default();
+    weighted_mod.train_weighted(&inputs, &targets, &weights).unwrap();
+    assert!(abs(weighted_mod.parameters().unwrap()[1] - 2.8) < 1e-8);
+
+    // Standardizing does not change the weighted fit.
+    let mut std_mod = LinRegressor::with_standardization(true);
+    std_mod.train_weighted(&inputs, &targets, &weights).unwrap();
+    for (p, s) in weighted_mod.parameters().unwrap().iter().zip(std_mod.parameters().unwrap().iter()) {
+        assert!(abs(p - s) < 1e-8);
+    }
+}
+
+#[test]
+fn test_weighted_regression_invalid_weights() {
+    let (inputs, targets) = two_line_data();
+    let mut lin_mod = LinRegressor::default();
+
+    assert!(lin_mod.train_weighted(&inputs, &targets, &Vector::new(vec![1.0; 5])).is_err());
+    assert!(lin_mod.train_weighted(&inputs, &targets, &Vector::new(vec![-1.0; 20])).is_err());
+    assert!(lin_mod.train_weighted(&inputs, &targets, &Vector::new(vec![0.0; 20])).is_err());
+}
+
+/// Generates noiseless polynomial data on `[lo, hi]`, whose normal equations are ill-conditioned.
+fn polynomial_data(degree: usize, lo: f64, hi: f64) -> (Matrix<f64>, Vector<f64>, Vec<f64>) {
+    let n = 25;
+    let true_params = (0..degree + 1).map(|d| 1.0 + 0.5 * d as f64).collect::<Vec<f64>>();
+
+    let mut data = Vec::with_capacity(n * degree);
+    let mut targets = Vec::with_capacity(n);
+    for i in 0..n {
+        let x = lo + (hi - lo) * i as f64 / (n - 1) as f64;
+        let mut target = true_params[0];
+        for d in 1..degree + 1 {
+            data.push(x.powi(d as i32));
+            target += true_params[d] * x.powi(d as i32);
+        }
+        targets.push(target);
+    }
+
+    (Matrix::new(n, degree, data), Vector::new(targets), true_params)
+}
+
+fn max_param_error(params: &Vector<f64>, true_params: &[f64]) -> f64 {
+    params.iter().zip(true_params.iter()).map(|(p, t)| abs(p - t)).fold(0f64, f64::max)
+}
+
+#[test]
+fn test_regression_factored_solve_accuracy() {
+    let (inputs, targets, true_params) = polynomial_data(4, 1.0, 3.0);
+
+    let mut lin_mod = LinRegressor::default();
+    lin_mod.train(&inputs, &targets).unwrap();
+    assert!(lin_mod.normal_factor().is_some());
+
+    // Solve the normal equations by explicitly inverting XᵀX.
+    let full_inputs = Matrix::ones(inputs.rows(), 1).hcat(&inputs);
+    let xt = full_inputs.transpose();
+    let inverted = (&xt * &full_inputs).inverse().unwrap() * (xt * &targets);
+
+    let max_error = |params: &Vector<f64>| max_param_error(params, &true_params);
+    let sq_residual = |params: &Vector<f64>| {
+        (&full_inputs * params - &targets).iter().map(|r| r * r).sum::<f64>()
+    };
+
+    let params = lin_mod.parameters().unwrap();
+    assert!(max_error(params) < 1e-6);
+    assert!(max_error(params) < max_error(&inverted));
+    assert!(sq_residual(params) * 100.0 < sq_residual(&inverted));
+}
+
+#[test]
+fn test_regression_normal_factor() {
+    let inputs = Matrix::new(4, 1, vec![1.0, 3.0, 5.0, 7.0]);
+    let targets = Vector::new(vec![1., 5., 9., 13.]);
+
+    let mut lin_mod = LinRegressor::default();
+    assert!(lin_mod.normal_factor().is_none());
+    lin_mod.train(&inputs, &targets).unwrap();
+
+    // The factor reproduces XᵀX.
+    let factor = lin_mod.normal_factor().unwrap();
+    let full_inputs = Matrix::ones(4, 1).hcat(&inputs);
+    let gram = full_inputs.transpose() * &full_inputs;
+    let diff = factor * factor.transpose() - gram;
+    assert!(diff.iter().all(|d| abs(*d) < 1e-10));
+
+    // Gradient descent does not produce a factor.
+    lin_mod.train_with_optimization(&inputs, &targets);
+    assert!(lin_mod.normal_factor().is_none());
+}
+
+#[test]
+fn test_regression_qr_solver_accuracy() {
+    let (inputs, targets, true_params) = polynomial_data(5, 2.0, 3.0);
+
+    let mut normal_mod = LinRegressor::default();
+    normal_mod.train(&inputs, &targets).unwrap();
+
+    let mut qr_mod = LinRegressor::default().with_solver(Solver::QR);
+    qr_mod.train(&inputs, &targets).unwrap();
+
+    assert!(max_param_error(normal_mod.parameters().unwrap(), &true_params) > 1e-4);
+    assert!(max_param_error(qr_mod.parameters().unwrap(), &true_params) < 1e-6);
+}
+
+#[test]
+fn test_regression_qr_solver_matches_normal_equations() {
+    let (inputs, targets) = two_line_data();
+    let weights = Vector::new((0..inputs.rows()).map(|i| 1.0 + (i % 3) as f64).collect::<Vec<f64>>());
+
+    let mut normal_mod = LinRegressor::default();
+    let mut qr_mod = LinRegressor::default().with_solver(Solver::QR);
+    assert_eq!(qr_mod.solver(), Solver::QR);
+
+    normal_mod.train(&inputs, &targets).unwrap();
+    qr_mod.train(&inputs, &targets).unwrap();
+    assert!(max_param_error(qr_mod.parameters().unwrap(), normal_mod.parameters().unwrap().data()) < 1e-10);
+
+    normal_mod.train_weighted(&inputs, &targets, &weights).unwrap();
+    qr_mod.train_weighted(&inputs, &targets, &weights).unwrap();
+    assert!(max_param_error(qr_mod.parameters().unwrap(), normal_mod.parameters().unwrap().data()) < 1e-10);
+
+    // Both solvers produce the Cholesky factor of the normal equations.
+    let normal_factor = normal_mod.normal_factor().unwrap();
+    let qr_factor = qr_mod.normal_factor().unwrap();
+    assert!((normal_factor - qr_factor).iter().all(|d| abs(*d) < 1e-10));
+}
+
+#[test]
+fn test_regression_qr_solver_rank_deficient() {
+    // The second column is twice the first.
+    let inputs = Matrix::new(4, 2, vec![1.0, 2.0, 2.0, 4.0, 3.0, 6.0, 4.0, 8.0]);
+    let targets = Vector::new(vec![1.0, 2.0, 3.0, 4.0]);
+
+    let mut lin_mod = LinRegressor::default().with_solver(Solver::QR);
+    assert!(lin_mod.train(&inputs, &targets).is_err());
+}
+
+#[test]
+fn test_regression_normal_equations_singular() {
+    // The second column is always zero, so no solution is unique.
+    let inputs = Matrix::new(3, 2, vec![1.0, 0.0, 2.0, 0.0, 3.0, 0.0]);
+    let targets = Vector::new(vec![1.0, 2.0, 3.0]);
+
+    let mut lin_mod = LinRegressor::default();
+    let err = lin_mod.train(&inputs, &targets).unwrap_err();
+    match *err.kind() {
+        ErrorKind::LinearAlgebra => {}
+        _ => panic!("Unexpected error kind."),
+    }
+}
+
+#[test]
+fn test_regression_non_finite_data() {
+    let inputs = Matrix::new(3, 2, vec![1.0, 2.0, 3.0, 4.0, ::std::f64::NAN, 6.0]);
+    let targets = Vector::new(vec![1.0, 2.0, 3.0]);
+
+    let mut lin_mod = LinRegressor::default();
+    let err = lin_mod.train(&inputs, &targets).unwrap_err();
+    match *err.kind() {
+        ErrorKind::InvalidData => {}
+        _ => panic!("Unexpected error kind."),
+    }
+    assert_eq!(err.to_string(), "input row 2, column 0 is NaN");
+
+    let inputs = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+    let targets = Vector::new(vec![1.0, ::std::f64::INFINITY, 3.0]);
+    let err = lin_mod.train(&inputs, &targets).unwrap_err();
+    assert_eq!(err.to_string(), "target 1 is infinite");
+}
@@ -0,0 +1,230 @@
+use rm::linalg::{Matrix, BaseMatrix};
+use rm::linalg::Vector;
+use rm::learning::{LabelClassifier, ProbabilisticClassifier, SupModel};
+use rm::learning::logistic_reg::LogisticRegressor;
+use rm::learning::optim::grad_desc::{GradientDesc, StochasticGD};
+
+/// Two overlapping classes, with a deterministic pattern
+/// of points in the overlap.
+fn overlapping_data() -> (Matrix<f64>, Vector<f64>) {
+    let n = 200;
+    let mut inputs = Vec::with_capacity(n * 2);
+    let mut targets = Vec::with_capacity(n);
+
+    for i in 0..n {
+        let x1 = (i as f64 * 0.37).sin() * 2.0;
+        let x2 = (i as f64 * 0.91).cos() * 2.0;
+        let noise = (i as f64 * 2.17).sin() * 1.5;
+
+        inputs.push(x1);
+        inputs.push(x2);
+        targets.push(if x1 - 0.5 * x2 + noise > 0.3 { 1.0 } else { 0.0 });
+    }
+
+    (Matrix::new(n, 2, inputs), Vector::new(targets))
+}
+
+#[test]
+fn test_sgd_matches_batch() {
+    let (inputs, targets) = overlapping_data();
+
+    let mut batch_mod = LogisticRegressor::new(GradientDesc::new(1.0, 5000));
+    batch_mod.train(&inputs, &targets).unwrap();
+
+    let sgd = StochasticGD::new(0.5, 0.1, 200).with_batch_size(10);
+    let mut sgd_mod = LogisticRegressor::new(sgd);
+    sgd_mod.train(&inputs, &targets).unwrap();
+
+    let batch_params = batch_mod.parameters().unwrap();
+    let sgd_params = sgd_mod.parameters().unwrap();
+
+    for i in 0..3 {
+        assert!((batch_params[i] - sgd_params[i]).abs() < 0.1);
+    }
+}
+
+#[test]
+fn test_partial_fit_matches_batch() {
+    let (inputs, targets) = overlapping_data();
+
+    let mut batch_mod = LogisticRegressor::new(GradientDesc::new(1.0, 5000));
+    batch_mod.train(&inputs, &targets).unwrap();
+
+    // Stream the data in chunks, several times over.
+    let sgd = StochasticGD::new(0.5, 0.05, 10).with_batch_size(10);
+    let mut sgd_mod = LogisticRegressor::new(sgd);
+    for _ in 0..40 {
+        for c in 0..4 {
+            let rows = (c * 50..(c + 1) * 50).collect::<Vec<usize>>();
+            let chunk_targets = Vector::new(rows.iter().map(|&i| targets[i]).collect::<Vec<f64>>());
+            sgd_mod.partial_fit(&inputs.select_rows(&rows), &chunk_targets).unwrap();
+        }
+    }
+
+    let batch_params = batch_mod.parameters().unwrap();
+    let sgd_params = sgd_mod.parameters().unwrap();
+
+    for i in 0..3 {
+        assert!((batch_params[i] - sgd_params[i]).abs() < 0.1);
+    }
+}
+
+#[test]
+fn test_partial_fit_wrong_cols() {
+    let mut log_mod = LogisticRegressor::default();
+    log_mod.partial_fit(&Matrix::new(2, 1, vec![1.0, 7.0]), &Vector::new(vec![0., 1.])).unwrap();
+
+    let res = log_mod.partial_fit(&Matrix::new(1, 2, vec![1.0, 2.0]), &Vector::new(vec![0.]));
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_weighted_shifts_towards_upweighted() {
+    let (inputs, targets) = overlapping_data();
+
+    let mut log_mod = LogisticRegressor::new(GradientDesc::new(0.5, 2000));
+    log_mod.train(&inputs, &targets).unwrap();
+
+    // Upweight the positive samples.
+    let weights = Vector::new(targets.iter().map(|&t| if t > 0.5 { 5.0 } else { 1.0 }).collect::<Vec<f64>>());
+    let mut weighted_mod = LogisticRegressor::new(GradientDesc::new(0.5, 2000));
+    weighted_mod.train_weighted(&inputs, &targets, &weights).unwrap();
+
+    let probs = log_mod.predict(&inputs).unwrap();
+    let weighted_probs = weighted_mod.predict(&inputs).unwrap();
+
+    assert!(weighted_mod.parameters().unwrap()[0] > log_mod.parameters().unwrap()[0] + 0.5);
+    assert!(weighted_probs.mean() > probs.mean() + 0.1);
+}
+
+#[test]
+fn test_weighted_uniform_matches_unweighted() {
+    let (inputs, targets) = overlapping_data();
+
+    let mut log_mod = LogisticRegressor::new(GradientDesc::new(0.5, 500));
+    log_mod.train(&inputs, &targets).unwrap();
+
+    let weights = Vector::new(vec![2.0; inputs.rows()]);
+    let mut weighted_mod = LogisticRegressor::new(GradientDesc::new(0.5, 500));
+    weighted_mod.train_weighted(&inputs, &targets, &weights).unwrap();
+
+    let params = log_mod.parameters().unwrap();
+    let weighted_params = weighted_mod.parameters().unwrap();
+    for (p, w) in params.iter().zip(weighted_params.iter()) {
+        assert!((p - w).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_weighted_sgd() {
+    let (inputs, targets) = overlapping_data();
+    let weights = Vector::new(targets.iter().map(|&t| if t > 0.5 { 5.0 } else { 1.0 }).collect::<Vec<f64>>());
+
+    let mut log_mod = LogisticRegressor::new(StochasticGD::new(0.5, 0.1, 30).with_batch_size(10));
+    log_mod.train(&inputs, &targets).unwrap();
+
+    let mut weighted_mod = LogisticRegressor::new(StochasticGD::new(0.5, 0.1, 30).with_batch_size(10));
+    weighted_mod.train_weighted(&inputs, &targets, &weights).unwrap();
+
+    assert!(weighted_mod.predict(&inputs).unwrap().mean() > log_mod.predict(&inputs).unwrap().mean());
+}
+
+#[test]
+fn test_weighted_sgd_zero_weight_batches() {
+    let (inputs, targets) = overlapping_data();
+
+    // Most single-sample batches have no weight at all.
+    let weights = Vector::new((0..inputs.rows()).map(|i| if i % 4 == 0 { 1.0 } else { 0.0 })
+                                                .collect::<Vec<f64>>());
+    let mut log_mod = LogisticRegressor::new(StochasticGD::new(0.1, 0.1, 20).with_batch_size(1));
+    log_mod.train_weighted(&inputs, &targets, &weights).unwrap();
+
+    let params = log_mod.parameters().unwrap();
+    assert!(params.iter().all(|p| p.is_finite()));
+
+    // Training on only the weighted samples gives the same fit.
+    let kept = (0..inputs.rows()).filter(|i| i % 4 == 0).collect::<Vec<usize>>();
+    let mut kept_mod = LogisticRegressor::new(GradientDesc::new(0.5, 2000));
+    kept_mod.train(&inputs.select_rows(&kept), &targets.select(&kept)).unwrap();
+
+    let predicted = log_mod.predict(&inputs).unwrap().apply(&|p| p.round());
+    let kept_predicted = kept_mod.predict(&inputs).unwrap().apply(&|p| p.round());
+    let agree = predicted.iter().zip(kept_predicted.iter()).filter(|&(a, b)| a == b).count();
+    assert!(agree as f64 > 0.9 * inputs.rows() as f64);
+}
+
+#[test]
+fn test_weighted_wrong_length() {
+    let (inputs, targets) = overlapping_data();
+    let weights = Vector::new(vec![1.0; 3]);
+
+    let mut log_mod = LogisticRegressor::default();
+    assert!(log_mod.train_weighted(&inputs, &targets, &weights).is_err());
+}
+
+#[test]
+fn test_convergence_reported() {
+    let (inputs, targets) = overlapping_data();
+
+    let mut short_mod = LogisticRegressor::new(GradientDesc::new(1.0, 2));
+    short_mod.train(&inputs, &targets).unwrap();
+    assert!(!short_mod.converged());
+    assert_eq!(short_mod.n_iter(), 2);
+
+    let mut long_mod = LogisticRegressor::new(GradientDesc::new(1.0, 5000));
+    long_mod.train(&inputs, &targets).unwrap();
+    assert!(long_mod.converged());
+    assert!(long_mod.n_iter() > 2);
+}
+
+#[test]
+fn test_convergence_tolerance() {
+    let (inputs, targets) = overlapping_data();
+
+    let mut strict_mod = LogisticRegressor::new(GradientDesc::new(1.0, 5000)).with_convergence_check_tol(1e-12);
+    strict_mod.train(&inputs, &targets).unwrap();
+    assert!(!strict_mod.converged());
+
+    let mut loose_mod = LogisticRegressor::new(GradientDesc::new(1.0, 2)).with_convergence_check_tol(10.0);
+    loose_mod.train(&inputs, &targets).unwrap();
+    assert!(loose_mod.converged());
+}
+
+#[test]
+fn test_integer_labels() {
+    let (inputs, targets) = overlapping_data();
+    let labels = targets.iter().map(|&t| t as usize).collect::<Vec<usize>>();
+
+    let mut label_mod = LogisticRegressor::default();
+    label_mod.train_labels(&inputs, &labels).unwrap();
+
+    let mut target_mod = LogisticRegressor::default();
+    target_mod.train(&inputs, &targets).unwrap();
+    assert_eq!(label_mod.parameters(), target_mod.parameters());
+
+    let predicted = label_mod.predict_labels(&inputs).unwrap();
+    let correct = predicted.iter().zip(labels.iter()).filter(|&(p, l)| p == l).count();
+    assert!(correct as f64 / labels.len() as f64 > 0.8);
+}
+
+#[test]
+fn test_integer_labels_not_binary() {
+    let inputs = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+
+    let mut log_mod = LogisticRegressor::default();
+    assert!(log_mod.train_labels(&inputs, &[0, 1, 2]).is_err());
+    assert!(log_mod.train_labels(&inputs, &[0, 1]).is_err());
+}
+
+#[test]
+fn test_predict_with_reject() {
+    let inputs = Matrix::new(6, 1, vec![-3.0, -2.0, -1.0, 1.0, 2.0, 3.0]);
+    let targets = Vector::new(vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0]);
+
+    let mut log_mod = LogisticRegressor::new(GradientDesc::new(0.5, 500));
+    log_mod.train(&inputs, &targets).unwrap();
+
+    let classes = log_mod.predict_with_reject(&Matrix::new(3, 1, vec![-10.0, 0.0, 10.0]), 0.9)
+                         .unwrap();
+    assert_eq!(classes.into_vec(), vec![Some(0), None, Some(1)]);
+}
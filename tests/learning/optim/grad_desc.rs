@@ -39,6 +39,80 @@ fn convex_fmincg_training() {
     assert!(x_sq.compute_grad(&params, &Matrix::zeros(1, 1), &Matrix::zeros(1, 1)).0 < 1e-10);
 }
 
+/// A model which uses the convex cost function
+/// y = sum_i s_i (x_i - i)^2 + (x_i - i)^4
+///
+/// with differently scaled coefficients s_i.
+struct QuarticModel;
+
+impl Optimizable for QuarticModel {
+    type Inputs = Matrix<f64>;
+    type Targets = Matrix<f64>;
+
+    fn compute_grad(&self, params: &[f64], _: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+        let mut cost = 0f64;
+        let mut grad = Vec::with_capacity(params.len());
+        for (i, p) in params.iter().enumerate() {
+            let scale = 0.2 * (i + 1) as f64;
+            let d = p - i as f64;
+            cost += scale * d * d + d.powi(4);
+            grad.push(2f64 * scale * d + 4f64 * d.powi(3));
+        }
+        (cost, grad)
+    }
+}
+
+/// The cost and gradient norm after each iteration.
+fn fmincg_trace(cgd: &ConjugateGD) -> (Vec<f64>, Vec<f64>) {
+    let (mut costs, mut norms) = (Vec::new(), Vec::new());
+    let data = Matrix::zeros(1, 1);
+    cgd.optimize_with_monitor(&QuarticModel, &[3.0, -2.0, 5.0, 0.0, 1.0], &data, &data, |p| {
+        costs.push(p.cost);
+        norms.push(p.grad_norm);
+    });
+    (costs, norms)
+}
+
+#[test]
+fn fmincg_monitor_costs_decrease() {
+    let (costs, norms) = fmincg_trace(&ConjugateGD::default());
+
+    assert!(costs.len() > 1);
+    assert_eq!(costs.len(), norms.len());
+    for w in costs.windows(2) {
+        assert!(w[1] <= w[0], "{:?}", costs);
+    }
+    assert!(norms[norms.len() - 1] < 1e-6);
+}
+
+#[test]
+fn fmincg_grad_tol_stops_early() {
+    let loose = ConjugateGD { grad_tol: 1e-2, ..ConjugateGD::default() };
+    let tight = ConjugateGD { grad_tol: 1e-8, ..ConjugateGD::default() };
+
+    let (loose_costs, loose_norms) = fmincg_trace(&loose);
+    let (tight_costs, tight_norms) = fmincg_trace(&tight);
+    let (full_costs, _) = fmincg_trace(&ConjugateGD::default());
+
+    assert!(loose_costs.len() < tight_costs.len());
+    assert!(tight_costs.len() < full_costs.len());
+    assert!(loose_norms[loose_norms.len() - 1] < 1e-2);
+    assert!(tight_norms[tight_norms.len() - 1] < 1e-8);
+}
+
+#[test]
+fn fmincg_callback_matches_monitor() {
+    let cgd = ConjugateGD::default();
+    let data = Matrix::zeros(1, 1);
+
+    let mut epochs = Vec::new();
+    let params = cgd.optimize_with_callback(&QuarticModel, &[3.0, -2.0, 5.0, 0.0, 1.0], &data, &data,
+                                            |i, _| epochs.push(i));
+
+    assert_eq!(epochs, (0..fmincg_trace(&cgd).0.len()).collect::<Vec<usize>>());
+    assert_eq!(params, cgd.optimize(&QuarticModel, &[3.0, -2.0, 5.0, 0.0, 1.0], &data, &data));
+}
+
 #[test]
 fn convex_gd_training() {
     let x_sq = XSqModel { c: 20f64 };